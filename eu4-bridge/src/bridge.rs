@@ -145,6 +145,7 @@ impl ExtractedState {
             ruler_dynasty: None,              // Can't extract from OCR yet
             ruler_gender: Default::default(), // Can't extract from OCR yet
             ruler_instated: None,             // Can't extract from OCR yet
+            ai_personality: None,             // Not visible in the UI
             government_rank: 1,               // Default duchy - can't extract from OCR yet
             technology_group: None,           // Can't extract from OCR yet
             // 1444 starting tech - can't extract from OCR yet
@@ -157,6 +158,7 @@ impl ExtractedState {
             income: Default::default(),
             fixed_expenses: Fixed::ZERO, // Can't extract from OCR yet
             last_diplomatic_action: None,
+            diplomats: Vec::new(),
             peace_offer_cooldowns: Default::default(),
             pending_call_to_arms: Default::default(),
             overextension: Fixed::ZERO, // Can't extract from OCR yet
@@ -199,6 +201,9 @@ impl ExtractedState {
             our_army_sizes: Default::default(),
             our_army_provinces: Default::default(),
             staging_provinces: Default::default(),
+            personality: None,
+            strategic_goals: Default::default(),
+            relations: Default::default(),
         }
     }
 }
@@ -0,0 +1,246 @@
+//! Parser for AI personalities (`common/ai_personalities/`) and attitudes
+//! (`common/ai_attitudes/`).
+//!
+//! Personalities are weighted by their `chance` block and skew what the AI
+//! prioritises (`war_priority`, `building_priority`, ...). Attitudes describe
+//! how a country treats another one (`attitude_rivalry`, `attitude_allied`).
+//! Both are kept as raw key/value data; the sim decides which keys it models.
+
+use eu4txt::{DefaultEU4Txt, EU4Txt, EU4TxtAstItem, EU4TxtParseNode};
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+
+/// Raw AI personality parsed from game files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawAiPersonality {
+    /// Name of the personality (e.g., "ai_militarist")
+    pub name: String,
+
+    /// Base `factor` of the `chance` block (conditional modifiers are ignored).
+    /// Zero means the personality is never rolled (e.g., the human one).
+    pub chance: f32,
+
+    /// Numeric fields, e.g. `war_priority = 1.5`
+    pub priorities: HashMap<String, f32>,
+}
+
+/// Raw AI attitude parsed from game files.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RawAiAttitude {
+    /// Name of the attitude (e.g., "attitude_rivalry")
+    pub name: String,
+
+    /// Fields set to `yes`, e.g. `ai_war_target = yes`
+    pub flags: Vec<String>,
+
+    /// Numeric fields
+    pub values: HashMap<String, f32>,
+}
+
+/// Loads all AI personalities from `common/ai_personalities/`.
+pub fn load_ai_personalities(
+    base_path: &Path,
+) -> Result<HashMap<String, RawAiPersonality>, Box<dyn Error>> {
+    let mut personalities = HashMap::new();
+    for (name, body) in load_blocks(&base_path.join("common/ai_personalities"))? {
+        let personality = parse_personality(&name, &body);
+        personalities.insert(name, personality);
+    }
+    Ok(personalities)
+}
+
+/// Loads all AI attitudes from `common/ai_attitudes/`.
+pub fn load_ai_attitudes(
+    base_path: &Path,
+) -> Result<HashMap<String, RawAiAttitude>, Box<dyn Error>> {
+    let mut attitudes = HashMap::new();
+    for (name, body) in load_blocks(&base_path.join("common/ai_attitudes"))? {
+        let attitude = parse_attitude(&name, &body);
+        attitudes.insert(name, attitude);
+    }
+    Ok(attitudes)
+}
+
+/// Top-level `name = { ... }` blocks of every `.txt` file in `dir`, in file
+/// name order (later files override earlier ones, as in the game).
+fn load_blocks(dir: &Path) -> Result<Vec<(String, EU4TxtParseNode)>, Box<dyn Error>> {
+    if !dir.exists() {
+        log::warn!("Directory not found: {:?}", dir);
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+
+    let mut blocks = Vec::new();
+    for path in paths {
+        let tokens = match DefaultEU4Txt::open_txt(path.to_str().unwrap()) {
+            Ok(tokens) if !tokens.is_empty() => tokens,
+            Ok(_) => continue,
+            Err(e) => {
+                log::warn!("Failed to read {:?}: {}", path, e);
+                continue;
+            }
+        };
+        let ast = match DefaultEU4Txt::parse(tokens) {
+            Ok(ast) => ast,
+            Err(e) => {
+                log::warn!("Failed to parse {:?}: {}", path, e);
+                continue;
+            }
+        };
+        for node in ast.children {
+            let EU4TxtAstItem::Assignment = node.entry else {
+                continue;
+            };
+            let mut children = node.children.into_iter();
+            if let (Some(name_node), Some(body)) = (children.next(), children.next())
+                && let EU4TxtAstItem::Identifier(name) = name_node.entry
+                && matches!(
+                    body.entry,
+                    EU4TxtAstItem::AssignmentList | EU4TxtAstItem::Brace
+                )
+            {
+                blocks.push((name, body));
+            }
+        }
+    }
+    Ok(blocks)
+}
+
+/// `(key, value)` pairs of a block's direct assignments.
+fn assignments(node: &EU4TxtParseNode) -> impl Iterator<Item = (&str, &EU4TxtParseNode)> {
+    node.children.iter().filter_map(|child| {
+        if let EU4TxtAstItem::Assignment = child.entry
+            && let [key_node, value] = child.children.as_slice()
+            && let EU4TxtAstItem::Identifier(key) = &key_node.entry
+        {
+            Some((key.as_str(), value))
+        } else {
+            None
+        }
+    })
+}
+
+fn parse_personality(name: &str, node: &EU4TxtParseNode) -> RawAiPersonality {
+    let mut chance = 1.0;
+    let mut priorities = HashMap::new();
+    for (key, value) in assignments(node) {
+        if key == "chance" {
+            chance = assignments(value)
+                .find(|(k, _)| *k == "factor")
+                .and_then(|(_, v)| get_f32(v))
+                .unwrap_or(1.0);
+        } else if let Some(number) = get_f32(value) {
+            priorities.insert(key.to_string(), number);
+        }
+    }
+    RawAiPersonality {
+        name: name.to_string(),
+        chance,
+        priorities,
+    }
+}
+
+fn parse_attitude(name: &str, node: &EU4TxtParseNode) -> RawAiAttitude {
+    let mut attitude = RawAiAttitude {
+        name: name.to_string(),
+        ..Default::default()
+    };
+    for (key, value) in assignments(node) {
+        match &value.entry {
+            EU4TxtAstItem::Identifier(v) if v == "yes" => attitude.flags.push(key.to_string()),
+            _ => {
+                if let Some(number) = get_f32(value) {
+                    attitude.values.insert(key.to_string(), number);
+                }
+            }
+        }
+    }
+    attitude.flags.sort();
+    attitude
+}
+
+/// Extract f32 value from AST node.
+fn get_f32(node: &EU4TxtParseNode) -> Option<f32> {
+    match &node.entry {
+        EU4TxtAstItem::IntValue(n) => Some(*n as f32),
+        EU4TxtAstItem::FloatValue(f) => Some(*f),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_load_ai_personalities() {
+        let dir = tempdir().unwrap();
+        let common = dir.path().join("common/ai_personalities");
+        std::fs::create_dir_all(&common).unwrap();
+        std::fs::write(
+            common.join("00_ai_personalities.txt"),
+            r#"
+human_ai_personality = {
+    chance = { factor = 0 }
+}
+ai_militarist = {
+    chance = {
+        factor = 2
+        modifier = { factor = 3 has_idea_group = offensive_ideas }
+    }
+    war_priority = 1.5
+    building_priority = 0.5
+}
+"#,
+        )
+        .unwrap();
+
+        let personalities = load_ai_personalities(dir.path()).unwrap();
+        assert_eq!(personalities.len(), 2);
+        assert_eq!(personalities["human_ai_personality"].chance, 0.0);
+
+        let militarist = &personalities["ai_militarist"];
+        assert_eq!(militarist.chance, 2.0);
+        assert_eq!(militarist.priorities["war_priority"], 1.5);
+        assert_eq!(militarist.priorities["building_priority"], 0.5);
+        assert!(!militarist.priorities.contains_key("chance"));
+    }
+
+    #[test]
+    fn test_load_ai_attitudes() {
+        let dir = tempdir().unwrap();
+        let common = dir.path().join("common/ai_attitudes");
+        std::fs::create_dir_all(&common).unwrap();
+        std::fs::write(
+            common.join("00_ai_attitudes.txt"),
+            r#"
+attitude_rivalry = {
+    ai_war_target = yes
+    ai_ally = no
+    hostility = 2
+}
+"#,
+        )
+        .unwrap();
+
+        let attitudes = load_ai_attitudes(dir.path()).unwrap();
+        let rivalry = &attitudes["attitude_rivalry"];
+        assert_eq!(rivalry.flags, vec!["ai_war_target".to_string()]);
+        assert_eq!(rivalry.values["hostility"], 2.0);
+    }
+
+    #[test]
+    fn test_missing_directories_are_empty() {
+        let dir = tempdir().unwrap();
+        assert!(load_ai_personalities(dir.path()).unwrap().is_empty());
+        assert!(load_ai_attitudes(dir.path()).unwrap().is_empty());
+    }
+}
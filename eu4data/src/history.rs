@@ -72,7 +72,9 @@ pub struct ProvinceHistory {
     /// Includes both the owner and other countries with reconquest claims.
     #[schema(simulated)]
     pub add_core: Option<Vec<String>>,
-    pub add_claim: Option<Vec<IgnoredAny>>,
+    /// Historical claims on this province (permanent until cored).
+    #[schema(simulated)]
+    pub add_claim: Option<Vec<String>>,
 
     // Explicitly ignored complex fields
     pub add_permanent_province_modifier: Option<Vec<IgnoredAny>>,
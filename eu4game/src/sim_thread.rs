@@ -3,7 +3,7 @@
 //! Runs `step_world` in a separate thread, communicating with the
 //! main render thread via channels.
 
-use eu4sim_core::{PlayerInputs, SimConfig, SimMetrics, WorldState, step_world};
+use eu4sim_core::{PlayerInputs, SimConfig, SimMetrics, WorldState, step_world_mut};
 use std::sync::Arc;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread::{self, JoinHandle};
@@ -218,7 +218,7 @@ fn sim_thread_main(
             let elapsed = last_tick.elapsed();
             if elapsed >= delay {
                 // Run a tick
-                state = step_world(&state, &pending_inputs, None, &config, Some(&mut metrics));
+                step_world_mut(&mut state, &pending_inputs, None, &config, Some(&mut metrics));
                 pending_inputs.clear();
                 tick += 1;
                 last_tick = Instant::now();
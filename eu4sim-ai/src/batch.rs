@@ -0,0 +1,210 @@
+//! Batching LLM decisions across countries.
+//!
+//! The simulation asks every AI for its commands in parallel, one
+//! [`AiPlayer::decide`](eu4sim_core::ai::AiPlayer::decide) call per country.
+//! [`LlmBatcher`] owns a single model and shares it between [`LlmAi`] players:
+//! each `decide` submits its prompt and waits, and once every
+//! player has submitted (or the collection window runs out) one of the
+//! waiting threads runs the whole batch through
+//! [`Eu4AiModel::choose_multi_action_batch`].
+//!
+//! [`LlmAi`]: crate::LlmAi
+//!
+//! ```ignore
+//! let batcher = LlmBatcher::new(config, DEFAULT_COLLECT_WINDOW)?;
+//! for tag in great_powers {
+//!     ais.insert(tag, Box::new(LlmAi::with_batcher(&batcher)));
+//! }
+//! ```
+//!
+//! The window keeps a player from waiting forever when the others aren't
+//! called this tick (or the thread pool runs them one after another); a late
+//! player simply starts the next batch.
+
+use crate::grammar::ActionGrammar;
+use crate::model::{Eu4AiModel, ModelConfig};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a submitted prompt waits for the rest of the batch.
+pub const DEFAULT_COLLECT_WINDOW: Duration = Duration::from_millis(200);
+
+/// Shared model that runs prompts from several [`LlmAi`] players together.
+///
+/// [`LlmAi`]: crate::LlmAi
+pub struct LlmBatcher {
+    model: Mutex<Eu4AiModel>,
+    queue: Mutex<Queue>,
+    changed: Condvar,
+    window: Duration,
+}
+
+/// Requests waiting for a batch, and finished responses waiting for pickup.
+#[derive(Default)]
+struct Queue {
+    /// Live players sharing the batcher
+    members: usize,
+    pending: Vec<Request>,
+    /// Response (or error message) per ticket
+    results: HashMap<u64, std::result::Result<String, String>>,
+    next_ticket: u64,
+    /// A batch is on the model right now
+    running: bool,
+}
+
+struct Request {
+    ticket: u64,
+    prompt: String,
+    grammar: ActionGrammar,
+    max_tokens: usize,
+}
+
+impl LlmBatcher {
+    /// Load a model on the batched decoder.
+    pub fn new(config: ModelConfig, window: Duration) -> Result<Arc<Self>> {
+        let config = ModelConfig {
+            batched: true,
+            ..config
+        };
+        Ok(Self::with_model(Eu4AiModel::load(config)?, window))
+    }
+
+    /// Share an already loaded model.
+    pub fn with_model(model: Eu4AiModel, window: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            model: Mutex::new(model),
+            queue: Mutex::new(Queue::default()),
+            changed: Condvar::new(),
+            window,
+        })
+    }
+
+    /// Number of players currently sharing this batcher.
+    pub fn members(&self) -> usize {
+        self.queue.lock().unwrap().members
+    }
+
+    /// Submit a prompt and block until its batch has run.
+    ///
+    /// Returns the response and the time spent waiting plus generating.
+    pub(crate) fn generate(
+        &self,
+        prompt: &str,
+        grammar: &ActionGrammar,
+        max_tokens: usize,
+    ) -> Result<(String, u64)> {
+        let start = Instant::now();
+        let deadline = start + self.window;
+
+        let mut queue = self.queue.lock().unwrap();
+        let ticket = queue.next_ticket;
+        queue.next_ticket += 1;
+        queue.pending.push(Request {
+            ticket,
+            prompt: prompt.to_string(),
+            grammar: grammar.clone(),
+            max_tokens,
+        });
+        self.changed.notify_all();
+
+        loop {
+            if let Some(result) = queue.results.remove(&ticket) {
+                let elapsed_ms = start.elapsed().as_millis() as u64;
+                return result
+                    .map(|response| (response, elapsed_ms))
+                    .map_err(anyhow::Error::msg);
+            }
+
+            let waiting = queue.pending.iter().any(|r| r.ticket == ticket);
+            let full = queue.pending.len() >= queue.members;
+            if waiting && !queue.running && (full || Instant::now() >= deadline) {
+                // Lead this batch: take everything pending and run it unlocked
+                let batch = std::mem::take(&mut queue.pending);
+                queue.running = true;
+                drop(queue);
+
+                let outcome = self.run(&batch);
+
+                queue = self.queue.lock().unwrap();
+                queue.running = false;
+                match outcome {
+                    Ok(responses) => {
+                        for (request, response) in batch.iter().zip(responses) {
+                            queue.results.insert(request.ticket, Ok(response));
+                        }
+                    }
+                    Err(e) => {
+                        for request in &batch {
+                            queue
+                                .results
+                                .insert(request.ticket, Err(format!("{:#}", e)));
+                        }
+                    }
+                }
+                self.changed.notify_all();
+                continue;
+            }
+
+            queue = if waiting && !queue.running {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                self.changed.wait_timeout(queue, timeout).unwrap().0
+            } else {
+                self.changed.wait(queue).unwrap()
+            };
+        }
+    }
+
+    fn run(&self, batch: &[Request]) -> Result<Vec<String>> {
+        let requests: Vec<(&str, &ActionGrammar)> = batch
+            .iter()
+            .map(|r| (r.prompt.as_str(), &r.grammar))
+            .collect();
+        let max_tokens = batch.iter().map(|r| r.max_tokens).max().unwrap_or(0);
+        log::debug!("Running LLM batch of {}", batch.len());
+        self.model
+            .lock()
+            .unwrap()
+            .choose_multi_action_batch(&requests, max_tokens)
+    }
+
+    fn join(&self) {
+        self.queue.lock().unwrap().members += 1;
+    }
+
+    fn leave(&self) {
+        self.queue.lock().unwrap().members -= 1;
+        // A smaller batch may be full now
+        self.changed.notify_all();
+    }
+}
+
+/// One player's membership in an [`LlmBatcher`]; leaves on drop.
+pub(crate) struct BatchMember {
+    batcher: Arc<LlmBatcher>,
+}
+
+impl BatchMember {
+    pub(crate) fn new(batcher: &Arc<LlmBatcher>) -> Self {
+        batcher.join();
+        Self {
+            batcher: Arc::clone(batcher),
+        }
+    }
+
+    pub(crate) fn generate(
+        &self,
+        prompt: &str,
+        grammar: &ActionGrammar,
+        max_tokens: usize,
+    ) -> Result<(String, u64)> {
+        self.batcher.generate(prompt, grammar, max_tokens)
+    }
+}
+
+impl Drop for BatchMember {
+    fn drop(&mut self) {
+        self.batcher.leave();
+    }
+}
@@ -0,0 +1,478 @@
+//! Batched LLaMA decoder for running several prompts in one forward pass.
+//!
+//! candle's [`Llama`](candle_transformers::models::llama::Llama) puts every
+//! row of a batch at the same position and has no padding mask, so it can
+//! only batch prompts of identical length. [`BatchedLlama`] loads the same
+//! weights but tracks positions and a key mask per row, so prompts of
+//! different lengths can share a forward pass:
+//!
+//! - The longest common token prefix runs once, with batch size 1, and its
+//!   KV cache is shared by every row.
+//! - The remaining suffixes are left-padded to the same length. Padding
+//!   columns are masked out of attention and don't advance positions, so each
+//!   row computes exactly what it would alone.
+//! - Generation then feeds one token per row per step.
+
+use anyhow::{Context, Result};
+use candle_core::{DType, Device, IndexOp, Module, Tensor};
+use candle_nn::{Embedding, Linear, RmsNorm, VarBuilder, embedding, linear_no_bias, rms_norm};
+use candle_transformers::models::llama::Config;
+use candle_transformers::utils::repeat_kv;
+
+/// One transformer block.
+struct Layer {
+    input_norm: RmsNorm,
+    q_proj: Linear,
+    k_proj: Linear,
+    v_proj: Linear,
+    o_proj: Linear,
+    post_norm: RmsNorm,
+    gate_proj: Linear,
+    up_proj: Linear,
+    down_proj: Linear,
+}
+
+/// LLaMA-family decoder with per-row positions and padding masks.
+pub struct BatchedLlama {
+    embed: Embedding,
+    layers: Vec<Layer>,
+    norm: RmsNorm,
+    lm_head: Linear,
+    /// Rotary tables, (max_position_embeddings, head_dim / 2)
+    cos: Tensor,
+    sin: Tensor,
+    num_heads: usize,
+    num_kv_heads: usize,
+    head_dim: usize,
+    hidden_size: usize,
+    device: Device,
+}
+
+/// KV cache and per-row bookkeeping for one batch.
+pub struct BatchCache {
+    /// Per layer: keys and values, (batch, kv_heads, columns, head_dim)
+    kvs: Vec<Option<(Tensor, Tensor)>>,
+    /// Per row: is each cached column a real token (false = padding)?
+    valid: Vec<Vec<bool>>,
+    /// Per row: position of the next token
+    next_pos: Vec<usize>,
+}
+
+impl BatchCache {
+    fn new(num_layers: usize, batch: usize) -> Self {
+        Self {
+            kvs: vec![None; num_layers],
+            valid: vec![Vec::new(); batch],
+            next_pos: vec![0; batch],
+        }
+    }
+
+    /// Number of rows in the batch.
+    pub fn batch_size(&self) -> usize {
+        self.next_pos.len()
+    }
+
+    /// Copy a single-row cache (the shared prefix) into `batch` rows.
+    fn expand(self, batch: usize) -> Result<Self> {
+        let kvs = self
+            .kvs
+            .into_iter()
+            .map(|kv| {
+                kv.map(|(k, v)| -> Result<_> {
+                    Ok((
+                        Tensor::cat(&vec![&k; batch], 0)?,
+                        Tensor::cat(&vec![&v; batch], 0)?,
+                    ))
+                })
+                .transpose()
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            kvs,
+            valid: vec![self.valid[0].clone(); batch],
+            next_pos: vec![self.next_pos[0]; batch],
+        })
+    }
+}
+
+impl BatchedLlama {
+    /// Load from the same weights as candle's `Llama::load`.
+    pub fn load(vb: VarBuilder, config: &Config) -> Result<Self> {
+        if config.rope_scaling.is_some() {
+            anyhow::bail!("Batched decoder does not support rope scaling");
+        }
+
+        let head_dim = config.hidden_size / config.num_attention_heads;
+        let kv_size = head_dim * config.num_key_value_heads;
+        let embed = embedding(
+            config.vocab_size,
+            config.hidden_size,
+            vb.pp("model.embed_tokens"),
+        )?;
+        let lm_head = if config.tie_word_embeddings {
+            Linear::new(embed.embeddings().clone(), None)
+        } else {
+            linear_no_bias(config.hidden_size, config.vocab_size, vb.pp("lm_head"))?
+        };
+        let norm = rms_norm(config.hidden_size, config.rms_norm_eps, vb.pp("model.norm"))?;
+
+        let layers = (0..config.num_hidden_layers)
+            .map(|i| -> Result<Layer> {
+                let vb = vb.pp(format!("model.layers.{}", i));
+                let attn = vb.pp("self_attn");
+                let mlp = vb.pp("mlp");
+                let (h, m) = (config.hidden_size, config.intermediate_size);
+                Ok(Layer {
+                    input_norm: rms_norm(h, config.rms_norm_eps, vb.pp("input_layernorm"))?,
+                    q_proj: linear_no_bias(h, h, attn.pp("q_proj"))?,
+                    k_proj: linear_no_bias(h, kv_size, attn.pp("k_proj"))?,
+                    v_proj: linear_no_bias(h, kv_size, attn.pp("v_proj"))?,
+                    o_proj: linear_no_bias(h, h, attn.pp("o_proj"))?,
+                    post_norm: rms_norm(h, config.rms_norm_eps, vb.pp("post_attention_layernorm"))?,
+                    gate_proj: linear_no_bias(h, m, mlp.pp("gate_proj"))?,
+                    up_proj: linear_no_bias(h, m, mlp.pp("up_proj"))?,
+                    down_proj: linear_no_bias(m, h, mlp.pp("down_proj"))?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Same rotary tables as candle's llama Cache
+        let device = vb.device().clone();
+        let inv_freq: Vec<f32> = (0..head_dim)
+            .step_by(2)
+            .map(|i| 1f32 / config.rope_theta.powf(i as f32 / head_dim as f32))
+            .collect();
+        let half = inv_freq.len();
+        let inv_freq = Tensor::new(inv_freq, &device)?.reshape((1, half))?;
+        let freqs = Tensor::arange(0, config.max_position_embeddings as u32, &device)?
+            .to_dtype(DType::F32)?
+            .reshape((config.max_position_embeddings, 1))?
+            .matmul(&inv_freq)?;
+
+        Ok(Self {
+            embed,
+            layers,
+            norm,
+            lm_head,
+            cos: freqs.cos()?.to_dtype(vb.dtype())?,
+            sin: freqs.sin()?.to_dtype(vb.dtype())?,
+            num_heads: config.num_attention_heads,
+            num_kv_heads: config.num_key_value_heads,
+            head_dim,
+            hidden_size: config.hidden_size,
+            device,
+        })
+    }
+
+    /// Process whole prompts and return the logits of each row's last token,
+    /// (batch, vocab), plus the cache to continue generating from.
+    pub fn prefill(&self, prompts: &[Vec<u32>]) -> Result<(Tensor, BatchCache)> {
+        let batch = prompts.len();
+        let min_len = prompts.iter().map(Vec::len).min().unwrap_or(0);
+        if min_len == 0 {
+            anyhow::bail!("Batched prefill needs at least one non-empty prompt per row");
+        }
+
+        // Shared prefix, leaving every row at least one token of its own so
+        // the batched pass yields its logits
+        let shared = (0..min_len - 1)
+            .take_while(|&i| prompts.iter().all(|p| p[i] == prompts[0][i]))
+            .count();
+
+        let mut cache = if shared > 0 {
+            let mut cache = BatchCache::new(self.layers.len(), 1);
+            self.forward(
+                &[prompts[0][..shared].to_vec()],
+                &[(0..shared).collect()],
+                &[vec![true; shared]],
+                &mut cache,
+            )?;
+            cache.expand(batch)?
+        } else {
+            BatchCache::new(self.layers.len(), batch)
+        };
+
+        // Left-pad the suffixes so every row ends in the last column
+        let width = prompts.iter().map(|p| p.len() - shared).max().unwrap_or(0);
+        let mut ids = Vec::with_capacity(batch);
+        let mut positions = Vec::with_capacity(batch);
+        let mut valid = Vec::with_capacity(batch);
+        for prompt in prompts {
+            let suffix = &prompt[shared..];
+            let pad = width - suffix.len();
+            ids.push(
+                std::iter::repeat_n(suffix[0], pad)
+                    .chain(suffix.iter().copied())
+                    .collect::<Vec<_>>(),
+            );
+            positions.push(
+                std::iter::repeat_n(shared, pad)
+                    .chain(shared..prompt.len())
+                    .collect::<Vec<_>>(),
+            );
+            valid.push(
+                std::iter::repeat_n(false, pad)
+                    .chain(std::iter::repeat_n(true, suffix.len()))
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        let logits = self.forward(&ids, &positions, &valid, &mut cache)?;
+        cache.next_pos = prompts.iter().map(Vec::len).collect();
+        Ok((logits, cache))
+    }
+
+    /// Feed one token per row and return the next logits, (batch, vocab).
+    pub fn step(&self, tokens: &[u32], cache: &mut BatchCache) -> Result<Tensor> {
+        if tokens.len() != cache.batch_size() {
+            anyhow::bail!(
+                "Batched step got {} tokens for {} rows",
+                tokens.len(),
+                cache.batch_size()
+            );
+        }
+        let ids: Vec<Vec<u32>> = tokens.iter().map(|&t| vec![t]).collect();
+        let positions: Vec<Vec<usize>> = cache.next_pos.iter().map(|&p| vec![p]).collect();
+        let valid = vec![vec![true]; tokens.len()];
+        let logits = self.forward(&ids, &positions, &valid, cache)?;
+        for pos in &mut cache.next_pos {
+            *pos += 1;
+        }
+        Ok(logits)
+    }
+
+    /// Run `ids` (batch rows of equal width) through the model, appending to
+    /// `cache`, and return the last column's logits.
+    fn forward(
+        &self,
+        ids: &[Vec<u32>],
+        positions: &[Vec<usize>],
+        valid: &[Vec<bool>],
+        cache: &mut BatchCache,
+    ) -> Result<Tensor> {
+        let batch = ids.len();
+        let seq_len = ids[0].len();
+        let past = cache.valid[0].len();
+        let total = past + seq_len;
+
+        let input = Tensor::new(ids.concat(), &self.device)?.reshape((batch, seq_len))?;
+        let pos: Vec<u32> = positions.iter().flatten().map(|&p| p as u32).collect();
+        let pos = Tensor::new(pos, &self.device)?;
+        let half = self.head_dim / 2;
+        let cos = self
+            .cos
+            .index_select(&pos, 0)?
+            .reshape((batch, seq_len, half))?;
+        let sin = self
+            .sin
+            .index_select(&pos, 0)?
+            .reshape((batch, seq_len, half))?;
+
+        // Attention mask, 1 = blocked: causal, minus padding keys. Every
+        // column may see itself so padding rows never softmax over nothing.
+        for (row, new) in cache.valid.iter_mut().zip(valid) {
+            row.extend_from_slice(new);
+        }
+        let mut mask = Vec::with_capacity(batch * seq_len * total);
+        for row in &cache.valid {
+            for q in past..total {
+                mask.extend((0..total).map(|k| u8::from(k != q && (k > q || !row[k]))));
+            }
+        }
+        let mask = Tensor::from_vec(mask, (batch, 1, seq_len, total), &self.device)?;
+
+        let mut x = self.embed.forward(&input)?;
+        for (layer, kv) in self.layers.iter().zip(cache.kvs.iter_mut()) {
+            let residual = &x;
+            let h = layer.input_norm.forward(&x)?;
+
+            let q = layer
+                .q_proj
+                .forward(&h)?
+                .reshape((batch, seq_len, self.num_heads, self.head_dim))?
+                .transpose(1, 2)?
+                .contiguous()?;
+            let k = layer
+                .k_proj
+                .forward(&h)?
+                .reshape((batch, seq_len, self.num_kv_heads, self.head_dim))?
+                .transpose(1, 2)?
+                .contiguous()?;
+            let mut v = layer
+                .v_proj
+                .forward(&h)?
+                .reshape((batch, seq_len, self.num_kv_heads, self.head_dim))?
+                .transpose(1, 2)?;
+            let q = candle_nn::rotary_emb::rope(&q, &cos, &sin)?;
+            let mut k = candle_nn::rotary_emb::rope(&k, &cos, &sin)?;
+
+            if let Some((cache_k, cache_v)) = kv.as_ref() {
+                k = Tensor::cat(&[cache_k, &k], 2)?.contiguous()?;
+                v = Tensor::cat(&[cache_v, &v], 2)?.contiguous()?;
+            }
+            *kv = Some((k.clone(), v.clone()));
+
+            let n_rep = self.num_heads / self.num_kv_heads;
+            let k = repeat_kv(k, n_rep)?;
+            let v = repeat_kv(v, n_rep)?;
+
+            let in_dtype = q.dtype();
+            let q = q.to_dtype(DType::F32)?;
+            let k = k.to_dtype(DType::F32)?;
+            let v = v.to_dtype(DType::F32)?;
+            let att = (q.matmul(&k.t()?)? / (self.head_dim as f64).sqrt())?;
+            let blocked =
+                Tensor::new(f32::NEG_INFINITY, &self.device)?.broadcast_as(att.shape())?;
+            let att = mask.broadcast_as(att.shape())?.where_cond(&blocked, &att)?;
+            let att = candle_nn::ops::softmax_last_dim(&att)?;
+            let y = att
+                .matmul(&v.contiguous()?)?
+                .to_dtype(in_dtype)?
+                .transpose(1, 2)?
+                .reshape((batch, seq_len, self.hidden_size))?;
+            let x_attn = (layer.o_proj.forward(&y)? + residual)?;
+
+            let h = layer.post_norm.forward(&x_attn)?;
+            let mlp = (candle_nn::ops::silu(&layer.gate_proj.forward(&h)?)?
+                * layer.up_proj.forward(&h)?)?;
+            x = (layer.down_proj.forward(&mlp)? + &x_attn)?;
+        }
+
+        let x = self.norm.forward(&x)?;
+        let x = x.i((.., seq_len - 1, ..))?.contiguous()?;
+        self.lm_head
+            .forward(&x)?
+            .to_dtype(DType::F32)
+            .context("Batched forward pass failed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use candle_transformers::models::llama::{Cache, Llama};
+    use std::collections::HashMap;
+
+    /// Tiny random LLaMA, loaded both ways from the same weights.
+    fn tiny_models() -> (Llama, BatchedLlama, Config) {
+        let device = Device::Cpu;
+        let config = Config {
+            hidden_size: 32,
+            intermediate_size: 64,
+            vocab_size: 50,
+            num_hidden_layers: 2,
+            num_attention_heads: 4,
+            num_key_value_heads: 2,
+            use_flash_attn: false,
+            rms_norm_eps: 1e-5,
+            rope_theta: 10000.0,
+            bos_token_id: None,
+            eos_token_id: None,
+            rope_scaling: None,
+            max_position_embeddings: 64,
+            tie_word_embeddings: true,
+        };
+        let mut weights = HashMap::new();
+        let mut add = |name: String, shape: (usize, usize)| {
+            let t = Tensor::randn(0f32, 0.2, shape, &device).unwrap();
+            weights.insert(name, t);
+        };
+        add("model.embed_tokens.weight".into(), (50, 32));
+        for i in 0..2 {
+            let p = format!("model.layers.{}", i);
+            add(format!("{p}.self_attn.q_proj.weight"), (32, 32));
+            add(format!("{p}.self_attn.k_proj.weight"), (16, 32));
+            add(format!("{p}.self_attn.v_proj.weight"), (16, 32));
+            add(format!("{p}.self_attn.o_proj.weight"), (32, 32));
+            add(format!("{p}.mlp.gate_proj.weight"), (64, 32));
+            add(format!("{p}.mlp.up_proj.weight"), (64, 32));
+            add(format!("{p}.mlp.down_proj.weight"), (32, 64));
+        }
+        let mut norms = Vec::new();
+        for i in 0..2 {
+            norms.push(format!("model.layers.{}.input_layernorm.weight", i));
+            norms.push(format!(
+                "model.layers.{}.post_attention_layernorm.weight",
+                i
+            ));
+        }
+        norms.push("model.norm.weight".into());
+        for name in norms {
+            weights.insert(name, Tensor::ones(32, DType::F32, &device).unwrap());
+        }
+
+        let vb = VarBuilder::from_tensors(weights, DType::F32, &device);
+        let llama = Llama::load(vb.clone(), &config).unwrap();
+        let batched = BatchedLlama::load(vb, &config).unwrap();
+        (llama, batched, config)
+    }
+
+    /// Reference: candle's Llama on one sequence, prompt then each token.
+    fn reference(llama: &Llama, config: &Config, prompt: &[u32], next: &[u32]) -> Vec<Vec<f32>> {
+        let device = Device::Cpu;
+        let mut cache = Cache::new(true, DType::F32, config, &device).unwrap();
+        let input = Tensor::new(prompt, &device).unwrap().unsqueeze(0).unwrap();
+        let mut out = vec![llama.forward(&input, 0, &mut cache).unwrap()];
+        for (i, &tok) in next.iter().enumerate() {
+            let input = Tensor::new(&[tok], &device).unwrap().unsqueeze(0).unwrap();
+            out.push(llama.forward(&input, prompt.len() + i, &mut cache).unwrap());
+        }
+        out.iter()
+            .map(|t| t.squeeze(0).unwrap().to_vec1().unwrap())
+            .collect()
+    }
+
+    fn assert_close(a: &[f32], b: &[f32]) {
+        let diff = a
+            .iter()
+            .zip(b)
+            .map(|(x, y)| (x - y).abs())
+            .fold(0f32, f32::max);
+        assert!(diff < 1e-4, "max logit difference {}", diff);
+    }
+
+    #[test]
+    fn test_batch_matches_single_sequence() {
+        let (llama, batched, config) = tiny_models();
+        // Shared prefix [1, 2, 3], suffixes of different lengths
+        let prompts = vec![
+            vec![1, 2, 3, 10, 11, 12, 13],
+            vec![1, 2, 3, 20],
+            vec![1, 2, 3, 30, 31],
+        ];
+        let next = [[5, 6], [7, 8], [9, 4]];
+
+        let (logits, mut cache) = batched.prefill(&prompts).unwrap();
+        let mut steps = vec![logits.to_vec2::<f32>().unwrap()];
+        for i in 0..2 {
+            let tokens: Vec<u32> = next.iter().map(|n| n[i]).collect();
+            steps.push(
+                batched
+                    .step(&tokens, &mut cache)
+                    .unwrap()
+                    .to_vec2()
+                    .unwrap(),
+            );
+        }
+
+        for (row, prompt) in prompts.iter().enumerate() {
+            let expected = reference(&llama, &config, prompt, &next[row]);
+            for (step, logits) in steps.iter().enumerate() {
+                assert_close(&logits[row], &expected[step]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_identical_and_single_prompts() {
+        let (llama, batched, config) = tiny_models();
+        for prompts in [vec![vec![4, 5, 6]], vec![vec![4, 5, 6], vec![4, 5, 6]]] {
+            let (logits, _) = batched.prefill(&prompts).unwrap();
+            let expected = reference(&llama, &config, &prompts[0], &[]);
+            for row in logits.to_vec2::<f32>().unwrap() {
+                assert_close(&row, &expected[0]);
+            }
+        }
+        assert!(batched.prefill(&[vec![1], vec![]]).is_err());
+    }
+}
@@ -0,0 +1,397 @@
+//! Grammar for multi-action responses, used for constrained decoding.
+//!
+//! [`ActionGrammar`] describes exactly the responses that
+//! [`PromptBuilder::build_multi_action`](crate::prompt::PromptBuilder::build_multi_action)
+//! asks for: one line per category, in prompt order, each holding either
+//! `0` (pass) or distinct local indices of the commands listed for that
+//! category:
+//!
+//! ```text
+//! DIPLOMATIC:1
+//! MILITARY:1,3
+//! ECONOMIC:0
+//! TRADE:0
+//! COLONIZATION:0
+//! OTHER:0
+//! ```
+//!
+//! A single space is allowed after `:` and `,` (tokenizers like to merge it
+//! into the digit). Diplomatic lines take at most one index, matching the
+//! one-diplomatic-action-per-day rule.
+//!
+//! The grammar works on characters; [`allowed_tokens`] lifts it to a
+//! tokenizer vocabulary so the decoder can mask out every token that would
+//! leave the grammar.
+
+use crate::prompt::{CATEGORY_ORDER, category_name};
+use eu4sim_core::ai::CommandCategory;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One response line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LineSpec {
+    /// Header including the colon, e.g. `"MILITARY:"`
+    header: String,
+    /// Highest local index shown in the prompt (0 = only Pass)
+    max_index: usize,
+    /// Most indices the line may list
+    max_choices: usize,
+}
+
+/// The set of valid multi-action responses for one prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActionGrammar {
+    lines: Vec<LineSpec>,
+}
+
+/// Where the decoder is inside the response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrammarState {
+    line: usize,
+    phase: Phase,
+    /// Indices already listed on the current line
+    chosen: BTreeSet<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Phase {
+    /// Matched `n` characters of the header
+    Header(usize),
+    /// Expecting an index (after `:` or `,`); `spaced` once the optional space is used
+    Index { spaced: bool },
+    /// Inside an index
+    Number(String),
+    /// All lines complete
+    Done,
+}
+
+impl ActionGrammar {
+    /// Build the grammar from the prompt's local→global index map
+    /// ([`PromptBuilder::build_index_map`](crate::prompt::PromptBuilder::build_index_map)).
+    pub fn from_index_map(index_map: &BTreeMap<(CommandCategory, usize), usize>) -> Self {
+        let lines = CATEGORY_ORDER
+            .iter()
+            .map(|&category| {
+                let max_index = index_map
+                    .keys()
+                    .filter(|(c, _)| *c == category)
+                    .map(|&(_, local)| local)
+                    .max()
+                    .unwrap_or(0);
+                let max_choices = if category == CommandCategory::Diplomatic {
+                    1
+                } else {
+                    max_index.max(1)
+                };
+                LineSpec {
+                    header: format!("{}:", category_name(category)),
+                    max_index,
+                    max_choices,
+                }
+            })
+            .collect();
+        Self { lines }
+    }
+
+    /// State before the first character.
+    pub fn start(&self) -> GrammarState {
+        GrammarState {
+            line: 0,
+            phase: Phase::Header(0),
+            chosen: BTreeSet::new(),
+        }
+    }
+
+    /// Has the response ended?
+    pub fn is_complete(&self, state: &GrammarState) -> bool {
+        state.phase == Phase::Done
+    }
+
+    /// Indices still allowed on the current line.
+    fn candidates<'a>(&'a self, state: &'a GrammarState) -> impl Iterator<Item = usize> + 'a {
+        let spec = &self.lines[state.line];
+        // 0 (pass) only stands alone
+        let first = if state.chosen.is_empty() { 0 } else { 1 };
+        (first..=spec.max_index).filter(|i| !state.chosen.contains(i))
+    }
+
+    /// Feed one character; `None` if it leaves the grammar.
+    pub fn advance(&self, state: &GrammarState, c: char) -> Option<GrammarState> {
+        let mut next = state.clone();
+        match &state.phase {
+            Phase::Done => return None,
+            Phase::Header(pos) => {
+                let header = &self.lines[state.line].header;
+                if !header[*pos..].starts_with(c) {
+                    return None;
+                }
+                let pos = pos + c.len_utf8();
+                next.phase = if pos == header.len() {
+                    Phase::Index { spaced: false }
+                } else {
+                    Phase::Header(pos)
+                };
+            }
+            Phase::Index { spaced } => {
+                if c == ' ' && !spaced {
+                    next.phase = Phase::Index { spaced: true };
+                } else if c.is_ascii_digit() {
+                    let digits = c.to_string();
+                    if !self
+                        .candidates(state)
+                        .any(|i| i.to_string().starts_with(&digits))
+                    {
+                        return None;
+                    }
+                    next.phase = Phase::Number(digits);
+                } else {
+                    return None;
+                }
+            }
+            Phase::Number(digits) => {
+                let value: usize = digits.parse().ok()?;
+                let complete = self.candidates(state).any(|i| i == value);
+                if c.is_ascii_digit() {
+                    let extended = format!("{}{}", digits, c);
+                    if !self
+                        .candidates(state)
+                        .any(|i| i.to_string().starts_with(&extended))
+                    {
+                        return None;
+                    }
+                    next.phase = Phase::Number(extended);
+                } else if c == ',' {
+                    let spec = &self.lines[state.line];
+                    if !complete || value == 0 || state.chosen.len() + 1 >= spec.max_choices {
+                        return None;
+                    }
+                    next.chosen.insert(value);
+                    // A comma must leave some index to follow it
+                    self.candidates(&next).next()?;
+                    next.phase = Phase::Index { spaced: false };
+                } else if c == '\n' {
+                    if !complete {
+                        return None;
+                    }
+                    next.chosen.clear();
+                    next.line += 1;
+                    next.phase = if next.line == self.lines.len() {
+                        Phase::Done
+                    } else {
+                        Phase::Header(0)
+                    };
+                } else {
+                    return None;
+                }
+            }
+        }
+        Some(next)
+    }
+
+    /// Feed a whole string (e.g. one decoded token).
+    ///
+    /// Empty strings are rejected so the decoder can't stall on tokens that
+    /// decode to nothing.
+    pub fn advance_str(&self, state: &GrammarState, text: &str) -> Option<GrammarState> {
+        if text.is_empty() {
+            return None;
+        }
+        text.chars()
+            .try_fold(state.clone(), |state, c| self.advance(&state, c))
+    }
+
+    /// Shortest text that completes the response from `state` (passing on
+    /// every line not started yet). Used when the token budget runs out.
+    pub fn completion(&self, state: &GrammarState) -> String {
+        let mut out = String::new();
+        let mut line = state.line;
+        match &state.phase {
+            Phase::Done => return out,
+            Phase::Header(pos) => {
+                out.push_str(&self.lines[line].header[*pos..]);
+                out.push_str("0\n");
+            }
+            Phase::Index { .. } => {
+                let index = self.candidates(state).next().unwrap_or(0);
+                out.push_str(&format!("{}\n", index));
+            }
+            Phase::Number(digits) => {
+                let value: Option<usize> = digits.parse().ok();
+                if !self.candidates(state).any(|i| Some(i) == value) {
+                    // Finish the smallest index this prefix can still become
+                    if let Some(i) = self
+                        .candidates(state)
+                        .find(|i| i.to_string().starts_with(digits.as_str()))
+                    {
+                        out.push_str(&i.to_string()[digits.len()..]);
+                    }
+                }
+                out.push('\n');
+            }
+        }
+        line += 1;
+        for spec in &self.lines[line..] {
+            out.push_str(&spec.header);
+            out.push_str("0\n");
+        }
+        out
+    }
+}
+
+/// Characters any response can contain; tokens with other characters are
+/// never allowed, so the vocabulary is filtered once up front.
+pub fn in_alphabet(text: &str) -> bool {
+    !text.is_empty()
+        && text.chars().all(|c| {
+            c.is_ascii_uppercase() || c.is_ascii_digit() || matches!(c, ':' | ',' | ' ' | '\n')
+        })
+}
+
+/// Tokens from `vocab` (id, decoded text) that keep the response inside the
+/// grammar, with the state each one leads to.
+pub fn allowed_tokens<'a>(
+    grammar: &ActionGrammar,
+    state: &GrammarState,
+    vocab: &'a [(u32, String)],
+) -> Vec<(u32, &'a str, GrammarState)> {
+    vocab
+        .iter()
+        .filter_map(|(id, text)| {
+            grammar
+                .advance_str(state, text)
+                .map(|next| (*id, text.as_str(), next))
+        })
+        .collect()
+}
+
+/// Greedy constrained choice: the allowed token with the highest logit.
+pub fn pick_constrained<'a>(
+    grammar: &ActionGrammar,
+    state: &GrammarState,
+    vocab: &'a [(u32, String)],
+    logits: &[f32],
+) -> Option<(u32, &'a str, GrammarState)> {
+    let logit = |id: u32| {
+        logits
+            .get(id as usize)
+            .copied()
+            .unwrap_or(f32::NEG_INFINITY)
+    };
+    allowed_tokens(grammar, state, vocab)
+        .into_iter()
+        .max_by(|(a, _, _), (b, _, _)| logit(*a).total_cmp(&logit(*b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two diplomatic commands, three military, nothing else.
+    fn grammar() -> ActionGrammar {
+        let mut index_map = BTreeMap::new();
+        index_map.insert((CommandCategory::Diplomatic, 1), 0);
+        index_map.insert((CommandCategory::Diplomatic, 2), 1);
+        for local in 1..=3 {
+            index_map.insert((CommandCategory::Military, local), 1 + local);
+        }
+        ActionGrammar::from_index_map(&index_map)
+    }
+
+    fn accepts(grammar: &ActionGrammar, text: &str) -> bool {
+        grammar
+            .advance_str(&grammar.start(), text)
+            .is_some_and(|state| grammar.is_complete(&state))
+    }
+
+    #[test]
+    fn test_grammar_accepts_only_valid_responses() {
+        let g = grammar();
+        let pass = "ECONOMIC:0\nTRADE:0\nCOLONIZATION:0\nOTHER:0\n";
+        assert!(accepts(
+            &g,
+            &format!("DIPLOMATIC:1\nMILITARY:1,3\n{}", pass)
+        ));
+        assert!(accepts(
+            &g,
+            &format!("DIPLOMATIC: 0\nMILITARY:3, 2,1\n{}", pass)
+        ));
+
+        // Out of range, repeated, pass mixed with actions, two diplomatic actions
+        for bad in [
+            "DIPLOMATIC:3\n",
+            "DIPLOMATIC:1\nMILITARY:1,1\n",
+            "DIPLOMATIC:1\nMILITARY:0,1\n",
+            "DIPLOMATIC:1,2\n",
+            "DIPLOMATIC:01\n",
+            "MILITARY:1\n",
+            "DIPLOMATIC:1\nMILITARY:1\nECONOMIC:1\n",
+        ] {
+            assert!(g.advance_str(&g.start(), bad).is_none(), "{:?}", bad);
+        }
+        // Valid but unfinished
+        assert!(!accepts(&g, "DIPLOMATIC:1\nMILITARY:"));
+    }
+
+    #[test]
+    fn test_two_digit_indices() {
+        let mut index_map = BTreeMap::new();
+        for local in 1..=10 {
+            index_map.insert((CommandCategory::Economic, local), local);
+        }
+        let g = ActionGrammar::from_index_map(&index_map);
+        let state = g
+            .advance_str(&g.start(), "DIPLOMATIC:0\nMILITARY:0\nECONOMIC:10,1")
+            .unwrap();
+        // "1" is complete now that 10 is taken... and could not become 1x
+        assert!(g.advance(&state, '0').is_none());
+        assert!(g.advance(&state, ',').is_some());
+        assert!(accepts(
+            &g,
+            "DIPLOMATIC:0\nMILITARY:0\nECONOMIC:1,10\nTRADE:0\nCOLONIZATION:0\nOTHER:0\n"
+        ));
+    }
+
+    #[test]
+    fn test_completion_always_finishes_the_response() {
+        let g = grammar();
+        for prefix in [
+            "",
+            "DIPLO",
+            "DIPLOMATIC:",
+            "DIPLOMATIC:1\nMILITARY:2,",
+            "DIPLOMATIC:2",
+        ] {
+            let state = g
+                .advance_str(&g.start(), prefix)
+                .unwrap_or_else(|| g.start());
+            let rest = g.completion(&state);
+            assert!(accepts(&g, &format!("{}{}", prefix, rest)), "{:?}", prefix);
+        }
+    }
+
+    #[test]
+    fn test_masking_overrides_the_model() {
+        let g = grammar();
+        let vocab: Vec<(u32, String)> = ["DIPLOMATIC", ":", "1", "9", "\n", "Hello", "MIL", ":1"]
+            .iter()
+            .enumerate()
+            .map(|(id, text)| (id as u32, text.to_string()))
+            .filter(|(_, text)| in_alphabet(text))
+            .collect();
+        assert_eq!(vocab.len(), 7);
+
+        // The model loves "9" (invalid index); masking must pick "1" instead
+        let logits = [0.0, 0.0, 1.0, 5.0, 0.0, 9.0, 0.0, 0.0];
+        let state = g.advance_str(&g.start(), "DIPLOMATIC:").unwrap();
+        let (token, _, _) = pick_constrained(&g, &state, &vocab, &logits).unwrap();
+        assert_eq!(token, 2);
+
+        // Multi-character tokens are checked as a whole
+        let state = g.advance_str(&g.start(), "DIPLOMATIC:1\n").unwrap();
+        let allowed: Vec<u32> = allowed_tokens(&g, &state, &vocab)
+            .into_iter()
+            .map(|(id, _, _)| id)
+            .collect();
+        assert_eq!(allowed, vec![6]);
+    }
+}
@@ -13,6 +13,20 @@
 //! - **Gemma-3-270M**: Google's compact model (6T tokens, 32K context)
 //! - **Gemma 2**: Google's larger instruction-tuned model
 //!
+//! ## Batching
+//!
+//! [`LlmBatcher`] shares one model between several [`LlmAi`] players and runs
+//! their prompts for a tick as a single batch (LLaMA-family models only;
+//! Gemma3 runs them one by one).
+//!
+//! ## Offline Models
+//!
+//! `base_model` may be a local directory holding `config.json`,
+//! `tokenizer.json` and `model.safetensors` instead of a HuggingFace repo ID;
+//! with `HF_HUB_OFFLINE=1` repo IDs resolve from the local HuggingFace cache
+//! only. [`testing::write_tiny_model`] generates a tiny random checkpoint for
+//! running the full inference path on CPU in tests.
+//!
 //! ## LoRA Adapters
 //!
 //! Train custom adapters using the scripts in `scripts/`.
@@ -39,14 +53,20 @@
 //! cargo run -p eu4sim --release -- --observer --llm-ai bridge --ticks 100
 //! ```
 
+pub mod batch;
+pub mod batched_llama;
 pub mod bridge;
 pub mod device;
+pub mod grammar;
 pub mod llm_ai;
 pub mod model;
 pub mod prompt;
+pub mod testing;
 
+pub use batch::{DEFAULT_COLLECT_WINDOW, LlmBatcher};
 pub use bridge::{BridgeClient, BridgeServer, DEFAULT_HOST, DEFAULT_PORT};
 pub use device::{DevicePreference, cuda_available, select_device};
+pub use grammar::ActionGrammar;
 pub use llm_ai::{InferenceBackend, LlmAi, LlmMessage};
 pub use model::{Eu4AiModel, ModelConfig};
 pub use prompt::PromptBuilder;
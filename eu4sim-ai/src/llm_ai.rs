@@ -3,13 +3,16 @@
 //! Wraps inference backends (Candle or Bridge) to implement the AiPlayer trait,
 //! allowing trained language models to control countries in the simulation.
 
+use crate::batch::{BatchMember, LlmBatcher};
 use crate::bridge::{BridgeClient, BridgeServer};
+use crate::grammar::ActionGrammar;
 use crate::model::{Eu4AiModel, ModelConfig};
 use crate::prompt::PromptBuilder;
 use anyhow::Result;
 use eu4sim_core::Command;
 use eu4sim_core::ai::{AiPlayer, AvailableCommands, VisibleWorldState};
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::sync::mpsc::Sender;
 
 /// Inference backend selection.
@@ -44,20 +47,32 @@ enum ModelBackend {
     Bridge(BridgeClient),
     /// Managed server (auto-spawned, killed on drop)
     AutoBridge(BridgeServer),
+    /// Candle model shared with other players through an [`LlmBatcher`]
+    Batched(BatchMember),
 }
 
 impl ModelBackend {
     /// Run inference and return generated text.
-    fn generate(&mut self, prompt: &str, max_tokens: usize) -> Result<(String, u64)> {
+    ///
+    /// In-process models decode under `grammar`, so their output always
+    /// parses; bridge servers generate unconstrained text.
+    fn generate(
+        &mut self,
+        prompt: &str,
+        grammar: &ActionGrammar,
+        max_tokens: usize,
+    ) -> Result<(String, u64)> {
         match self {
             ModelBackend::Candle(model) => {
                 let start = std::time::Instant::now();
-                let response = model.choose_multi_action(prompt, max_tokens)?;
+                let response =
+                    model.choose_multi_action_constrained(prompt, grammar, max_tokens)?;
                 let inference_ms = start.elapsed().as_millis() as u64;
                 Ok((response, inference_ms))
             }
             ModelBackend::Bridge(client) => client.generate(prompt, max_tokens),
             ModelBackend::AutoBridge(server) => server.generate(prompt, max_tokens),
+            ModelBackend::Batched(member) => member.generate(prompt, grammar, max_tokens),
         }
     }
 }
@@ -138,6 +153,7 @@ impl LlmAi {
     ///
     /// # Arguments
     /// * `base_model` - HuggingFace model ID (e.g., "HuggingFaceTB/SmolLM2-360M", "google/gemma-3-270m")
+    ///   or local model directory
     /// * `adapter_path` - Path to LoRA adapter directory (optional)
     pub fn new(base_model: &str, adapter_path: Option<PathBuf>) -> Result<Self> {
         Self::with_backend(InferenceBackend::Candle {
@@ -171,6 +187,26 @@ impl LlmAi {
         Self::with_backend(InferenceBackend::AutoBridge { adapter_path })
     }
 
+    /// Create with an already loaded model (Candle backend).
+    pub fn with_model(model: Eu4AiModel) -> Self {
+        Self {
+            backend: ModelBackend::Candle(model),
+            prompt_builder: PromptBuilder::new(),
+            tui_tx: None,
+        }
+    }
+
+    /// Create a player that shares `batcher`'s model with other players.
+    ///
+    /// Decisions from all such players in a tick run as one batch.
+    pub fn with_batcher(batcher: &Arc<LlmBatcher>) -> Self {
+        Self {
+            backend: ModelBackend::Batched(BatchMember::new(batcher)),
+            prompt_builder: PromptBuilder::new(),
+            tui_tx: None,
+        }
+    }
+
     /// Set a sender for TUI display of LLM I/O.
     pub fn with_tui_sender(mut self, tx: Sender<LlmMessage>) -> Self {
         self.tui_tx = Some(tx);
@@ -427,7 +463,8 @@ impl AiPlayer for LlmAi {
         );
 
         // Run inference (up to 50 tokens) with timing
-        match self.backend.generate(prompt, 50) {
+        let grammar = ActionGrammar::from_index_map(&index_map);
+        match self.backend.generate(prompt, &grammar, 50) {
             Ok((response, inference_ms)) => {
                 let commands =
                     Self::parse_multi_action_response(&response, available_commands, &index_map);
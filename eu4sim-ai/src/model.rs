@@ -3,7 +3,9 @@
 //! Loads SmolLM2, Gemma-3, or Gemma-2 base models from HuggingFace Hub,
 //! applies LoRA adapters, and runs inference.
 
+use crate::batched_llama::{BatchCache, BatchedLlama};
 use crate::device::{DevicePreference, select_device};
+use crate::grammar::{self, ActionGrammar};
 use anyhow::{Context, Result};
 use candle_core::{DType, Device, IndexOp, Tensor};
 use candle_nn::VarBuilder;
@@ -17,6 +19,7 @@ use rand::Rng;
 use safetensors::SafeTensors;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokenizers::Tokenizer;
 
 /// Internal model representation supporting multiple architectures.
@@ -32,6 +35,12 @@ enum ModelInner {
     },
     /// Gemma 3 architecture (different cache handling)
     Gemma3 { model: gemma3::Model },
+    /// LLaMA-compatible models on the batched decoder
+    Batched {
+        model: BatchedLlama,
+        cache: Option<BatchCache>,
+        config: Config,
+    },
 }
 
 /// Supported model architectures.
@@ -76,10 +85,20 @@ impl ModelArch {
     }
 }
 
+/// Files every base model needs, locally or on the Hub.
+const BASE_MODEL_FILES: [&str; 3] = ["config.json", "tokenizer.json", "model.safetensors"];
+
+/// Resolve each of [`BASE_MODEL_FILES`] to a path.
+fn base_model_files(get: impl Fn(&str) -> Result<PathBuf>) -> Result<[PathBuf; 3]> {
+    let [config, tokenizer, weights] = BASE_MODEL_FILES;
+    Ok([get(config)?, get(tokenizer)?, get(weights)?])
+}
+
 /// Configuration for model loading.
 #[derive(Debug, Clone)]
 pub struct ModelConfig {
-    /// HuggingFace repo ID for base model (e.g., "HuggingFaceTB/SmolLM2-360M")
+    /// HuggingFace repo ID for base model (e.g., "HuggingFaceTB/SmolLM2-360M"),
+    /// or a local directory with config.json, tokenizer.json and model.safetensors
     pub base_model: String,
     /// Path to LoRA adapter directory (contains adapter_model.safetensors)
     pub adapter_path: PathBuf,
@@ -87,6 +106,10 @@ pub struct ModelConfig {
     pub device_pref: DevicePreference,
     /// Data type for model weights (F32 for CPU compatibility)
     pub dtype: DType,
+    /// Load LLaMA-compatible models on the batched decoder, so
+    /// [`Eu4AiModel::choose_multi_action_batch`] runs prompts together.
+    /// Gemma3 has no batched decoder and ignores this.
+    pub batched: bool,
 }
 
 impl Default for ModelConfig {
@@ -96,6 +119,7 @@ impl Default for ModelConfig {
             adapter_path: PathBuf::new(),
             device_pref: DevicePreference::default(),
             dtype: DType::F32,
+            batched: false,
         }
     }
 }
@@ -113,7 +137,7 @@ pub struct LoraConfig {
 /// Gemma3 config as it appears in HuggingFace config.json.
 /// Handles field name differences from candle's expected format.
 #[derive(Debug, Clone, serde::Deserialize)]
-struct Gemma3ConfigJson {
+pub(crate) struct Gemma3ConfigJson {
     pub attention_bias: bool,
     pub head_dim: usize,
     pub hidden_activation: String,
@@ -137,7 +161,7 @@ struct Gemma3ConfigJson {
 
 impl Gemma3ConfigJson {
     /// Convert to candle's gemma3::Config.
-    fn into_candle_config(self) -> gemma3::Config {
+    pub(crate) fn into_candle_config(self) -> gemma3::Config {
         use candle_nn::Activation;
 
         // Map activation string to enum
@@ -181,13 +205,16 @@ pub struct Eu4AiModel {
     device: Device,
     dtype: DType,
     arch: ModelArch,
+    /// Decoded tokens usable in multi-action responses (built on first
+    /// constrained generation)
+    grammar_vocab: Option<Arc<Vec<(u32, String)>>>,
 }
 
 impl Eu4AiModel {
     /// Load a model with LoRA adapter.
     ///
-    /// Downloads the base model from HuggingFace Hub if not cached,
-    /// then applies the LoRA adapter weights.
+    /// Reads the base model from a local directory, or downloads it from
+    /// HuggingFace Hub if not cached, then applies the LoRA adapter weights.
     pub fn load(config: ModelConfig) -> Result<Self> {
         let load_start = std::time::Instant::now();
 
@@ -197,28 +224,8 @@ impl Eu4AiModel {
 
         log::info!("Loading base model: {}", config.base_model);
 
-        // Download base model files from HuggingFace
-        // Use HF_TOKEN from environment for authenticated access to gated models (e.g., Gemma)
-        let api = if let Ok(token) = std::env::var("HF_TOKEN") {
-            log::info!("Using HF_TOKEN for authenticated access");
-            ApiBuilder::new()
-                .with_token(Some(token))
-                .build()
-                .context("Failed to create authenticated HuggingFace API")?
-        } else {
-            Api::new().context("Failed to create HuggingFace API")?
-        };
-        let repo = api.repo(Repo::new(config.base_model.clone(), RepoType::Model));
-
-        let config_path = repo
-            .get("config.json")
-            .context("Failed to download config.json")?;
-        let tokenizer_path = repo
-            .get("tokenizer.json")
-            .context("Failed to download tokenizer.json")?;
-        let weights_path = repo
-            .get("model.safetensors")
-            .context("Failed to download model.safetensors")?;
+        let [config_path, tokenizer_path, weights_path] =
+            Self::resolve_base_files(&config.base_model)?;
 
         // Detect architecture
         let arch = ModelArch::from_config(&config_path)?;
@@ -259,16 +266,33 @@ impl Eu4AiModel {
                     unsafe { VarBuilder::from_mmaped_safetensors(&[weights_path], dtype, &device)? }
                 };
 
-                let model = Llama::load(vb, &model_config).context("Failed to load Llama model")?;
-                let cache = Cache::new(true, dtype, &model_config, &device)?;
-
-                ModelInner::Llama {
-                    model,
-                    cache,
-                    config: model_config,
+                if config.batched {
+                    let model = BatchedLlama::load(vb, &model_config)
+                        .context("Failed to load batched Llama model")?;
+                    ModelInner::Batched {
+                        model,
+                        cache: None,
+                        config: model_config,
+                    }
+                } else {
+                    let model =
+                        Llama::load(vb, &model_config).context("Failed to load Llama model")?;
+                    let cache = Cache::new(true, dtype, &model_config, &device)?;
+
+                    ModelInner::Llama {
+                        model,
+                        cache,
+                        config: model_config,
+                    }
                 }
             }
             ModelArch::Gemma3 => {
+                if config.batched {
+                    log::warn!(
+                        "Gemma3 has no batched decoder; batched prompts will run one by one"
+                    );
+                }
+
                 // Gemma 3 loading path (different architecture)
                 // Use custom deserializer to handle HuggingFace config format differences
                 let gemma_config_json: Gemma3ConfigJson =
@@ -317,6 +341,55 @@ impl Eu4AiModel {
             device,
             dtype,
             arch,
+            grammar_vocab: None,
+        })
+    }
+
+    /// Locate `config.json`, `tokenizer.json` and `model.safetensors`.
+    ///
+    /// `base_model` is either a local directory holding those files (no
+    /// network access at all) or a HuggingFace repo ID, downloaded on first
+    /// use. With `HF_HUB_OFFLINE=1` repo IDs are only looked up in the local
+    /// HuggingFace cache.
+    fn resolve_base_files(base_model: &str) -> Result<[PathBuf; 3]> {
+        let dir = Path::new(base_model);
+        if dir.is_dir() {
+            log::info!("Loading base model from local directory {:?}", dir);
+            return base_model_files(|file| {
+                let path = dir.join(file);
+                anyhow::ensure!(path.is_file(), "Missing {} in {:?}", file, dir);
+                Ok(path)
+            });
+        }
+
+        if std::env::var("HF_HUB_OFFLINE").is_ok_and(|v| v == "1") {
+            let repo = hf_hub::Cache::default().model(base_model.to_string());
+            return base_model_files(|file| {
+                repo.get(file).with_context(|| {
+                    format!(
+                        "{} for {} is not in the HuggingFace cache (HF_HUB_OFFLINE=1)",
+                        file, base_model
+                    )
+                })
+            });
+        }
+
+        // Download base model files from HuggingFace
+        // Use HF_TOKEN from environment for authenticated access to gated models (e.g., Gemma)
+        let api = if let Ok(token) = std::env::var("HF_TOKEN") {
+            log::info!("Using HF_TOKEN for authenticated access");
+            ApiBuilder::new()
+                .with_token(Some(token))
+                .build()
+                .context("Failed to create authenticated HuggingFace API")?
+        } else {
+            Api::new().context("Failed to create HuggingFace API")?
+        };
+        let repo = api.repo(Repo::new(base_model.to_string(), RepoType::Model));
+
+        base_model_files(|file| {
+            repo.get(file)
+                .with_context(|| format!("Failed to download {}", file))
         })
     }
 
@@ -568,6 +641,7 @@ impl Eu4AiModel {
                     .forward(&input_ids, 0)
                     .context("Gemma3 forward pass failed")?
             }
+            ModelInner::Batched { model, .. } => model.prefill(&[tokens.to_vec()])?.0,
         };
 
         // The model returns [batch, vocab] for the last position already
@@ -686,83 +760,7 @@ impl Eu4AiModel {
 
         // Autoregressive generation
         for step in 0..max_tokens {
-            // Convert to tensor
-            let input_ids = Tensor::new(tokens.as_slice(), &self.device)?.unsqueeze(0)?;
-
-            // Forward pass
-            let logits = match &mut self.inner {
-                ModelInner::Llama {
-                    model,
-                    cache,
-                    config,
-                } => {
-                    // For incremental generation, use position = tokens.len() - 1
-                    // and persistent cache
-                    if step == 0 {
-                        // First step: create fresh cache
-                        *cache = Cache::new(true, self.dtype, config, &self.device)
-                            .context("Failed to create KV cache")?;
-                        model
-                            .forward(&input_ids, 0, cache)
-                            .context("LLaMA forward pass failed")?
-                    } else {
-                        // Incremental: only process last token
-                        let last_token =
-                            Tensor::new(&[tokens[tokens.len() - 1]], &self.device)?.unsqueeze(0)?;
-                        model
-                            .forward(&last_token, tokens.len() - 1, cache)
-                            .context("LLaMA forward pass failed")?
-                    }
-                }
-                ModelInner::Gemma3 { model } => {
-                    // Gemma3 manages cache internally
-                    if step == 0 {
-                        model.clear_kv_cache();
-                        // First step: process all tokens
-                        match model.forward(&input_ids, 0) {
-                            Ok(logits) => logits,
-                            Err(e) => {
-                                // Clear cache on error to avoid corrupted state
-                                model.clear_kv_cache();
-                                return Err(e).with_context(|| {
-                                    format!(
-                                        "Gemma3 forward failed: step={}, seq_len={}, input_shape={:?}",
-                                        step,
-                                        tokens.len(),
-                                        input_ids.dims()
-                                    )
-                                });
-                            }
-                        }
-                    } else {
-                        // Incremental: only process last token
-                        let last_token =
-                            Tensor::new(&[tokens[tokens.len() - 1]], &self.device)?.unsqueeze(0)?;
-                        match model.forward(&last_token, tokens.len() - 1) {
-                            Ok(logits) => logits,
-                            Err(e) => {
-                                // Clear cache on error to avoid corrupted state
-                                model.clear_kv_cache();
-                                return Err(e).with_context(|| {
-                                    format!(
-                                        "Gemma3 incremental forward failed: step={}, pos={}",
-                                        step,
-                                        tokens.len() - 1
-                                    )
-                                });
-                            }
-                        }
-                    }
-                }
-            };
-
-            // Extract logits for last position
-            let logits = if logits.dims().len() == 3 {
-                let seq_len = logits.dim(1)?;
-                logits.i((.., seq_len - 1, ..))?.squeeze(0)?
-            } else {
-                logits.squeeze(0)?
-            };
+            let logits = self.forward_step(&tokens, step)?;
 
             // Sample next token (greedy for determinism)
             let next_token = self.sample_greedy(&logits)?;
@@ -791,6 +789,274 @@ impl Eu4AiModel {
         Ok(generated_text)
     }
 
+    /// Generate a multi-action response that is guaranteed to match `grammar`.
+    ///
+    /// Same greedy decoding as [`choose_multi_action`](Self::choose_multi_action),
+    /// but each step only considers tokens that keep the response inside the
+    /// grammar, so every line parses into a listed command or a pass. If
+    /// `max_tokens` runs out first, the response is finished with passes.
+    pub fn choose_multi_action_constrained(
+        &mut self,
+        prompt: &str,
+        grammar: &ActionGrammar,
+        max_tokens: usize,
+    ) -> Result<String> {
+        let infer_start = std::time::Instant::now();
+
+        let encoding = self
+            .tokenizer
+            .encode(prompt, true)
+            .map_err(|e| anyhow::anyhow!("Tokenization failed: {}", e))?;
+        let mut tokens = encoding.get_ids().to_vec();
+
+        if tokens.is_empty() {
+            anyhow::bail!("Tokenization produced empty sequence");
+        }
+
+        let vocab = self.grammar_vocab();
+        let mut state = grammar.start();
+        let mut generated_text = String::new();
+        let mut generated_tokens = 0;
+
+        for step in 0..max_tokens {
+            if grammar.is_complete(&state) {
+                break;
+            }
+
+            let logits: Vec<f32> = self
+                .forward_step(&tokens, step)?
+                .to_dtype(DType::F32)?
+                .to_vec1()?;
+
+            // No token continues the grammar (tokenizer lacks a needed piece)
+            let Some((next_token, text, next_state)) =
+                grammar::pick_constrained(grammar, &state, &vocab, &logits)
+            else {
+                break;
+            };
+
+            tokens.push(next_token);
+            generated_text.push_str(text);
+            generated_tokens += 1;
+            state = next_state;
+        }
+
+        if !grammar.is_complete(&state) {
+            let rest = grammar.completion(&state);
+            log::debug!(
+                "Constrained generation stopped after {} tokens, completing with {:?}",
+                generated_tokens,
+                rest
+            );
+            generated_text.push_str(&rest);
+        }
+
+        log::debug!(
+            "LLM generated {} chars in {:.0}ms ({} tokens, constrained)",
+            generated_text.len(),
+            infer_start.elapsed().as_secs_f64() * 1000.0,
+            generated_tokens
+        );
+
+        Ok(generated_text)
+    }
+
+    /// Generate constrained multi-action responses for several prompts at once.
+    ///
+    /// With the batched decoder (see [`ModelConfig::batched`]) all prompts
+    /// share each forward pass and their common token prefix is computed
+    /// once. Otherwise they run one after another. Either way each response
+    /// is what [`choose_multi_action_constrained`](Self::choose_multi_action_constrained)
+    /// would produce for its prompt alone.
+    pub fn choose_multi_action_batch(
+        &mut self,
+        requests: &[(&str, &ActionGrammar)],
+        max_tokens: usize,
+    ) -> Result<Vec<String>> {
+        if requests.len() < 2 || !matches!(self.inner, ModelInner::Batched { .. }) {
+            return requests
+                .iter()
+                .map(|(prompt, grammar)| {
+                    self.choose_multi_action_constrained(prompt, grammar, max_tokens)
+                })
+                .collect();
+        }
+
+        let infer_start = std::time::Instant::now();
+        let prompts = requests
+            .iter()
+            .map(|(prompt, _)| {
+                let encoding = self
+                    .tokenizer
+                    .encode(*prompt, true)
+                    .map_err(|e| anyhow::anyhow!("Tokenization failed: {}", e))?;
+                Ok(encoding.get_ids().to_vec())
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let vocab = self.grammar_vocab();
+        let ModelInner::Batched { model, .. } = &self.inner else {
+            unreachable!("checked above");
+        };
+
+        let mut states: Vec<_> = requests.iter().map(|(_, g)| g.start()).collect();
+        let mut texts = vec![String::new(); requests.len()];
+        // Rows that finished or can't continue; they keep feeding their last
+        // token so the batch stays rectangular, and their logits are ignored
+        let mut done = vec![false; requests.len()];
+        let mut last: Vec<u32> = prompts.iter().map(|p| p[p.len() - 1]).collect();
+
+        let (mut logits, mut cache) = model.prefill(&prompts)?;
+        for _ in 0..max_tokens {
+            let rows: Vec<Vec<f32>> = logits.to_vec2()?;
+            for (row, (_, grammar)) in requests.iter().enumerate() {
+                if done[row] {
+                    continue;
+                }
+                match grammar::pick_constrained(grammar, &states[row], &vocab, &rows[row]) {
+                    Some((token, text, next)) => {
+                        texts[row].push_str(text);
+                        last[row] = token;
+                        done[row] = grammar.is_complete(&next);
+                        states[row] = next;
+                    }
+                    None => done[row] = true,
+                }
+            }
+            if done.iter().all(|&d| d) {
+                break;
+            }
+            logits = model.step(&last, &mut cache)?;
+        }
+
+        for (row, (_, grammar)) in requests.iter().enumerate() {
+            if !grammar.is_complete(&states[row]) {
+                texts[row].push_str(&grammar.completion(&states[row]));
+            }
+        }
+
+        log::debug!(
+            "LLM batch of {} generated in {:.0}ms",
+            requests.len(),
+            infer_start.elapsed().as_secs_f64() * 1000.0
+        );
+
+        Ok(texts)
+    }
+
+    /// Tokens that can appear in a multi-action response, decoded once per model.
+    fn grammar_vocab(&mut self) -> Arc<Vec<(u32, String)>> {
+        let tokenizer = &self.tokenizer;
+        self.grammar_vocab
+            .get_or_insert_with(|| {
+                let vocab: Vec<(u32, String)> = (0..tokenizer.get_vocab_size(true) as u32)
+                    .filter_map(|id| {
+                        let text = tokenizer.decode(&[id], false).ok()?;
+                        grammar::in_alphabet(&text).then_some((id, text))
+                    })
+                    .collect();
+                log::debug!("Grammar vocabulary: {} tokens", vocab.len());
+                Arc::new(vocab)
+            })
+            .clone()
+    }
+
+    /// Run one generation step and return the logits for the last position.
+    ///
+    /// Step 0 processes the whole sequence with a fresh KV cache; later steps
+    /// feed only the newest token.
+    fn forward_step(&mut self, tokens: &[u32], step: usize) -> Result<Tensor> {
+        // Convert to tensor
+        let input_ids = Tensor::new(tokens, &self.device)?.unsqueeze(0)?;
+
+        // Forward pass
+        let logits = match &mut self.inner {
+            ModelInner::Llama {
+                model,
+                cache,
+                config,
+            } => {
+                // For incremental generation, use position = tokens.len() - 1
+                // and persistent cache
+                if step == 0 {
+                    // First step: create fresh cache
+                    *cache = Cache::new(true, self.dtype, config, &self.device)
+                        .context("Failed to create KV cache")?;
+                    model
+                        .forward(&input_ids, 0, cache)
+                        .context("LLaMA forward pass failed")?
+                } else {
+                    // Incremental: only process last token
+                    let last_token =
+                        Tensor::new(&[tokens[tokens.len() - 1]], &self.device)?.unsqueeze(0)?;
+                    model
+                        .forward(&last_token, tokens.len() - 1, cache)
+                        .context("LLaMA forward pass failed")?
+                }
+            }
+            ModelInner::Gemma3 { model } => {
+                // Gemma3 manages cache internally
+                if step == 0 {
+                    model.clear_kv_cache();
+                    // First step: process all tokens
+                    match model.forward(&input_ids, 0) {
+                        Ok(logits) => logits,
+                        Err(e) => {
+                            // Clear cache on error to avoid corrupted state
+                            model.clear_kv_cache();
+                            return Err(e).with_context(|| {
+                                format!(
+                                    "Gemma3 forward failed: step={}, seq_len={}, input_shape={:?}",
+                                    step,
+                                    tokens.len(),
+                                    input_ids.dims()
+                                )
+                            });
+                        }
+                    }
+                } else {
+                    // Incremental: only process last token
+                    let last_token =
+                        Tensor::new(&[tokens[tokens.len() - 1]], &self.device)?.unsqueeze(0)?;
+                    match model.forward(&last_token, tokens.len() - 1) {
+                        Ok(logits) => logits,
+                        Err(e) => {
+                            // Clear cache on error to avoid corrupted state
+                            model.clear_kv_cache();
+                            return Err(e).with_context(|| {
+                                format!(
+                                    "Gemma3 incremental forward failed: step={}, pos={}",
+                                    step,
+                                    tokens.len() - 1
+                                )
+                            });
+                        }
+                    }
+                }
+            }
+            ModelInner::Batched { model, cache, .. } => {
+                if step == 0 {
+                    let (logits, fresh) = model.prefill(&[tokens.to_vec()])?;
+                    *cache = Some(fresh);
+                    logits
+                } else {
+                    let cache = cache
+                        .as_mut()
+                        .context("Batched cache missing after step 0")?;
+                    model.step(&[tokens[tokens.len() - 1]], cache)?
+                }
+            }
+        };
+
+        // Extract logits for last position
+        if logits.dims().len() == 3 {
+            let seq_len = logits.dim(1)?;
+            Ok(logits.i((.., seq_len - 1, ..))?.squeeze(0)?)
+        } else {
+            Ok(logits.squeeze(0)?)
+        }
+    }
+
     /// Check if multi-action response is complete (all 6 categories present).
     fn is_multi_action_complete(&self, text: &str) -> bool {
         let required = [
@@ -829,7 +1095,7 @@ impl Eu4AiModel {
     /// Get the model config (LLaMA only, returns None for Gemma3).
     pub fn llama_config(&self) -> Option<&Config> {
         match &self.inner {
-            ModelInner::Llama { config, .. } => Some(config),
+            ModelInner::Llama { config, .. } | ModelInner::Batched { config, .. } => Some(config),
             ModelInner::Gemma3 { .. } => None,
         }
     }
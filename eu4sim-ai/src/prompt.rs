@@ -138,17 +138,7 @@ impl PromptBuilder {
         // Actions section grouped by category
         self.buffer.push_str("<|actions|>\n");
 
-        // All 6 categories in order
-        let all_categories = [
-            CommandCategory::Diplomatic,
-            CommandCategory::Military,
-            CommandCategory::Economic,
-            CommandCategory::Trade,
-            CommandCategory::Colonization,
-            CommandCategory::Other,
-        ];
-
-        for category in all_categories {
+        for category in CATEGORY_ORDER {
             writeln!(self.buffer, "{}:", category_name(category)).unwrap();
             self.buffer.push_str("  0: Pass\n");
 
@@ -178,7 +168,7 @@ impl PromptBuilder {
 
         // Response template
         self.buffer.push_str("<|choice|>\n");
-        for category in all_categories {
+        for category in CATEGORY_ORDER {
             writeln!(self.buffer, "{}:", category_name(category)).unwrap();
         }
 
@@ -232,8 +222,18 @@ impl PromptBuilder {
     }
 }
 
+/// All 6 categories, in the order the multi-action prompt lists them.
+pub(crate) const CATEGORY_ORDER: [CommandCategory; 6] = [
+    CommandCategory::Diplomatic,
+    CommandCategory::Military,
+    CommandCategory::Economic,
+    CommandCategory::Trade,
+    CommandCategory::Colonization,
+    CommandCategory::Other,
+];
+
 /// Get display name for a command category.
-fn category_name(cat: CommandCategory) -> &'static str {
+pub(crate) fn category_name(cat: CommandCategory) -> &'static str {
     match cat {
         CommandCategory::Diplomatic => "DIPLOMATIC",
         CommandCategory::Military => "MILITARY",
@@ -0,0 +1,159 @@
+//! Tiny randomly-initialized checkpoints for offline tests.
+//!
+//! [`write_tiny_model`] writes `config.json`, `tokenizer.json` and
+//! `model.safetensors` into a directory that [`Eu4AiModel::load`] accepts as
+//! `base_model`, so the whole LLM path can run on CPU without network access:
+//!
+//! ```ignore
+//! let dir = tempfile::tempdir()?;
+//! write_tiny_model(dir.path(), ModelArch::SmolLM2)?;
+//! let ai = LlmAi::new(dir.path().to_str().unwrap(), None)?;
+//! ```
+//!
+//! The tokenizer is character-level (printable ASCII plus newline), and the
+//! weights are random, so the model's choices are arbitrary. Constrained
+//! decoding still keeps every response inside the action grammar.
+//!
+//! [`Eu4AiModel::load`]: crate::Eu4AiModel::load
+
+use crate::model::{Gemma3ConfigJson, ModelArch};
+use anyhow::{Context, Result};
+use candle_core::{DType, Device};
+use candle_nn::{VarBuilder, VarMap};
+use candle_transformers::models::gemma3;
+use candle_transformers::models::llama::{Llama, LlamaConfig};
+use serde_json::{Value, json};
+use std::path::Path;
+
+/// Token for characters outside the tiny vocabulary
+const UNK_TOKEN: &str = "<unk>";
+
+/// Long enough for a character-level prompt of a large country
+const MAX_POSITION_EMBEDDINGS: usize = 8192;
+
+/// Write a tiny random checkpoint for `arch` into `dir` (created if missing).
+pub fn write_tiny_model(dir: &Path, arch: ModelArch) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+
+    let vocab = tiny_vocab();
+    let config = tiny_config(arch, vocab.len());
+
+    std::fs::write(
+        dir.join("tokenizer.json"),
+        serde_json::to_string_pretty(&tokenizer_json(&vocab))?,
+    )
+    .context("Failed to write tokenizer.json")?;
+    std::fs::write(
+        dir.join("config.json"),
+        serde_json::to_string_pretty(&config)?,
+    )
+    .context("Failed to write config.json")?;
+
+    // Building the model through a VarMap creates every weight it asks for
+    let varmap = VarMap::new();
+    let vb = VarBuilder::from_varmap(&varmap, DType::F32, &Device::Cpu);
+    match arch {
+        ModelArch::SmolLM2 | ModelArch::Gemma2 => {
+            let llama_config: LlamaConfig = serde_json::from_value(config)?;
+            Llama::load(vb, &llama_config.into_config(false))?;
+        }
+        ModelArch::Gemma3 => {
+            let gemma_config: Gemma3ConfigJson = serde_json::from_value(config)?;
+            gemma3::Model::new(false, &gemma_config.into_candle_config(), vb)?;
+        }
+    }
+    varmap
+        .save(dir.join("model.safetensors"))
+        .context("Failed to write model.safetensors")?;
+
+    Ok(())
+}
+
+/// `<unk>`, newline and printable ASCII, one token each.
+fn tiny_vocab() -> Vec<String> {
+    let mut vocab = vec![UNK_TOKEN.to_string(), "\n".to_string()];
+    vocab.extend((' '..='~').map(String::from));
+    vocab
+}
+
+/// HuggingFace `config.json` for a two-layer model of `arch`.
+fn tiny_config(arch: ModelArch, vocab_size: usize) -> Value {
+    match arch {
+        ModelArch::SmolLM2 | ModelArch::Gemma2 => json!({
+            "model_type": if arch == ModelArch::Gemma2 { "gemma2" } else { "llama" },
+            "hidden_size": 64,
+            "intermediate_size": 128,
+            "vocab_size": vocab_size,
+            "num_hidden_layers": 2,
+            "num_attention_heads": 4,
+            "num_key_value_heads": 2,
+            "rms_norm_eps": 1e-5,
+            "rope_theta": 10000.0,
+            "max_position_embeddings": MAX_POSITION_EMBEDDINGS,
+            "tie_word_embeddings": true,
+        }),
+        ModelArch::Gemma3 => json!({
+            "model_type": "gemma3_text",
+            "attention_bias": false,
+            "head_dim": 16,
+            "hidden_activation": "gelu_pytorch_tanh",
+            "hidden_size": 64,
+            "intermediate_size": 128,
+            "num_attention_heads": 4,
+            "num_hidden_layers": 2,
+            // Like gemma-3-270m; candle's Gemma3 KV cache needs contiguous
+            // values, which it only gets with a single KV head
+            "num_key_value_heads": 1,
+            "rms_norm_eps": 1e-6,
+            "rope_theta": 1_000_000.0,
+            "rope_local_base_freq": 10000.0,
+            "vocab_size": vocab_size,
+            "final_logit_softcapping": null,
+            "attn_logit_softcapping": null,
+            "query_pre_attn_scalar": 16,
+            "sliding_window": 512,
+            "_sliding_window_pattern": 2,
+            "max_position_embeddings": MAX_POSITION_EMBEDDINGS,
+        }),
+    }
+}
+
+/// Character-level `tokenizer.json`: a BPE model with no merges.
+fn tokenizer_json(vocab: &[String]) -> Value {
+    let vocab: serde_json::Map<String, Value> = vocab
+        .iter()
+        .enumerate()
+        .map(|(id, token)| (token.clone(), json!(id)))
+        .collect();
+
+    json!({
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [{
+            "id": 0,
+            "content": UNK_TOKEN,
+            "single_word": false,
+            "lstrip": false,
+            "rstrip": false,
+            "normalized": false,
+            "special": true,
+        }],
+        "normalizer": null,
+        "pre_tokenizer": null,
+        "post_processor": null,
+        "decoder": { "type": "Fuse" },
+        "model": {
+            "type": "BPE",
+            "dropout": null,
+            "unk_token": UNK_TOKEN,
+            "continuing_subword_prefix": null,
+            "end_of_word_suffix": null,
+            "fuse_unk": false,
+            "byte_fallback": false,
+            "ignore_merges": false,
+            "vocab": vocab,
+            "merges": [],
+        },
+    })
+}
@@ -0,0 +1,158 @@
+//! End-to-end LLM tests on tiny random checkpoints.
+//!
+//! These tests run offline on CPU (CI-safe). They verify that:
+//! 1. Generated checkpoints load from a local directory
+//! 2. `LlmAi::decide` only returns offered commands, for every architecture
+//! 3. The batched decoder completes every prompt in a batch and serves
+//!    several players from one model
+
+use candle_core::DType;
+use eu4sim_ai::model::ModelArch;
+use eu4sim_ai::testing::write_tiny_model;
+use eu4sim_ai::{
+    ActionGrammar, DevicePreference, Eu4AiModel, LlmAi, LlmBatcher, ModelConfig, PromptBuilder,
+};
+use eu4sim_core::ai::{AiPlayer, AvailableCommands, VisibleWorldState};
+use eu4sim_core::input::{Command, DevType};
+use eu4sim_core::state::{Date, TechType};
+use std::path::Path;
+use std::time::Duration;
+
+fn cpu_config(dir: &Path) -> ModelConfig {
+    ModelConfig {
+        base_model: dir.to_str().unwrap().to_string(),
+        device_pref: DevicePreference::CpuOnly,
+        dtype: DType::F32,
+        ..Default::default()
+    }
+}
+
+fn visible_state(tag: &str) -> VisibleWorldState {
+    VisibleWorldState {
+        date: Date::new(1444, 11, 11),
+        observer: tag.to_string(),
+        ..Default::default()
+    }
+}
+
+fn available_commands() -> AvailableCommands {
+    vec![
+        Command::BuyTech {
+            tech_type: TechType::Adm,
+        },
+        Command::BuyTech {
+            tech_type: TechType::Mil,
+        },
+        Command::DevelopProvince {
+            province: 183,
+            dev_type: DevType::Tax,
+        },
+        Command::OfferAlliance {
+            target: "CAS".to_string(),
+        },
+        Command::Pass,
+    ]
+}
+
+fn assert_offered(commands: &[Command], available: &AvailableCommands) {
+    for command in commands {
+        assert!(available.contains(command), "{:?} was not offered", command);
+    }
+}
+
+fn decide_with(arch: ModelArch) {
+    let dir = tempfile::tempdir().unwrap();
+    write_tiny_model(dir.path(), arch).unwrap();
+
+    let mut model = Eu4AiModel::load(cpu_config(dir.path())).unwrap();
+    let available = available_commands();
+
+    // decide() passes on inference errors, so check generation directly too
+    let grammar = ActionGrammar::from_index_map(&PromptBuilder::build_index_map(&available));
+    let response = model
+        .choose_multi_action_constrained("<|country|>FRA\n<|choice|>", &grammar, 50)
+        .unwrap();
+    let state = grammar.advance_str(&grammar.start(), &response).unwrap();
+    assert!(grammar.is_complete(&state), "incomplete: {:?}", response);
+
+    let mut ai = LlmAi::with_model(model);
+    let commands = ai.decide(&visible_state("FRA"), &available);
+    assert_offered(&commands, &available);
+}
+
+#[test]
+fn test_tiny_llama_decides_offline() {
+    decide_with(ModelArch::SmolLM2);
+}
+
+#[test]
+fn test_tiny_gemma3_decides_offline() {
+    decide_with(ModelArch::Gemma3);
+}
+
+#[test]
+fn test_missing_local_file_is_reported() {
+    let dir = tempfile::tempdir().unwrap();
+    write_tiny_model(dir.path(), ModelArch::SmolLM2).unwrap();
+    std::fs::remove_file(dir.path().join("tokenizer.json")).unwrap();
+
+    let err = Eu4AiModel::load(cpu_config(dir.path()))
+        .err()
+        .expect("load should fail without tokenizer.json");
+    assert!(format!("{:#}", err).contains("tokenizer.json"));
+}
+
+#[test]
+fn test_tiny_llama_batched_decoder() {
+    let dir = tempfile::tempdir().unwrap();
+    write_tiny_model(dir.path(), ModelArch::SmolLM2).unwrap();
+
+    let config = ModelConfig {
+        batched: true,
+        ..cpu_config(dir.path())
+    };
+    let mut model = Eu4AiModel::load(config).unwrap();
+
+    // Different prompt lengths and grammars within one batch
+    let available = available_commands();
+    let full = ActionGrammar::from_index_map(&PromptBuilder::build_index_map(&available));
+    let pass_only =
+        ActionGrammar::from_index_map(&PromptBuilder::build_index_map(&[Command::Pass]));
+    let requests = [
+        ("<|country|>FRA\n<|choice|>", &full),
+        ("<|country|>ENG\nAt peace\n<|choice|>", &full),
+        ("<|country|>CAS\n<|choice|>", &pass_only),
+    ];
+
+    let responses = model.choose_multi_action_batch(&requests, 50).unwrap();
+    assert_eq!(responses.len(), requests.len());
+    for ((_, grammar), response) in requests.iter().zip(&responses) {
+        let state = grammar.advance_str(&grammar.start(), response).unwrap();
+        assert!(grammar.is_complete(&state), "incomplete: {:?}", response);
+    }
+}
+
+#[test]
+fn test_tiny_llama_batched_players() {
+    let dir = tempfile::tempdir().unwrap();
+    write_tiny_model(dir.path(), ModelArch::SmolLM2).unwrap();
+
+    let batcher = LlmBatcher::new(cpu_config(dir.path()), Duration::from_secs(5)).unwrap();
+    let players: Vec<(&str, LlmAi)> = ["FRA", "ENG"]
+        .into_iter()
+        .map(|tag| (tag, LlmAi::with_batcher(&batcher)))
+        .collect();
+    assert_eq!(batcher.members(), 2);
+
+    let available = available_commands();
+    std::thread::scope(|s| {
+        for (tag, mut ai) in players {
+            let available = &available;
+            s.spawn(move || {
+                let commands = ai.decide(&visible_state(tag), available);
+                assert_offered(&commands, available);
+            });
+        }
+    });
+    assert_eq!(batcher.members(), 0);
+}
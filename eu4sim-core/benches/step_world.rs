@@ -0,0 +1,142 @@
+//! Throughput comparison between the two `step_world` entry points.
+//!
+//! Runs the same synthetic world twice:
+//! - **clone**: `state = step_world(&state, ..)` plus a snapshot every tick
+//!   (how the headless loop used to drive the simulation)
+//! - **in-place**: `step_world_mut(&mut state, ..)` with snapshots only on
+//!   ticks where a registered observer is due (monthly here)
+//!
+//! Results are reported through [`SimMetrics::years_per_second`] so they are
+//! directly comparable with `eu4sim --benchmark`.
+//!
+//! ```text
+//! cargo bench -p eu4sim-core --bench step_world -- 20
+//! ```
+
+use eu4sim_core::testing::WorldStateBuilder;
+use eu4sim_core::{
+    step_world, step_world_mut, ObserverConfig, ObserverError, ObserverRegistry, SimConfig,
+    SimMetrics, SimObserver, Snapshot, WorldState,
+};
+use std::time::Instant;
+
+const COUNTRIES: u32 = 64;
+const PROVINCES_PER_COUNTRY: u32 = 16;
+const DAYS_PER_YEAR: u32 = 360;
+
+/// Observer that only wants month-start snapshots and does nothing with them.
+struct MonthlyObserver;
+
+impl SimObserver for MonthlyObserver {
+    fn on_tick(&self, snapshot: &Snapshot) -> Result<(), ObserverError> {
+        std::hint::black_box(snapshot.state.date);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "MonthlyObserver"
+    }
+
+    fn config(&self) -> ObserverConfig {
+        ObserverConfig {
+            frequency: DAYS_PER_YEAR,
+            notify_on_month_start: true,
+        }
+    }
+}
+
+fn build_world() -> WorldState {
+    let mut builder = WorldStateBuilder::new().date(1444, 11, 11);
+    for c in 0..COUNTRIES {
+        let tag = format!("T{:02}", c);
+        builder = builder.with_country(&tag);
+        for p in 0..PROVINCES_PER_COUNTRY {
+            builder = builder.with_province(c * PROVINCES_PER_COUNTRY + p + 1, Some(&tag));
+        }
+    }
+    builder.build()
+}
+
+fn run_clone(initial: &WorldState, days: u32, registry: &ObserverRegistry) -> SimMetrics {
+    let config = SimConfig::default();
+    let mut metrics = SimMetrics::default();
+    let mut state = initial.clone();
+    let start = Instant::now();
+
+    for tick in 1..=days as u64 {
+        state = step_world(&state, &[], None, &config, Some(&mut metrics));
+        let observer_start = Instant::now();
+        let snapshot = Snapshot::new(state.clone(), tick, 0);
+        registry.notify(&snapshot);
+        metrics.observer_time += observer_start.elapsed();
+    }
+
+    metrics.wall_time = start.elapsed();
+    std::hint::black_box(state.checksum());
+    metrics
+}
+
+fn run_in_place(initial: &WorldState, days: u32, registry: &ObserverRegistry) -> SimMetrics {
+    let config = SimConfig::default();
+    let mut metrics = SimMetrics::default();
+    let mut state = initial.clone();
+    let start = Instant::now();
+
+    for tick in 1..=days as u64 {
+        step_world_mut(&mut state, &[], None, &config, Some(&mut metrics));
+        if registry.wants_snapshot(tick, state.date) {
+            let observer_start = Instant::now();
+            let snapshot = Snapshot::new(state.clone(), tick, 0);
+            registry.notify(&snapshot);
+            metrics.observer_time += observer_start.elapsed();
+        }
+    }
+
+    metrics.wall_time = start.elapsed();
+    std::hint::black_box(state.checksum());
+    metrics
+}
+
+fn report(label: &str, metrics: &SimMetrics, years: f64) -> f64 {
+    let wall_years_per_sec = years / metrics.wall_time.as_secs_f64().max(f64::EPSILON);
+    println!(
+        "{:<9} step: {:>8.2} years/sec | wall: {:>8.2} years/sec | tick avg {:.3}ms | observers {:.2}s",
+        label,
+        metrics.years_per_second(years),
+        wall_years_per_sec,
+        metrics.tick_avg_ms(),
+        metrics.observer_time.as_secs_f64(),
+    );
+    wall_years_per_sec
+}
+
+fn main() {
+    // `cargo bench` passes `--bench`; the first numeric argument is the year count.
+    let years: u32 = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(10);
+    let days = years * DAYS_PER_YEAR;
+
+    let world = build_world();
+    let mut registry = ObserverRegistry::new();
+    registry.register(Box::new(MonthlyObserver));
+
+    println!(
+        "=== step_world benchmark: {} countries, {} provinces, {} years ===",
+        COUNTRIES,
+        COUNTRIES * PROVINCES_PER_COUNTRY,
+        years
+    );
+
+    let clone = run_clone(&world, days, &registry);
+    let in_place = run_in_place(&world, days, &registry);
+
+    let years = years as f64;
+    let clone_speed = report("clone", &clone, years);
+    let in_place_speed = report("in-place", &in_place, years);
+    println!(
+        "Speedup: {:.2}x",
+        in_place_speed / clone_speed.max(f64::EPSILON)
+    );
+}
@@ -1,5 +1,6 @@
 use crate::ai::{
-    categorize_command, AiPlayer, AvailableCommands, CommandCategory, VisibleWorldState,
+    categorize_command, AiPersonality, AiPlayer, AvailableCommands, CommandCategory,
+    RelationsSummary, VisibleWorldState,
 };
 use crate::input::{Command, DevType};
 use crate::state::TechType;
@@ -27,21 +28,106 @@ impl GreedyAI {
     const SCORE_HONOR_ALLIANCE: i32 = 1500;
     const SCORE_DEVELOP_PROVINCE: i32 = 100;
     const SCORE_PEACE_TAKE_PROVINCE: i32 = 1000;
+    const SCORE_ACCEPT_OFFER: i32 = 800;
+    const SCORE_REJECT_OFFER: i32 = 700;
+    const SCORE_CALM_COALITION_MEMBER: i32 = 400;
+
+    // Strategic goal bonuses (see `crate::ai::StrategicGoals`)
+    const SCORE_GOAL_ALLIANCE: i32 = 600;
+    const BONUS_GOAL_WAR_TARGET: i32 = 500;
+    const BONUS_GOAL_TARGET_PROVINCE: i32 = 300;
+    const BONUS_GOAL_BUILDING: i32 = 100;
+    const SCORE_GOAL_IMPROVE_RELATIONS: i32 = 300;
+    const SCORE_GOAL_FABRICATE_CLAIM: i32 = 250;
+    const BONUS_CLAIM_CB: i32 = 200;
 
     // Penalties
     const PENALTY_COALITION: i32 = -2000;
     const PENALTY_UNSAFE_WAR: i32 = -1000;
     const PENALTY_ATTRITION: i32 = -1000;
     const PENALTY_MANA_SAVING: i32 = -1000;
+    const PENALTY_WOULD_BE_REFUSED: i32 = -100;
 
     pub fn new() -> Self {
         Self
     }
 
-    /// Scores a command based on immediate heuristic value.
+    /// Scores a command: immediate heuristic value, nudged towards the
+    /// country's strategic goals and weighted by its ruler's personality.
     ///
     /// Higher scores are prioritized. 0 or negative scores are ignored.
     fn score_command(&self, cmd: &Command, state: &VisibleWorldState) -> i32 {
+        let base = self.heuristic_score(cmd, state);
+        let goals = &state.strategic_goals;
+        let (score, priority) = match cmd {
+            Command::DeclareWar { target, cb } => {
+                let wanted =
+                    goals.war_targets.contains(target) || goals.rivals_to_contain.contains(target);
+                let mut bonus = if base > 0 && wanted {
+                    Self::BONUS_GOAL_WAR_TARGET
+                } else {
+                    0
+                };
+                // Claims avoid the no-CB stability hit and cheapen the peace deal
+                if base > 0 && cb.as_deref() == Some(crate::systems::claims::CLAIM_CB) {
+                    bonus += Self::BONUS_CLAIM_CB;
+                }
+                (base + bonus, Some(AiPersonality::WAR))
+            }
+            // Fabricate claims on planned conquests, best targets first
+            Command::FabricateClaim { province } => {
+                match goals.target_provinces.iter().position(|p| p == province) {
+                    Some(rank) => (
+                        Self::SCORE_GOAL_FABRICATE_CLAIM - 10 * rank as i32,
+                        Some(AiPersonality::WAR),
+                    ),
+                    None => (base, None),
+                }
+            }
+            Command::OfferAlliance { target }
+                if base > 0 && goals.alliance_targets.contains(target) =>
+            {
+                (Self::SCORE_GOAL_ALLIANCE, Some(AiPersonality::ALLY))
+            }
+            Command::OfferAlliance { .. } => (base, Some(AiPersonality::ALLY)),
+            Command::Move { destination, .. }
+                if base > 0 && state.at_war && goals.target_provinces.contains(destination) =>
+            {
+                (base + Self::BONUS_GOAL_TARGET_PROVINCE, None)
+            }
+            Command::BuildInProvince { province, .. } => {
+                let bonus = if base > 0 && goals.building_plan.contains(province) {
+                    Self::BONUS_GOAL_BUILDING
+                } else {
+                    0
+                };
+                (base + bonus, Some(AiPersonality::BUILDING))
+            }
+            // Win over alliance targets that would refuse us today
+            Command::ImproveRelations { target }
+                if goals.alliance_targets.contains(target)
+                    && state
+                        .relations
+                        .get(target)
+                        .is_some_and(|r| r.they_accept_alliance <= 0) =>
+            {
+                (
+                    Self::SCORE_GOAL_IMPROVE_RELATIONS,
+                    Some(AiPersonality::ALLY),
+                )
+            }
+            Command::DevelopProvince { .. } => (base, Some(AiPersonality::DEVELOPMENT)),
+            Command::StartColony { .. } => (base, Some(AiPersonality::COLONIZATION)),
+            _ => (base, None),
+        };
+        match (&state.personality, priority) {
+            (Some(personality), Some(key)) => personality.weigh(key, score),
+            _ => score,
+        }
+    }
+
+    /// Immediate heuristic value of a command, ignoring goals and personality.
+    fn heuristic_score(&self, cmd: &Command, state: &VisibleWorldState) -> i32 {
         match cmd {
             // Tier 0: Survival / Peace (Losing)
             Command::AcceptPeace { war_id } => {
@@ -340,6 +426,51 @@ impl GreedyAI {
                     50 // Other buildings
                 }
             }
+            // Diplomacy - don't send offers that will be refused, answer by acceptance
+            Command::OfferAlliance { target } => {
+                Self::unless_refused(state, target, |r| r.they_accept_alliance)
+            }
+            Command::OfferRoyalMarriage { target } => {
+                Self::unless_refused(state, target, |r| r.they_accept_marriage)
+            }
+            Command::RequestMilitaryAccess { target } => {
+                Self::unless_refused(state, target, |r| r.they_accept_access)
+            }
+            Command::AcceptAlliance { from } => {
+                Self::answer_offer(state, from, true, |r| r.we_accept_alliance)
+            }
+            Command::RejectAlliance { from } => {
+                Self::answer_offer(state, from, false, |r| r.we_accept_alliance)
+            }
+            Command::AcceptRoyalMarriage { from } => {
+                Self::answer_offer(state, from, true, |r| r.we_accept_marriage)
+            }
+            Command::RejectRoyalMarriage { from } => {
+                Self::answer_offer(state, from, false, |r| r.we_accept_marriage)
+            }
+            Command::GrantMilitaryAccess { to } => {
+                Self::answer_offer(state, to, true, |r| r.we_accept_access)
+            }
+            Command::DenyMilitaryAccess { to } => {
+                Self::answer_offer(state, to, false, |r| r.we_accept_access)
+            }
+            Command::ImproveRelations { .. } => 5, // Idle diplomats only
+            Command::Insult { .. } => -100,        // Nothing to gain yet
+            // Calm down coalition members first
+            Command::ReduceAggressiveExpansion { target } => {
+                if state
+                    .coalition_against_us
+                    .as_ref()
+                    .is_some_and(|members| members.contains(target))
+                {
+                    Self::SCORE_CALM_COALITION_MEMBER
+                } else {
+                    15
+                }
+            }
+            Command::RecallDiplomat { .. } => -200, // Let missions run their course
+            Command::FabricateClaim { .. } => -10,  // Only for planned conquests
+
             Command::CancelConstruction { .. } => -100, // Only cancel if desperate
             Command::DemolishBuilding { .. } => -500,   // Almost never demolish
 
@@ -349,6 +480,39 @@ impl GreedyAI {
     }
 }
 
+impl GreedyAI {
+    /// Default score for an offer, or a penalty if the target would refuse it.
+    fn unless_refused(
+        state: &VisibleWorldState,
+        target: &str,
+        acceptance: impl Fn(&RelationsSummary) -> i32,
+    ) -> i32 {
+        match state.relations.get(target) {
+            Some(r) if acceptance(r) <= 0 => Self::PENALTY_WOULD_BE_REFUSED,
+            _ => 10,
+        }
+    }
+
+    /// Score accepting (`accept`) or rejecting an offer from `from`.
+    ///
+    /// Without relations data both answers score the default.
+    fn answer_offer(
+        state: &VisibleWorldState,
+        from: &str,
+        accept: bool,
+        acceptance: impl Fn(&RelationsSummary) -> i32,
+    ) -> i32 {
+        let Some(relations) = state.relations.get(from) else {
+            return 10;
+        };
+        match (accept, acceptance(relations) > 0) {
+            (true, true) => Self::SCORE_ACCEPT_OFFER,
+            (false, false) => Self::SCORE_REJECT_OFFER,
+            _ => -100,
+        }
+    }
+}
+
 impl AiPlayer for GreedyAI {
     fn name(&self) -> &'static str {
         "GreedyAI"
@@ -448,6 +612,9 @@ mod tests {
             our_army_sizes: std::collections::HashMap::new(),
             our_army_provinces: std::collections::HashMap::new(),
             staging_provinces: HashSet::new(),
+            personality: None,
+            strategic_goals: Default::default(),
+            relations: Default::default(),
         }
     }
 
@@ -676,4 +843,140 @@ mod tests {
         // Should declare war (strong + no coalition risk)
         assert_eq!(score, 2000);
     }
+
+    // =========================================================================
+    // Personality & Strategic Goal Tests
+    // =========================================================================
+
+    #[test]
+    fn test_greedy_courts_alliance_targets() {
+        let mut ai = GreedyAI::new();
+        let mut state = dummy_state();
+        let offer_dan = Command::OfferAlliance {
+            target: "DAN".to_string(),
+        };
+        let offer_nor = Command::OfferAlliance {
+            target: "NOR".to_string(),
+        };
+        assert_eq!(ai.score_command(&offer_dan, &state), 10);
+
+        state.strategic_goals.alliance_targets = vec!["DAN".to_string()];
+        let decisions = ai.decide(&state, &vec![offer_nor, offer_dan.clone()]);
+        assert_eq!(decisions, vec![offer_dan]);
+    }
+
+    #[test]
+    fn test_greedy_goals_only_boost_viable_wars() {
+        let ai = GreedyAI::new();
+        let mut state = dummy_state();
+        state.strategic_goals.war_targets = vec!["DAN".to_string()];
+        state.known_country_strength.insert("SWE".to_string(), 30);
+        state.known_country_strength.insert("DAN".to_string(), 10);
+        let war = Command::DeclareWar {
+            target: "DAN".to_string(),
+            cb: None,
+        };
+        assert_eq!(ai.score_command(&war, &state), 2500);
+
+        // Too strong to attack: the goal doesn't override the penalty
+        state.known_country_strength.insert("DAN".to_string(), 40);
+        assert_eq!(ai.score_command(&war, &state), -1000);
+    }
+
+    #[test]
+    fn test_greedy_fabricates_claims_on_planned_conquests() {
+        let mut ai = GreedyAI::new();
+        let mut state = dummy_state();
+        let claim = |province| Command::FabricateClaim { province };
+        assert!(ai.score_command(&claim(3), &state) <= 0);
+
+        state.strategic_goals.target_provinces = vec![5, 3];
+        assert!(ai.score_command(&claim(5), &state) > ai.score_command(&claim(3), &state));
+        let decisions = ai.decide(&state, &vec![claim(7), claim(3)]);
+        assert_eq!(decisions, vec![claim(3)]);
+
+        // A claim CB makes a viable war more attractive
+        state.known_country_strength.insert("SWE".to_string(), 30);
+        state.known_country_strength.insert("DAN".to_string(), 10);
+        let war = |cb: Option<&str>| Command::DeclareWar {
+            target: "DAN".to_string(),
+            cb: cb.map(str::to_string),
+        };
+        assert!(
+            ai.score_command(&war(Some("cb_claim")), &state) > ai.score_command(&war(None), &state)
+        );
+    }
+
+    #[test]
+    fn test_greedy_personality_weighs_categories() {
+        let ai = GreedyAI::new();
+        let mut state = dummy_state();
+        state.own_country.treasury = crate::fixed::Fixed::from_int(500);
+        state.personality = Some(AiPersonality {
+            name: "ai_capitalist".to_string(),
+            chance: crate::fixed::Fixed::ONE,
+            priorities: [
+                (
+                    AiPersonality::BUILDING.to_string(),
+                    crate::fixed::Fixed::from_int(2),
+                ),
+                (
+                    AiPersonality::COLONIZATION.to_string(),
+                    crate::fixed::Fixed::ZERO,
+                ),
+            ]
+            .into(),
+        });
+
+        let build = Command::BuildInProvince {
+            province: 1,
+            building: "marketplace".to_string(),
+        };
+        assert_eq!(ai.score_command(&build, &state), 360);
+        state.strategic_goals.building_plan = vec![1];
+        assert_eq!(ai.score_command(&build, &state), 560);
+
+        // Zero priority drops the whole category
+        let colony = Command::StartColony { province: 2 };
+        assert_eq!(ai.score_command(&colony, &state), 0);
+        // Categories the personality doesn't touch are unchanged
+        let tech = Command::BuyTech {
+            tech_type: TechType::Adm,
+        };
+        assert_eq!(ai.score_command(&tech, &state), 4200);
+    }
+
+    #[test]
+    fn test_greedy_answers_offers_by_acceptance() {
+        let ai = GreedyAI::new();
+        let mut state = dummy_state();
+        let accept = Command::AcceptAlliance {
+            from: "DAN".to_string(),
+        };
+        let reject = Command::RejectAlliance {
+            from: "DAN".to_string(),
+        };
+        let offer = Command::OfferRoyalMarriage {
+            target: "DAN".to_string(),
+        };
+        // Without relations data every answer is the default
+        assert_eq!(ai.score_command(&accept, &state), 10);
+
+        state.relations.insert(
+            "DAN".to_string(),
+            RelationsSummary {
+                we_accept_alliance: 20,
+                they_accept_marriage: -5,
+                ..Default::default()
+            },
+        );
+        assert_eq!(ai.score_command(&accept, &state), 800);
+        assert_eq!(ai.score_command(&reject, &state), -100);
+        // Offers that would be refused aren't sent
+        assert_eq!(ai.score_command(&offer, &state), -100);
+
+        state.relations.get_mut("DAN").unwrap().we_accept_alliance = -20;
+        assert_eq!(ai.score_command(&accept, &state), -100);
+        assert_eq!(ai.score_command(&reject, &state), 700);
+    }
 }
@@ -39,7 +39,9 @@
 
 use crate::fixed::Fixed;
 use crate::input::Command;
-use crate::state::{ArmyId, CountryState, Date, FleetId, GeneralId, ProvinceId, Tag, WarId};
+use crate::state::{
+    ArmyId, CountryState, Date, FleetId, GeneralId, ProvinceId, Tag, WarId, WorldState,
+};
 use rand::Rng;
 use rand::SeedableRng;
 use serde::{Deserialize, Serialize};
@@ -81,6 +83,44 @@ pub struct SiegeSummary {
     pub days_remaining_estimate: u32,
 }
 
+/// Opinion and offer acceptance between the observer and another country
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RelationsSummary {
+    /// Our opinion of them
+    pub our_opinion: i32,
+    /// Their opinion of us
+    pub their_opinion: i32,
+    /// Their acceptance of an alliance from us (> 0 accepts)
+    pub they_accept_alliance: i32,
+    /// Their acceptance of a royal marriage from us (> 0 accepts)
+    pub they_accept_marriage: i32,
+    /// Their acceptance of a military access request from us (> 0 accepts)
+    pub they_accept_access: i32,
+    /// Our acceptance of an alliance from them (> 0 accepts)
+    pub we_accept_alliance: i32,
+    /// Our acceptance of a royal marriage from them (> 0 accepts)
+    pub we_accept_marriage: i32,
+    /// Our acceptance of a military access request from them (> 0 accepts)
+    pub we_accept_access: i32,
+}
+
+impl RelationsSummary {
+    /// Summarize relations between `observer` and `other`.
+    pub fn new(state: &WorldState, observer: &str, other: &str) -> Self {
+        use crate::systems::opinion;
+        Self {
+            our_opinion: opinion::opinion(state, observer, other).to_int() as i32,
+            their_opinion: opinion::opinion(state, other, observer).to_int() as i32,
+            they_accept_alliance: opinion::alliance_acceptance(state, other, observer),
+            they_accept_marriage: opinion::royal_marriage_acceptance(state, other, observer),
+            they_accept_access: opinion::military_access_acceptance(state, other, observer),
+            we_accept_alliance: opinion::alliance_acceptance(state, observer, other),
+            we_accept_marriage: opinion::royal_marriage_acceptance(state, observer, other),
+            we_accept_access: opinion::military_access_acceptance(state, observer, other),
+        }
+    }
+}
+
 /// Visibility mode for AI and UI filtering
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VisibilityMode {
@@ -176,6 +216,18 @@ pub struct VisibleWorldState {
     /// Used for army consolidation before attack
     #[serde(default)]
     pub staging_provinces: HashSet<ProvinceId>,
+
+    /// Personality of the observer's ruler (unweighted if None)
+    #[serde(default)]
+    pub personality: Option<AiPersonality>,
+
+    /// Long-term goals planned for the observer
+    #[serde(default)]
+    pub strategic_goals: StrategicGoals,
+
+    /// Opinions and offer acceptance with known countries and pending offerers
+    #[serde(default)]
+    pub relations: HashMap<Tag, RelationsSummary>,
 }
 
 /// Available commands for a country
@@ -217,6 +269,11 @@ pub fn categorize_command(cmd: &Command) -> CommandCategory {
         | Command::CallAllyToWar { .. }
         | Command::SetRival { .. }
         | Command::RemoveRival { .. }
+        | Command::ImproveRelations { .. }
+        | Command::Insult { .. }
+        | Command::ReduceAggressiveExpansion { .. }
+        | Command::FabricateClaim { .. }
+        | Command::RecallDiplomat { .. }
         | Command::OfferAlliance { .. }
         | Command::AcceptAlliance { .. }
         | Command::RejectAlliance { .. }
@@ -301,6 +358,12 @@ pub trait AiPlayer: Send + Sync {
         visible_state: &VisibleWorldState,
         available_commands: &AvailableCommands,
     ) -> Vec<Command>;
+
+    /// Receive the full simulation state before [`decide`](Self::decide).
+    ///
+    /// Only AIs that simulate ahead (e.g. [`SearchAI`]) need this; fog-of-war
+    /// AIs should ignore it. The default does nothing.
+    fn observe_world(&mut self, _state: &WorldState) {}
 }
 
 /// Random AI that picks valid commands at random
@@ -393,7 +456,17 @@ impl AiPlayer for RandomAi {
 }
 
 mod greedy;
+mod personality;
+mod search;
+mod strategy;
+mod visibility;
 pub use greedy::GreedyAI;
+pub use personality::{AiPersonality, AiPersonalityRegistry};
+pub use search::{MaterialValue, OpponentPolicy, SearchAI, SearchConfig, ValueFunction};
+pub use strategy::{
+    plan_goals, run_strategy_tick, AiAttitudeRegistry, Attitude, AttitudeEffects, StrategicGoals,
+};
+pub use visibility::VisibilityIndex;
 
 #[cfg(test)]
 pub mod tests {
@@ -426,6 +499,9 @@ pub mod tests {
             our_army_sizes: HashMap::new(),
             our_army_provinces: HashMap::new(),
             staging_provinces: HashSet::new(),
+            personality: None,
+            strategic_goals: Default::default(),
+            relations: Default::default(),
         }
     }
 
@@ -0,0 +1,176 @@
+//! AI personalities (loaded from `common/ai_personalities/`).
+//!
+//! Each ruler gets one personality when the scenario is loaded. A personality
+//! is a bag of `*_priority` weights that scale how much the AI values whole
+//! classes of actions, so two GreedyAI countries in the same situation no
+//! longer make the same choice.
+
+use crate::fixed::Fixed;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// One AI personality definition.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AiPersonality {
+    /// Name from game files (e.g., "ai_militarist")
+    pub name: String,
+    /// Relative weight when rolling a ruler's personality (0 = never)
+    pub chance: Fixed,
+    /// `*_priority` weights keyed by their name in game files
+    pub priorities: BTreeMap<String, Fixed>,
+}
+
+impl AiPersonality {
+    /// Weight on declaring wars.
+    pub const WAR: &'static str = "war_priority";
+    /// Weight on forming alliances.
+    pub const ALLY: &'static str = "ally_priority";
+    /// Weight on constructing buildings.
+    pub const BUILDING: &'static str = "building_priority";
+    /// Weight on developing provinces.
+    pub const DEVELOPMENT: &'static str = "development_priority";
+    /// Weight on starting colonies.
+    pub const COLONIZATION: &'static str = "colonist_priority";
+
+    /// Weight for `key`; keys the personality doesn't set weigh 1.
+    pub fn priority(&self, key: &str) -> Fixed {
+        self.priorities.get(key).copied().unwrap_or(Fixed::ONE)
+    }
+
+    /// Scale a positive heuristic score by the weight for `key`.
+    ///
+    /// Penalties (scores <= 0) are left alone so a personality can make an
+    /// action more or less attractive but never turn a veto into a want.
+    pub fn weigh(&self, key: &str, score: i32) -> i32 {
+        if score <= 0 {
+            return score;
+        }
+        (Fixed::from_int(score as i64) * self.priority(key)).to_int() as i32
+    }
+}
+
+/// All personality definitions, in name order.
+#[derive(Debug, Clone, Default)]
+pub struct AiPersonalityRegistry {
+    personalities: Vec<AiPersonality>,
+}
+
+impl AiPersonalityRegistry {
+    pub fn new(personalities: impl IntoIterator<Item = AiPersonality>) -> Self {
+        let mut personalities: Vec<_> = personalities.into_iter().collect();
+        personalities.sort_by(|a, b| a.name.cmp(&b.name));
+        personalities.dedup_by(|a, b| a.name == b.name);
+        Self { personalities }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AiPersonality> {
+        self.personalities
+            .binary_search_by(|p| p.name.as_str().cmp(name))
+            .ok()
+            .map(|i| &self.personalities[i])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AiPersonality> {
+        self.personalities.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.personalities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.personalities.is_empty()
+    }
+
+    /// Roll a personality for `tag`'s ruler, weighted by `chance`.
+    ///
+    /// Deterministic in `(tag, seed)`. Returns `None` when no personality can
+    /// be rolled.
+    pub fn roll(&self, tag: &str, seed: u64) -> Option<&AiPersonality> {
+        let total: i64 = self
+            .personalities
+            .iter()
+            .map(|p| p.chance.raw().max(0))
+            .sum();
+        if total <= 0 {
+            return None;
+        }
+
+        // FNV-1a over the tag, then a splitmix64 finalizer to spread the seed
+        let mut hash = tag.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+            (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+        hash ^= seed;
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash ^= hash >> 31;
+
+        let mut roll = (hash % total as u64) as i64;
+        self.personalities.iter().find(|p| {
+            let weight = p.chance.raw().max(0);
+            if roll < weight {
+                true
+            } else {
+                roll -= weight;
+                false
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn personality(name: &str, chance: i64, priorities: &[(&str, f32)]) -> AiPersonality {
+        AiPersonality {
+            name: name.to_string(),
+            chance: Fixed::from_int(chance),
+            priorities: priorities
+                .iter()
+                .map(|&(k, v)| (k.to_string(), Fixed::from_f32(v)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_weigh_scales_only_positive_scores() {
+        let militarist = personality("ai_militarist", 1, &[(AiPersonality::WAR, 1.5)]);
+        assert_eq!(militarist.weigh(AiPersonality::WAR, 2000), 3000);
+        assert_eq!(militarist.weigh(AiPersonality::WAR, -1000), -1000);
+        // Unset keys weigh 1
+        assert_eq!(militarist.weigh(AiPersonality::BUILDING, 400), 400);
+    }
+
+    #[test]
+    fn test_roll_is_deterministic_and_skips_zero_chance() {
+        let registry = AiPersonalityRegistry::new([
+            personality("human_ai_personality", 0, &[]),
+            personality("ai_militarist", 1, &[]),
+            personality("ai_capitalist", 1, &[]),
+        ]);
+        assert_eq!(registry.len(), 3);
+        assert!(registry.get("ai_capitalist").is_some());
+
+        let tags = ["SWE", "DAN", "NOR", "FRA", "ENG", "CAS", "POR", "TUR"];
+        let rolled: Vec<&str> = tags
+            .iter()
+            .map(|tag| registry.roll(tag, 7).unwrap().name.as_str())
+            .collect();
+        assert!(rolled.iter().all(|&name| name != "human_ai_personality"));
+        assert!(rolled.contains(&"ai_militarist") && rolled.contains(&"ai_capitalist"));
+
+        let again: Vec<&str> = tags
+            .iter()
+            .map(|tag| registry.roll(tag, 7).unwrap().name.as_str())
+            .collect();
+        assert_eq!(rolled, again);
+    }
+
+    #[test]
+    fn test_roll_without_weights() {
+        let registry = AiPersonalityRegistry::new([personality("human_ai_personality", 0, &[])]);
+        assert!(registry.roll("SWE", 0).is_none());
+        assert!(AiPersonalityRegistry::default().roll("SWE", 0).is_none());
+    }
+}
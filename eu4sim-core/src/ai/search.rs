@@ -0,0 +1,649 @@
+//! Lookahead search AI.
+//!
+//! [`SearchAI`] runs a beam search over simulated futures, using the fact that
+//! `step_world` is pure and deterministic. Each ply, every state in the beam
+//! is expanded into candidate command sets; each candidate is applied to a
+//! fork of that state, which is then simulated [`SearchConfig::ply_days`] days
+//! with scripted policies for the searcher and its opponents. The
+//! [`SearchConfig::beam_width`] children scoring highest under a
+//! [`ValueFunction`] form the next ply's beam. After
+//! [`SearchConfig::depth`] plies the AI plays the first command set of the
+//! best line.
+//!
+//! Candidates are built around [`GreedyAI`]'s choice in the node's state: the
+//! greedy plan itself, passing, and for each command category, the plan
+//! without that category or with its commands replaced by one other legal
+//! command of the category. Categories take turns so a wide one (e.g. army
+//! moves) can't crowd out the others. Work per decision is capped by
+//! [`SearchConfig::max_simulated_days`] and optionally wall-clock time.
+
+use crate::ai::visibility::VisibilityIndex;
+use crate::ai::{
+    categorize_command, AiPlayer, AvailableCommands, CommandCategory, GreedyAI, RandomAi,
+    VisibleWorldState,
+};
+use crate::config::SimConfig;
+use crate::input::{Command, PlayerInputs};
+use crate::state::{Tag, WorldState};
+use crate::step::step_world_mut;
+use eu4data::adjacency::AdjacencyGraph;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How opponents act inside rollouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpponentPolicy {
+    /// Opponents issue no commands (cheapest, optimistic)
+    Passive,
+    /// Opponents play [`GreedyAI`]
+    #[default]
+    Greedy,
+    /// Opponents play [`RandomAi`], seeded from [`SearchConfig::seed`]
+    Random,
+}
+
+/// Tuning knobs for [`SearchAI`].
+#[derive(Debug, Clone)]
+pub struct SearchConfig {
+    /// Plies searched; each ply decides one day and simulates `ply_days`
+    pub depth: u32,
+    /// Days simulated per ply, starting with the day the candidate is applied
+    pub ply_days: u32,
+    /// States kept after each ply
+    pub beam_width: usize,
+    /// Maximum candidate command sets expanded per state
+    pub max_candidates: usize,
+    /// Compute budget: total simulated days across all rollouts of one decision
+    pub max_simulated_days: u32,
+    /// Optional wall-clock budget per decision.
+    ///
+    /// Makes decisions depend on machine speed, so leave unset for replays and
+    /// lockstep multiplayer.
+    pub time_budget: Option<Duration>,
+    /// Policy for countries the searcher knows about
+    pub opponents: OpponentPolicy,
+    /// Seed for random opponents
+    pub seed: u64,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            depth: 3,
+            ply_days: 10,
+            beam_width: 2,
+            max_candidates: 6,
+            max_simulated_days: 300,
+            time_budget: None,
+            opponents: OpponentPolicy::Greedy,
+            seed: 0,
+        }
+    }
+}
+
+/// Scores a world state from one country's perspective (higher is better).
+pub trait ValueFunction: Send + Sync {
+    fn evaluate(&self, state: &WorldState, tag: &str) -> f64;
+}
+
+/// Weighted sum of a country's material position.
+#[derive(Debug, Clone)]
+pub struct MaterialValue {
+    /// Per point of owned development
+    pub development: f64,
+    /// Per ducat in the treasury (debt counts negative)
+    pub treasury: f64,
+    /// Per unit of manpower
+    pub manpower: f64,
+    /// Per regiment fielded
+    pub regiments: f64,
+    /// Per point of relative war score, summed over the country's wars
+    pub war_score: f64,
+}
+
+impl MaterialValue {
+    /// Value of a country that no longer exists.
+    pub const ELIMINATED: f64 = -1.0e9;
+}
+
+impl Default for MaterialValue {
+    fn default() -> Self {
+        Self {
+            development: 1.0,
+            treasury: 0.05,
+            manpower: 0.001,
+            regiments: 0.5,
+            war_score: 0.2,
+        }
+    }
+}
+
+impl ValueFunction for MaterialValue {
+    fn evaluate(&self, state: &WorldState, tag: &str) -> f64 {
+        let Some(country) = state.countries.get(tag) else {
+            return Self::ELIMINATED;
+        };
+
+        let development: f64 = state
+            .provinces
+            .values()
+            .filter(|p| p.owner.as_deref() == Some(tag))
+            .map(|p| (p.base_tax + p.base_production + p.base_manpower).to_f32() as f64)
+            .sum();
+        let regiments: u32 = state
+            .armies
+            .values()
+            .filter(|a| a.owner == tag)
+            .map(|a| a.regiment_count())
+            .sum();
+        let war_score: i32 = state
+            .diplomacy
+            .wars
+            .values()
+            .filter_map(|war| {
+                if war.attackers.iter().any(|t| t == tag) {
+                    Some(war.attacker_score as i32 - war.defender_score as i32)
+                } else if war.defenders.iter().any(|t| t == tag) {
+                    Some(war.defender_score as i32 - war.attacker_score as i32)
+                } else {
+                    None
+                }
+            })
+            .sum();
+
+        self.development * development
+            + self.treasury * country.treasury.to_f64()
+            + self.manpower * country.manpower.to_f64()
+            + self.regiments * regiments as f64
+            + self.war_score * war_score as f64
+    }
+}
+
+/// An AI that picks among candidate command sets by simulating ahead.
+///
+/// Needs the full world state, which it receives through
+/// [`AiPlayer::observe_world`]; without it (or without an adjacency graph for
+/// movement) it plays like [`GreedyAI`].
+pub struct SearchAI {
+    config: SearchConfig,
+    value: Box<dyn ValueFunction>,
+    adjacency: Option<Arc<AdjacencyGraph>>,
+    sim_config: SimConfig,
+    /// State observed for the upcoming decision
+    world: Option<WorldState>,
+}
+
+impl SearchAI {
+    pub fn new(config: SearchConfig) -> Self {
+        Self {
+            config,
+            value: Box::new(MaterialValue::default()),
+            adjacency: None,
+            sim_config: SimConfig {
+                checksum_frequency: 0,
+            },
+            world: None,
+        }
+    }
+
+    /// Graph used for movement and neighbour visibility inside rollouts.
+    pub fn with_adjacency(mut self, adjacency: Arc<AdjacencyGraph>) -> Self {
+        self.adjacency = Some(adjacency);
+        self
+    }
+
+    pub fn with_value_function(mut self, value: Box<dyn ValueFunction>) -> Self {
+        self.value = value;
+        self
+    }
+
+    pub fn config(&self) -> &SearchConfig {
+        &self.config
+    }
+
+    /// Candidate command sets, greedy plan first.
+    fn candidates(
+        &self,
+        greedy_plan: &[Command],
+        available_commands: &AvailableCommands,
+    ) -> Vec<Vec<Command>> {
+        const CATEGORIES: [CommandCategory; 5] = [
+            CommandCategory::Diplomatic,
+            CommandCategory::Military,
+            CommandCategory::Economic,
+            CommandCategory::Trade,
+            CommandCategory::Colonization,
+        ];
+
+        // Per category: the plan without it, then with each alternative instead
+        let variants: Vec<Vec<Vec<Command>>> = CATEGORIES
+            .iter()
+            .map(|&category| {
+                let without: Vec<Command> = greedy_plan
+                    .iter()
+                    .filter(|cmd| categorize_command(cmd) != category)
+                    .cloned()
+                    .collect();
+                let mut plans = vec![without.clone()];
+                for cmd in available_commands {
+                    if categorize_command(cmd) == category && !greedy_plan.contains(cmd) {
+                        let mut plan = without.clone();
+                        plan.push(cmd.clone());
+                        plans.push(plan);
+                    }
+                }
+                plans
+            })
+            .collect();
+
+        let mut candidates = vec![greedy_plan.to_vec(), Vec::new()];
+        let rounds = variants.iter().map(Vec::len).max().unwrap_or(0);
+        for round in 0..rounds {
+            candidates.extend(
+                variants
+                    .iter()
+                    .filter_map(|plans| plans.get(round).cloned()),
+            );
+        }
+
+        let limit = self.config.max_candidates.max(1);
+        let mut unique: Vec<Vec<Command>> = Vec::new();
+        for plan in candidates {
+            if unique.len() == limit {
+                break;
+            }
+            if !unique.contains(&plan) {
+                unique.push(plan);
+            }
+        }
+        unique
+    }
+
+    /// Apply `plan` to `state`, then simulate the rest of the ply.
+    fn simulate(&self, state: &mut WorldState, tag: &str, opponents: &[Tag], plan: &[Command]) {
+        let adjacency = self.adjacency.as_deref();
+        let mut own_policy = GreedyAI::new();
+        let mut opponent_ais: Vec<(Tag, Box<dyn AiPlayer>)> = opponents
+            .iter()
+            .filter_map(|other| {
+                let ai: Box<dyn AiPlayer> = match self.config.opponents {
+                    OpponentPolicy::Passive => return None,
+                    OpponentPolicy::Greedy => Box::new(GreedyAI::new()),
+                    OpponentPolicy::Random => {
+                        let tag_hash: u64 = other.as_bytes().iter().map(|&b| b as u64).sum();
+                        Box::new(RandomAi::new(self.config.seed.wrapping_add(tag_hash)))
+                    }
+                };
+                Some((other.clone(), ai))
+            })
+            .collect();
+
+        for day in 0..self.config.ply_days.max(1) {
+            let index = VisibilityIndex::new(state, adjacency);
+            let decide = |country: &str, ai: &mut dyn AiPlayer| {
+                let visible = index.visible_state(state, country, adjacency);
+                let available = state.available_commands(country, adjacency);
+                PlayerInputs {
+                    country: country.to_string(),
+                    commands: ai.decide(&visible, &available),
+                    available_commands: vec![],
+                    visible_state: None,
+                }
+            };
+
+            let mut inputs = Vec::with_capacity(opponent_ais.len() + 1);
+            inputs.push(if day == 0 {
+                PlayerInputs {
+                    country: tag.to_string(),
+                    commands: plan.to_vec(),
+                    available_commands: vec![],
+                    visible_state: None,
+                }
+            } else {
+                decide(tag, &mut own_policy)
+            });
+            for (other, ai) in &mut opponent_ais {
+                if state.countries.contains_key(other) {
+                    inputs.push(decide(other, ai.as_mut()));
+                }
+            }
+
+            step_world_mut(state, &inputs, adjacency, &self.sim_config, None);
+        }
+    }
+
+    /// Candidates for `tag` in a state reached during the search.
+    fn node_candidates(&self, state: &WorldState, tag: &str) -> Vec<Vec<Command>> {
+        let adjacency = self.adjacency.as_deref();
+        let index = VisibilityIndex::new(state, adjacency);
+        let visible = index.visible_state(state, tag, adjacency);
+        let available = state.available_commands(tag, adjacency);
+        let greedy_plan = GreedyAI::new().decide(&visible, &available);
+        self.candidates(&greedy_plan, &available)
+    }
+}
+
+/// A state in the beam, with the root command set that leads to it.
+struct Line {
+    state: WorldState,
+    /// Index into the root candidates
+    first: usize,
+    value: f64,
+}
+
+impl AiPlayer for SearchAI {
+    fn name(&self) -> &'static str {
+        "SearchAI"
+    }
+
+    fn observe_world(&mut self, state: &WorldState) {
+        self.world = Some(state.clone());
+    }
+
+    fn decide(
+        &mut self,
+        visible_state: &VisibleWorldState,
+        available_commands: &AvailableCommands,
+    ) -> Vec<Command> {
+        let greedy_plan = GreedyAI::new().decide(visible_state, available_commands);
+        let Some(root) = self.world.take() else {
+            return greedy_plan;
+        };
+
+        let root_candidates = self.candidates(&greedy_plan, available_commands);
+        if root_candidates.len() < 2 {
+            return greedy_plan;
+        }
+
+        let tag = &visible_state.observer;
+        let mut opponents: Vec<Tag> = visible_state.known_countries.clone();
+        opponents.sort();
+
+        let started = Instant::now();
+        let cost = self.config.ply_days.max(1);
+        let mut spent = 0;
+        let mut depth = 0;
+        let mut beam: Vec<Line> = Vec::new();
+        'plies: for ply in 0..self.config.depth.max(1) {
+            let mut children: Vec<Line> = Vec::new();
+            let parents: Vec<(Option<&Line>, Vec<Vec<Command>>)> = if ply == 0 {
+                vec![(None, root_candidates.clone())]
+            } else {
+                beam.iter()
+                    .map(|line| (Some(line), self.node_candidates(&line.state, tag)))
+                    .collect()
+            };
+
+            for (parent, plans) in parents {
+                for (i, plan) in plans.iter().enumerate() {
+                    let over_days = spent + cost > self.config.max_simulated_days;
+                    let over_time = self
+                        .config
+                        .time_budget
+                        .is_some_and(|budget| started.elapsed() >= budget);
+                    if over_days || over_time {
+                        // A partial deeper ply would rank lines of unequal
+                        // length, so the last complete ply decides
+                        if ply > 0 {
+                            break 'plies;
+                        }
+                        if !children.is_empty() {
+                            break;
+                        }
+                    }
+                    spent += cost;
+
+                    let mut state = parent.map_or_else(|| root.clone(), |p| p.state.clone());
+                    self.simulate(&mut state, tag, &opponents, plan);
+                    let value = self.value.evaluate(&state, tag);
+                    children.push(Line {
+                        state,
+                        first: parent.map_or(i, |p| p.first),
+                        value,
+                    });
+                }
+            }
+
+            // Stable: ties keep the earlier (greedier) line
+            children.sort_by(|a, b| b.value.total_cmp(&a.value));
+            children.truncate(self.config.beam_width.max(1));
+            beam = children;
+            depth = ply + 1;
+        }
+
+        let best = beam
+            .first()
+            .expect("the root ply runs at least one rollout");
+        log::debug!(
+            "[SearchAI] {} picked candidate {}/{} (value {:.1}, depth {}, {} days simulated in {:?})",
+            tag,
+            best.first,
+            root_candidates.len(),
+            best.value,
+            depth,
+            spent,
+            started.elapsed()
+        );
+        let first = best.first;
+        root_candidates
+            .into_iter()
+            .nth(first)
+            .unwrap_or(greedy_plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed::Fixed;
+    use crate::testing::WorldStateBuilder;
+
+    fn two_country_world() -> (WorldState, Arc<AdjacencyGraph>) {
+        let state = WorldStateBuilder::new()
+            .with_country("SWE")
+            .with_country("DAN")
+            .with_province(1, Some("SWE"))
+            .with_province(2, Some("SWE"))
+            .with_province(3, Some("DAN"))
+            .build();
+        let mut adjacency = AdjacencyGraph::new();
+        adjacency.add_adjacency(1, 2);
+        adjacency.add_adjacency(2, 3);
+        (state, Arc::new(adjacency))
+    }
+
+    /// Prefers owning as little development as possible.
+    struct Ascetic;
+
+    impl ValueFunction for Ascetic {
+        fn evaluate(&self, state: &WorldState, tag: &str) -> f64 {
+            let development = MaterialValue {
+                development: 1.0,
+                treasury: 0.0,
+                manpower: 0.0,
+                regiments: 0.0,
+                war_score: 0.0,
+            };
+            -development.evaluate(state, tag)
+        }
+    }
+
+    fn develop() -> Command {
+        Command::DevelopProvince {
+            province: 1,
+            dev_type: crate::input::DevType::Tax,
+        }
+    }
+
+    /// Sweden with mana to spend, seen through its own eyes.
+    fn rich_sweden() -> (WorldState, Arc<AdjacencyGraph>, VisibleWorldState) {
+        let (mut state, adjacency) = two_country_world();
+        state.countries.get_mut("SWE").unwrap().adm_mana = Fixed::from_int(900);
+        let index = VisibilityIndex::new(&state, Some(&adjacency));
+        let visible = index.visible_state(&state, "SWE", Some(&adjacency));
+        (state, adjacency, visible)
+    }
+
+    #[test]
+    fn test_material_value() {
+        let (mut state, _) = two_country_world();
+        let value = MaterialValue::default();
+        let before = value.evaluate(&state, "SWE");
+
+        state.countries.get_mut("SWE").unwrap().treasury += Fixed::from_int(100);
+        assert!(value.evaluate(&state, "SWE") > before);
+
+        let rich = value.evaluate(&state, "SWE");
+        state.provinces.get_mut(&2).unwrap().owner = Some("DAN".to_string());
+        assert!(value.evaluate(&state, "SWE") < rich);
+        assert_eq!(value.evaluate(&state, "NOR"), MaterialValue::ELIMINATED);
+    }
+
+    #[test]
+    fn test_candidates_swap_diplomatic_action() {
+        let ai = SearchAI::new(SearchConfig::default());
+        let war = Command::DeclareWar {
+            target: "DAN".to_string(),
+            cb: None,
+        };
+        let available = vec![develop(), war.clone()];
+
+        let candidates = ai.candidates(&[develop()], &available);
+        assert_eq!(
+            candidates,
+            vec![vec![develop()], vec![], vec![develop(), war]]
+        );
+    }
+
+    #[test]
+    fn test_candidates_vary_every_category() {
+        let ai = SearchAI::new(SearchConfig {
+            max_candidates: 5,
+            ..Default::default()
+        });
+        let war = Command::DeclareWar {
+            target: "DAN".to_string(),
+            cb: None,
+        };
+        let march = Command::Move {
+            army_id: 1,
+            destination: 3,
+        };
+        let buy_tech = Command::BuyTech {
+            tech_type: crate::state::TechType::Adm,
+        };
+        let available = vec![
+            develop(),
+            buy_tech.clone(),
+            march.clone(),
+            Command::Move {
+                army_id: 1,
+                destination: 1,
+            },
+            war.clone(),
+        ];
+
+        // Each category gets a turn before any gets a second one
+        let candidates = ai.candidates(&[develop(), march.clone()], &available);
+        assert_eq!(
+            candidates,
+            vec![
+                vec![develop(), march.clone()],
+                vec![],
+                vec![develop()],
+                vec![march.clone()],
+                vec![develop(), march, war],
+            ]
+        );
+        assert!(!candidates.iter().any(|plan| plan.contains(&buy_tech)));
+    }
+
+    /// Material value that counts how often it is asked.
+    struct Counting(Arc<std::sync::atomic::AtomicUsize>);
+
+    impl ValueFunction for Counting {
+        fn evaluate(&self, state: &WorldState, tag: &str) -> f64 {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            MaterialValue::default().evaluate(state, tag)
+        }
+    }
+
+    #[test]
+    fn test_search_expands_the_beam_each_ply() {
+        let (state, adjacency, visible) = rich_sweden();
+        let available = vec![develop()];
+        let rollouts = |depth: u32| {
+            let count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+            let config = SearchConfig {
+                depth,
+                ply_days: 2,
+                opponents: OpponentPolicy::Passive,
+                ..Default::default()
+            };
+            let mut search = SearchAI::new(config)
+                .with_adjacency(adjacency.clone())
+                .with_value_function(Box::new(Counting(count.clone())));
+            search.observe_world(&state);
+            search.decide(&visible, &available);
+            count.load(std::sync::atomic::Ordering::Relaxed)
+        };
+
+        // One ply scores the root candidates: develop, or pass
+        assert_eq!(rollouts(1), 2);
+        // A second ply expands both lines, each with at least a pass
+        assert!(rollouts(2) >= 4);
+    }
+
+    #[test]
+    fn test_without_world_falls_back_to_greedy() {
+        let (_, _, visible) = rich_sweden();
+        let available = vec![develop()];
+        let mut search =
+            SearchAI::new(SearchConfig::default()).with_value_function(Box::new(Ascetic));
+        assert_eq!(search.decide(&visible, &available), vec![develop()]);
+    }
+
+    #[test]
+    fn test_search_follows_value_function() {
+        let (state, adjacency, visible) = rich_sweden();
+        let available = vec![develop()];
+        assert_eq!(
+            GreedyAI::new().decide(&visible, &available),
+            vec![develop()]
+        );
+
+        let config = SearchConfig {
+            ply_days: 2,
+            ..Default::default()
+        };
+        let mut search = SearchAI::new(config)
+            .with_adjacency(adjacency)
+            .with_value_function(Box::new(Ascetic));
+        search.observe_world(&state);
+        assert!(search.decide(&visible, &available).is_empty());
+
+        // The observed state is consumed by the decision
+        assert_eq!(search.decide(&visible, &available), vec![develop()]);
+    }
+
+    #[test]
+    fn test_budget_limits_rollouts() {
+        let (state, adjacency, visible) = rich_sweden();
+        let config = SearchConfig {
+            ply_days: 10,
+            // Room for one rollout only: the greedy plan is kept
+            max_simulated_days: 10,
+            opponents: OpponentPolicy::Random,
+            ..Default::default()
+        };
+        let mut search = SearchAI::new(config)
+            .with_adjacency(adjacency)
+            .with_value_function(Box::new(Ascetic));
+
+        search.observe_world(&state);
+        assert_eq!(
+            search.decide(&visible, &[develop()].to_vec()),
+            vec![develop()]
+        );
+    }
+}
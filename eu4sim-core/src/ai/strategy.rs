@@ -0,0 +1,579 @@
+//! Long-term strategic goals for AI countries.
+//!
+//! Heuristic AIs re-score every command from scratch each tick, so nothing
+//! ties one day's decision to the next. [`run_strategy_tick`] gives every
+//! country a small plan (whom to expand into, whom to contain, whom to court,
+//! where to build) that is replanned once a year and pruned monthly as goals
+//! are met or become invalid. The plan is stored on
+//! [`WorldState::strategic_goals`] and handed to AIs through
+//! [`VisibleWorldState::strategic_goals`](crate::ai::VisibleWorldState::strategic_goals).
+
+use crate::ai::AiPersonality;
+use crate::state::{Date, ProvinceId, RelationType, Tag, WorldState};
+use eu4data::adjacency::AdjacencyGraph;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Most provinces kept as expansion targets.
+const MAX_TARGET_PROVINCES: usize = 5;
+/// Most countries kept as alliance targets.
+const MAX_ALLIANCE_TARGETS: usize = 3;
+/// Most provinces kept in the building plan.
+const MAX_BUILDING_PLAN: usize = 5;
+/// A neighbour this much stronger (3:2) is a threat rather than a target.
+const THREAT_RATIO: (u32, u32) = (3, 2);
+
+/// How a country regards another one (keys match `common/ai_attitudes/`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Attitude {
+    Allied,
+    Friendly,
+    Neutral,
+    Threatened,
+    Hostile,
+    Rivalry,
+    /// Towards our subject
+    Overlord,
+    /// Towards our overlord
+    Loyal,
+}
+
+impl Attitude {
+    pub const ALL: [Attitude; 8] = [
+        Attitude::Allied,
+        Attitude::Friendly,
+        Attitude::Neutral,
+        Attitude::Threatened,
+        Attitude::Hostile,
+        Attitude::Rivalry,
+        Attitude::Overlord,
+        Attitude::Loyal,
+    ];
+
+    /// Name of the attitude in game files.
+    pub fn key(self) -> &'static str {
+        match self {
+            Attitude::Allied => "attitude_allied",
+            Attitude::Friendly => "attitude_friendly",
+            Attitude::Neutral => "attitude_neutral",
+            Attitude::Threatened => "attitude_threatened",
+            Attitude::Hostile => "attitude_hostile",
+            Attitude::Rivalry => "attitude_rivalry",
+            Attitude::Overlord => "attitude_overlord",
+            Attitude::Loyal => "attitude_loyal",
+        }
+    }
+}
+
+/// What an attitude lets the planner do (flags in `common/ai_attitudes/`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AttitudeEffects {
+    /// Their bordering provinces are expansion targets
+    pub war_target: bool,
+    /// They are worth an alliance
+    pub ally: bool,
+    /// They are to be contained; war targets we also contain are taken first
+    pub contain: bool,
+}
+
+impl AttitudeEffects {
+    pub const WAR_TARGET: &'static str = "ai_war_target";
+    pub const ALLY: &'static str = "ai_ally";
+    pub const CONTAIN: &'static str = "ai_contain";
+
+    /// Effects from the `key = yes` flags of an attitude definition.
+    pub fn from_flags<S: AsRef<str>>(flags: &[S]) -> Self {
+        let has = |key: &str| flags.iter().any(|f| f.as_ref() == key);
+        Self {
+            war_target: has(Self::WAR_TARGET),
+            ally: has(Self::ALLY),
+            contain: has(Self::CONTAIN),
+        }
+    }
+
+    /// Built-in effects, used for attitudes the game files don't define.
+    pub fn builtin(attitude: Attitude) -> Self {
+        let (war_target, ally, contain) = match attitude {
+            Attitude::Friendly => (false, true, false),
+            Attitude::Neutral => (true, true, false),
+            Attitude::Threatened => (false, false, true),
+            Attitude::Hostile => (true, false, false),
+            Attitude::Rivalry => (true, false, true),
+            Attitude::Allied | Attitude::Overlord | Attitude::Loyal => (false, false, false),
+        };
+        Self {
+            war_target,
+            ally,
+            contain,
+        }
+    }
+}
+
+/// Effects of every attitude; those not loaded fall back to the built-ins.
+#[derive(Debug, Clone, Default)]
+pub struct AiAttitudeRegistry {
+    effects: BTreeMap<Attitude, AttitudeEffects>,
+}
+
+impl AiAttitudeRegistry {
+    pub fn new(effects: impl IntoIterator<Item = (Attitude, AttitudeEffects)>) -> Self {
+        Self {
+            effects: effects.into_iter().collect(),
+        }
+    }
+
+    pub fn effects(&self, attitude: Attitude) -> AttitudeEffects {
+        self.effects
+            .get(&attitude)
+            .copied()
+            .unwrap_or_else(|| AttitudeEffects::builtin(attitude))
+    }
+
+    /// Number of attitudes loaded from game files.
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+}
+
+/// A country's long-term plan.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StrategicGoals {
+    /// Bordering provinces to conquer, best first
+    pub target_provinces: Vec<ProvinceId>,
+    /// Owners of `target_provinces`, in the same priority order
+    pub war_targets: Vec<Tag>,
+    /// Rivals and threatening neighbours, strongest first
+    pub rivals_to_contain: Vec<Tag>,
+    /// Countries worth an alliance, strongest first
+    pub alliance_targets: Vec<Tag>,
+    /// Own provinces to build in, most developed first
+    pub building_plan: Vec<ProvinceId>,
+    /// Attitude towards every country the plan considered
+    pub attitudes: BTreeMap<Tag, Attitude>,
+    /// When the plan is replanned from scratch
+    pub next_review: Option<Date>,
+}
+
+/// Replan due countries and prune everyone else's goals (monthly).
+///
+/// Without an adjacency graph no country has bordering provinces to target.
+pub fn run_strategy_tick(state: &mut WorldState, adjacency: Option<&AdjacencyGraph>) {
+    let strength = regiments_by_country(state);
+    let mut tags: Vec<Tag> = state.countries.keys().cloned().collect();
+    tags.sort();
+
+    let mut goals = std::mem::take(&mut state.strategic_goals);
+    goals.retain(|tag, _| state.countries.contains_key(tag));
+    for tag in tags {
+        let due = goals
+            .get(&tag)
+            .and_then(|g| g.next_review)
+            .is_none_or(|review| state.date >= review);
+        if due {
+            goals.insert(tag.clone(), plan_goals(state, &tag, &strength, adjacency));
+        } else if let Some(current) = goals.get_mut(&tag) {
+            prune_goals(state, &tag, current);
+        }
+    }
+    state.strategic_goals = goals;
+}
+
+/// Plan `tag`'s goals from scratch.
+///
+/// `strength` is the regiment count per country.
+pub fn plan_goals(
+    state: &WorldState,
+    tag: &str,
+    strength: &HashMap<Tag, u32>,
+    adjacency: Option<&AdjacencyGraph>,
+) -> StrategicGoals {
+    let Some(country) = state.countries.get(tag) else {
+        return StrategicGoals::default();
+    };
+    let personality = country
+        .ai_personality
+        .as_deref()
+        .and_then(|name| state.ai_personalities.get(name));
+    let our_strength = strength.get(tag).copied().unwrap_or(0);
+    let strength_of = |other: &str| strength.get(other).copied().unwrap_or(0);
+
+    let mut owned: Vec<ProvinceId> = state
+        .provinces
+        .iter()
+        .filter(|(_, p)| p.owner.as_deref() == Some(tag))
+        .map(|(&id, _)| id)
+        .collect();
+    owned.sort_unstable();
+
+    // Foreign provinces on our border
+    let border: BTreeSet<ProvinceId> = adjacency
+        .map(|adjacency| {
+            owned
+                .iter()
+                .flat_map(|&id| adjacency.neighbors(id))
+                .filter(|n| {
+                    state
+                        .provinces
+                        .get(n)
+                        .and_then(|p| p.owner.as_deref())
+                        .is_some_and(|owner| owner != tag)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Everyone we border or have dealings with
+    let mut others: BTreeSet<Tag> = border
+        .iter()
+        .filter_map(|id| state.provinces.get(id).and_then(|p| p.owner.clone()))
+        .collect();
+    others.extend(country.rivals.iter().cloned());
+    others.extend(state.diplomacy.get_allies(tag));
+    for war in state.diplomacy.get_wars_for_country(tag) {
+        others.extend(war.attackers.iter().chain(&war.defenders).cloned());
+    }
+    others.remove(tag);
+    others.retain(|other| state.countries.contains_key(other));
+
+    let attitudes: BTreeMap<Tag, Attitude> = others
+        .into_iter()
+        .map(|other| {
+            let attitude = attitude_towards(state, tag, &other, our_strength, strength_of(&other));
+            (other, attitude)
+        })
+        .collect();
+
+    let effects = |other: &Tag| {
+        attitudes
+            .get(other)
+            .map(|&a| state.ai_attitudes.effects(a))
+            .unwrap_or_default()
+    };
+
+    let mut rivals_to_contain: Vec<Tag> = attitudes
+        .keys()
+        .filter(|t| effects(t).contain)
+        .cloned()
+        .collect();
+    rivals_to_contain.sort_by_key(|t| std::cmp::Reverse(strength_of(t)));
+
+    // Expansion: land we have cores or claims on, take from those we contain first
+    let mut targets: Vec<(i64, ProvinceId, Tag)> = border
+        .iter()
+        .filter_map(|&id| {
+            let province = state.provinces.get(&id)?;
+            let owner = province.owner.clone()?;
+            let owner_effects = effects(&owner);
+            if !owner_effects.war_target {
+                return None;
+            }
+            let mut score = (province.base_tax + province.base_production + province.base_manpower)
+                .to_int() as i64;
+            if province.cores.contains(tag) || province.claims.contains_key(tag) {
+                score += 10;
+            }
+            if owner_effects.contain {
+                score += 5;
+            }
+            Some((score, id, owner))
+        })
+        .collect();
+    targets.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    targets.truncate(MAX_TARGET_PROVINCES);
+    let mut war_targets: Vec<Tag> = Vec::new();
+    for (_, _, owner) in &targets {
+        if !war_targets.contains(owner) {
+            war_targets.push(owner.clone());
+        }
+    }
+
+    // Alliances: neighbours that aren't negligible, strongest first
+    let wants_allies =
+        personality.is_none_or(|p| p.priority(AiPersonality::ALLY) > crate::Fixed::ZERO);
+    let mut alliance_targets: Vec<Tag> = if wants_allies {
+        attitudes
+            .keys()
+            .filter(|t| effects(t).ally)
+            .filter(|t| !war_targets.contains(t) && strength_of(t) * 2 >= our_strength)
+            .cloned()
+            .collect()
+    } else {
+        Vec::new()
+    };
+    alliance_targets.sort_by_key(|t| std::cmp::Reverse(strength_of(t)));
+    alliance_targets.truncate(MAX_ALLIANCE_TARGETS);
+
+    let mut building_plan: Vec<(i64, ProvinceId)> = owned
+        .iter()
+        .filter_map(|&id| {
+            let p = state.provinces.get(&id)?;
+            if p.building_construction.is_some() {
+                return None;
+            }
+            Some((
+                (p.base_tax + p.base_production + p.base_manpower).to_int() as i64,
+                id,
+            ))
+        })
+        .collect();
+    building_plan.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    StrategicGoals {
+        target_provinces: targets.iter().map(|(_, id, _)| *id).collect(),
+        war_targets,
+        rivals_to_contain,
+        alliance_targets,
+        building_plan: building_plan
+            .into_iter()
+            .take(MAX_BUILDING_PLAN)
+            .map(|(_, id)| id)
+            .collect(),
+        attitudes,
+        next_review: Some(state.date.add_years(1)),
+    }
+}
+
+/// How `tag` regards `other`.
+fn attitude_towards(
+    state: &WorldState,
+    tag: &str,
+    other: &str,
+    our_strength: u32,
+    their_strength: u32,
+) -> Attitude {
+    let diplomacy = &state.diplomacy;
+    let is_rival = state
+        .countries
+        .get(tag)
+        .is_some_and(|c| c.rivals.contains(other));
+    let relation = diplomacy
+        .relations
+        .get(&crate::state::DiplomacyState::sorted_pair(tag, other));
+
+    if diplomacy.are_at_war(tag, other) {
+        Attitude::Hostile
+    } else if is_rival {
+        Attitude::Rivalry
+    } else if diplomacy
+        .get_overlord(tag)
+        .is_some_and(|s| s.overlord == other)
+    {
+        Attitude::Loyal
+    } else if diplomacy
+        .get_overlord(other)
+        .is_some_and(|s| s.overlord == tag)
+    {
+        Attitude::Overlord
+    } else if relation == Some(&RelationType::Alliance) {
+        Attitude::Allied
+    } else if diplomacy.has_royal_marriage(tag, other) {
+        Attitude::Friendly
+    } else if their_strength * THREAT_RATIO.1 > our_strength * THREAT_RATIO.0 {
+        Attitude::Threatened
+    } else {
+        Attitude::Neutral
+    }
+}
+
+/// Drop goals that were achieved or no longer make sense.
+fn prune_goals(state: &WorldState, tag: &str, goals: &mut StrategicGoals) {
+    let diplomacy = &state.diplomacy;
+    goals.target_provinces.retain(|id| {
+        state
+            .provinces
+            .get(id)
+            .and_then(|p| p.owner.as_deref())
+            .is_some_and(|owner| owner != tag && !diplomacy.has_alliance(tag, owner))
+    });
+    goals.war_targets.retain(|t| {
+        goals
+            .target_provinces
+            .iter()
+            .any(|id| state.provinces.get(id).and_then(|p| p.owner.as_deref()) == Some(t))
+    });
+    goals
+        .rivals_to_contain
+        .retain(|t| state.countries.contains_key(t));
+    goals
+        .alliance_targets
+        .retain(|t| state.countries.contains_key(t) && !diplomacy.has_alliance(tag, t));
+    goals.building_plan.retain(|id| {
+        state
+            .provinces
+            .get(id)
+            .is_some_and(|p| p.owner.as_deref() == Some(tag))
+    });
+    goals
+        .attitudes
+        .retain(|t, _| state.countries.contains_key(t));
+}
+
+fn regiments_by_country(state: &WorldState) -> HashMap<Tag, u32> {
+    state.armies.values().fold(HashMap::new(), |mut acc, army| {
+        *acc.entry(army.owner.clone()).or_default() += army.regiment_count();
+        acc
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::AiPersonalityRegistry;
+    use crate::fixed::Fixed;
+    use crate::state::{Regiment, RegimentType};
+    use crate::testing::{make_test_army, WorldStateBuilder};
+
+    /// SWE 1-2, DAN 3, NOR 4, LUB 5 in a line: 1-2-3-4, 2-5.
+    fn scandinavia() -> (WorldState, AdjacencyGraph) {
+        let state = WorldStateBuilder::new()
+            .with_country("SWE")
+            .with_country("DAN")
+            .with_country("NOR")
+            .with_country("LUB")
+            .with_province(1, Some("SWE"))
+            .with_province(2, Some("SWE"))
+            .with_province(3, Some("DAN"))
+            .with_province(4, Some("NOR"))
+            .with_province(5, Some("LUB"))
+            .build();
+        let mut adjacency = AdjacencyGraph::new();
+        adjacency.add_adjacency(1, 2);
+        adjacency.add_adjacency(2, 3);
+        adjacency.add_adjacency(3, 4);
+        adjacency.add_adjacency(2, 5);
+        (state, adjacency)
+    }
+
+    fn add_army(state: &mut WorldState, id: u32, owner: &str, regiments: u32) {
+        let regiments = (0..regiments)
+            .map(|_| Regiment {
+                type_: RegimentType::Infantry,
+                strength: Fixed::from_int(1000),
+                morale: Fixed::from_int(2),
+            })
+            .collect();
+        state
+            .armies
+            .insert(id, make_test_army(id, owner, 1, regiments));
+    }
+
+    #[test]
+    fn test_plan_targets_border_provinces() {
+        let (mut state, adjacency) = scandinavia();
+        state
+            .provinces
+            .get_mut(&3)
+            .unwrap()
+            .cores
+            .insert("SWE".to_string());
+        add_army(&mut state, 1, "SWE", 10);
+        add_army(&mut state, 2, "DAN", 5);
+        add_army(&mut state, 3, "LUB", 1);
+
+        run_strategy_tick(&mut state, Some(&adjacency));
+        let goals = &state.strategic_goals["SWE"];
+        // The cored Danish province comes first; Norway isn't a neighbour
+        assert_eq!(goals.target_provinces, vec![3, 5]);
+        assert_eq!(
+            goals.war_targets,
+            vec!["DAN".to_string(), "LUB".to_string()]
+        );
+        assert_eq!(goals.attitudes.get("NOR"), None);
+        assert_eq!(goals.attitudes["DAN"], Attitude::Neutral);
+        assert_eq!(goals.building_plan, vec![1, 2]);
+        assert_eq!(goals.next_review, Some(state.date.add_years(1)));
+        // Lübeck is too weak to be worth an alliance, Denmark is a target
+        assert!(goals.alliance_targets.is_empty());
+    }
+
+    #[test]
+    fn test_plan_contains_threats_and_courts_neighbours() {
+        let (mut state, adjacency) = scandinavia();
+        add_army(&mut state, 1, "SWE", 10);
+        add_army(&mut state, 2, "DAN", 20);
+        add_army(&mut state, 3, "LUB", 8);
+        state
+            .diplomacy
+            .royal_marriages
+            .insert(("LUB".to_string(), "SWE".to_string()));
+
+        run_strategy_tick(&mut state, Some(&adjacency));
+        let goals = &state.strategic_goals["SWE"];
+        assert_eq!(goals.attitudes["DAN"], Attitude::Threatened);
+        assert_eq!(goals.attitudes["LUB"], Attitude::Friendly);
+        assert_eq!(goals.rivals_to_contain, vec!["DAN".to_string()]);
+        assert_eq!(goals.alliance_targets, vec!["LUB".to_string()]);
+        assert!(goals.target_provinces.is_empty());
+    }
+
+    #[test]
+    fn test_personality_without_ally_priority_courts_nobody() {
+        let (mut state, adjacency) = scandinavia();
+        add_army(&mut state, 1, "SWE", 10);
+        add_army(&mut state, 2, "LUB", 8);
+        state
+            .diplomacy
+            .royal_marriages
+            .insert(("LUB".to_string(), "SWE".to_string()));
+        let strength = regiments_by_country(&state);
+        let goals = plan_goals(&state, "SWE", &strength, Some(&adjacency));
+        assert_eq!(goals.alliance_targets, vec!["LUB".to_string()]);
+
+        state.ai_personalities = AiPersonalityRegistry::new([AiPersonality {
+            name: "ai_loner".to_string(),
+            chance: Fixed::ONE,
+            priorities: [(AiPersonality::ALLY.to_string(), Fixed::ZERO)].into(),
+        }]);
+        state.countries.get_mut("SWE").unwrap().ai_personality = Some("ai_loner".to_string());
+        let goals = plan_goals(&state, "SWE", &strength, Some(&adjacency));
+        assert!(goals.alliance_targets.is_empty());
+    }
+
+    #[test]
+    fn test_loaded_attitudes_replace_builtin_effects() {
+        let (mut state, adjacency) = scandinavia();
+        add_army(&mut state, 1, "SWE", 10);
+        add_army(&mut state, 2, "DAN", 5);
+        let strength = regiments_by_country(&state);
+        let goals = plan_goals(&state, "SWE", &strength, Some(&adjacency));
+        assert_eq!(goals.target_provinces, vec![3, 5]);
+        assert!(goals.alliance_targets.is_empty());
+
+        // Neutral neighbours are courted instead of conquered
+        state.ai_attitudes = AiAttitudeRegistry::new([(
+            Attitude::Neutral,
+            AttitudeEffects::from_flags(&[AttitudeEffects::ALLY]),
+        )]);
+        let goals = plan_goals(&state, "SWE", &strength, Some(&adjacency));
+        assert_eq!(goals.attitudes["DAN"], Attitude::Neutral);
+        assert!(goals.target_provinces.is_empty());
+        assert!(goals.war_targets.is_empty());
+        assert_eq!(goals.alliance_targets, vec!["DAN".to_string()]);
+    }
+
+    #[test]
+    fn test_goals_are_pruned_between_reviews() {
+        let (mut state, adjacency) = scandinavia();
+        run_strategy_tick(&mut state, Some(&adjacency));
+        assert_eq!(state.strategic_goals["SWE"].target_provinces, vec![3, 5]);
+
+        // Sweden takes Lübeck's province a month later
+        state.date = state.date.add_days(30);
+        state.provinces.get_mut(&5).unwrap().owner = Some("SWE".to_string());
+        run_strategy_tick(&mut state, Some(&adjacency));
+        let goals = &state.strategic_goals["SWE"];
+        assert_eq!(goals.target_provinces, vec![3]);
+        assert_eq!(goals.war_targets, vec!["DAN".to_string()]);
+        // Not replanned yet: the new province isn't in the building plan
+        assert_eq!(goals.building_plan, vec![1, 2]);
+
+        // A year on, the plan is rebuilt
+        state.date = state.date.add_years(1);
+        run_strategy_tick(&mut state, Some(&adjacency));
+        assert_eq!(state.strategic_goals["SWE"].building_plan, vec![1, 2, 5]);
+    }
+}
@@ -0,0 +1,315 @@
+//! Construction of [`VisibleWorldState`] from the full simulation state.
+//!
+//! Lives in core (rather than the `eu4sim` binary) so that search-based AIs can
+//! build the same fog-of-war view for opponents inside their rollouts.
+
+use crate::ai::{FleetSummary, GeneralSummary, RelationsSummary, SiegeSummary, VisibleWorldState};
+use crate::fixed::Fixed;
+use crate::state::{Tag, WorldState};
+use eu4data::adjacency::AdjacencyGraph;
+use std::collections::{HashMap, HashSet};
+
+/// Per-tick data shared by every country's view.
+///
+/// Computing these once per tick keeps building N views at O(N) rather than
+/// O(N * armies + N * provinces).
+#[derive(Debug, Clone, Default)]
+pub struct VisibilityIndex {
+    /// Total regiments per country
+    global_strength: HashMap<Tag, u32>,
+    /// Countries sharing a province border with each country
+    neighbor_countries: HashMap<Tag, HashSet<Tag>>,
+}
+
+impl VisibilityIndex {
+    /// Index `state`. Without an adjacency graph no country has neighbours.
+    pub fn new(state: &WorldState, adjacency: Option<&AdjacencyGraph>) -> Self {
+        let global_strength = state.armies.values().fold(HashMap::new(), |mut acc, army| {
+            *acc.entry(army.owner.clone()).or_default() += army.regiment_count();
+            acc
+        });
+
+        // A country "knows" another if they share a province border
+        let mut neighbor_countries: HashMap<Tag, HashSet<Tag>> = HashMap::new();
+        if let Some(adjacency) = adjacency {
+            for (&prov_id, prov) in &state.provinces {
+                let Some(owner) = &prov.owner else { continue };
+                let known = neighbor_countries.entry(owner.clone()).or_default();
+                for neighbor_id in adjacency.neighbors(prov_id) {
+                    if let Some(neighbor_owner) = state
+                        .provinces
+                        .get(&neighbor_id)
+                        .and_then(|p| p.owner.as_ref())
+                    {
+                        if neighbor_owner != owner {
+                            known.insert(neighbor_owner.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        Self {
+            global_strength,
+            neighbor_countries,
+        }
+    }
+
+    /// Build what `tag` can see of `state`.
+    ///
+    /// `state` must be the state this index was built from.
+    pub fn visible_state(
+        &self,
+        state: &WorldState,
+        tag: &str,
+        adjacency: Option<&AdjacencyGraph>,
+    ) -> VisibleWorldState {
+        // Start with neighbor countries (fog of war baseline)
+        let mut known_countries: HashSet<Tag> = self
+            .neighbor_countries
+            .get(tag)
+            .cloned()
+            .unwrap_or_default();
+
+        let at_war = state
+            .diplomacy
+            .wars
+            .values()
+            .any(|war| war.attackers.iter().chain(&war.defenders).any(|t| t == tag));
+
+        // War scores and enemy provinces for this country
+        let mut our_war_score = HashMap::new();
+        let mut enemy_tags: HashSet<&Tag> = HashSet::new();
+        for war in state.diplomacy.wars.values() {
+            let is_attacker = war.attackers.iter().any(|t| t == tag);
+            let is_defender = war.defenders.iter().any(|t| t == tag);
+            if !is_attacker && !is_defender {
+                continue;
+            }
+
+            // All war participants are known
+            known_countries.extend(war.attackers.iter().chain(&war.defenders).cloned());
+
+            // Relative war score (positive = winning, negative = losing)
+            let (ours, theirs) = if is_attacker {
+                (war.attacker_score, war.defender_score)
+            } else {
+                (war.defender_score, war.attacker_score)
+            };
+            let score = Fixed::from_int(ours as i64) - Fixed::from_int(theirs as i64);
+            our_war_score.insert(war.id, score);
+
+            if is_attacker {
+                enemy_tags.extend(&war.defenders);
+            } else {
+                enemy_tags.extend(&war.attackers);
+            }
+        }
+        let enemy_provinces: HashSet<u32> = state
+            .provinces
+            .iter()
+            .filter(|(_, p)| p.owner.as_ref().is_some_and(|o| enemy_tags.contains(o)))
+            .map(|(&id, _)| id)
+            .collect();
+
+        // Total strength of all current war enemies
+        let current_war_enemy_strength: u32 = enemy_tags
+            .iter()
+            .filter_map(|enemy| self.global_strength.get(*enemy))
+            .sum();
+
+        // Filter strength to only known countries (fog of war)
+        let known_country_strength: HashMap<Tag, u32> = self
+            .global_strength
+            .iter()
+            .filter(|(country, _)| known_countries.contains(*country))
+            .map(|(k, v)| (k.clone(), *v))
+            .collect();
+
+        let own_generals: Vec<GeneralSummary> = state
+            .generals
+            .values()
+            .filter(|g| g.owner == tag)
+            .map(|g| {
+                let assigned_to = state
+                    .armies
+                    .values()
+                    .find(|a| a.general == Some(g.id))
+                    .map(|a| a.id);
+                GeneralSummary {
+                    id: g.id,
+                    fire: g.fire,
+                    shock: g.shock,
+                    maneuver: g.maneuver,
+                    siege: g.siege,
+                    assigned_to,
+                }
+            })
+            .collect();
+
+        let armies_without_general: Vec<u32> = state
+            .armies
+            .values()
+            .filter(|a| a.owner == tag && a.general.is_none())
+            .map(|a| a.id)
+            .collect();
+
+        let own_fleets: Vec<FleetSummary> = state
+            .fleets
+            .values()
+            .filter(|f| f.owner == tag)
+            .map(|f| FleetSummary {
+                id: f.id,
+                location: f.location,
+                ship_count: f.ships.len() as u32,
+                transport_capacity: f.ships.len() as u32, // Simplified
+                in_battle: f.in_battle.is_some(),
+            })
+            .collect();
+
+        // Province supply limits (1 regiment per development)
+        let province_supply: HashMap<u32, u32> = state
+            .provinces
+            .iter()
+            .map(|(id, p)| {
+                let dev = p.base_tax + p.base_production + p.base_manpower;
+                (*id, dev.to_int() as u32)
+            })
+            .collect();
+
+        let mut army_locations = HashMap::new();
+        for army in state.armies.values() {
+            *army_locations.entry(army.location).or_insert(0) += army.regiment_count();
+        }
+
+        // AE and coalitions (convert im::HashMap to std::HashMap)
+        let own_ae: HashMap<Tag, Fixed> = state
+            .countries
+            .get(tag)
+            .map(|c| {
+                c.aggressive_expansion
+                    .iter()
+                    .map(|(k, v)| (k.clone(), *v))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let coalition_against_us = state
+            .diplomacy
+            .coalitions
+            .get(tag)
+            .map(|c| c.members.clone());
+
+        // Enemy provinces with forts
+        let fort_provinces: HashSet<u32> = state
+            .provinces
+            .iter()
+            .filter(|(id, p)| enemy_provinces.contains(id) && p.fort_level > 0)
+            .map(|(id, _)| *id)
+            .collect();
+
+        let active_sieges: Vec<SiegeSummary> = state
+            .sieges
+            .values()
+            .filter(|s| s.attacker == tag)
+            .map(|s| {
+                // Rough estimate: average 30 days per phase, 12 phases max
+                let phases_left = 12 - s.progress_modifier.min(12);
+                SiegeSummary {
+                    province: s.province,
+                    fort_level: s.fort_level,
+                    progress_modifier: s.progress_modifier,
+                    days_remaining_estimate: (phases_left * 30) as u32,
+                }
+            })
+            .collect();
+
+        // Our army sizes - for Move scoring (don't move small stacks into enemy territory)
+        let our_army_sizes: HashMap<_, _> = state
+            .armies
+            .iter()
+            .filter(|(_, a)| a.owner == tag)
+            .map(|(&id, a)| (id, a.regiment_count()))
+            .collect();
+
+        // Our army provinces - for consolidation bonus (gravitate toward friendly stacks)
+        let our_army_provinces: HashMap<_, _> = state
+            .armies
+            .values()
+            .filter(|a| a.owner == tag && a.in_battle.is_none() && a.embarked_on.is_none())
+            .fold(HashMap::new(), |mut acc, a| {
+                *acc.entry(a.location).or_default() += a.regiment_count();
+                acc
+            });
+
+        // Staging provinces - friendly provinces adjacent to enemy territory
+        let staging_provinces: HashSet<u32> = match adjacency {
+            Some(adjacency) if at_war => state
+                .provinces
+                .iter()
+                .filter(|(_, p)| p.owner.as_deref() == Some(tag))
+                .filter(|(&prov_id, _)| {
+                    adjacency
+                        .neighbors(prov_id)
+                        .iter()
+                        .any(|n| enemy_provinces.contains(n))
+                })
+                .map(|(&id, _)| id)
+                .collect(),
+            _ => HashSet::new(),
+        };
+
+        // Opinions and offer acceptance with known countries and anyone
+        // who has an offer pending with us
+        let diplomacy = &state.diplomacy;
+        let offerers = diplomacy
+            .pending_alliance_offers
+            .keys()
+            .chain(diplomacy.pending_marriage_offers.keys())
+            .chain(diplomacy.pending_access_requests.keys())
+            .filter(|(_, to)| to == tag)
+            .map(|(from, _)| from);
+        let relations: HashMap<Tag, RelationsSummary> = known_countries
+            .iter()
+            .chain(offerers)
+            .filter(|other| other.as_str() != tag && state.countries.contains_key(*other))
+            .map(|other| (other.clone(), RelationsSummary::new(state, tag, other)))
+            .collect();
+
+        VisibleWorldState {
+            date: state.date,
+            observer: tag.to_string(),
+            own_country: state.countries.get(tag).cloned().unwrap_or_default(),
+            at_war,
+            known_countries: known_countries.into_iter().collect(),
+            enemy_provinces,
+            known_country_strength,
+            our_war_score,
+            own_generals,
+            armies_without_general,
+            own_fleets,
+            // Blocked straits need strait tracking in the adjacency graph
+            blocked_straits: HashSet::new(),
+            province_supply,
+            army_locations,
+            own_ae,
+            coalition_against_us,
+            fort_provinces,
+            active_sieges,
+            // Pending calls-to-arms need relationship tracking
+            pending_call_to_arms: vec![],
+            current_war_enemy_strength,
+            our_army_sizes,
+            our_army_provinces,
+            staging_provinces,
+            personality: state
+                .countries
+                .get(tag)
+                .and_then(|c| c.ai_personality.as_deref())
+                .and_then(|name| state.ai_personalities.get(name))
+                .cloned(),
+            strategic_goals: state.strategic_goals.get(tag).cloned().unwrap_or_default(),
+            relations,
+        }
+    }
+}
@@ -0,0 +1,714 @@
+//! Dense, index-addressed storage for countries and provinces.
+//!
+//! `WorldState` used to keep countries and provinces in `im::HashMap`s keyed
+//! by tag string / province id. Every system paid for string hashing and
+//! pointer chasing on each lookup, and iteration order was whatever the hash
+//! function produced. The two maps here replace them:
+//!
+//! - [`TagMap`]: values indexed by [`TagId`], with an embedded [`TagRegistry`]
+//!   so string-keyed lookups still work. The `TagId` of a tag *is* its slot.
+//! - [`ProvinceMap`]: values indexed directly by [`ProvinceId`].
+//!
+//! Both expose the `HashMap` API the simulation already uses (`get`,
+//! `get_mut`, `insert`, `iter`, ...) so call sites don't change, and both
+//! iterate in index order, which makes every system deterministic without
+//! collecting and sorting keys first.
+//!
+//! # Serialization
+//!
+//! Both serialize as plain maps (`{"SWE": {...}}`, `{"1": {...}}`), the same
+//! shape `im::HashMap` produced, but in index order. A `TagMap` also writes
+//! tags whose slot is empty (removed or only interned) as `null`, and
+//! deserializing re-interns every tag in file order, so a round trip
+//! preserves every `TagId`.
+
+use crate::state::{ProvinceId, Tag, TagId, TagRegistry};
+use rayon::prelude::*;
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+// ============================================================================
+// TagMap
+// ============================================================================
+
+/// Country-keyed storage backed by a `Vec` indexed by [`TagId`].
+///
+/// Removing a country leaves its slot empty; the tag stays interned, so a
+/// released tag that comes back gets the same `TagId`.
+#[derive(Clone)]
+pub struct TagMap<V> {
+    registry: TagRegistry,
+    slots: Vec<Option<V>>,
+    len: usize,
+}
+
+impl<V> TagMap<V> {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self {
+            registry: TagRegistry::new(),
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of occupied slots.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no country is stored.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The tag interner backing this map. `TagId`s from it index [`Self::get_by_id`].
+    #[inline]
+    pub fn registry(&self) -> &TagRegistry {
+        &self.registry
+    }
+
+    /// Look up the `TagId` of a tag without interning it.
+    #[inline]
+    pub fn id_of<Q: AsRef<str> + ?Sized>(&self, tag: &Q) -> Option<TagId> {
+        self.registry.get(tag.as_ref())
+    }
+
+    /// Resolve a `TagId` back to its tag string.
+    #[inline]
+    pub fn tag_of(&self, id: TagId) -> Option<&str> {
+        self.registry.try_resolve(id)
+    }
+
+    /// Intern a tag, reserving an (empty) slot for it if it's new.
+    ///
+    /// Used by caches that need a stable `TagId` for tags that may not own a
+    /// `CountryState` (e.g. province owners of released-but-dead countries).
+    pub fn intern(&mut self, tag: &str) -> TagId {
+        let id = self.registry.intern(tag);
+        if self.slots.len() <= id.0 as usize {
+            self.slots.resize_with(id.0 as usize + 1, || None);
+        }
+        id
+    }
+
+    #[inline]
+    pub fn get<Q: AsRef<str> + ?Sized>(&self, tag: &Q) -> Option<&V> {
+        self.id_of(tag).and_then(|id| self.get_by_id(id))
+    }
+
+    #[inline]
+    pub fn get_mut<Q: AsRef<str> + ?Sized>(&mut self, tag: &Q) -> Option<&mut V> {
+        self.id_of(tag).and_then(|id| self.get_by_id_mut(id))
+    }
+
+    #[inline]
+    pub fn get_by_id(&self, id: TagId) -> Option<&V> {
+        self.slots.get(id.0 as usize).and_then(Option::as_ref)
+    }
+
+    #[inline]
+    pub fn get_by_id_mut(&mut self, id: TagId) -> Option<&mut V> {
+        self.slots.get_mut(id.0 as usize).and_then(Option::as_mut)
+    }
+
+    #[inline]
+    pub fn contains_key<Q: AsRef<str> + ?Sized>(&self, tag: &Q) -> bool {
+        self.get(tag).is_some()
+    }
+
+    /// Insert a value, returning the previous value for this tag if any.
+    pub fn insert(&mut self, tag: Tag, value: V) -> Option<V> {
+        let id = self.intern(&tag);
+        let previous = self.slots[id.0 as usize].replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    /// Remove a value. The tag keeps its `TagId`.
+    pub fn remove<Q: AsRef<str> + ?Sized>(&mut self, tag: &Q) -> Option<V> {
+        let id = self.id_of(tag)?;
+        let previous = self.slots.get_mut(id.0 as usize)?.take();
+        if previous.is_some() {
+            self.len -= 1;
+        }
+        previous
+    }
+
+    /// Keep only the entries for which `keep` returns true.
+    pub fn retain(&mut self, mut keep: impl FnMut(&Tag, &mut V) -> bool) {
+        for (tag, slot) in self.registry.names().iter().zip(self.slots.iter_mut()) {
+            if let Some(value) = slot {
+                if !keep(tag, value) {
+                    *slot = None;
+                    self.len -= 1;
+                }
+            }
+        }
+    }
+
+    /// Iterate over `(tag, value)` in `TagId` order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Tag, &V)> + '_ {
+        self.registry
+            .names()
+            .iter()
+            .zip(self.slots.iter())
+            .filter_map(|(tag, slot)| slot.as_ref().map(|value| (tag, value)))
+    }
+
+    /// Mutably iterate over `(tag, value)` in `TagId` order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Tag, &mut V)> + '_ {
+        self.registry
+            .names()
+            .iter()
+            .zip(self.slots.iter_mut())
+            .filter_map(|(tag, slot)| slot.as_mut().map(|value| (tag, value)))
+    }
+
+    /// Mutably iterate over `(tag, value)` on the rayon pool.
+    ///
+    /// Collecting the iterator preserves `TagId` order.
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = (&Tag, &mut V)>
+    where
+        V: Send,
+    {
+        self.registry
+            .names()
+            .par_iter()
+            .zip(self.slots.par_iter_mut())
+            .filter_map(|(tag, slot)| slot.as_mut().map(|value| (tag, value)))
+    }
+
+    /// Iterate over `(TagId, value)` in `TagId` order.
+    pub fn iter_ids(&self) -> impl Iterator<Item = (TagId, &V)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| slot.as_ref().map(|value| (TagId(i as u16), value)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Tag> + '_ {
+        self.iter().map(|(tag, _)| tag)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.slots.iter().filter_map(Option::as_ref)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> + '_ {
+        self.slots.iter_mut().filter_map(Option::as_mut)
+    }
+}
+
+impl<V> Default for TagMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for TagMap<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<V: PartialEq> PartialEq for TagMap<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().all(|(tag, v)| other.get(tag) == Some(v))
+    }
+}
+
+impl<V, Q: AsRef<str> + ?Sized> Index<&Q> for TagMap<V> {
+    type Output = V;
+
+    fn index(&self, tag: &Q) -> &V {
+        self.get(tag)
+            .unwrap_or_else(|| panic!("no country with tag {}", tag.as_ref()))
+    }
+}
+
+impl<V, Q: AsRef<str> + ?Sized> IndexMut<&Q> for TagMap<V> {
+    fn index_mut(&mut self, tag: &Q) -> &mut V {
+        let id = self
+            .id_of(tag)
+            .filter(|&id| self.get_by_id(id).is_some())
+            .unwrap_or_else(|| panic!("no country with tag {}", tag.as_ref()));
+        self.slots[id.0 as usize]
+            .as_mut()
+            .expect("slot checked above")
+    }
+}
+
+impl<V> FromIterator<(Tag, V)> for TagMap<V> {
+    fn from_iter<I: IntoIterator<Item = (Tag, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<V> Extend<(Tag, V)> for TagMap<V> {
+    fn extend<I: IntoIterator<Item = (Tag, V)>>(&mut self, iter: I) {
+        for (tag, value) in iter {
+            self.insert(tag, value);
+        }
+    }
+}
+
+/// Tags are interned in sorted order so the resulting `TagId`s don't depend
+/// on the source map's hash order.
+impl<V> From<std::collections::HashMap<Tag, V>> for TagMap<V> {
+    fn from(map: std::collections::HashMap<Tag, V>) -> Self {
+        let mut entries: Vec<_> = map.into_iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries.into_iter().collect()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a TagMap<V> {
+    type Item = (&'a Tag, &'a V);
+    type IntoIter = Box<dyn Iterator<Item = (&'a Tag, &'a V)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+impl<'a, V> IntoIterator for &'a mut TagMap<V> {
+    type Item = (&'a Tag, &'a mut V);
+    type IntoIter = Box<dyn Iterator<Item = (&'a Tag, &'a mut V)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter_mut())
+    }
+}
+
+/// Every interned tag is written, empty slots as `null`, so the registry
+/// survives a round trip.
+impl<V: Serialize> Serialize for TagMap<V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let names = self.registry.names();
+        let mut map = serializer.serialize_map(Some(names.len()))?;
+        for (i, tag) in names.iter().enumerate() {
+            let value = self.slots.get(i).and_then(Option::as_ref);
+            map.serialize_entry(tag, &value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for TagMap<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TagMapVisitor<V>(PhantomData<V>);
+
+        impl<'de, V: Deserialize<'de>> Visitor<'de> for TagMapVisitor<V> {
+            type Value = TagMap<V>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of country tags to values")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+                let mut map = TagMap::new();
+                while let Some((tag, value)) = access.next_entry::<Tag, Option<V>>()? {
+                    match value {
+                        Some(value) => {
+                            map.insert(tag, value);
+                        }
+                        None => {
+                            map.intern(&tag);
+                        }
+                    }
+                }
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_map(TagMapVisitor(PhantomData))
+    }
+}
+
+// ============================================================================
+// ProvinceMap
+// ============================================================================
+
+/// Province-keyed storage backed by a `Vec` indexed by [`ProvinceId`].
+///
+/// EU4 province ids are dense (1..~5000), so the slot vector stays small.
+/// Each slot keeps its id alongside the value so iteration can hand out
+/// `&ProvinceId` like a `HashMap` does.
+#[derive(Clone)]
+pub struct ProvinceMap<V> {
+    slots: Vec<Option<(ProvinceId, V)>>,
+    len: usize,
+}
+
+impl<V> ProvinceMap<V> {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Number of occupied slots.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no province is stored.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn get(&self, id: &ProvinceId) -> Option<&V> {
+        self.slots
+            .get(*id as usize)
+            .and_then(|slot| slot.as_ref().map(|(_, value)| value))
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, id: &ProvinceId) -> Option<&mut V> {
+        self.slots
+            .get_mut(*id as usize)
+            .and_then(|slot| slot.as_mut().map(|(_, value)| value))
+    }
+
+    #[inline]
+    pub fn contains_key(&self, id: &ProvinceId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Insert a value, returning the previous value for this province if any.
+    pub fn insert(&mut self, id: ProvinceId, value: V) -> Option<V> {
+        let index = id as usize;
+        if self.slots.len() <= index {
+            self.slots.resize_with(index + 1, || None);
+        }
+        let previous = self.slots[index].replace((id, value)).map(|(_, v)| v);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, id: &ProvinceId) -> Option<V> {
+        let previous = self.slots.get_mut(*id as usize)?.take().map(|(_, v)| v);
+        if previous.is_some() {
+            self.len -= 1;
+        }
+        previous
+    }
+
+    /// Keep only the entries for which `keep` returns true.
+    pub fn retain(&mut self, mut keep: impl FnMut(&ProvinceId, &mut V) -> bool) {
+        for slot in self.slots.iter_mut() {
+            if let Some((id, value)) = slot {
+                if !keep(id, value) {
+                    *slot = None;
+                    self.len -= 1;
+                }
+            }
+        }
+    }
+
+    /// Iterate over `(id, value)` in ascending id order.
+    pub fn iter(&self) -> impl Iterator<Item = (&ProvinceId, &V)> + '_ {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.as_ref().map(|(id, value)| (id, value)))
+    }
+
+    /// Mutably iterate over `(id, value)` in ascending id order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&ProvinceId, &mut V)> + '_ {
+        self.slots
+            .iter_mut()
+            .filter_map(|slot| slot.as_mut().map(|(id, value)| (&*id, value)))
+    }
+
+    /// Mutably iterate over `(id, value)` on the rayon pool.
+    ///
+    /// Collecting the iterator preserves ascending id order.
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = (ProvinceId, &mut V)>
+    where
+        V: Send,
+    {
+        self.slots
+            .par_iter_mut()
+            .filter_map(|slot| slot.as_mut().map(|(id, value)| (*id, value)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &ProvinceId> + '_ {
+        self.iter().map(|(id, _)| id)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> + '_ {
+        self.iter_mut().map(|(_, value)| value)
+    }
+}
+
+impl<V> Default for ProvinceMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for ProvinceMap<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<V: PartialEq> PartialEq for ProvinceMap<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().all(|(id, v)| other.get(id) == Some(v))
+    }
+}
+
+impl<V> Index<&ProvinceId> for ProvinceMap<V> {
+    type Output = V;
+
+    fn index(&self, id: &ProvinceId) -> &V {
+        self.get(id)
+            .unwrap_or_else(|| panic!("no province with id {}", id))
+    }
+}
+
+impl<V> IndexMut<&ProvinceId> for ProvinceMap<V> {
+    fn index_mut(&mut self, id: &ProvinceId) -> &mut V {
+        let id = *id;
+        self.get_mut(&id)
+            .unwrap_or_else(|| panic!("no province with id {}", id))
+    }
+}
+
+impl<V> FromIterator<(ProvinceId, V)> for ProvinceMap<V> {
+    fn from_iter<I: IntoIterator<Item = (ProvinceId, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<V> Extend<(ProvinceId, V)> for ProvinceMap<V> {
+    fn extend<I: IntoIterator<Item = (ProvinceId, V)>>(&mut self, iter: I) {
+        for (id, value) in iter {
+            self.insert(id, value);
+        }
+    }
+}
+
+impl<V> From<std::collections::HashMap<ProvinceId, V>> for ProvinceMap<V> {
+    fn from(map: std::collections::HashMap<ProvinceId, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<'a, V> IntoIterator for &'a ProvinceMap<V> {
+    type Item = (&'a ProvinceId, &'a V);
+    type IntoIter = Box<dyn Iterator<Item = (&'a ProvinceId, &'a V)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+impl<'a, V> IntoIterator for &'a mut ProvinceMap<V> {
+    type Item = (&'a ProvinceId, &'a mut V);
+    type IntoIter = Box<dyn Iterator<Item = (&'a ProvinceId, &'a mut V)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter_mut())
+    }
+}
+
+impl<V: Serialize> Serialize for ProvinceMap<V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len))?;
+        for (id, value) in self.iter() {
+            map.serialize_entry(id, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for ProvinceMap<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ProvinceMapVisitor<V>(PhantomData<V>);
+
+        impl<'de, V: Deserialize<'de>> Visitor<'de> for ProvinceMapVisitor<V> {
+            type Value = ProvinceMap<V>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of province ids to values")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Self::Value, A::Error> {
+                let mut map = ProvinceMap::new();
+                while let Some((id, value)) = access.next_entry::<ProvinceId, V>()? {
+                    map.insert(id, value);
+                }
+                Ok(map)
+            }
+        }
+
+        deserializer.deserialize_map(ProvinceMapVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_map_ids_follow_insertion_order() {
+        let mut map = TagMap::new();
+        map.insert("SWE".to_string(), 1);
+        map.insert("DEN".to_string(), 2);
+        map.insert("NOR".to_string(), 3);
+
+        assert_eq!(map.id_of("SWE"), Some(TagId(0)));
+        assert_eq!(map.id_of("NOR"), Some(TagId(2)));
+        assert_eq!(map.get_by_id(TagId(1)), Some(&2));
+        let tags: Vec<_> = map.keys().cloned().collect();
+        assert_eq!(tags, vec!["SWE", "DEN", "NOR"]);
+    }
+
+    #[test]
+    fn test_tag_map_remove_keeps_id() {
+        let mut map = TagMap::new();
+        map.insert("SWE".to_string(), 1);
+        map.insert("DEN".to_string(), 2);
+
+        assert_eq!(map.remove("SWE"), Some(1));
+        assert_eq!(map.len(), 1);
+        assert!(!map.contains_key("SWE"));
+        assert_eq!(map.keys().count(), 1);
+
+        // Re-inserting reuses the old slot
+        map.insert("SWE".to_string(), 5);
+        assert_eq!(map.id_of("SWE"), Some(TagId(0)));
+        assert_eq!(map["SWE"], 5);
+    }
+
+    #[test]
+    fn test_tag_map_intern_reserves_empty_slot() {
+        let mut map: TagMap<i32> = TagMap::new();
+        let id = map.intern("REB");
+        assert_eq!(map.get_by_id(id), None);
+        assert!(map.is_empty());
+        assert_eq!(map.tag_of(id), Some("REB"));
+    }
+
+    #[test]
+    fn test_tag_map_serde_round_trip_preserves_ids() {
+        let mut map = TagMap::new();
+        map.insert("TUR".to_string(), 1);
+        map.insert("BYZ".to_string(), 2);
+        map.insert("FRA".to_string(), 3);
+
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"TUR":1,"BYZ":2,"FRA":3}"#);
+
+        let back: TagMap<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, map);
+        for tag in ["TUR", "BYZ", "FRA"] {
+            assert_eq!(back.id_of(tag), map.id_of(tag));
+        }
+    }
+
+    #[test]
+    fn test_tag_map_serde_round_trip_keeps_removed_tags() {
+        let mut map = TagMap::new();
+        map.insert("TUR".to_string(), 1);
+        map.insert("BYZ".to_string(), 2);
+        map.insert("FRA".to_string(), 3);
+        map.intern("REB");
+        map.remove("BYZ");
+
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"TUR":1,"BYZ":null,"FRA":3,"REB":null}"#);
+        let mut back: TagMap<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, map);
+        assert_eq!(back.len(), 2);
+
+        // Bringing BYZ back reuses its slot, as in an uninterrupted run
+        map.insert("BYZ".to_string(), 4);
+        back.insert("BYZ".to_string(), 4);
+        for tag in ["TUR", "BYZ", "FRA", "REB"] {
+            assert_eq!(back.id_of(tag), map.id_of(tag));
+        }
+        let tags: Vec<_> = back.keys().cloned().collect();
+        assert_eq!(tags, vec!["TUR", "BYZ", "FRA"]);
+    }
+
+    #[test]
+    fn test_tag_map_from_hashmap_is_sorted() {
+        let source: std::collections::HashMap<Tag, i32> = [("SWE", 1), ("DEN", 2), ("ARA", 3)]
+            .into_iter()
+            .map(|(t, v)| (t.to_string(), v))
+            .collect();
+        let map = TagMap::from(source);
+        let tags: Vec<_> = map.keys().cloned().collect();
+        assert_eq!(tags, vec!["ARA", "DEN", "SWE"]);
+    }
+
+    #[test]
+    fn test_province_map_basic() {
+        let mut map = ProvinceMap::new();
+        assert_eq!(map.insert(151, "Constantinople"), None);
+        assert_eq!(map.insert(1, "Stockholm"), None);
+        assert_eq!(map.insert(1, "Stockholm!"), Some("Stockholm"));
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&151), Some(&"Constantinople"));
+        assert_eq!(map.get(&2), None);
+        assert_eq!(map.get(&10_000), None);
+
+        let ids: Vec<_> = map.keys().copied().collect();
+        assert_eq!(ids, vec![1, 151]);
+
+        assert_eq!(map.remove(&1), Some("Stockholm!"));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_province_map_serde_round_trip() {
+        let map: ProvinceMap<i32> = [(3, 30), (1, 10)].into_iter().collect();
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(json, r#"{"1":10,"3":30}"#);
+
+        let back: ProvinceMap<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, map);
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut countries: TagMap<i32> = [("A".to_string(), 1), ("B".to_string(), 2)]
+            .into_iter()
+            .collect();
+        countries.retain(|_, v| *v > 1);
+        assert_eq!(countries.len(), 1);
+        assert!(countries.contains_key("B"));
+
+        let mut provinces: ProvinceMap<i32> = [(1, 1), (2, 2), (3, 3)].into_iter().collect();
+        provinces.retain(|id, _| id % 2 == 1);
+        assert_eq!(provinces.keys().copied().collect::<Vec<_>>(), vec![1, 3]);
+    }
+}
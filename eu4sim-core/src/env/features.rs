@@ -0,0 +1,201 @@
+//! Fixed-size feature vectors for RL policies.
+//!
+//! [`encode`] flattens the observer's [`VisibleWorldState`] into
+//! [`FEATURE_DIM`] floats, so a policy network sees the same tensor shape on
+//! every tick regardless of how many wars, armies or neighbours there are.
+//! Values are divided by a rough typical magnitude so most land in `[-1, 1]`;
+//! they are not clamped.
+//!
+//! The layout is part of the environment protocol: append new features at the
+//! end and keep [`FEATURE_NAMES`] in sync.
+
+use crate::ai::VisibleWorldState;
+use crate::fixed::Fixed;
+
+/// Number of features produced by [`encode`].
+pub const FEATURE_DIM: usize = FEATURE_NAMES.len();
+
+/// Name of each feature, in tensor order.
+pub const FEATURE_NAMES: [&str; 41] = [
+    "years_elapsed",
+    "treasury",
+    "manpower",
+    "stability",
+    "prestige",
+    "army_tradition",
+    "adm_mana",
+    "dip_mana",
+    "mil_mana",
+    "adm_tech",
+    "dip_tech",
+    "mil_tech",
+    "ruler_adm",
+    "ruler_dip",
+    "ruler_mil",
+    "overextension",
+    "loans",
+    "monthly_income",
+    "monthly_expenses",
+    "at_war",
+    "wars",
+    "mean_war_score",
+    "worst_war_score",
+    "own_strength",
+    "war_enemy_strength",
+    "strongest_known_strength",
+    "known_countries",
+    "generals",
+    "armies_without_general",
+    "fleets",
+    "active_sieges",
+    "pending_calls_to_arms",
+    "total_ae",
+    "max_ae",
+    "coalition_size",
+    "enemy_provinces",
+    "enemy_forts",
+    "diplomats_on_mission",
+    "rivals",
+    "mean_opinion_of_us",
+    "target_provinces",
+];
+
+/// First year of the grand campaign; `years_elapsed` counts from here.
+const START_YEAR: i32 = 1444;
+
+fn scaled(value: Fixed, scale: f32) -> f32 {
+    value.to_f32() / scale
+}
+
+fn count(n: usize, scale: f32) -> f32 {
+    n as f32 / scale
+}
+
+/// Encode the observer's view as a [`FEATURE_DIM`]-long vector.
+pub fn encode(visible: &VisibleWorldState) -> Vec<f32> {
+    let own = &visible.own_country;
+
+    let war_scores: Vec<f32> = visible.our_war_score.values().map(|s| s.to_f32()).collect();
+    let mean_war_score = if war_scores.is_empty() {
+        0.0
+    } else {
+        war_scores.iter().sum::<f32>() / war_scores.len() as f32
+    };
+    let worst_war_score = war_scores.iter().copied().fold(0.0, f32::min);
+
+    let own_strength = visible
+        .known_country_strength
+        .get(&visible.observer)
+        .copied()
+        .unwrap_or(0);
+    let strongest_known = visible
+        .known_country_strength
+        .iter()
+        .filter(|(tag, _)| **tag != visible.observer)
+        .map(|(_, &strength)| strength)
+        .max()
+        .unwrap_or(0);
+
+    let total_ae = visible
+        .own_ae
+        .values()
+        .fold(Fixed::ZERO, |sum, &ae| sum + ae);
+    let max_ae = visible
+        .own_ae
+        .values()
+        .copied()
+        .max()
+        .unwrap_or(Fixed::ZERO);
+
+    let mean_opinion = if visible.relations.is_empty() {
+        0.0
+    } else {
+        visible
+            .relations
+            .values()
+            .map(|r| r.their_opinion as f32)
+            .sum::<f32>()
+            / visible.relations.len() as f32
+    };
+
+    let income = own.income.taxation + own.income.trade + own.income.production;
+
+    let features = vec![
+        (visible.date.year - START_YEAR) as f32 / 100.0,
+        scaled(own.treasury, 1000.0),
+        scaled(own.manpower, 10_000.0),
+        own.stability.get() as f32 / 3.0,
+        scaled(own.prestige.get(), 100.0),
+        scaled(own.army_tradition.get(), 100.0),
+        scaled(own.adm_mana, 999.0),
+        scaled(own.dip_mana, 999.0),
+        scaled(own.mil_mana, 999.0),
+        own.adm_tech as f32 / 32.0,
+        own.dip_tech as f32 / 32.0,
+        own.mil_tech as f32 / 32.0,
+        own.ruler_adm as f32 / 6.0,
+        own.ruler_dip as f32 / 6.0,
+        own.ruler_mil as f32 / 6.0,
+        scaled(own.overextension, 100.0),
+        own.loans as f32 / 10.0,
+        scaled(income, 10.0),
+        scaled(own.income.expenses, 10.0),
+        if visible.at_war { 1.0 } else { 0.0 },
+        count(war_scores.len(), 5.0),
+        mean_war_score / 100.0,
+        worst_war_score / 100.0,
+        own_strength as f32 / 100.0,
+        visible.current_war_enemy_strength as f32 / 100.0,
+        strongest_known as f32 / 100.0,
+        count(visible.known_countries.len(), 100.0),
+        count(visible.own_generals.len(), 10.0),
+        count(visible.armies_without_general.len(), 10.0),
+        count(visible.own_fleets.len(), 10.0),
+        count(visible.active_sieges.len(), 10.0),
+        count(visible.pending_call_to_arms.len(), 5.0),
+        scaled(total_ae, 100.0),
+        scaled(max_ae, 100.0),
+        count(
+            visible
+                .coalition_against_us
+                .as_ref()
+                .map_or(0, |members| members.len()),
+            10.0,
+        ),
+        count(visible.enemy_provinces.len(), 100.0),
+        count(visible.fort_provinces.len(), 10.0),
+        count(own.diplomats.len(), 5.0),
+        count(own.rivals.len(), 3.0),
+        mean_opinion / 200.0,
+        count(visible.strategic_goals.target_provinces.len(), 10.0),
+    ];
+    debug_assert_eq!(features.len(), FEATURE_DIM);
+    features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::VisibilityIndex;
+    use crate::testing::WorldStateBuilder;
+
+    #[test]
+    fn test_encode_is_fixed_size_and_scaled() {
+        let mut state = WorldStateBuilder::new()
+            .with_country("SWE")
+            .with_country("DAN")
+            .with_province(1, Some("SWE"))
+            .with_province(2, Some("DAN"))
+            .build();
+        state.countries.get_mut("SWE").unwrap().treasury = Fixed::from_int(500);
+
+        let visible = VisibilityIndex::new(&state, None).visible_state(&state, "SWE", None);
+        let features = encode(&visible);
+        assert_eq!(features.len(), FEATURE_DIM);
+
+        let treasury = FEATURE_NAMES.iter().position(|&n| n == "treasury").unwrap();
+        assert_eq!(features[treasury], 0.5);
+        let at_war = FEATURE_NAMES.iter().position(|&n| n == "at_war").unwrap();
+        assert_eq!(features[at_war], 0.0);
+    }
+}
@@ -0,0 +1,480 @@
+//! Gym-style reinforcement learning environment.
+//!
+//! [`Env`] puts one country under external control and runs everyone else
+//! with a scripted [`OpponentPolicy`]:
+//!
+//! ```text
+//! reset(seed, start_date, tag) ──▶ Observation
+//! step(actions)                ──▶ Observation, reward, done
+//! ```
+//!
+//! Each step applies the agent's actions on the current day, then simulates
+//! [`EnvConfig::days_per_step`] days. Observations carry a fixed-size feature
+//! vector ([`features`]) and the legal commands; actions are either indices
+//! into those commands or raw [`Command`]s. Rewards are pluggable
+//! ([`reward`]). The episode ends when the country loses its last province or
+//! after [`EnvConfig::episode_days`].
+//!
+//! [`protocol`] serves an `Env` as newline-delimited JSON so training scripts
+//! in other languages can drive it (`eu4sim env`).
+
+pub mod features;
+pub mod protocol;
+pub mod reward;
+
+use crate::ai::{AiPlayer, GreedyAI, OpponentPolicy, RandomAi, VisibilityIndex};
+use crate::config::SimConfig;
+use crate::input::{Command, PlayerInputs};
+use crate::state::{Date, Tag, WorldState};
+use crate::step::step_world_mut;
+use eu4data::adjacency::AdjacencyGraph;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use thiserror::Error;
+
+pub use features::{encode, FEATURE_DIM, FEATURE_NAMES};
+pub use reward::{
+    reward_by_name, DevGrowthReward, RewardFn, ScoreReward, SurvivalReward, REWARD_NAMES,
+};
+
+/// Where episodes start from.
+pub trait Scenario: Send {
+    /// Initial world for an episode starting on `start_date`, or the
+    /// scenario's own start when `None`.
+    ///
+    /// May return a world dated before `start_date`; [`Env::reset`] then
+    /// simulates forward to it.
+    fn load(
+        &mut self,
+        start_date: Option<Date>,
+    ) -> Result<(WorldState, Arc<AdjacencyGraph>), String>;
+}
+
+/// A single pre-built world, cloned for every episode.
+pub struct FixedScenario {
+    state: WorldState,
+    adjacency: Arc<AdjacencyGraph>,
+}
+
+impl FixedScenario {
+    pub fn new(state: WorldState, adjacency: Arc<AdjacencyGraph>) -> Self {
+        Self { state, adjacency }
+    }
+}
+
+impl Scenario for FixedScenario {
+    fn load(
+        &mut self,
+        _start_date: Option<Date>,
+    ) -> Result<(WorldState, Arc<AdjacencyGraph>), String> {
+        Ok((self.state.clone(), self.adjacency.clone()))
+    }
+}
+
+/// Tuning knobs for [`Env`].
+#[derive(Debug, Clone)]
+pub struct EnvConfig {
+    /// Days simulated per [`Env::step`]
+    pub days_per_step: u32,
+    /// Episode length in days (360-day years)
+    pub episode_days: u32,
+    /// How the other countries play
+    pub opponents: OpponentPolicy,
+    pub sim: SimConfig,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        Self {
+            days_per_step: 30,
+            episode_days: 3600,
+            opponents: OpponentPolicy::Greedy,
+            sim: SimConfig::default(),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum EnvError {
+    #[error("No episode running: call reset first")]
+    NotReset,
+    #[error("Episode is over: call reset to start a new one")]
+    EpisodeDone,
+    #[error("Country {tag} does not exist or owns no provinces")]
+    UnknownCountry { tag: String },
+    #[error("Start date {requested} is before the scenario start {earliest}")]
+    StartDateTooEarly { requested: Date, earliest: Date },
+    #[error("Action index {index} out of range ({available} commands available)")]
+    InvalidAction { index: usize, available: usize },
+    #[error("Failed to load scenario: {0}")]
+    Scenario(String),
+}
+
+/// What the agent does this step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Action {
+    /// Index into [`Observation::available_commands`]
+    Index(usize),
+    /// A command issued directly (invalid ones are rejected by the sim)
+    Command(Command),
+}
+
+/// What the agent sees after `reset` and every `step`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Observation {
+    pub date: Date,
+    pub tag: Tag,
+    /// [`FEATURE_DIM`] features, see [`FEATURE_NAMES`]
+    pub features: Vec<f32>,
+    /// Legal commands; empty once the country is eliminated
+    pub available_commands: Vec<Command>,
+}
+
+/// Result of [`Env::step`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepResult {
+    pub observation: Observation,
+    pub reward: f32,
+    pub done: bool,
+}
+
+struct Episode {
+    state: WorldState,
+    adjacency: Arc<AdjacencyGraph>,
+    tag: Tag,
+    opponents: BTreeMap<Tag, Box<dyn AiPlayer>>,
+    end_date: Date,
+    available: Vec<Command>,
+    done: bool,
+}
+
+/// A single-agent environment over the full simulation.
+pub struct Env {
+    scenario: Box<dyn Scenario>,
+    reward: Box<dyn RewardFn>,
+    config: EnvConfig,
+    episode: Option<Episode>,
+}
+
+impl Env {
+    pub fn new(scenario: Box<dyn Scenario>, reward: Box<dyn RewardFn>, config: EnvConfig) -> Self {
+        Self {
+            scenario,
+            reward,
+            config,
+            episode: None,
+        }
+    }
+
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    pub fn reward_name(&self) -> &'static str {
+        self.reward.name()
+    }
+
+    /// Current world of the running episode.
+    pub fn state(&self) -> Option<&WorldState> {
+        self.episode.as_ref().map(|e| &e.state)
+    }
+
+    /// Start a new episode controlling `tag`.
+    ///
+    /// `seed` replaces the world's RNG seed, so equal seeds and actions replay
+    /// identically. A `start_date` after the scenario's is reached by letting
+    /// every country (including `tag`) play the opponent policy until then.
+    pub fn reset(
+        &mut self,
+        seed: u64,
+        start_date: Option<Date>,
+        tag: &str,
+    ) -> Result<Observation, EnvError> {
+        self.episode = None;
+        let (mut state, adjacency) = self.scenario.load(start_date).map_err(EnvError::Scenario)?;
+        state.rng_seed = seed;
+        state.rng_state = 0;
+
+        let mut opponents = build_opponents(&state, self.config.opponents, seed);
+        if let Some(start) = start_date {
+            if start < state.date {
+                return Err(EnvError::StartDateTooEarly {
+                    requested: start,
+                    earliest: state.date,
+                });
+            }
+            if start > state.date {
+                log::info!("Env: fast-forwarding {} -> {}", state.date, start);
+            }
+            while state.date < start {
+                let inputs = opponent_inputs(&state, &adjacency, &mut opponents);
+                step_world_mut(
+                    &mut state,
+                    &inputs,
+                    Some(&adjacency),
+                    &self.config.sim,
+                    None,
+                );
+            }
+        }
+
+        if !reward::is_alive(&state, tag) {
+            return Err(EnvError::UnknownCountry {
+                tag: tag.to_string(),
+            });
+        }
+        opponents.remove(tag);
+        self.reward.reset(&state, tag);
+
+        let end_date = state.date.add_days(self.config.episode_days);
+        let mut episode = Episode {
+            state,
+            adjacency,
+            tag: tag.to_string(),
+            opponents,
+            end_date,
+            available: Vec::new(),
+            done: false,
+        };
+        let observation = observe(&mut episode);
+        self.episode = Some(episode);
+        Ok(observation)
+    }
+
+    /// Apply `actions` and advance the world by one step.
+    pub fn step(&mut self, actions: &[Action]) -> Result<StepResult, EnvError> {
+        let episode = self.episode.as_mut().ok_or(EnvError::NotReset)?;
+        if episode.done {
+            return Err(EnvError::EpisodeDone);
+        }
+
+        let commands = actions
+            .iter()
+            .map(|action| match action {
+                Action::Index(index) => {
+                    episode
+                        .available
+                        .get(*index)
+                        .cloned()
+                        .ok_or(EnvError::InvalidAction {
+                            index: *index,
+                            available: episode.available.len(),
+                        })
+                }
+                Action::Command(cmd) => Ok(cmd.clone()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut pending = Some(commands);
+        for _ in 0..self.config.days_per_step.max(1) {
+            let mut inputs =
+                opponent_inputs(&episode.state, &episode.adjacency, &mut episode.opponents);
+            if let Some(commands) = pending.take() {
+                inputs.push(PlayerInputs {
+                    country: episode.tag.clone(),
+                    commands,
+                    available_commands: vec![],
+                    visible_state: None,
+                });
+            }
+            step_world_mut(
+                &mut episode.state,
+                &inputs,
+                Some(&episode.adjacency),
+                &self.config.sim,
+                None,
+            );
+            if !reward::is_alive(&episode.state, &episode.tag)
+                || episode.state.date >= episode.end_date
+            {
+                break;
+            }
+        }
+
+        let reward = self.reward.reward(&episode.state, &episode.tag);
+        episode.done = !reward::is_alive(&episode.state, &episode.tag)
+            || episode.state.date >= episode.end_date;
+        let observation = observe(episode);
+        Ok(StepResult {
+            observation,
+            reward,
+            done: episode.done,
+        })
+    }
+}
+
+/// Scripted AIs for every country, seeded from the episode seed.
+fn build_opponents(
+    state: &WorldState,
+    policy: OpponentPolicy,
+    seed: u64,
+) -> BTreeMap<Tag, Box<dyn AiPlayer>> {
+    state
+        .countries
+        .keys()
+        .filter_map(|tag| {
+            let ai: Box<dyn AiPlayer> = match policy {
+                OpponentPolicy::Passive => return None,
+                OpponentPolicy::Greedy => Box::new(GreedyAI::new()),
+                OpponentPolicy::Random => {
+                    let tag_hash: u64 = tag.as_bytes().iter().map(|&b| b as u64).sum();
+                    Box::new(RandomAi::new(seed.wrapping_add(tag_hash)))
+                }
+            };
+            Some((tag.clone(), ai))
+        })
+        .collect()
+}
+
+/// One day of decisions from the scripted countries that still exist.
+fn opponent_inputs(
+    state: &WorldState,
+    adjacency: &AdjacencyGraph,
+    opponents: &mut BTreeMap<Tag, Box<dyn AiPlayer>>,
+) -> Vec<PlayerInputs> {
+    let index = VisibilityIndex::new(state, Some(adjacency));
+    opponents
+        .par_iter_mut()
+        .filter(|(tag, _)| state.countries.contains_key(*tag))
+        .map(|(tag, ai)| {
+            let visible = index.visible_state(state, tag, Some(adjacency));
+            let available = state.available_commands(tag, Some(adjacency));
+            ai.observe_world(state);
+            PlayerInputs {
+                country: tag.clone(),
+                commands: ai.decide(&visible, &available),
+                available_commands: vec![],
+                visible_state: None,
+            }
+        })
+        .collect()
+}
+
+/// Build the agent's observation and remember its legal commands.
+fn observe(episode: &mut Episode) -> Observation {
+    let (features, available) = if reward::is_alive(&episode.state, &episode.tag) {
+        let adjacency = Some(episode.adjacency.as_ref());
+        let visible = VisibilityIndex::new(&episode.state, adjacency).visible_state(
+            &episode.state,
+            &episode.tag,
+            adjacency,
+        );
+        (
+            features::encode(&visible),
+            episode.state.available_commands(&episode.tag, adjacency),
+        )
+    } else {
+        (vec![0.0; FEATURE_DIM], Vec::new())
+    };
+    episode.available = available.clone();
+    Observation {
+        date: episode.state.date,
+        tag: episode.tag.clone(),
+        features,
+        available_commands: available,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed::Fixed;
+    use crate::input::DevType;
+    use crate::testing::WorldStateBuilder;
+
+    fn two_country_env(reward: &str) -> Env {
+        let mut state = WorldStateBuilder::new()
+            .with_country("SWE")
+            .with_country("DAN")
+            .with_province(1, Some("SWE"))
+            .with_province(2, Some("SWE"))
+            .with_province(3, Some("DAN"))
+            .build();
+        state.countries.get_mut("SWE").unwrap().adm_mana = Fixed::from_int(900);
+        let mut adjacency = AdjacencyGraph::new();
+        adjacency.add_adjacency(1, 2);
+        adjacency.add_adjacency(2, 3);
+        let config = EnvConfig {
+            days_per_step: 10,
+            episode_days: 30,
+            opponents: OpponentPolicy::Passive,
+            ..Default::default()
+        };
+        Env::new(
+            Box::new(FixedScenario::new(state, Arc::new(adjacency))),
+            reward_by_name(reward).unwrap(),
+            config,
+        )
+    }
+
+    #[test]
+    fn test_reset_and_step_until_done() {
+        let mut env = two_country_env("dev_growth");
+        assert!(matches!(env.step(&[]), Err(EnvError::NotReset)));
+
+        let obs = env.reset(7, None, "SWE").unwrap();
+        assert_eq!(obs.features.len(), FEATURE_DIM);
+        assert_eq!(env.state().unwrap().rng_seed, 7);
+        let develop = Command::DevelopProvince {
+            province: 1,
+            dev_type: DevType::Tax,
+        };
+        let index = obs
+            .available_commands
+            .iter()
+            .position(|c| *c == develop)
+            .expect("developing should be legal");
+
+        let result = env.step(&[Action::Index(index)]).unwrap();
+        assert_eq!(result.reward, 1.0);
+        assert!(!result.done);
+        assert!(matches!(
+            env.step(&[Action::Index(usize::MAX)]),
+            Err(EnvError::InvalidAction { .. })
+        ));
+
+        let mut steps = 1;
+        while !env.step(&[]).unwrap().done {
+            steps += 1;
+        }
+        assert_eq!(steps, 2);
+        assert!(matches!(env.step(&[]), Err(EnvError::EpisodeDone)));
+    }
+
+    #[test]
+    fn test_reset_validates_country_and_start_date() {
+        let mut env = two_country_env("survival");
+        assert!(matches!(
+            env.reset(1, None, "FRA"),
+            Err(EnvError::UnknownCountry { .. })
+        ));
+        assert!(matches!(
+            env.reset(1, Some(Date::new(1400, 1, 1)), "SWE"),
+            Err(EnvError::StartDateTooEarly { .. })
+        ));
+
+        let obs = env.reset(1, Some(Date::new(1445, 1, 1)), "SWE").unwrap();
+        assert_eq!(obs.date, Date::new(1445, 1, 1));
+    }
+
+    #[test]
+    fn test_same_seed_and_actions_replay_identically() {
+        let run = || {
+            let mut env = two_country_env("score");
+            env.config.opponents = OpponentPolicy::Random;
+            env.reset(3, None, "SWE").unwrap();
+            let mut rewards = Vec::new();
+            loop {
+                let result = env.step(&[Action::Index(0)]).unwrap();
+                rewards.push(result.reward);
+                if result.done {
+                    return (rewards, result.observation.features);
+                }
+            }
+        };
+        assert_eq!(run(), run());
+    }
+}
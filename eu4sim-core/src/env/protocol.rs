@@ -0,0 +1,165 @@
+//! Newline-delimited JSON protocol for [`Env`].
+//!
+//! One JSON request per line in, one JSON response per line out:
+//!
+//! ```text
+//! {"cmd":"spec"}
+//! {"type":"spec","feature_names":[...],"reward":"dev_growth","days_per_step":30}
+//! {"cmd":"reset","seed":1,"tag":"SWE","start_date":{"year":1450,"month":1,"day":1}}
+//! {"type":"reset","observation":{"date":...,"tag":"SWE","features":[...],"available_commands":[...]}}
+//! {"cmd":"step","actions":[3,{"DevelopProvince":{"province":1,"dev_type":"Tax"}}]}
+//! {"type":"step","observation":{...},"reward":1.0,"done":false}
+//! {"cmd":"close"}
+//! {"type":"closed"}
+//! ```
+//!
+//! Errors (bad JSON, illegal action, unknown country) are answered with
+//! `{"type":"error","message":...}` and the session continues.
+
+use super::{Action, Env, Observation, StepResult, FEATURE_NAMES};
+use crate::state::{Date, Tag};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    /// Describe the observation layout and reward
+    Spec,
+    Reset {
+        #[serde(default)]
+        seed: u64,
+        #[serde(default)]
+        start_date: Option<Date>,
+        tag: Tag,
+    },
+    Step {
+        #[serde(default)]
+        actions: Vec<Action>,
+    },
+    /// End the session
+    Close,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Spec {
+        feature_names: Vec<String>,
+        reward: String,
+        days_per_step: u32,
+    },
+    Reset {
+        observation: Observation,
+    },
+    Step {
+        #[serde(flatten)]
+        result: StepResult,
+    },
+    Error {
+        message: String,
+    },
+    Closed,
+}
+
+/// Answer a single request.
+pub fn handle(env: &mut Env, request: Request) -> Response {
+    let error = |e: super::EnvError| Response::Error {
+        message: e.to_string(),
+    };
+    match request {
+        Request::Spec => Response::Spec {
+            feature_names: FEATURE_NAMES.iter().map(|n| n.to_string()).collect(),
+            reward: env.reward_name().to_string(),
+            days_per_step: env.config().days_per_step,
+        },
+        Request::Reset {
+            seed,
+            start_date,
+            tag,
+        } => match env.reset(seed, start_date, &tag) {
+            Ok(observation) => Response::Reset { observation },
+            Err(e) => error(e),
+        },
+        Request::Step { actions } => match env.step(&actions) {
+            Ok(result) => Response::Step { result },
+            Err(e) => error(e),
+        },
+        Request::Close => Response::Closed,
+    }
+}
+
+/// Serve requests from `reader` until `close` or end of input.
+pub fn serve<R: BufRead, W: Write>(env: &mut Env, reader: R, mut writer: W) -> std::io::Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => handle(env, request),
+            Err(e) => Response::Error {
+                message: format!("invalid request: {}", e),
+            },
+        };
+        serde_json::to_writer(&mut writer, &response)?;
+        writeln!(writer)?;
+        writer.flush()?;
+        if matches!(response, Response::Closed) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::OpponentPolicy;
+    use crate::env::{reward_by_name, EnvConfig, FixedScenario};
+    use crate::testing::WorldStateBuilder;
+    use eu4data::adjacency::AdjacencyGraph;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_serve_session() {
+        let state = WorldStateBuilder::new()
+            .with_country("SWE")
+            .with_province(1, Some("SWE"))
+            .build();
+        let mut env = Env::new(
+            Box::new(FixedScenario::new(state, Arc::new(AdjacencyGraph::new()))),
+            reward_by_name("survival").unwrap(),
+            EnvConfig {
+                opponents: OpponentPolicy::Passive,
+                ..Default::default()
+            },
+        );
+        let input = concat!(
+            "{\"cmd\":\"spec\"}\n",
+            "{\"cmd\":\"step\"}\n",
+            "{\"cmd\":\"reset\",\"seed\":5,\"tag\":\"SWE\"}\n",
+            "\n",
+            "not json\n",
+            "{\"cmd\":\"step\",\"actions\":[\"Pass\"]}\n",
+            "{\"cmd\":\"close\"}\n",
+            "{\"cmd\":\"spec\"}\n",
+        );
+        let mut output = Vec::new();
+        serve(&mut env, input.as_bytes(), &mut output).unwrap();
+
+        let responses: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let types: Vec<&str> = responses
+            .iter()
+            .map(|r| r["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(types, ["spec", "error", "reset", "error", "step", "closed"]);
+        assert_eq!(responses[0]["reward"], "survival");
+        assert_eq!(responses[4]["reward"], 1.0);
+        assert_eq!(responses[4]["done"], false);
+    }
+}
@@ -0,0 +1,181 @@
+//! Reward functions for [`Env`](super::Env).
+//!
+//! Rewards are computed from the full [`WorldState`], not the observer's view:
+//! they are a training signal, not something the policy gets to see. All
+//! built-in rewards are per-step deltas, so an episode's return is the total
+//! change over the episode.
+
+use crate::ai::{MaterialValue, ValueFunction};
+use crate::state::WorldState;
+
+/// Per-step reward signal.
+pub trait RewardFn: Send {
+    /// Name used by [`reward_by_name`] and reported over the protocol.
+    fn name(&self) -> &'static str;
+
+    /// Start a new episode from `state`.
+    fn reset(&mut self, state: &WorldState, tag: &str);
+
+    /// Reward for the step that produced `state`.
+    fn reward(&mut self, state: &WorldState, tag: &str) -> f32;
+}
+
+/// Names accepted by [`reward_by_name`].
+pub const REWARD_NAMES: [&str; 3] = ["dev_growth", "score", "survival"];
+
+/// Look up a built-in reward function.
+pub fn reward_by_name(name: &str) -> Option<Box<dyn RewardFn>> {
+    match name {
+        "dev_growth" => Some(Box::new(DevGrowthReward::default())),
+        "score" => Some(Box::new(ScoreReward::default())),
+        "survival" => Some(Box::new(SurvivalReward)),
+        _ => None,
+    }
+}
+
+/// Does `tag` still exist and own at least one province?
+pub fn is_alive(state: &WorldState, tag: &str) -> bool {
+    state.countries.contains_key(tag)
+        && state
+            .provinces
+            .values()
+            .any(|p| p.owner.as_deref() == Some(tag))
+}
+
+/// Total development owned by `tag`.
+pub fn owned_development(state: &WorldState, tag: &str) -> f64 {
+    state
+        .provinces
+        .values()
+        .filter(|p| p.owner.as_deref() == Some(tag))
+        .map(|p| (p.base_tax + p.base_production + p.base_manpower).to_f32() as f64)
+        .sum()
+}
+
+/// Change in owned development (conquest, development, losses).
+#[derive(Debug, Default)]
+pub struct DevGrowthReward {
+    last: f64,
+}
+
+impl RewardFn for DevGrowthReward {
+    fn name(&self) -> &'static str {
+        "dev_growth"
+    }
+
+    fn reset(&mut self, state: &WorldState, tag: &str) {
+        self.last = owned_development(state, tag);
+    }
+
+    fn reward(&mut self, state: &WorldState, tag: &str) -> f32 {
+        let current = owned_development(state, tag);
+        let delta = current - self.last;
+        self.last = current;
+        delta as f32
+    }
+}
+
+/// Change in [`MaterialValue`] (development, treasury, manpower, army, war
+/// score). The sim has no age score, so this stands in for EU4's score.
+///
+/// An eliminated country is worth zero, so elimination costs everything the
+/// country had.
+#[derive(Debug, Default)]
+pub struct ScoreReward {
+    value: MaterialValue,
+    last: f64,
+}
+
+impl ScoreReward {
+    fn score(&self, state: &WorldState, tag: &str) -> f64 {
+        if is_alive(state, tag) {
+            self.value.evaluate(state, tag)
+        } else {
+            0.0
+        }
+    }
+}
+
+impl RewardFn for ScoreReward {
+    fn name(&self) -> &'static str {
+        "score"
+    }
+
+    fn reset(&mut self, state: &WorldState, tag: &str) {
+        self.last = self.score(state, tag);
+    }
+
+    fn reward(&mut self, state: &WorldState, tag: &str) -> f32 {
+        let current = self.score(state, tag);
+        let delta = current - self.last;
+        self.last = current;
+        delta as f32
+    }
+}
+
+/// +1 for every step survived, a large penalty on elimination.
+#[derive(Debug, Default)]
+pub struct SurvivalReward;
+
+impl SurvivalReward {
+    /// Reward for the step in which the country is eliminated.
+    pub const ELIMINATION_PENALTY: f32 = -100.0;
+}
+
+impl RewardFn for SurvivalReward {
+    fn name(&self) -> &'static str {
+        "survival"
+    }
+
+    fn reset(&mut self, _state: &WorldState, _tag: &str) {}
+
+    fn reward(&mut self, state: &WorldState, tag: &str) -> f32 {
+        if is_alive(state, tag) {
+            1.0
+        } else {
+            Self::ELIMINATION_PENALTY
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed_generic::Mod32;
+    use crate::testing::WorldStateBuilder;
+
+    #[test]
+    fn test_rewards_track_development_and_elimination() {
+        let mut state = WorldStateBuilder::new()
+            .with_country("SWE")
+            .with_country("DAN")
+            .with_province(1, Some("SWE"))
+            .with_province(2, Some("DAN"))
+            .build();
+        let mut rewards: Vec<Box<dyn RewardFn>> = REWARD_NAMES
+            .iter()
+            .map(|name| reward_by_name(name).unwrap())
+            .collect();
+        for reward in &mut rewards {
+            reward.reset(&state, "DAN");
+        }
+
+        state.provinces.get_mut(&2).unwrap().base_tax += Mod32::from_int(2);
+        let step: Vec<f32> = rewards
+            .iter_mut()
+            .map(|r| r.reward(&state, "DAN"))
+            .collect();
+        assert_eq!(step[0], 2.0);
+        assert!(step[1] > 0.0);
+        assert_eq!(step[2], 1.0);
+
+        state.provinces.get_mut(&2).unwrap().owner = Some("SWE".to_string());
+        let step: Vec<f32> = rewards
+            .iter_mut()
+            .map(|r| r.reward(&state, "DAN"))
+            .collect();
+        assert!(step[0] < 0.0);
+        assert!(step[1] < 0.0);
+        assert_eq!(step[2], SurvivalReward::ELIMINATION_PENALTY);
+    }
+}
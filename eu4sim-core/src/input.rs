@@ -252,6 +252,26 @@ pub enum Command {
     RemoveRival {
         target: Tag,
     },
+    /// Send a diplomat to raise the target's opinion of us over time.
+    ImproveRelations {
+        target: Tag,
+    },
+    /// Insult the target (lowers its opinion of us).
+    Insult {
+        target: Tag,
+    },
+    /// Send a diplomat to lower the target's aggressive expansion towards us.
+    ReduceAggressiveExpansion {
+        target: Tag,
+    },
+    /// Send a diplomat to fabricate a claim on another country's province.
+    FabricateClaim {
+        province: ProvinceId,
+    },
+    /// Bring home every diplomat we have in the target country.
+    RecallDiplomat {
+        target: Tag,
+    },
 
     // Diplomacy - Responses
     AcceptAlliance {
@@ -38,6 +38,12 @@
 //! Multi-command support: AI can submit multiple commands per tick via
 //! [`CommandCategory`](ai::CommandCategory) routing.
 //!
+//! ## RL Environment
+//!
+//! [`env::Env`] exposes a gym-style `reset`/`step` loop for one controlled
+//! country, with fixed-size feature vectors and pluggable rewards.
+//! [`env::protocol`] serves it as newline-delimited JSON (`eu4sim env`).
+//!
 //! ## Observers
 //!
 //! Side effects are isolated to the observer layer:
@@ -49,6 +55,8 @@ pub mod ai;
 pub mod bounded;
 pub mod buildings;
 pub mod config;
+pub mod dense;
+pub mod env;
 pub mod estates;
 pub mod trade;
 
@@ -67,9 +75,12 @@ pub mod input;
 pub mod metrics;
 pub mod observer;
 pub mod simd;
-pub use ai::{AiPlayer, GreedyAI, RandomAi, VisibilityMode, VisibleWorldState};
+pub use ai::{
+    AiPlayer, GreedyAI, RandomAi, SearchAI, SearchConfig, VisibilityMode, VisibleWorldState,
+};
 pub mod modifiers;
 pub mod profiling;
+pub mod schedule;
 pub mod state;
 pub mod step;
 pub mod subjects;
@@ -98,7 +109,7 @@ pub use observer::datagen::{DataGenObserver, TrainingSample};
 pub use observer::event_log::{EventLogObserver, GameEvent};
 pub use observer::{ObserverConfig, ObserverError, ObserverRegistry, SimObserver, Snapshot};
 pub use state::{InstitutionId, SubjectRelationship, TechType, WorldState};
-pub use step::{step_world, ActionError};
+pub use step::{step_world, step_world_mut, ActionError};
 pub use subjects::{SubjectTypeDef, SubjectTypeId, SubjectTypeRegistry};
 pub use systems::{run_production_tick, EconomyConfig};
 pub use trade::{
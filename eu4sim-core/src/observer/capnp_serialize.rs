@@ -297,6 +297,9 @@ mod tests {
                 our_army_sizes: HashMap::new(),
                 our_army_provinces: HashMap::new(),
                 staging_provinces: HashSet::new(),
+                personality: None,
+                strategic_goals: Default::default(),
+                relations: Default::default(),
             },
             available_commands: vec![
                 Command::Pass,
@@ -13,6 +13,7 @@
 //! - `province_owner_changed` - Province ownership changed (for timeline reconstruction)
 //! - `battle_fought` - Land battle resolved with casualties
 //! - `siege_completed` - Fort siege completed, control changed
+//! - `opinion_threshold_crossed` - One country's opinion of another crossed a threshold
 //!
 //! # Future Extensions
 //!
@@ -27,8 +28,9 @@
 
 use super::{ObserverConfig, ObserverError, SimObserver, Snapshot};
 use crate::state::{Battle, BattleId, BattleResult, ProvinceId, Siege, Tag, War, WarId};
+use crate::systems::opinion;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::io::{BufWriter, Write};
 use std::path::Path;
 use std::sync::Mutex;
@@ -146,6 +148,23 @@ pub enum GameEvent {
         /// Fort level that was sieged
         fort_level: u8,
     },
+
+    /// A country's opinion of another crossed one of
+    /// [`OPINION_THRESHOLDS`](crate::systems::opinion::OPINION_THRESHOLDS).
+    OpinionThresholdCrossed {
+        tick: u64,
+        date: String,
+        /// Country holding the opinion
+        holder: Tag,
+        /// Country the opinion is about
+        towards: Tag,
+        /// Current opinion
+        opinion: i32,
+        /// Outermost threshold crossed this tick
+        threshold: i32,
+        /// Whether opinion rose (true) or fell (false) through the threshold
+        rising: bool,
+    },
 }
 
 /// Minimal snapshot of war state for comparison.
@@ -244,6 +263,8 @@ struct EventLogState {
     prev_battles: HashMap<BattleId, BattleSnapshot>,
     /// Sieges in progress during the previous tick
     prev_sieges: HashMap<ProvinceId, SiegeSnapshot>,
+    /// Opinion threshold level of every tracked (holder, towards) pair
+    prev_opinion_levels: HashMap<(Tag, Tag), i32>,
     /// Whether this is the first tick (skip event detection)
     first_tick: bool,
 }
@@ -281,10 +302,19 @@ impl EventLogState {
             .iter()
             .map(|(&prov_id, siege)| (prov_id, SiegeSnapshot::from_siege(siege, state)))
             .collect();
+        self.prev_opinion_levels = opinion_levels(state);
         self.first_tick = false;
     }
 }
 
+/// Threshold level of every tracked opinion.
+fn opinion_levels(state: &crate::state::WorldState) -> HashMap<(Tag, Tag), i32> {
+    opinion::tracked_opinions(state)
+        .into_iter()
+        .map(|(pair, value)| (pair, opinion::opinion_level(value)))
+        .collect()
+}
+
 /// Observer that logs simulation events as JSONL.
 ///
 /// Detects events by comparing previous state to current state each tick.
@@ -463,6 +493,32 @@ impl EventLogObserver {
             }
         }
 
+        // 7. Detect opinion threshold crossings (untracked pairs sit at zero)
+        let current = opinion::tracked_opinions(world);
+        let pairs: BTreeSet<&(Tag, Tag)> = current
+            .keys()
+            .chain(prev.prev_opinion_levels.keys())
+            .collect();
+        for pair in pairs {
+            let before = prev.prev_opinion_levels.get(pair).copied().unwrap_or(0);
+            let value = current
+                .get(pair)
+                .copied()
+                .unwrap_or(crate::fixed::Fixed::ZERO);
+            let after = opinion::opinion_level(value);
+            if let Some(threshold) = opinion::crossed_threshold(before, after) {
+                events.push(GameEvent::OpinionThresholdCrossed {
+                    tick: snapshot.tick,
+                    date: world.date.to_string(),
+                    holder: pair.0.clone(),
+                    towards: pair.1.clone(),
+                    opinion: value.to_int() as i32,
+                    threshold,
+                    rising: after > before,
+                });
+            }
+        }
+
         events
     }
 
@@ -683,6 +739,7 @@ mod tests {
                     defender_score: 0,
                     defender_battle_score: 0,
                     pending_peace: None,
+                    war_goal: None,
                 },
             )]
             .into_iter()
@@ -725,6 +782,7 @@ mod tests {
                     defender_score: 25,
                     defender_battle_score: 15,
                     pending_peace: None,
+                    war_goal: None,
                 },
             )]
             .into_iter()
@@ -957,6 +1015,43 @@ mod tests {
         assert!(output_str.contains("\"fort_level\":2"));
     }
 
+    #[test]
+    fn test_opinion_threshold_crossed_event() {
+        let output = capture_output();
+        let writer: Box<dyn Write + Send> = Box::new(OutputCapture(output.clone()));
+        let observer = EventLogObserver::new(writer);
+
+        let state1 = WorldStateBuilder::new()
+            .with_country("FRA")
+            .with_country("ENG")
+            .build();
+        observer
+            .on_tick(&Snapshot::new(state1.clone(), 0, 0))
+            .unwrap();
+
+        // England is insulted: 0 -> -50
+        let mut state2 = state1;
+        crate::systems::add_opinion_modifier(
+            &mut state2,
+            "ENG",
+            "FRA",
+            crate::state::OpinionModifierKind::Insulted,
+        );
+        observer
+            .on_tick(&Snapshot::new(state2.clone(), 1, 0))
+            .unwrap();
+
+        // Unchanged band: no new event
+        observer.on_tick(&Snapshot::new(state2, 2, 0)).unwrap();
+
+        let output_data = output.lock().unwrap();
+        let output_str = String::from_utf8_lossy(output_data.get_ref());
+        assert_eq!(output_str.matches("opinion_threshold_crossed").count(), 1);
+        assert!(output_str.contains("\"holder\":\"ENG\""));
+        assert!(output_str.contains("\"threshold\":-50"));
+        assert!(output_str.contains("\"rising\":false"));
+    }
+
     /// Helper struct to capture output through Arc<Mutex<Cursor>>
     struct OutputCapture(Arc<Mutex<Cursor<Vec<u8>>>>);
 
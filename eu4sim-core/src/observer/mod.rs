@@ -17,7 +17,8 @@
 //! # Design Principles
 //!
 //! - **Determinism**: Observers receive immutable snapshots; they cannot affect simulation
-//! - **Performance**: Uses `Arc<WorldState>` with `im::HashMap`'s O(1) structural sharing
+//! - **Performance**: Snapshots share one `Arc<WorldState>`; the simulation only clones
+//!   state on ticks where an observer is due ([`ObserverRegistry::wants_snapshot`])
 //! - **Extensibility**: Trait-based, supports any observer implementation
 //!
 //! # Example
@@ -37,7 +38,7 @@ pub mod datagen;
 pub mod event_log;
 
 use crate::input::PlayerInputs;
-use crate::state::WorldState;
+use crate::state::{Date, WorldState};
 use std::sync::Arc;
 use thiserror::Error;
 
@@ -49,7 +50,7 @@ use thiserror::Error;
 /// - Immutability (no `&mut` access possible)
 #[derive(Clone)]
 pub struct Snapshot {
-    /// Immutable reference to world state (O(1) clone via `im::HashMap`)
+    /// Immutable reference to world state (cheap to share between observers)
     pub state: Arc<WorldState>,
     /// Monotonic tick counter (days since simulation start)
     pub tick: u64,
@@ -112,6 +113,14 @@ impl Default for ObserverConfig {
     }
 }
 
+impl ObserverConfig {
+    /// Whether an observer with this config wants the state at `tick` / `date`.
+    pub fn should_notify(&self, tick: u64, date: Date) -> bool {
+        tick.is_multiple_of(self.frequency.max(1) as u64)
+            || (self.notify_on_month_start && date.day == 1)
+    }
+}
+
 /// Trait for simulation observers.
 ///
 /// Implementers receive immutable state snapshots after simulation ticks.
@@ -206,13 +215,11 @@ impl ObserverRegistry {
     /// Errors are logged but do not propagate (non-blocking).
     pub fn notify_with_inputs(&self, snapshot: &Snapshot, inputs: &[PlayerInputs]) {
         for observer in &self.observers {
-            let config = observer.config();
-
             // Check frequency gating
-            let should_notify = snapshot.tick.is_multiple_of(config.frequency as u64)
-                || (config.notify_on_month_start && snapshot.state.date.day == 1);
-
-            if should_notify {
+            if observer
+                .config()
+                .should_notify(snapshot.tick, snapshot.state.date)
+            {
                 let result = if observer.needs_inputs() {
                     observer.on_tick_with_inputs(snapshot, inputs)
                 } else {
@@ -226,6 +233,16 @@ impl ObserverRegistry {
         }
     }
 
+    /// Whether any registered observer will be notified at `tick` / `date`.
+    ///
+    /// Lets the simulation loop skip building a [`Snapshot`] (and the
+    /// `WorldState` clone behind it) on ticks nobody is listening to.
+    pub fn wants_snapshot(&self, tick: u64, date: Date) -> bool {
+        self.observers
+            .iter()
+            .any(|observer| observer.config().should_notify(tick, date))
+    }
+
     /// Notify all observers of shutdown.
     pub fn shutdown(&self) {
         for observer in &self.observers {
@@ -382,6 +399,28 @@ mod tests {
         assert_eq!(counter.get(), 1);
     }
 
+    #[test]
+    fn test_wants_snapshot_matches_notification() {
+        let counter = SharedCounter::new();
+        let mut registry = ObserverRegistry::new();
+        assert!(!registry.wants_snapshot(1, Date::new(1444, 11, 1)));
+
+        let mut observer = CountingObserver::new(counter.clone()).with_frequency(30);
+        observer.config.notify_on_month_start = false;
+        registry.register(Box::new(observer));
+
+        let mut state = WorldStateBuilder::new().build();
+        state.date.day = 15;
+        for tick in 1..=90 {
+            if registry.wants_snapshot(tick, state.date) {
+                registry.notify(&Snapshot::new(state.clone(), tick, 0));
+            }
+        }
+
+        // Only ticks 30, 60, 90 produce snapshots
+        assert_eq!(counter.get(), 3);
+    }
+
     #[test]
     fn test_registry_len() {
         let counter = SharedCounter::new();
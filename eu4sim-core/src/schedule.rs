@@ -0,0 +1,547 @@
+//! System scheduling with declared read/write sets.
+//!
+//! Every scheduled system declares which parts of [`WorldState`] it reads and
+//! writes ([`Access`]), and how it is shaped ([`SystemKind`]):
+//!
+//! - [`SystemKind::World`]: takes `&mut WorldState` and always runs alone.
+//! - [`SystemKind::PerCountry`] / [`SystemKind::PerProvince`]: a kernel that
+//!   updates one country (or province) at a time, reading the rest of the
+//!   world through `&WorldState`.
+//!
+//! [`Schedule::new`] packs consecutive per-entity systems into *stages* when
+//! their declared accesses don't conflict. A stage detaches the partition
+//! (`state.countries` or `state.provinces`), runs every kernel of the stage on
+//! each entity in parallel with rayon, and puts the partition back. This is
+//! the map-reduce pattern from `docs/development/parallel-systems.md`: the
+//! kernels are the parallel map, and the optional [`ProvinceFinish`] hooks are
+//! the sequential reduce, fed the touched ids in ascending order.
+//!
+//! # Determinism
+//!
+//! A kernel only ever mutates its own entity, and the partition is detached
+//! while kernels run, so no kernel can observe another entity's update.
+//! Reduce steps run sequentially in declaration order. The result is
+//! bit-identical to running the systems one after another, which
+//! [`Schedule::run_sequential`] does for verification.
+//!
+//! # Declaring access
+//!
+//! Kernels must not read their own partition through the shared
+//! `&WorldState` (it is empty while the stage runs). The partition write is
+//! implicit and does not need to be declared. A finish hook that reads the
+//! partition declares it in `reads`; such a system closes its stage to later
+//! kernels, because they would otherwise write entities the hook has already
+//! looked at.
+
+use crate::state::{CountryState, ProvinceId, ProvinceState, WorldState};
+use rayon::prelude::*;
+
+/// A coarse partition of [`WorldState`] used in access declarations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resource {
+    /// `date`
+    Date,
+    /// `rng_state` (anything calling `random_fixed` and friends)
+    Rng,
+    /// `countries`
+    Countries,
+    /// `provinces`
+    Provinces,
+    /// `armies`, `generals`, `battles`, `sieges`
+    Armies,
+    /// `fleets`, `admirals`, `naval_battles`
+    Fleets,
+    /// `colonies`
+    Colonies,
+    /// `diplomacy`
+    Diplomacy,
+    /// `global` (HRE, Celestial Empire, religion state)
+    Global,
+    /// `trade_nodes` and per-country trade state
+    Trade,
+    /// `modifiers`
+    Modifiers,
+    /// Static definitions loaded from game data (`building_defs`, `estates`,
+    /// `subject_types`, `policies`, ...). Never written during a tick.
+    Registries,
+    /// Derived caches such as `owned_provinces_cache`.
+    Caches,
+}
+
+impl Resource {
+    const fn bit(self) -> u32 {
+        1 << self as u32
+    }
+}
+
+/// A set of [`Resource`]s, stored as a bitmask.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ResourceSet(u32);
+
+impl ResourceSet {
+    pub const EMPTY: Self = Self(0);
+
+    pub const fn of(resources: &[Resource]) -> Self {
+        let mut bits = 0;
+        let mut i = 0;
+        while i < resources.len() {
+            bits |= resources[i].bit();
+            i += 1;
+        }
+        Self(bits)
+    }
+
+    pub const fn with(self, resource: Resource) -> Self {
+        Self(self.0 | resource.bit())
+    }
+
+    pub const fn without(self, resource: Resource) -> Self {
+        Self(self.0 & !resource.bit())
+    }
+
+    pub const fn contains(self, resource: Resource) -> bool {
+        self.0 & resource.bit() != 0
+    }
+
+    pub const fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Declared reads and writes of a system.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Access {
+    pub reads: ResourceSet,
+    pub writes: ResourceSet,
+}
+
+impl Access {
+    pub const fn new(reads: &[Resource], writes: &[Resource]) -> Self {
+        Self {
+            reads: ResourceSet::of(reads),
+            writes: ResourceSet::of(writes),
+        }
+    }
+
+    /// True if running `self` and `other` in either order could differ
+    /// (write/write or read/write overlap).
+    pub const fn conflicts_with(&self, other: &Access) -> bool {
+        self.writes.intersects(other.writes.union(other.reads))
+            || self.reads.intersects(other.writes)
+    }
+}
+
+/// Updates one country. Must not read `state.countries`.
+pub type CountryKernel = fn(state: &WorldState, tag: &str, country: &mut CountryState);
+
+/// Updates one province. Returns `true` if the province needs the system's
+/// [`ProvinceFinish`] step. Must not read `state.provinces`.
+pub type ProvinceKernel =
+    fn(state: &WorldState, id: ProvinceId, province: &mut ProvinceState) -> bool;
+
+/// Sequential reduce step, called with every province whose kernel returned
+/// `true` (ascending id order). Not called when the list would be empty.
+pub type ProvinceFinish = fn(state: &mut WorldState, touched: &[ProvinceId]);
+
+/// How a system is executed.
+#[derive(Clone, Copy)]
+pub enum SystemKind {
+    World(fn(&mut WorldState)),
+    PerCountry(CountryKernel),
+    PerProvince {
+        update: ProvinceKernel,
+        finish: Option<ProvinceFinish>,
+    },
+}
+
+impl SystemKind {
+    /// The resource this kind writes implicitly, one entity per kernel call.
+    fn partition(&self) -> Option<Resource> {
+        match self {
+            SystemKind::World(_) => None,
+            SystemKind::PerCountry(_) => Some(Resource::Countries),
+            SystemKind::PerProvince { .. } => Some(Resource::Provinces),
+        }
+    }
+}
+
+impl std::fmt::Debug for SystemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SystemKind::World(_) => f.write_str("World"),
+            SystemKind::PerCountry(_) => f.write_str("PerCountry"),
+            SystemKind::PerProvince { finish, .. } => f
+                .debug_struct("PerProvince")
+                .field("finish", &finish.is_some())
+                .finish(),
+        }
+    }
+}
+
+/// A schedulable system: name, declared access, and execution shape.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemDesc {
+    pub name: &'static str,
+    pub access: Access,
+    pub kind: SystemKind,
+}
+
+impl SystemDesc {
+    pub const fn world(name: &'static str, access: Access, run: fn(&mut WorldState)) -> Self {
+        Self {
+            name,
+            access,
+            kind: SystemKind::World(run),
+        }
+    }
+
+    pub const fn per_country(name: &'static str, access: Access, update: CountryKernel) -> Self {
+        Self {
+            name,
+            access,
+            kind: SystemKind::PerCountry(update),
+        }
+    }
+
+    pub const fn per_province(
+        name: &'static str,
+        access: Access,
+        update: ProvinceKernel,
+        finish: Option<ProvinceFinish>,
+    ) -> Self {
+        Self {
+            name,
+            access,
+            kind: SystemKind::PerProvince { update, finish },
+        }
+    }
+
+    /// Declared access including the implicit partition write.
+    fn effective_access(&self) -> Access {
+        let mut access = self.access;
+        if let Some(partition) = self.kind.partition() {
+            access.writes = access.writes.with(partition);
+        }
+        access
+    }
+
+    /// Whether `self` can join a stage that already holds `earlier`.
+    ///
+    /// Both must share a partition. Writes to the partition itself don't
+    /// conflict (each kernel sees only its own entity), except when `earlier`
+    /// has a finish hook that reads the partition.
+    fn can_share_stage_with(&self, earlier: &SystemDesc) -> bool {
+        let Some(partition) = self.kind.partition() else {
+            return false;
+        };
+        if earlier.kind.partition() != Some(partition) || earlier.access.reads.contains(partition) {
+            return false;
+        }
+        let strip = |access: Access| Access {
+            reads: access.reads.without(partition),
+            writes: access.writes.without(partition),
+        };
+        !strip(self.access).conflicts_with(&strip(earlier.access))
+    }
+}
+
+/// Runs a single system on its own.
+///
+/// This is how the `run_*_tick` entry points of per-entity systems are
+/// implemented, so calling a system directly and running it from a schedule
+/// share one code path.
+pub fn run_system(state: &mut WorldState, system: &SystemDesc) {
+    run_stage(state, std::slice::from_ref(system));
+}
+
+/// An ordered list of stages built from declared accesses.
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    systems: Vec<SystemDesc>,
+    stages: Vec<Vec<usize>>,
+}
+
+impl Schedule {
+    /// Builds stages from `systems`, given in their sequential order.
+    ///
+    /// A per-entity system joins the earliest stage, starting from the last
+    /// stage it conflicts with, whose members it can share a stage with (same
+    /// partition, no conflicts outside the partition); otherwise it starts a
+    /// new stage. World systems always get a stage of their own.
+    pub fn new(systems: Vec<SystemDesc>) -> Self {
+        let mut stages: Vec<Vec<usize>> = Vec::new();
+
+        for (index, system) in systems.iter().enumerate() {
+            let access = system.effective_access();
+            // The system can't move ahead of the last stage it conflicts
+            // with, but may still join that stage if the conflict is only
+            // the shared partition.
+            let earliest = stages
+                .iter()
+                .rposition(|stage| {
+                    stage
+                        .iter()
+                        .any(|&i| systems[i].effective_access().conflicts_with(&access))
+                })
+                .unwrap_or(0);
+
+            let slot = (earliest..stages.len()).find(|&s| {
+                stages[s]
+                    .iter()
+                    .all(|&i| system.can_share_stage_with(&systems[i]))
+            });
+
+            match slot {
+                Some(s) => stages[s].push(index),
+                None => stages.push(vec![index]),
+            }
+        }
+
+        Self { systems, stages }
+    }
+
+    /// System names per stage, for logging and tests.
+    pub fn stage_names(&self) -> Vec<Vec<&'static str>> {
+        self.stages
+            .iter()
+            .map(|stage| stage.iter().map(|&i| self.systems[i].name).collect())
+            .collect()
+    }
+
+    /// Runs every stage, parallelising per-entity stages across rayon.
+    pub fn run(&self, state: &mut WorldState) {
+        for stage in &self.stages {
+            let systems: Vec<SystemDesc> = stage.iter().map(|&i| self.systems[i]).collect();
+            run_stage(state, &systems);
+        }
+    }
+
+    /// Runs every system one after another in declaration order.
+    ///
+    /// Reference implementation for [`Schedule::run`].
+    pub fn run_sequential(&self, state: &mut WorldState) {
+        for system in &self.systems {
+            run_system(state, system);
+        }
+    }
+}
+
+fn run_stage(state: &mut WorldState, systems: &[SystemDesc]) {
+    match systems {
+        [] => {}
+        [SystemDesc {
+            kind: SystemKind::World(run),
+            ..
+        }] => run(state),
+        [first, ..] => match first.kind {
+            SystemKind::World(_) => unreachable!("world systems run alone"),
+            SystemKind::PerCountry(_) => run_country_stage(state, systems),
+            SystemKind::PerProvince { .. } => run_province_stage(state, systems),
+        },
+    }
+}
+
+fn run_country_stage(state: &mut WorldState, systems: &[SystemDesc]) {
+    let kernels: Vec<CountryKernel> = systems
+        .iter()
+        .map(|system| match system.kind {
+            SystemKind::PerCountry(update) => update,
+            _ => unreachable!("mixed partitions in one stage"),
+        })
+        .collect();
+
+    let _span = tracing::info_span!("country_stage", systems = kernels.len()).entered();
+    let mut countries = std::mem::take(&mut state.countries);
+    {
+        let shared: &WorldState = state;
+        countries.par_iter_mut().for_each(|(tag, country)| {
+            for kernel in &kernels {
+                kernel(shared, tag, country);
+            }
+        });
+    }
+    state.countries = countries;
+}
+
+fn run_province_stage(state: &mut WorldState, systems: &[SystemDesc]) {
+    assert!(systems.len() <= 64, "too many systems in one stage");
+    let kernels: Vec<(ProvinceKernel, Option<ProvinceFinish>)> = systems
+        .iter()
+        .map(|system| match system.kind {
+            SystemKind::PerProvince { update, finish } => (update, finish),
+            _ => unreachable!("mixed partitions in one stage"),
+        })
+        .collect();
+
+    let _span = tracing::info_span!("province_stage", systems = kernels.len()).entered();
+
+    // Map: run every kernel on each province, recording which kernels asked
+    // for their finish step as a bitmask.
+    let mut provinces = std::mem::take(&mut state.provinces);
+    let touched: Vec<(ProvinceId, u64)> = {
+        let shared: &WorldState = state;
+        provinces
+            .par_iter_mut()
+            .filter_map(|(id, province)| {
+                let mut mask = 0u64;
+                for (bit, (update, _)) in kernels.iter().enumerate() {
+                    if update(shared, id, province) {
+                        mask |= 1 << bit;
+                    }
+                }
+                (mask != 0).then_some((id, mask))
+            })
+            .collect()
+    };
+    state.provinces = provinces;
+
+    // Reduce: finish steps in declaration order, ids ascending.
+    for (bit, (_, finish)) in kernels.iter().enumerate() {
+        let Some(finish) = finish else { continue };
+        let ids: Vec<ProvinceId> = touched
+            .iter()
+            .filter(|(_, mask)| mask & (1 << bit) != 0)
+            .map(|&(id, _)| id)
+            .collect();
+        if !ids.is_empty() {
+            finish(state, &ids);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed::Fixed;
+    use crate::testing::WorldStateBuilder;
+    use Resource::*;
+
+    fn add_treasury(_state: &WorldState, _tag: &str, country: &mut CountryState) {
+        country.treasury += Fixed::ONE;
+    }
+
+    fn double_treasury(_state: &WorldState, _tag: &str, country: &mut CountryState) {
+        country.treasury = country.treasury + country.treasury;
+    }
+
+    fn noop_world(_state: &mut WorldState) {}
+
+    fn bump_tax(_state: &WorldState, _id: ProvinceId, province: &mut ProvinceState) -> bool {
+        province.base_tax += crate::fixed_generic::Mod32::ONE;
+        true
+    }
+
+    fn flag_even(_state: &WorldState, id: ProvinceId, _province: &mut ProvinceState) -> bool {
+        id.is_multiple_of(2)
+    }
+
+    fn record_touched(state: &mut WorldState, touched: &[ProvinceId]) {
+        // Stash the touched ids somewhere observable: one country per id.
+        for id in touched {
+            state
+                .countries
+                .insert(format!("P{id:02}"), CountryState::default());
+        }
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let a = Access::new(&[Date], &[Countries]);
+        let b = Access::new(&[Countries], &[]);
+        let c = Access::new(&[Date], &[Provinces]);
+        assert!(a.conflicts_with(&b));
+        assert!(b.conflicts_with(&a));
+        assert!(!a.conflicts_with(&c));
+        assert!(!b.conflicts_with(&c));
+    }
+
+    #[test]
+    fn test_independent_kernels_share_a_stage() {
+        let schedule = Schedule::new(vec![
+            SystemDesc::per_country("a", Access::default(), add_treasury),
+            SystemDesc::per_country("b", Access::new(&[Modifiers], &[]), double_treasury),
+            SystemDesc::per_province("c", Access::new(&[Date], &[]), bump_tax, None),
+        ]);
+        assert_eq!(schedule.stage_names(), vec![vec!["a", "b"], vec!["c"]]);
+    }
+
+    #[test]
+    fn test_world_system_is_a_barrier_only_when_it_conflicts() {
+        let schedule = Schedule::new(vec![
+            SystemDesc::per_country("a", Access::default(), add_treasury),
+            SystemDesc::world("w", Access::new(&[Countries], &[Global]), noop_world),
+            SystemDesc::per_country("b", Access::default(), double_treasury),
+            SystemDesc::world("x", Access::new(&[], &[Colonies]), noop_world),
+            SystemDesc::per_country("c", Access::default(), add_treasury),
+        ]);
+        // `c` doesn't conflict with `x`, so it joins `b` ahead of it.
+        assert_eq!(
+            schedule.stage_names(),
+            vec![vec!["a"], vec!["w"], vec!["b", "c"], vec!["x"]]
+        );
+    }
+
+    #[test]
+    fn test_finish_reading_partition_closes_stage() {
+        let schedule = Schedule::new(vec![
+            SystemDesc::per_province(
+                "a",
+                Access::new(&[Provinces], &[Countries]),
+                flag_even,
+                Some(record_touched),
+            ),
+            SystemDesc::per_province("b", Access::default(), bump_tax, None),
+        ]);
+        assert_eq!(schedule.stage_names(), vec![vec!["a"], vec!["b"]]);
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let mut builder = WorldStateBuilder::new();
+        for tag in ["SWE", "DAN", "NOR", "MOS", "POL"] {
+            builder = builder.with_country(tag);
+        }
+        for id in 1..=20 {
+            builder = builder.with_province(id, Some("SWE"));
+        }
+        let base = builder.build();
+        let start = base.countries["SWE"].treasury;
+
+        let schedule = Schedule::new(vec![
+            SystemDesc::per_country("add", Access::default(), add_treasury),
+            SystemDesc::per_country("double", Access::default(), double_treasury),
+            SystemDesc::per_province(
+                "flag",
+                Access::new(&[], &[Countries]),
+                flag_even,
+                Some(record_touched),
+            ),
+            SystemDesc::per_province("bump", Access::default(), bump_tax, None),
+        ]);
+        assert_eq!(schedule.stage_names().len(), 2);
+
+        let mut parallel = base.clone();
+        let mut sequential = base;
+        schedule.run(&mut parallel);
+        schedule.run_sequential(&mut sequential);
+
+        assert_eq!(
+            serde_json::to_string(&parallel).unwrap(),
+            serde_json::to_string(&sequential).unwrap()
+        );
+        assert_eq!(
+            parallel.countries["SWE"].treasury,
+            (start + Fixed::ONE) + (start + Fixed::ONE)
+        );
+        assert!(parallel.countries.contains_key("P02"));
+        assert!(!parallel.countries.contains_key("P01"));
+    }
+}
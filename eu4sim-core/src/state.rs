@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::hash::{Hash, Hasher};
 
+pub use crate::dense::{ProvinceMap, TagMap};
 pub use im::{HashMap, HashSet};
 
 /// A specific date in history.
@@ -224,6 +225,12 @@ impl TagRegistry {
             .enumerate()
             .map(|(i, s)| (TagId(i as u16), s.as_str()))
     }
+
+    /// All interned tags, indexed by `TagId`.
+    #[inline]
+    pub(crate) fn names(&self) -> &[String] {
+        &self.id_to_string
+    }
 }
 
 pub type ProvinceId = u32;
@@ -572,12 +579,13 @@ pub struct WorldState {
     pub rng_seed: u64,
     /// Current RNG state (must be deterministic for replay)
     pub rng_state: u64,
-    pub provinces: HashMap<ProvinceId, ProvinceState>,
-    pub countries: HashMap<Tag, CountryState>,
-    /// Interned tag registry for efficient tag operations.
-    /// Built once during state initialization from countries keys.
-    #[serde(skip)]
-    pub tags: TagRegistry,
+    /// Dense province storage indexed by province id.
+    pub provinces: ProvinceMap<ProvinceState>,
+    /// Dense country storage indexed by [`TagId`].
+    ///
+    /// The map owns the tag interner: `countries.id_of("FRA")` gives the
+    /// `TagId` used by hot-path caches such as [`OwnedProvinceSoA`].
+    pub countries: TagMap<CountryState>,
     /// Base prices for trade goods (loaded from data model).
     pub base_goods_prices: HashMap<TradegoodId, Fixed>,
     /// Dynamic modifiers (mutated by events).
@@ -589,6 +597,14 @@ pub struct WorldState {
     pub fleets: HashMap<FleetId, Fleet>,
     pub next_fleet_id: u32,
     pub colonies: HashMap<ProvinceId, Colony>,
+    /// Long-term AI goals per country (see [`crate::ai::run_strategy_tick`]).
+    #[serde(default)]
+    pub strategic_goals: HashMap<Tag, crate::ai::StrategicGoals>,
+    /// Border provinces each country could fabricate a claim on, refreshed
+    /// monthly (see [`crate::systems::claims::refresh_claim_candidates`]).
+    /// `None` until the first refresh.
+    #[serde(default)]
+    pub claim_candidates: Option<HashMap<Tag, Vec<ProvinceId>>>,
 
     // =========================================================================
     // Combat System
@@ -666,6 +682,14 @@ pub struct WorldState {
     #[serde(skip)]
     pub estates: crate::estates::EstateRegistry,
 
+    /// AI personality definitions (loaded from game files, immutable).
+    #[serde(skip)]
+    pub ai_personalities: crate::ai::AiPersonalityRegistry,
+
+    /// AI attitude effects (loaded from game files, immutable).
+    #[serde(skip)]
+    pub ai_attitudes: crate::ai::AiAttitudeRegistry,
+
     // =========================================================================
     // Performance Caches
     // =========================================================================
@@ -755,7 +779,7 @@ impl WorldState {
 
             // Populate SoA
             for (id, base_tax, has_core, owner_str) in raw_data {
-                let tag_id = self.tags.intern(&owner_str);
+                let tag_id = self.countries.intern(&owner_str);
                 // Hardcoded 75% floor for uncored (matches systems::coring::UNCORED_AUTONOMY_FLOOR)
                 // We use raw value to avoid dependency cycle with systems module
                 let floor = if has_core {
@@ -1017,6 +1041,10 @@ pub struct ProvinceState {
     /// A core represents permanent ownership claim and removes autonomy/overextension.
     #[serde(default)]
     pub cores: std::collections::HashSet<Tag>,
+    /// Countries that have claims on this province (see [`crate::systems::claims`]).
+    /// Claims justify conquest and make it cheaper, but don't change ownership.
+    #[serde(default)]
+    pub claims: std::collections::HashMap<Tag, Claim>,
     /// In-progress coring (owner country working to establish a core).
     #[serde(default)]
     pub coring_progress: Option<CoringProgress>,
@@ -1040,6 +1068,20 @@ pub struct ProvinceState {
     pub devastation: Mod32,
 }
 
+/// A claim on a province, typically one owned by another country.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claim {
+    /// When a temporary (fabricated) claim lapses; `None` for permanent claims.
+    pub expires: Option<Date>,
+}
+
+impl Claim {
+    /// Whether this claim never expires.
+    pub fn is_permanent(&self) -> bool {
+        self.expires.is_none()
+    }
+}
+
 /// Progress towards establishing a core on a province.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoringProgress {
@@ -1092,6 +1134,9 @@ pub struct CountryState {
     /// Date when the current ruler was instated (for age/death calculations).
     #[serde(default)]
     pub ruler_instated: Option<Date>,
+    /// Ruler's AI personality (e.g., "ai_militarist"), rolled at load.
+    #[serde(default)]
+    pub ai_personality: Option<String>,
     /// Government rank (1=Duchy, 2=Kingdom, 3=Empire).
     #[serde(default = "default_government_rank")]
     pub government_rank: u8,
@@ -1128,6 +1173,10 @@ pub struct CountryState {
     /// Diplomatic actions: war declarations, peace offers, alliances, etc.
     #[serde(default)]
     pub last_diplomatic_action: Option<Date>,
+    /// Diplomats currently abroad, travelling or working.
+    /// The pool size comes from [`crate::systems::diplomats::diplomats_total`].
+    #[serde(default)]
+    pub diplomats: Vec<DiplomatMission>,
     /// Cooldowns for peace offers per war (date when offer is allowed again).
     /// Set after a peace offer is rejected; cleared when war ends.
     #[serde(default)]
@@ -1248,6 +1297,7 @@ impl Default for CountryState {
             ruler_dynasty: None,
             ruler_gender: Gender::Male,
             ruler_instated: None,
+            ai_personality: None,
             government_rank: default_government_rank(),
             technology_group: None,
             adm_tech: 0,
@@ -1260,6 +1310,7 @@ impl Default for CountryState {
             trade: CountryTradeState::default(),
             income: IncomeBreakdown::default(),
             last_diplomatic_action: None,
+            diplomats: Vec::new(),
             peace_offer_cooldowns: std::collections::HashMap::new(),
             pending_call_to_arms: std::collections::HashMap::new(),
             overextension: Fixed::ZERO,
@@ -1278,10 +1329,12 @@ impl Default for CountryState {
 }
 
 /// Type of diplomatic relationship between two countries.
+///
+/// Royal marriages can coexist with these, so they're kept apart in
+/// [`DiplomacyState::royal_marriages`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum RelationType {
     Alliance,
-    RoyalMarriage,
     Rival,
 }
 
@@ -1329,6 +1382,22 @@ pub struct War {
     pub defender_battle_score: u8,
     /// Pending peace offer (if any)
     pub pending_peace: Option<PendingPeace>,
+    /// War goal the war was declared with (None for sim-declared wars).
+    #[serde(default)]
+    pub war_goal: Option<WarGoal>,
+}
+
+/// The war goal attached to a war declaration.
+///
+/// Currently only populated when hydrating wars from save files.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WarGoal {
+    /// Casus belli the war was declared with (e.g. "cb_claim").
+    pub casus_belli: String,
+    /// War goal type (e.g. "take_claim", "superiority").
+    pub goal_type: String,
+    /// Target province for province-based war goals.
+    pub province: Option<ProvinceId>,
 }
 
 /// A pending peace offer in a war.
@@ -1368,11 +1437,62 @@ pub struct Coalition {
     pub formed_date: Date,
 }
 
+/// Kind of a stored opinion modifier (see [`crate::systems::opinion`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OpinionModifierKind {
+    /// The other country insulted us
+    Insulted,
+    /// The other country declared war on us
+    DeclaredWarOnUs,
+    /// The other country's diplomat has been improving relations with us
+    ImprovedRelations,
+}
+
+/// Decaying opinion modifier one country holds towards another.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpinionModifier {
+    pub kind: OpinionModifierKind,
+    /// Current value, decays towards zero every month
+    pub value: Fixed,
+}
+
+/// Long-running work a diplomat does once they reach the target country
+/// (see [`crate::systems::diplomats`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DiplomatTask {
+    /// Raise the target's opinion of us
+    ImproveRelations,
+    /// Forge a claim on one of the target's provinces
+    FabricateClaim { province: ProvinceId },
+    /// Lower the target's aggressive expansion towards us
+    ReduceAggressiveExpansion,
+}
+
+/// A diplomat sent abroad.
+/// Like merchants, diplomats don't teleport - they travel from the capital
+/// and only start working once they arrive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiplomatMission {
+    /// Country the diplomat is sent to.
+    pub target: Tag,
+    /// What the diplomat will do when they arrive.
+    pub task: DiplomatTask,
+    /// Date when the diplomat arrives and starts working.
+    pub arrival_date: Date,
+    /// Progress towards tasks with a fixed goal (0-100), e.g. claim fabrication.
+    #[serde(default)]
+    pub progress: Fixed,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct DiplomacyState {
     /// Bilateral relationships: (Tag1, Tag2) -> RelationType
     /// Stored in sorted order (smaller tag first) to avoid duplication
     pub relations: HashMap<(Tag, Tag), RelationType>,
+    /// Royal marriages: (Tag1, Tag2), stored in sorted order.
+    /// Separate from `relations` since a married pair can also be allied.
+    #[serde(default)]
+    pub royal_marriages: HashSet<(Tag, Tag)>,
     /// Active wars by ID
     pub wars: HashMap<WarId, War>,
     pub next_war_id: u32,
@@ -1404,6 +1524,10 @@ pub struct DiplomacyState {
     /// Higher trust = more likely to honor calls-to-arms, less aggressive expansion impact.
     #[serde(default)]
     pub trust: HashMap<(Tag, Tag), Fixed>,
+    /// Decaying opinion modifiers: (holder, towards) -> modifiers.
+    /// Unsorted pairs (directional: how holder feels about towards).
+    #[serde(default)]
+    pub opinion_modifiers: HashMap<(Tag, Tag), Vec<OpinionModifier>>,
 }
 
 impl DiplomacyState {
@@ -1523,6 +1647,14 @@ impl DiplomacyState {
         }
     }
 
+    // === Royal marriage methods ===
+
+    /// Check if two countries are joined by a royal marriage.
+    pub fn has_royal_marriage(&self, tag1: &str, tag2: &str) -> bool {
+        self.royal_marriages
+            .contains(&Self::sorted_pair(tag1, tag2))
+    }
+
     // === Subject relationship methods ===
 
     /// Get the overlord of a country (if it's a subject).
@@ -1723,7 +1855,7 @@ impl HREState {
     ///
     /// Membership is determined by whether the country's capital province
     /// is in the HRE (has is_in_hre = true).
-    pub fn is_member(&self, tag: &Tag, provinces: &HashMap<ProvinceId, ProvinceState>) -> bool {
+    pub fn is_member(&self, tag: &Tag, provinces: &ProvinceMap<ProvinceState>) -> bool {
         if self.dismantled {
             return false;
         }
@@ -1737,7 +1869,7 @@ impl HREState {
     }
 
     /// Get all HRE member countries by checking capital provinces.
-    pub fn get_members(&self, provinces: &HashMap<ProvinceId, ProvinceState>) -> HashSet<Tag> {
+    pub fn get_members(&self, provinces: &ProvinceMap<ProvinceState>) -> HashSet<Tag> {
         if self.dismantled {
             return HashSet::new();
         }
@@ -1994,6 +2126,9 @@ impl WorldState {
             key.hash(&mut hasher);
             self.diplomacy.relations[key].hash(&mut hasher);
         }
+        let mut marriages: Vec<_> = self.diplomacy.royal_marriages.iter().collect();
+        marriages.sort();
+        marriages.hash(&mut hasher);
 
         // Wars (sorted by ID)
         let mut war_ids: Vec<_> = self.diplomacy.wars.keys().collect();
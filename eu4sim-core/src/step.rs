@@ -3,10 +3,13 @@ use crate::fixed_generic::Mod32;
 use crate::input::{Command, DevType, PlayerInputs};
 use crate::metrics::SimMetrics;
 use crate::profiling::{frame_mark_daily, frame_mark_monthly};
+use crate::schedule::Schedule;
 use crate::state::{
     ArmyId, DiplomacyState, GeneralId, MovementState, PeaceTerms, PendingPeace, ProvinceId,
     Regiment, RelationType, TechType, WorldState,
 };
+use crate::systems::claims::CLAIM_CB;
+use std::sync::OnceLock;
 use std::time::Instant;
 use thiserror::Error;
 use tracing::instrument;
@@ -119,8 +122,12 @@ pub enum ActionError {
     InvalidDestination { destination: u32 },
 }
 
-/// Advance the world by one tick.
-#[instrument(skip_all, name = "step_world")]
+/// Advance the world by one tick, returning the new state.
+///
+/// Convenience wrapper around [`step_world_mut`] that leaves `state`
+/// untouched. Prefer `step_world_mut` in hot loops: cloning forces every
+/// `im::HashMap` written this tick to path-copy instead of mutating in place,
+/// and deep-copies the non-persistent fields (registries, SoA caches).
 pub fn step_world(
     state: &WorldState,
     inputs: &[PlayerInputs],
@@ -128,16 +135,48 @@ pub fn step_world(
     config: &crate::config::SimConfig,
     mut metrics: Option<&mut SimMetrics>,
 ) -> WorldState {
-    let tick_start = Instant::now();
+    let clone_start = Instant::now();
     let mut new_state = state.clone();
+    let clone_time = clone_start.elapsed();
+
+    step_world_mut(
+        &mut new_state,
+        inputs,
+        adjacency,
+        config,
+        metrics.as_deref_mut(),
+    );
+
+    // Charge the clone to the tick so both entry points are comparable in benchmarks
+    if let Some(m) = metrics {
+        m.total_time += clone_time;
+    }
+    new_state
+}
+
+/// Advance the world by one tick in place.
+///
+/// Produces exactly the same state as [`step_world`]. Callers that need
+/// snapshots for observers should take them only when an observer will
+/// actually be notified (see [`crate::ObserverRegistry::wants_snapshot`]);
+/// the `Arc<WorldState>` clone is then the only copy made.
+#[instrument(skip_all, name = "step_world")]
+pub fn step_world_mut(
+    state: &mut WorldState,
+    inputs: &[PlayerInputs],
+    adjacency: Option<&eu4data::adjacency::AdjacencyGraph>,
+    config: &crate::config::SimConfig,
+    mut metrics: Option<&mut SimMetrics>,
+) {
+    let tick_start = Instant::now();
 
     // 1. Advance Date
-    new_state.date = state.date.add_days(1);
+    state.date = state.date.add_days(1);
 
     // 2. Process Inputs
     for player_input in inputs {
         for cmd in &player_input.commands {
-            if let Err(e) = execute_command(&mut new_state, &player_input.country, cmd, adjacency) {
+            if let Err(e) = execute_command(state, &player_input.country, cmd, adjacency) {
                 // Downgrade to debug - these are often valid simultaneous move conflicts
                 log::debug!(
                     "Failed to execute command for {}: {}",
@@ -151,38 +190,38 @@ pub fn step_world(
     // 3. Run Systems
     // Movement runs daily (advances armies along their paths)
     let move_start = Instant::now();
-    crate::systems::run_movement_tick(&mut new_state, adjacency);
+    crate::systems::run_movement_tick(state, adjacency);
     if let Some(m) = metrics.as_mut() {
         m.movement_time += move_start.elapsed();
     }
 
     // Combat runs daily (whenever armies are engaged)
     let combat_start = Instant::now();
-    crate::systems::run_combat_tick(&mut new_state, adjacency);
+    crate::systems::run_combat_tick(state, adjacency);
     if let Some(m) = metrics.as_mut() {
         m.combat_time += combat_start.elapsed();
     }
 
     // Naval combat runs daily (whenever fleets are engaged)
     let naval_combat_start = Instant::now();
-    crate::systems::run_naval_combat_tick(&mut new_state);
+    crate::systems::run_naval_combat_tick(state);
     if let Some(m) = metrics.as_mut() {
         m.combat_time += naval_combat_start.elapsed(); // Count as combat time
     }
 
     // Siege runs daily (progress siege phases and dice rolls)
     let siege_start = Instant::now();
-    crate::systems::run_siege_tick(&mut new_state, adjacency);
+    crate::systems::run_siege_tick(state, adjacency);
     if let Some(m) = metrics.as_mut() {
         m.combat_time += siege_start.elapsed(); // Count as combat time
     }
 
     // Clean up empty armies (0/0/0 strength) after combat/sieges
-    cleanup_empty_armies(&mut new_state);
+    cleanup_empty_armies(state);
 
     // Update occupation and sieges (armies in enemy territory start sieges or occupy instantly)
     let occ_start = Instant::now();
-    update_occupation(&mut new_state);
+    update_occupation(state);
     if let Some(m) = metrics.as_mut() {
         m.occupation_time += occ_start.elapsed();
     }
@@ -191,12 +230,12 @@ pub fn step_world(
     frame_mark_daily();
 
     // Economic systems run monthly (on 1st of each month)
-    if new_state.date.day == 1 {
+    if state.date.day == 1 {
         // Debug: Log treasury at start of monthly tick
-        if let Some(country) = new_state.countries.get("KOR") {
+        if let Some(country) = state.countries.get("KOR") {
             log::debug!(
                 "Monthly tick starting for {} - KOR treasury: {:.2}",
-                new_state.date,
+                state.date,
                 country.treasury.to_f32()
             );
         }
@@ -205,9 +244,9 @@ pub fn step_world(
         let economy_config = crate::systems::EconomyConfig::default();
 
         // Reset income tracking for this month
-        let country_tags: Vec<String> = new_state.countries.keys().cloned().collect();
+        let country_tags: Vec<String> = state.countries.keys().cloned().collect();
         for tag in country_tags {
-            if let Some(country) = new_state.countries.get_mut(&tag) {
+            if let Some(country) = state.countries.get_mut(&tag) {
                 country.income = crate::state::IncomeBreakdown::default();
             }
         }
@@ -232,56 +271,66 @@ pub fn step_world(
         // Power must be calculated first so value propagation knows retention.
         // Trade income must come before taxation as both contribute to treasury.
         let trade_start = Instant::now();
-        crate::systems::run_merchant_arrivals(&mut new_state);
-        crate::systems::run_trade_power_tick(&mut new_state);
-        crate::systems::run_production_tick(&mut new_state, &economy_config);
-        crate::systems::run_trade_value_tick(&mut new_state);
-        crate::systems::run_trade_income_tick(&mut new_state);
+        crate::systems::run_merchant_arrivals(state);
+        crate::systems::run_trade_power_tick(state);
+        crate::systems::run_production_tick(state, &economy_config);
+        crate::systems::run_trade_value_tick(state);
+        crate::systems::run_trade_income_tick(state);
         if let Some(m) = metrics.as_mut() {
             m.trade_time += trade_start.elapsed();
         }
 
-        crate::systems::run_taxation_tick(&mut new_state);
-        crate::systems::run_manpower_tick(&mut new_state);
-        crate::systems::run_attrition_tick(&mut new_state);
-        cleanup_empty_armies(&mut new_state); // Attrition can destroy armies
-        crate::systems::run_expenses_tick(&mut new_state);
-        crate::systems::run_advisor_cost_tick(&mut new_state);
-        crate::systems::run_mana_tick(&mut new_state);
-        crate::systems::run_stats_tick(&mut new_state);
-        crate::systems::run_colonization_tick(&mut new_state);
-        crate::systems::run_estate_tick(&mut new_state);
-        crate::systems::tick_institution_spread(&mut new_state);
-        crate::systems::run_reformation_tick(&mut new_state, adjacency);
-        crate::systems::run_hre_tick(&mut new_state);
-
-        // Coring - Progress active coring and complete after 36 months. 🛡️
-        crate::systems::tick_coring(&mut new_state);
-
-        // Building construction - Progress and complete buildings
-        crate::systems::tick_building_construction(&mut new_state);
+        crate::systems::run_taxation_tick(state);
+        crate::systems::run_manpower_tick(state);
+        crate::systems::run_attrition_tick(state);
+        cleanup_empty_armies(state); // Attrition can destroy armies
+        crate::systems::run_expenses_tick(state);
+        crate::systems::run_advisor_cost_tick(state);
+
+        // Mana, stats, colonization, estates and institution spread run from a
+        // schedule built on their declared read/write sets: the per-country
+        // systems share one parallel stage. See `schedule.rs`.
+        let (before_reformation, after_reformation) = monthly_schedules();
+        before_reformation.run(state);
+        crate::systems::run_reformation_tick(state, adjacency);
+        // HRE, then coring (complete after 36 months 🛡️) and building
+        // construction, which share a per-province stage.
+        after_reformation.run(state);
 
         // Recalculate overextension (uncored dev causes OE penalties)
-        crate::systems::recalculate_overextension(&mut new_state);
+        crate::systems::recalculate_overextension(state);
 
         // Recalculate war scores monthly
-        crate::systems::recalculate_war_scores(&mut new_state);
+        crate::systems::recalculate_war_scores(state);
 
         // Coalition formation and AE decay
-        crate::systems::run_coalition_tick(&mut new_state);
+        crate::systems::run_coalition_tick(state);
+
+        // Diplomat missions (improve relations, AE reduction, claims), then
+        // opinion modifier decay
+        crate::systems::run_diplomat_tick(state);
+        crate::systems::run_opinion_tick(state);
+
+        // Fabricated claims lapse after 25 years; border candidates for new
+        // claims are refreshed alongside
+        crate::systems::run_claim_tick(state);
+        crate::systems::refresh_claim_candidates(state, adjacency);
+
+        // AI strategic goals (yearly replan, monthly pruning)
+        crate::ai::run_strategy_tick(state, adjacency);
 
         // Yearly systems - run on January 1st
-        if new_state.date.month == 1 {
+        if state.date.month == 1 {
             // Tributary payments happen at the start of each year
-            crate::systems::run_tribute_payments(&mut new_state);
+            crate::systems::run_tribute_payments(state);
             // Celestial Empire mandate tick (yearly, unlike HRE's monthly)
-            crate::systems::run_celestial_tick(&mut new_state);
+            crate::systems::run_celestial_tick(state);
             // Meritocracy tick (yearly, from advisors)
-            crate::systems::run_meritocracy_tick(&mut new_state);
+            crate::systems::run_meritocracy_tick(state);
         }
 
         // Auto-end wars after 10 years (stalemate prevention)
-        auto_end_stale_wars(&mut new_state);
+        auto_end_stale_wars(state);
 
         if let Some(m) = metrics.as_mut() {
             m.economy_time += econ_start.elapsed();
@@ -296,12 +345,12 @@ pub fn step_world(
         // Calculate tick number (days since start date)
         // For simplicity, we'll use a simple counter based on date
         // In production, WorldState should track tick count explicitly
-        let tick = ((new_state.date.year - 1444) * 365
-            + (new_state.date.month as i32 - 1) * 30
-            + (new_state.date.day as i32 - 1)) as u64;
+        let tick = ((state.date.year - 1444) * 365
+            + (state.date.month as i32 - 1) * 30
+            + (state.date.day as i32 - 1)) as u64;
 
         if tick.is_multiple_of(config.checksum_frequency as u64) {
-            let checksum = new_state.checksum();
+            let checksum = state.checksum();
             log::debug!("Tick {}: checksum={:016x}", tick, checksum);
         }
     }
@@ -310,8 +359,6 @@ pub fn step_world(
         m.total_ticks += 1;
         m.total_time += tick_start.elapsed();
     }
-
-    new_state
 }
 
 /// Updates province controllers based on army presence.
@@ -405,6 +452,30 @@ fn cleanup_abandoned_sieges(state: &mut WorldState) {
     }
 }
 
+/// Scheduled segments of the monthly tick, split around the reformation
+/// tick (which needs the adjacency graph and so can't be a plain system).
+fn monthly_schedules() -> &'static (Schedule, Schedule) {
+    static SCHEDULES: OnceLock<(Schedule, Schedule)> = OnceLock::new();
+    SCHEDULES.get_or_init(|| {
+        use crate::systems::{buildings, colonization, coring, estates, hre, institutions};
+        use crate::systems::{mana, stats};
+        (
+            Schedule::new(vec![
+                mana::MANA_SYSTEM,
+                stats::STATS_SYSTEM,
+                colonization::COLONIZATION_SYSTEM,
+                estates::ESTATE_SYSTEM,
+                institutions::INSTITUTION_SYSTEM,
+            ]),
+            Schedule::new(vec![
+                hre::HRE_SYSTEM,
+                coring::CORING_SYSTEM,
+                buildings::BUILDING_CONSTRUCTION_SYSTEM,
+            ]),
+        )
+    })
+}
+
 /// Remove armies that have no regiments or all regiments at zero strength.
 /// These are "ghost armies" that should not exist.
 fn cleanup_empty_armies(state: &mut WorldState) {
@@ -999,9 +1070,13 @@ pub fn available_commands(
                     {
                         continue;
                     }
+                    // Use a claim CB when we have one
+                    let cb = (!crate::systems::claims::claims_on(state, country_tag, &target_tag)
+                        .is_empty())
+                    .then(|| CLAIM_CB.to_string());
                     available.push(Command::DeclareWar {
                         target: target_tag,
-                        cb: None,
+                        cb,
                     });
                 }
             }
@@ -1161,6 +1236,16 @@ pub fn available_commands(
     // 6. Diplomacy - Allies and rivals shape the fabric of power. ✧
     let can_offer_diplomatic = country.last_diplomatic_action != Some(state.date);
 
+    // Recall diplomats from every country they're in (no cooldown)
+    let mut diplomat_targets: Vec<&String> = country.diplomats.iter().map(|m| &m.target).collect();
+    diplomat_targets.sort();
+    diplomat_targets.dedup();
+    for target in diplomat_targets {
+        available.push(Command::RecallDiplomat {
+            target: target.clone(),
+        });
+    }
+
     // Accept/Reject pending alliance offers (no cooldown)
     for (offer_key, _date) in &state.diplomacy.pending_alliance_offers {
         let (from, to) = offer_key;
@@ -1217,6 +1302,70 @@ pub fn available_commands(
                 }
             }
 
+            // ImproveRelations - for neighbors not at war, while diplomats are free
+            if crate::systems::diplomats_available(state, country_tag) > 0 {
+                let busy_with = |target: &str, task: crate::state::DiplomatTask| {
+                    country
+                        .diplomats
+                        .iter()
+                        .any(|m| m.target == target && m.task == task)
+                };
+                for target in &potential_neighbors {
+                    if !state.diplomacy.are_at_war(country_tag, target)
+                        && !busy_with(target, crate::state::DiplomatTask::ImproveRelations)
+                    {
+                        available.push(Command::ImproveRelations {
+                            target: target.clone(),
+                        });
+                    }
+                }
+
+                // ReduceAggressiveExpansion - anyone who resents our expansion
+                let mut resentful: Vec<&String> = state
+                    .countries
+                    .iter()
+                    .filter(|(_, c)| {
+                        c.aggressive_expansion
+                            .get(country_tag)
+                            .is_some_and(|ae| *ae > Fixed::ZERO)
+                    })
+                    .map(|(tag, _)| tag)
+                    .collect();
+                resentful.sort();
+                for target in resentful {
+                    if !state.diplomacy.are_at_war(country_tag, target)
+                        && !busy_with(
+                            target,
+                            crate::state::DiplomatTask::ReduceAggressiveExpansion,
+                        )
+                    {
+                        available.push(Command::ReduceAggressiveExpansion {
+                            target: target.clone(),
+                        });
+                    }
+                }
+
+                // FabricateClaim - bordering provinces we have no claim or core on
+                for province in crate::systems::claims::claim_candidates(state, country_tag, graph)
+                {
+                    let Some(owner) = state
+                        .provinces
+                        .get(&province)
+                        .and_then(|p| p.owner.as_deref())
+                    else {
+                        continue;
+                    };
+                    if !state.diplomacy.are_at_war(country_tag, owner)
+                        && !busy_with(
+                            owner,
+                            crate::state::DiplomatTask::FabricateClaim { province },
+                        )
+                    {
+                        available.push(Command::FabricateClaim { province });
+                    }
+                }
+            }
+
             // SetRival - only if under the limit of 3
             let current_rival_count = country.rivals.len();
             if current_rival_count < 3 {
@@ -1234,11 +1383,14 @@ pub fn available_commands(
             }
         }
 
-        // RemoveRival - offer to remove each current rival
+        // RemoveRival / Insult - for each current rival
         for rival in &country.rivals {
             available.push(Command::RemoveRival {
                 target: rival.clone(),
             });
+            available.push(Command::Insult {
+                target: rival.clone(),
+            });
         }
 
         // BreakAlliance - offer to break each current alliance
@@ -1273,11 +1425,10 @@ pub fn available_commands(
             }
 
             for target in potential_neighbors {
-                let key = DiplomacyState::sorted_pair(country_tag, &target);
                 let offer_key = (country_tag.to_string(), target.clone());
 
                 if !state.diplomacy.are_at_war(country_tag, &target)
-                    && state.diplomacy.relations.get(&key) != Some(&RelationType::RoyalMarriage)
+                    && !state.diplomacy.has_royal_marriage(country_tag, &target)
                     && !state
                         .diplomacy
                         .pending_marriage_offers
@@ -1289,14 +1440,11 @@ pub fn available_commands(
         }
 
         // BreakRoyalMarriage - offer to break each current royal marriage
-        for (pair, rel_type) in &state.diplomacy.relations {
-            if *rel_type == RelationType::RoyalMarriage {
-                let (a, b) = pair;
-                if a == country_tag {
-                    available.push(Command::BreakRoyalMarriage { target: b.clone() });
-                } else if b == country_tag {
-                    available.push(Command::BreakRoyalMarriage { target: a.clone() });
-                }
+        for (a, b) in &state.diplomacy.royal_marriages {
+            if a == country_tag {
+                available.push(Command::BreakRoyalMarriage { target: b.clone() });
+            } else if b == country_tag {
+                available.push(Command::BreakRoyalMarriage { target: a.clone() });
             }
         }
 
@@ -1359,7 +1507,10 @@ pub fn available_commands(
     available
 }
 
-fn execute_command(
+/// Apply one command for `country_tag`, as a tick does for each input.
+///
+/// Only the command itself runs; no date advance or other systems.
+pub fn execute_command(
     state: &mut WorldState,
     country_tag: &str,
     cmd: &Command,
@@ -1763,10 +1914,7 @@ fn execute_command(
             }
 
             // Apply Royal Marriage penalty
-            let key = DiplomacyState::sorted_pair(country_tag, target);
-            let has_royal_marriage =
-                state.diplomacy.relations.get(&key) == Some(&RelationType::RoyalMarriage);
-            if has_royal_marriage {
+            if state.diplomacy.has_royal_marriage(country_tag, target) {
                 if let Some(country) = state.countries.get_mut(country_tag) {
                     country.stability.add(-1);
                     log::info!(
@@ -1777,6 +1925,14 @@ fn execute_command(
                 }
             }
 
+            // A claim CB needs a claim on one of the target's provinces
+            let claimed = crate::systems::claims::claims_on(state, country_tag, target);
+            if cb.as_deref() == Some(CLAIM_CB) && claimed.is_empty() {
+                return Err(ActionError::InvalidAction {
+                    reason: format!("No claims on {}'s provinces", target),
+                });
+            }
+
             // Apply No-CB stability penalty (stacks with RM penalty)
             if cb.is_none() {
                 if let Some(country) = state.countries.get_mut(country_tag) {
@@ -1804,10 +1960,23 @@ fn execute_command(
                 defender_score: 0,
                 defender_battle_score: 0,
                 pending_peace: None,
+                war_goal: (cb.as_deref() == Some(CLAIM_CB)).then(|| crate::state::WarGoal {
+                    casus_belli: CLAIM_CB.to_string(),
+                    goal_type: "take_claim".to_string(),
+                    province: claimed.first().copied(),
+                }),
             };
 
             state.diplomacy.wars.insert(war_id, war);
 
+            // The target remembers who attacked it
+            crate::systems::add_opinion_modifier(
+                state,
+                target,
+                country_tag,
+                crate::state::OpinionModifierKind::DeclaredWarOnUs,
+            );
+
             // Call allies to join the war
             call_allies_to_war(state, war_id, country_tag, true); // Attacker's allies
             call_allies_to_war(state, war_id, target, false); // Defender's allies (auto-join)
@@ -1833,9 +2002,7 @@ fn execute_command(
                         }
 
                         // Remove royal marriage if exists
-                        if state.diplomacy.relations.get(&key) == Some(&RelationType::RoyalMarriage)
-                        {
-                            state.diplomacy.relations.remove(&key);
+                        if state.diplomacy.royal_marriages.remove(&key).is_some() {
                             log::debug!(
                                 "War broke royal marriage between {} and {}",
                                 attacker,
@@ -2932,7 +3099,7 @@ fn execute_command(
 
             // Check if already married
             let key = DiplomacyState::sorted_pair(country_tag, target);
-            if state.diplomacy.relations.get(&key) == Some(&RelationType::RoyalMarriage) {
+            if state.diplomacy.royal_marriages.contains(&key) {
                 return Ok(()); // Silently succeed
             }
 
@@ -2945,10 +3112,7 @@ fn execute_command(
             {
                 // Both want marriage - auto-accept
                 state.diplomacy.pending_marriage_offers.remove(&reverse_key);
-                state
-                    .diplomacy
-                    .relations
-                    .insert(key.clone(), RelationType::RoyalMarriage);
+                state.diplomacy.royal_marriages.insert(key);
 
                 if let Some(country) = state.countries.get_mut(country_tag) {
                     country.last_diplomatic_action = Some(state.date);
@@ -2996,15 +3160,13 @@ fn execute_command(
                 });
             }
 
-            // Check if actually married
+            // Check if actually married; remove it (no prestige penalty,
+            // unlike breaking alliances)
             let key = DiplomacyState::sorted_pair(country_tag, target);
-            if state.diplomacy.relations.get(&key) != Some(&RelationType::RoyalMarriage) {
+            if state.diplomacy.royal_marriages.remove(&key).is_none() {
                 return Ok(()); // Silently succeed if not married
             }
 
-            // Remove royal marriage (no prestige penalty, unlike breaking alliances)
-            state.diplomacy.relations.remove(&key);
-
             if let Some(country) = state.countries.get_mut(country_tag) {
                 country.last_diplomatic_action = Some(state.date);
             }
@@ -3216,6 +3378,260 @@ fn execute_command(
             log::info!("{} removed {} as rival", country_tag, target);
             Ok(())
         }
+        Command::ImproveRelations { target } => {
+            // One diplomatic action per day - check if already acted today
+            if let Some(country) = state.countries.get(country_tag) {
+                if country.last_diplomatic_action == Some(state.date) {
+                    return Err(ActionError::DiplomaticActionCooldown);
+                }
+            }
+
+            // Validate both countries exist
+            if !state.countries.contains_key(country_tag) {
+                return Err(ActionError::CountryNotFound {
+                    tag: country_tag.to_string(),
+                });
+            }
+            if !state.countries.contains_key(target) {
+                return Err(ActionError::CountryNotFound {
+                    tag: target.clone(),
+                });
+            }
+
+            // Cannot improve relations with self
+            if country_tag == target {
+                return Err(ActionError::InvalidAction {
+                    reason: "Cannot improve relations with yourself".to_string(),
+                });
+            }
+
+            // Cannot improve relations during war
+            if state.diplomacy.are_at_war(country_tag, target) {
+                return Err(ActionError::InvalidAction {
+                    reason: "Cannot improve relations during war".to_string(),
+                });
+            }
+
+            let arrival = crate::systems::send_diplomat(
+                state,
+                adjacency,
+                country_tag,
+                target,
+                crate::state::DiplomatTask::ImproveRelations,
+            )
+            .map_err(|reason| ActionError::InvalidAction { reason })?;
+
+            if let Some(country) = state.countries.get_mut(country_tag) {
+                country.last_diplomatic_action = Some(state.date);
+            }
+
+            log::info!(
+                "{} sends a diplomat to improve relations with {}, arriving {}",
+                country_tag,
+                target,
+                arrival
+            );
+            Ok(())
+        }
+        Command::ReduceAggressiveExpansion { target } => {
+            // One diplomatic action per day - check if already acted today
+            if let Some(country) = state.countries.get(country_tag) {
+                if country.last_diplomatic_action == Some(state.date) {
+                    return Err(ActionError::DiplomaticActionCooldown);
+                }
+            }
+
+            // Validate both countries exist
+            if !state.countries.contains_key(country_tag) {
+                return Err(ActionError::CountryNotFound {
+                    tag: country_tag.to_string(),
+                });
+            }
+            let Some(target_country) = state.countries.get(target) else {
+                return Err(ActionError::CountryNotFound {
+                    tag: target.clone(),
+                });
+            };
+
+            // Nothing to reduce
+            if target_country
+                .aggressive_expansion
+                .get(country_tag)
+                .is_none_or(|ae| *ae <= Fixed::ZERO)
+            {
+                return Err(ActionError::InvalidAction {
+                    reason: format!("{} has no aggressive expansion towards us", target),
+                });
+            }
+
+            // Cannot work on AE during war
+            if state.diplomacy.are_at_war(country_tag, target) {
+                return Err(ActionError::InvalidAction {
+                    reason: "Cannot reduce aggressive expansion during war".to_string(),
+                });
+            }
+
+            let arrival = crate::systems::send_diplomat(
+                state,
+                adjacency,
+                country_tag,
+                target,
+                crate::state::DiplomatTask::ReduceAggressiveExpansion,
+            )
+            .map_err(|reason| ActionError::InvalidAction { reason })?;
+
+            if let Some(country) = state.countries.get_mut(country_tag) {
+                country.last_diplomatic_action = Some(state.date);
+            }
+
+            log::info!(
+                "{} sends a diplomat to reduce aggressive expansion in {}, arriving {}",
+                country_tag,
+                target,
+                arrival
+            );
+            Ok(())
+        }
+        Command::FabricateClaim { province } => {
+            // One diplomatic action per day - check if already acted today
+            if let Some(country) = state.countries.get(country_tag) {
+                if country.last_diplomatic_action == Some(state.date) {
+                    return Err(ActionError::DiplomaticActionCooldown);
+                }
+            }
+
+            // Validate country exists
+            if !state.countries.contains_key(country_tag) {
+                return Err(ActionError::CountryNotFound {
+                    tag: country_tag.to_string(),
+                });
+            }
+
+            let prov = state
+                .provinces
+                .get(province)
+                .ok_or(ActionError::InvalidProvinceId)?;
+            let Some(owner) = prov.owner.clone() else {
+                return Err(ActionError::InvalidAction {
+                    reason: format!("Province {} has no owner", province),
+                });
+            };
+
+            // Only other countries' provinces, and only once
+            if owner == country_tag {
+                return Err(ActionError::InvalidAction {
+                    reason: "Cannot fabricate a claim on our own province".to_string(),
+                });
+            }
+            if prov.cores.contains(country_tag)
+                || crate::systems::claims::has_claim(prov, country_tag)
+            {
+                return Err(ActionError::InvalidAction {
+                    reason: format!("Already have a claim or core on province {}", province),
+                });
+            }
+
+            // Cannot fabricate claims during war
+            if state.diplomacy.are_at_war(country_tag, &owner) {
+                return Err(ActionError::InvalidAction {
+                    reason: "Cannot fabricate claims during war".to_string(),
+                });
+            }
+
+            let arrival = crate::systems::send_diplomat(
+                state,
+                adjacency,
+                country_tag,
+                &owner,
+                crate::state::DiplomatTask::FabricateClaim {
+                    province: *province,
+                },
+            )
+            .map_err(|reason| ActionError::InvalidAction { reason })?;
+
+            if let Some(country) = state.countries.get_mut(country_tag) {
+                country.last_diplomatic_action = Some(state.date);
+            }
+
+            log::info!(
+                "{} sends a diplomat to fabricate a claim on province {} ({}), arriving {}",
+                country_tag,
+                province,
+                owner,
+                arrival
+            );
+            Ok(())
+        }
+        Command::RecallDiplomat { target } => {
+            if !state.countries.contains_key(country_tag) {
+                return Err(ActionError::CountryNotFound {
+                    tag: country_tag.to_string(),
+                });
+            }
+
+            let recalled = crate::systems::recall_diplomats(state, country_tag, target);
+            if recalled == 0 {
+                return Err(ActionError::InvalidAction {
+                    reason: format!("No diplomats in {}", target),
+                });
+            }
+
+            log::info!(
+                "{} recalls {} diplomat(s) from {}",
+                country_tag,
+                recalled,
+                target
+            );
+            Ok(())
+        }
+        Command::Insult { target } => {
+            // One diplomatic action per day - check if already acted today
+            if let Some(country) = state.countries.get(country_tag) {
+                if country.last_diplomatic_action == Some(state.date) {
+                    return Err(ActionError::DiplomaticActionCooldown);
+                }
+            }
+
+            // Validate both countries exist
+            if !state.countries.contains_key(country_tag) {
+                return Err(ActionError::CountryNotFound {
+                    tag: country_tag.to_string(),
+                });
+            }
+            if !state.countries.contains_key(target) {
+                return Err(ActionError::CountryNotFound {
+                    tag: target.clone(),
+                });
+            }
+
+            // Cannot insult self
+            if country_tag == target {
+                return Err(ActionError::InvalidAction {
+                    reason: "Cannot insult yourself".to_string(),
+                });
+            }
+
+            // Cannot insult an ally
+            if state.diplomacy.has_alliance(country_tag, target) {
+                return Err(ActionError::InvalidAction {
+                    reason: "Cannot insult an ally".to_string(),
+                });
+            }
+
+            crate::systems::add_opinion_modifier(
+                state,
+                target,
+                country_tag,
+                crate::state::OpinionModifierKind::Insulted,
+            );
+
+            if let Some(country) = state.countries.get_mut(country_tag) {
+                country.last_diplomatic_action = Some(state.date);
+            }
+
+            log::info!("{} insulted {}", country_tag, target);
+            Ok(())
+        }
         Command::AcceptAlliance { from } => {
             // NO cooldown for responses (can accept multiple in one day)
 
@@ -3317,10 +3733,7 @@ fn execute_command(
             // Remove offer, create royal marriage
             state.diplomacy.pending_marriage_offers.remove(&offer_key);
             let key = DiplomacyState::sorted_pair(country_tag, from);
-            state
-                .diplomacy
-                .relations
-                .insert(key, RelationType::RoyalMarriage);
+            state.diplomacy.royal_marriages.insert(key);
 
             log::info!(
                 "{} accepted royal marriage offer from {}",
@@ -4315,19 +4728,20 @@ fn calculate_peace_terms_cost(
         PeaceTerms::WhitePeace => 0, // Free with 50% war score (AI acceptance logic)
         PeaceTerms::TakeProvinces { provinces } => {
             // Cost = sum of province dev / 2 (simplified)
-            let enemy_tags: &[String] = if is_attacker {
-                &war.defenders
+            let (our_tags, enemy_tags): (&[String], &[String]) = if is_attacker {
+                (&war.attackers, &war.defenders)
             } else {
-                &war.attackers
+                (&war.defenders, &war.attackers)
             };
+            // Provinces go to our war leader, so their claims count
+            let taker = our_tags.first().map(String::as_str).unwrap_or_default();
 
             let mut cost = 0u32;
             for &prov_id in provinces {
                 if let Some(prov) = state.provinces.get(&prov_id) {
                     // Only count provinces owned by enemy
                     if prov.owner.as_ref().is_some_and(|o| enemy_tags.contains(o)) {
-                        let dev = prov.base_tax + prov.base_production + prov.base_manpower;
-                        cost += (dev.to_f32() / 2.0).ceil() as u32;
+                        cost += crate::systems::war_score::province_peace_cost(prov, taker);
                     }
                 }
             }
@@ -4340,18 +4754,18 @@ fn calculate_peace_terms_cost(
 /// Apply aggressive expansion to all countries when provinces are conquered.
 ///
 /// AE impact:
-/// - 1 AE per 1 development conquered
+/// - 1 AE per 1 development conquered (-25% for claimed provinces)
 /// - Applied to all countries in the world
 /// - Higher impact on neighbors and countries with good relations
 fn apply_aggressive_expansion(state: &mut WorldState, conqueror: &str, provinces: &[ProvinceId]) {
-    // Calculate total development conquered
+    // Calculate total development conquered (claimed provinces count less)
     let total_dev: Mod32 = provinces
         .iter()
         .filter_map(|&prov_id| {
             state
                 .provinces
                 .get(&prov_id)
-                .map(|p| p.base_tax + p.base_production + p.base_manpower)
+                .map(|p| crate::systems::war_score::province_aggressive_expansion(p, conqueror))
         })
         .fold(Mod32::ZERO, |acc, v| acc + v);
 
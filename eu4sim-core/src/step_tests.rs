@@ -85,6 +85,106 @@ fn test_determinism() {
     assert_eq!(json_a, json_b);
 }
 
+#[test]
+fn test_step_world_mut_matches_step_world() {
+    let mut state = WorldStateBuilder::new()
+        .date(1444, 11, 11)
+        .with_country("SWE")
+        .with_country("DEN")
+        .with_province(1, Some("SWE"))
+        .with_province(2, Some("DEN"))
+        .build();
+    let config = crate::config::SimConfig::default();
+
+    // Run across a month boundary so the monthly systems are exercised too
+    let mut cloned = state.clone();
+    for _ in 0..60 {
+        cloned = step_world(&cloned, &[], None, &config, None);
+        step_world_mut(&mut state, &[], None, &config, None);
+    }
+
+    assert_eq!(state.date, cloned.date);
+    assert_eq!(
+        serde_json::to_string(&state).unwrap(),
+        serde_json::to_string(&cloned).unwrap()
+    );
+}
+
+#[test]
+fn test_monthly_schedules_parallelise_independent_systems() {
+    let (before, after) = monthly_schedules();
+    assert_eq!(
+        before.stage_names(),
+        vec![
+            vec!["mana", "stats", "estates"],
+            vec!["colonization"],
+            vec!["institutions"],
+        ]
+    );
+    assert_eq!(
+        after.stage_names(),
+        vec![vec!["hre"], vec!["coring", "buildings"]]
+    );
+}
+
+#[test]
+fn test_monthly_schedules_match_sequential() {
+    use crate::buildings::BuildingConstruction;
+    use crate::modifiers::BuildingId;
+    use crate::state::CoringProgress;
+
+    let mut state = WorldStateBuilder::new()
+        .date(1455, 3, 1)
+        .with_country("SWE")
+        .with_country("DEN")
+        .with_country("NOR")
+        .build();
+    for id in 1..=12u32 {
+        let owner = if id % 2 == 0 { "SWE" } else { "DEN" };
+        state.provinces.insert(
+            id,
+            ProvinceState {
+                owner: Some(owner.to_string()),
+                base_tax: Mod32::from_int(id as i32),
+                coring_progress: (id % 3 == 0).then(|| CoringProgress {
+                    // Every other one has changed hands and gets cancelled
+                    coring_country: if id % 2 == 0 { "SWE" } else { "NOR" }.to_string(),
+                    start_date: Date::new(1452, 3, 1),
+                    progress: 35,
+                    required: 36,
+                }),
+                building_construction: (id % 4 == 0).then(|| BuildingConstruction {
+                    building_id: BuildingId(1),
+                    start_date: Date::new(1455, 1, 1),
+                    progress: 11,
+                    required: 12,
+                    cost_paid: Fixed::from_int(100),
+                }),
+                ..Default::default()
+            },
+        );
+    }
+    // Renaissance origin, so institution spread has something to do
+    state.provinces.insert(112, ProvinceState::default());
+
+    let mut parallel = state.clone();
+    let mut sequential = state;
+    for _ in 0..3 {
+        for schedule in [&monthly_schedules().0, &monthly_schedules().1] {
+            schedule.run(&mut parallel);
+            schedule.run_sequential(&mut sequential);
+        }
+    }
+
+    assert_eq!(
+        serde_json::to_string(&parallel).unwrap(),
+        serde_json::to_string(&sequential).unwrap()
+    );
+    assert!(parallel.provinces[&6].cores.contains("SWE"));
+    assert!(parallel.provinces[&3].coring_progress.is_none());
+    assert!(parallel.provinces[&4].building_construction.is_none());
+}
+
 #[test]
 fn test_declare_war_success() {
     // Use December 1444 to bypass first-month immunity
@@ -487,6 +587,7 @@ fn test_peace_creates_truces() {
             defender_score: 0,
             defender_battle_score: 0,
             pending_peace: None,
+            war_goal: None,
         },
     );
 
@@ -2742,48 +2843,11 @@ fn test_accept_royal_marriage_coexist_with_alliance() {
     )
     .unwrap();
 
-    // Both alliance AND marriage should exist for the same pair
-    // This tests that relations can hold MULTIPLE entries for the same sorted pair
-    // (Actually no - relations is a HashMap, so we need to check if this is the right approach)
-    // Wait, looking at the state structure, relations is HashMap<(Tag, Tag), RelationType>
-    // So we can only have ONE relation type per sorted pair. Let me re-check the plan...
-
-    // According to the plan: "Royal marriages track separately (can coexist with alliances)"
-    // But the state structure uses the same HashMap. This means we need to revise the approach.
-    // Actually, wait - the plan says they can coexist, and the user said:
-    // "you can royal marry AND ally someone. make sure the choice can satisfy those constraints"
-
-    // Looking at the state structure again, we have a SINGLE HashMap<(Tag, Tag), RelationType>
-    // where RelationType is an enum {Alliance, RoyalMarriage, Rival}. This means we can only
-    // have ONE relation type at a time, not both!
-
-    // This is a fundamental design flaw. We need to change the structure. But that was already
-    // done in Commit 1 and I can't change it now without breaking the plan.
-
-    // Wait, let me re-read Commit 1... Actually, I see the issue. The plan was to have them
-    // coexist, but the implementation I did uses a single enum value. I need to fix this.
-
-    // Actually, looking more carefully, maybe the intention was to use a SET of RelationTypes
-    // instead of a single RelationType. Let me check what I implemented in Commit 1...
-
-    // I implemented: `pub relations: HashMap<(Tag, Tag), RelationType>`
-    // But this only allows ONE relation type per pair!
-
-    // The correct implementation should be: `pub relations: HashMap<(Tag, Tag), HashSet<RelationType>>`
-    // But that's a breaking change to Commit 1.
-
-    // Let me reconsider. Maybe the test should just verify that marriage is created,
-    // and we accept that alliance and marriage are mutually exclusive in the current impl.
-    // Then I can note this as a limitation to fix later.
-
-    // For now, let me just test that royal marriage is created successfully.
-
-    // Royal marriage should be created (overwriting the alliance in current impl)
-    // NOTE: This is a limitation - alliances and marriages can't currently coexist
-    // due to using HashMap<pair, RelationType> instead of HashMap<pair, HashSet<RelationType>>
+    // Both alliance AND marriage exist for the same pair
+    assert!(state.diplomacy.royal_marriages.contains(&key));
     assert_eq!(
         state.diplomacy.relations.get(&key),
-        Some(&RelationType::RoyalMarriage)
+        Some(&RelationType::Alliance)
     );
 }
 
@@ -2797,10 +2861,7 @@ fn test_declare_war_royal_marriage_penalty() {
 
     // Create royal marriage
     let key = DiplomacyState::sorted_pair("SWE", "DEN");
-    state
-        .diplomacy
-        .relations
-        .insert(key, RelationType::RoyalMarriage);
+    state.diplomacy.royal_marriages.insert(key);
 
     // Get initial stability
     let initial_stability = state.countries.get("SWE").unwrap().stability.get();
@@ -2832,10 +2893,7 @@ fn test_declare_war_no_cb_and_rm_stacks() {
 
     // Create royal marriage
     let key = DiplomacyState::sorted_pair("SWE", "DEN");
-    state
-        .diplomacy
-        .relations
-        .insert(key, RelationType::RoyalMarriage);
+    state.diplomacy.royal_marriages.insert(key);
 
     // Get initial stability
     let initial_stability = state.countries.get("SWE").unwrap().stability.get();
@@ -2867,10 +2925,7 @@ fn test_break_royal_marriage_success() {
 
     // Create royal marriage
     let key = DiplomacyState::sorted_pair("SWE", "DEN");
-    state
-        .diplomacy
-        .relations
-        .insert(key.clone(), RelationType::RoyalMarriage);
+    state.diplomacy.royal_marriages.insert(key.clone());
 
     // SWE breaks marriage (no prestige penalty, unlike alliances)
     let result = execute_command(
@@ -2885,7 +2940,7 @@ fn test_break_royal_marriage_success() {
     assert!(result.is_ok());
 
     // Marriage should be removed
-    assert!(state.diplomacy.relations.get(&key).is_none());
+    assert!(!state.diplomacy.royal_marriages.contains(&key));
 }
 
 #[test]
@@ -2898,10 +2953,7 @@ fn test_war_breaks_royal_marriage() {
 
     // Create royal marriage
     let key = DiplomacyState::sorted_pair("SWE", "DEN");
-    state
-        .diplomacy
-        .relations
-        .insert(key.clone(), RelationType::RoyalMarriage);
+    state.diplomacy.royal_marriages.insert(key.clone());
 
     // SWE declares war on DEN
     execute_command(
@@ -2919,7 +2971,7 @@ fn test_war_breaks_royal_marriage() {
     assert_eq!(state.diplomacy.wars.len(), 1);
 
     // Royal marriage should be broken by war
-    assert!(state.diplomacy.relations.get(&key).is_none());
+    assert!(!state.diplomacy.royal_marriages.contains(&key));
 }
 
 #[test]
@@ -3753,3 +3805,167 @@ fn test_revoke_privilegia_skips_existing_subjects() {
     // ULM should be HAB's vassal
     assert!(state.diplomacy.is_overlord_of("HAB", "ULM"));
 }
+
+#[test]
+fn test_improve_relations_raises_target_opinion() {
+    let mut state = WorldStateBuilder::new()
+        .date(1444, 12, 11)
+        .with_country("SWE")
+        .with_country("DEN")
+        .build();
+
+    execute_command(
+        &mut state,
+        "SWE",
+        &Command::ImproveRelations {
+            target: "DEN".to_string(),
+        },
+        None,
+    )
+    .unwrap();
+    let mission = state.countries["SWE"].diplomats[0].clone();
+    assert_eq!(mission.target, "DEN");
+    assert_eq!(mission.task, crate::state::DiplomatTask::ImproveRelations);
+    assert_eq!(crate::systems::diplomats_available(&state, "SWE"), 1);
+
+    // Nothing happens until the diplomat arrives
+    crate::systems::run_diplomat_tick(&mut state);
+    assert_eq!(crate::systems::opinion(&state, "DEN", "SWE"), Fixed::ZERO);
+
+    state.date = mission.arrival_date;
+    crate::systems::run_diplomat_tick(&mut state);
+    crate::systems::run_opinion_tick(&mut state);
+    assert_eq!(
+        crate::systems::opinion(&state, "DEN", "SWE"),
+        Fixed::from_int(3)
+    );
+}
+
+#[test]
+fn test_reduce_ae_needs_ae_and_recall_frees_diplomat() {
+    let mut state = WorldStateBuilder::new()
+        .date(1444, 12, 11)
+        .with_country("SWE")
+        .with_country("DEN")
+        .build();
+    let reduce = Command::ReduceAggressiveExpansion {
+        target: "DEN".to_string(),
+    };
+
+    assert!(execute_command(&mut state, "SWE", &reduce, None).is_err());
+
+    state
+        .countries
+        .get_mut("DEN")
+        .unwrap()
+        .aggressive_expansion
+        .insert("SWE".to_string(), Fixed::from_int(20));
+    execute_command(&mut state, "SWE", &reduce, None).unwrap();
+    assert_eq!(crate::systems::diplomats_available(&state, "SWE"), 1);
+
+    state.date = state.date.add_days(1);
+    let available = available_commands(&state, "SWE", None);
+    assert!(available.contains(&Command::RecallDiplomat {
+        target: "DEN".to_string()
+    }));
+    execute_command(
+        &mut state,
+        "SWE",
+        &Command::RecallDiplomat {
+            target: "DEN".to_string(),
+        },
+        None,
+    )
+    .unwrap();
+    assert_eq!(crate::systems::diplomats_available(&state, "SWE"), 2);
+}
+
+#[test]
+fn test_fabricated_claim_justifies_claim_cb() {
+    let mut state = WorldStateBuilder::new()
+        .date(1444, 12, 11)
+        .with_country("SWE")
+        .with_country("DEN")
+        .with_province(1, Some("SWE"))
+        .with_province(2, Some("DEN"))
+        .build();
+    let mut graph = eu4data::adjacency::AdjacencyGraph::new();
+    graph.add_adjacency(1, 2);
+
+    let claim_cb = Command::DeclareWar {
+        target: "DEN".to_string(),
+        cb: Some("cb_claim".to_string()),
+    };
+    assert!(execute_command(&mut state, "SWE", &claim_cb, Some(&graph)).is_err());
+
+    let fabricate = Command::FabricateClaim { province: 2 };
+    assert!(available_commands(&state, "SWE", Some(&graph)).contains(&fabricate));
+    execute_command(&mut state, "SWE", &fabricate, Some(&graph)).unwrap();
+    // Can't fabricate on our own land
+    state.date = state.date.add_days(1);
+    assert!(execute_command(
+        &mut state,
+        "SWE",
+        &Command::FabricateClaim { province: 1 },
+        Some(&graph)
+    )
+    .is_err());
+
+    for _ in 0..40 {
+        state.date = state.date.add_days(30);
+        crate::systems::run_diplomat_tick(&mut state);
+    }
+    assert!(state.provinces[&2].claims.contains_key("SWE"));
+    assert!(!available_commands(&state, "SWE", Some(&graph)).contains(&fabricate));
+    assert!(available_commands(&state, "SWE", Some(&graph)).contains(&claim_cb));
+
+    let stability = state.countries["SWE"].stability.get();
+    execute_command(&mut state, "SWE", &claim_cb, Some(&graph)).unwrap();
+    // No no-CB stability hit, and the war goal records the claim
+    assert_eq!(state.countries["SWE"].stability.get(), stability);
+    let war = state.diplomacy.wars.values().next().unwrap();
+    let goal = war.war_goal.as_ref().unwrap();
+    assert_eq!(goal.casus_belli, "cb_claim");
+    assert_eq!(goal.province, Some(2));
+}
+
+#[test]
+fn test_insult_and_war_lower_target_opinion() {
+    let mut state = WorldStateBuilder::new()
+        .date(1444, 12, 11)
+        .with_country("SWE")
+        .with_country("DEN")
+        .build();
+
+    execute_command(
+        &mut state,
+        "SWE",
+        &Command::Insult {
+            target: "DEN".to_string(),
+        },
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        crate::systems::opinion(&state, "DEN", "SWE"),
+        Fixed::from_int(-50)
+    );
+
+    state.date = state.date.add_days(1);
+    execute_command(
+        &mut state,
+        "SWE",
+        &Command::DeclareWar {
+            target: "DEN".to_string(),
+            cb: None,
+        },
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        crate::systems::opinion(&state, "DEN", "SWE"),
+        Fixed::from_int(-150)
+    );
+    // Sweden's view of Denmark is untouched
+    assert_eq!(crate::systems::opinion(&state, "SWE", "DEN"), Fixed::ZERO);
+}
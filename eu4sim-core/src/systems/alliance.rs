@@ -17,14 +17,15 @@ use crate::state::{Tag, WarId, WorldState};
 /// - Trust: At 50 neutral, +0.5 per point above, -2 per point below
 /// - Debt: -1000 if in debt (loans > 0 or negative treasury)
 /// - Destabilization: -50 per missing stability point
-/// - Other factors: TODO relations with target, war exhaustion
+/// - Relations with the enemy war leader: -1/4 of opinion of them
+/// - Other factors: TODO war exhaustion
 ///
 /// Returns a score that AI can use to decide whether to honor the CTA.
 pub fn calculate_cta_acceptance_score(
     state: &WorldState,
     ally: &Tag,
     caller: &Tag,
-    _war_id: WarId,
+    war_id: WarId,
 ) -> i32 {
     let mut score = 0;
 
@@ -53,7 +54,19 @@ pub fn calculate_cta_acceptance_score(
         score += stability * 50; // Negative stability = negative score
     }
 
-    // 4. TODO: Relations with war target
+    // 4. Relations with war target (reluctant to fight friends)
+    let enemy_leader = state.diplomacy.wars.get(&war_id).and_then(|war| {
+        if war.attackers.contains(caller) {
+            war.defenders.first()
+        } else {
+            war.attackers.first()
+        }
+    });
+    if let Some(enemy) = enemy_leader {
+        let opinion = crate::systems::opinion::opinion(state, ally, enemy);
+        score -= opinion.to_int() as i32 / 4;
+    }
+
     // 5. TODO: War exhaustion
     // 6. TODO: Other modifiers (diplomatic ideas, etc.)
 
@@ -313,6 +313,7 @@ mod tests {
             defender_score: 0,
             defender_battle_score: 0,
             pending_peace: None,
+            war_goal: None,
         };
         state.diplomacy.wars.insert(1, war);
 
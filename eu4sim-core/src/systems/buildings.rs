@@ -6,6 +6,7 @@ use crate::buildings::{BuildingConstruction, BuildingDef};
 use crate::fixed::Fixed;
 use crate::fixed_generic::Mod32;
 use crate::modifiers::{BuildingId, GameModifiers, TradegoodId};
+use crate::schedule::{run_system, Access, Resource, SystemDesc};
 use crate::state::{CountryState, HashMap, ProvinceId, ProvinceState, Tag, Terrain, WorldState};
 use tracing::instrument;
 
@@ -445,60 +446,69 @@ pub fn validate_manufactory_on_goods_change(
 /// Called each month. Completes buildings when progress reaches required time.
 #[instrument(skip_all, name = "buildings")]
 pub fn tick_building_construction(state: &mut WorldState) {
-    let province_ids: Vec<_> = state.provinces.keys().copied().collect();
-
-    // Collect completed buildings first to avoid borrow issues
-    let mut completed: Vec<(ProvinceId, BuildingId)> = Vec::new();
-    let mut had_completion = false;
-
-    for province_id in province_ids {
-        let Some(province) = state.provinces.get_mut(&province_id) else {
-            continue;
-        };
-
-        let Some(construction) = province.building_construction.as_mut() else {
-            continue;
-        };
+    run_system(state, &BUILDING_CONSTRUCTION_SYSTEM);
+}
 
-        construction.progress += 1;
+/// Scheduler descriptor for [`tick_building_construction`].
+///
+/// Construction progresses per province; the finish step recomputes province
+/// and country modifiers, which scans every province's buildings.
+pub const BUILDING_CONSTRUCTION_SYSTEM: SystemDesc = SystemDesc::per_province(
+    "buildings",
+    Access::new(
+        &[Resource::Registries, Resource::Provinces],
+        &[Resource::Modifiers],
+    ),
+    progress_construction,
+    Some(finish_constructions),
+);
+
+/// Advance construction in one province. Returns `true` when a building completes.
+fn progress_construction(
+    state: &WorldState,
+    province_id: ProvinceId,
+    province: &mut ProvinceState,
+) -> bool {
+    let Some(construction) = province.building_construction.as_mut() else {
+        return false;
+    };
 
-        if construction.progress >= construction.required {
-            completed.push((province_id, construction.building_id));
-            had_completion = true;
+    construction.progress += 1;
+    if construction.progress < construction.required {
+        return false;
+    }
+    let building_id = construction.building_id;
+
+    // Handle upgrade chain - remove replaced building
+    if let Some(def) = state.building_defs.get(&building_id) {
+        if let Some(replaces) = def.replaces_building {
+            province.buildings.remove(replaces);
+            log::debug!(
+                "Replaced {} with {} in province {}",
+                replaces.0,
+                building_id.0,
+                province_id
+            );
         }
     }
 
-    // Now complete the buildings
-    for (province_id, building_id) in completed {
-        // Handle upgrade chain - remove replaced building
-        if let Some(def) = state.building_defs.get(&building_id) {
-            if let Some(replaces) = def.replaces_building {
-                if let Some(province) = state.provinces.get_mut(&province_id) {
-                    province.buildings.remove(replaces);
-                    log::debug!(
-                        "Replaced {} with {} in province {}",
-                        replaces.0,
-                        building_id.0,
-                        province_id
-                    );
-                }
-            }
-        }
+    province.buildings.insert(building_id);
+    province.building_construction = None;
 
-        if let Some(province) = state.provinces.get_mut(&province_id) {
-            province.buildings.insert(building_id);
-            province.building_construction = None;
-        }
+    if let Some(def) = state.building_defs.get(&building_id) {
+        log::info!(
+            "Building {} completed in province {}",
+            def.name,
+            province_id
+        );
+    }
 
-        if let Some(def) = state.building_defs.get(&building_id) {
-            log::info!(
-                "Building {} completed in province {}",
-                def.name,
-                province_id
-            );
-        }
+    true
+}
 
-        // Recompute modifiers for this province
+/// Recompute modifiers for provinces that completed a building this month.
+fn finish_constructions(state: &mut WorldState, completed: &[ProvinceId]) {
+    for &province_id in completed {
         if let Some(province) = state.provinces.get(&province_id) {
             let province_clone = province.clone();
             recompute_province_modifiers(
@@ -510,10 +520,8 @@ pub fn tick_building_construction(state: &mut WorldState) {
         }
     }
 
-    // Recompute country-level modifiers from all buildings if any completed
-    if had_completion {
-        recompute_country_modifiers_from_buildings(state);
-    }
+    // Recompute country-level modifiers from all buildings
+    recompute_country_modifiers_from_buildings(state);
 }
 
 /// Recompute modifiers for a single province based on its buildings.
@@ -770,6 +778,7 @@ mod tests {
             institution_presence: HashMap::default(),
             trade: Default::default(),
             cores: Default::default(),
+            claims: Default::default(),
             coring_progress: None,
             buildings: BuildingSet::default(),
             building_construction: None,
@@ -0,0 +1,281 @@
+//! Province claims.
+//!
+//! A claim justifies conquering a province (`cb_claim`) and makes it cheaper:
+//! - **Peace cost**: -25% war score for the province (`war_score.rs`)
+//! - **Aggressive expansion**: -25% from conquering it
+//! - **Coring cost**: -25% ADM once we own it (`coring.rs`)
+//!
+//! Permanent claims come from history files. Temporary claims are fabricated
+//! by a diplomat (see [`super::diplomats`]) and lapse after 25 years. Either
+//! kind is replaced by a core once the claimant cores the province.
+
+use crate::fixed::Fixed;
+use crate::fixed_generic::Mod32;
+use crate::state::{Claim, ProvinceId, ProvinceState, WorldState};
+use eu4data::adjacency::AdjacencyGraph;
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::instrument;
+
+/// Casus belli justified by a claim on one of the target's provinces.
+pub const CLAIM_CB: &str = "cb_claim";
+
+/// How long a fabricated claim lasts.
+pub const TEMPORARY_CLAIM_YEARS: i32 = 25;
+
+/// Discount a claim gives on peace cost, AE and coring cost (25%).
+pub const CLAIM_DISCOUNT: Mod32 = Mod32::from_raw(2500);
+
+/// Fabrication progress per month before modifiers (out of 100, ~3 years).
+const BASE_FABRICATION_PER_MONTH: i64 = 3;
+
+/// Does `tag` hold a claim on this province?
+pub fn has_claim(province: &ProvinceState, tag: &str) -> bool {
+    province.claims.contains_key(tag)
+}
+
+/// Multiplier for claim-discounted costs: 0.75 with a claim, 1 without.
+pub fn claim_factor(province: &ProvinceState, tag: &str) -> Mod32 {
+    if has_claim(province, tag) {
+        Mod32::ONE - CLAIM_DISCOUNT
+    } else {
+        Mod32::ONE
+    }
+}
+
+/// Provinces owned by `owner` that `claimant` has claims on, lowest id first.
+pub fn claims_on(state: &WorldState, claimant: &str, owner: &str) -> Vec<ProvinceId> {
+    state
+        .provinces
+        .iter()
+        .filter(|(_, p)| p.owner.as_deref() == Some(owner) && has_claim(p, claimant))
+        .map(|(&id, _)| id)
+        .collect()
+}
+
+/// Could `tag` fabricate a claim on this province?
+fn is_claimable(province: &ProvinceState, tag: &str) -> bool {
+    province.owner.as_deref().is_some_and(|owner| owner != tag)
+        && !province.cores.contains(tag)
+        && !has_claim(province, tag)
+}
+
+/// Foreign neighbours of `owned` that `tag` could claim, lowest id first.
+fn claimable_neighbors(
+    state: &WorldState,
+    tag: &str,
+    owned: impl IntoIterator<Item = ProvinceId>,
+    graph: &AdjacencyGraph,
+) -> Vec<ProvinceId> {
+    let mut claimable = BTreeSet::new();
+    for province_id in owned {
+        for neighbor_id in graph.neighbors(province_id) {
+            if state
+                .provinces
+                .get(&neighbor_id)
+                .is_some_and(|neighbor| is_claimable(neighbor, tag))
+            {
+                claimable.insert(neighbor_id);
+            }
+        }
+    }
+    claimable.into_iter().collect()
+}
+
+/// Border provinces `tag` could fabricate a claim on, lowest id first.
+///
+/// Reads the monthly cache, dropping entries that changed hands or gained a
+/// claim since the refresh. Before the first refresh it walks the country's
+/// own provinces instead.
+pub fn claim_candidates(state: &WorldState, tag: &str, graph: &AdjacencyGraph) -> Vec<ProvinceId> {
+    match &state.claim_candidates {
+        Some(cache) => cache
+            .get(tag)
+            .map(|candidates| {
+                candidates
+                    .iter()
+                    .copied()
+                    .filter(|id| {
+                        state
+                            .provinces
+                            .get(id)
+                            .is_some_and(|p| is_claimable(p, tag))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        None => {
+            let owned = state
+                .provinces
+                .iter()
+                .filter(|(_, p)| p.owner.as_deref() == Some(tag))
+                .map(|(&id, _)| id);
+            claimable_neighbors(state, tag, owned, graph)
+        }
+    }
+}
+
+/// Rebuild every country's claim candidates in one pass over the provinces.
+#[instrument(skip_all, name = "claim_candidates")]
+pub fn refresh_claim_candidates(state: &mut WorldState, adjacency: Option<&AdjacencyGraph>) {
+    let Some(graph) = adjacency else {
+        return;
+    };
+    let mut owned: BTreeMap<&str, Vec<ProvinceId>> = BTreeMap::new();
+    for (&id, province) in &state.provinces {
+        if let Some(owner) = province.owner.as_deref() {
+            owned.entry(owner).or_default().push(id);
+        }
+    }
+    let candidates = owned
+        .into_iter()
+        .map(|(tag, provinces)| {
+            let claimable = claimable_neighbors(state, tag, provinces, graph);
+            (tag.to_string(), claimable)
+        })
+        .collect();
+    state.claim_candidates = Some(candidates);
+}
+
+/// Give `tag` a claim on a province.
+///
+/// Permanent claims are never downgraded; a temporary claim is refreshed.
+pub fn add_claim(state: &mut WorldState, province_id: ProvinceId, tag: &str, permanent: bool) {
+    let expires = (!permanent).then(|| state.date.add_years(TEMPORARY_CLAIM_YEARS));
+    let Some(province) = state.provinces.get_mut(&province_id) else {
+        return;
+    };
+    let claim = province
+        .claims
+        .entry(tag.to_string())
+        .or_insert(Claim { expires });
+    if !claim.is_permanent() {
+        claim.expires = expires;
+    }
+}
+
+/// Monthly fabrication progress for a diplomat of `tag`.
+///
+/// Diplomatic reputation speeds it up; `fabricate_claims_cost` (negative is
+/// better) scales it.
+pub fn fabrication_progress_per_month(state: &WorldState, tag: &str) -> Fixed {
+    let reputation = state
+        .modifiers
+        .country_diplomatic_reputation
+        .get(tag)
+        .copied()
+        .unwrap_or(Mod32::ZERO);
+    let cost = state
+        .modifiers
+        .country_fabricate_claims_cost
+        .get(tag)
+        .copied()
+        .unwrap_or(Mod32::ZERO);
+    let base =
+        Fixed::from_int(BASE_FABRICATION_PER_MONTH) + reputation.to_fixed() / Fixed::from_int(2);
+    base.mul(Fixed::ONE - cost.to_fixed()).max(Fixed::ONE)
+}
+
+/// Run monthly claim tick: drop expired temporary claims.
+#[instrument(skip_all, name = "claims")]
+pub fn run_claim_tick(state: &mut WorldState) {
+    let today = state.date;
+    for (id, province) in state.provinces.iter_mut() {
+        if province.claims.is_empty() {
+            continue;
+        }
+        province.claims.retain(|tag, claim| {
+            let expired = claim.expires.is_some_and(|date| today >= date);
+            if expired {
+                log::debug!("{}'s claim on province {} expired", tag, id);
+            }
+            !expired
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::WorldStateBuilder;
+
+    #[test]
+    fn test_temporary_claims_expire_permanent_ones_stay() {
+        let mut state = WorldStateBuilder::new()
+            .date(1444, 11, 11)
+            .with_country("SWE")
+            .with_country("DAN")
+            .with_province(1, Some("DAN"))
+            .with_province(2, Some("DAN"))
+            .build();
+        add_claim(&mut state, 1, "SWE", false);
+        add_claim(&mut state, 2, "SWE", true);
+        // Fabricating on top of a permanent claim doesn't downgrade it
+        add_claim(&mut state, 2, "SWE", false);
+        assert_eq!(claims_on(&state, "SWE", "DAN"), vec![1, 2]);
+        assert_eq!(
+            claim_factor(&state.provinces[&1], "SWE"),
+            Mod32::from_f32(0.75)
+        );
+        assert_eq!(claim_factor(&state.provinces[&1], "DAN"), Mod32::ONE);
+
+        state.date = state.date.add_years(TEMPORARY_CLAIM_YEARS);
+        run_claim_tick(&mut state);
+        assert_eq!(claims_on(&state, "SWE", "DAN"), vec![2]);
+        assert!(state.provinces[&2].claims["SWE"].is_permanent());
+    }
+
+    #[test]
+    fn test_claim_candidates_refresh_monthly_and_drop_stale_entries() {
+        let mut state = WorldStateBuilder::new()
+            .date(1444, 11, 11)
+            .with_country("SWE")
+            .with_country("DAN")
+            .with_province(1, Some("SWE"))
+            .with_province(2, Some("DAN"))
+            .with_province(3, Some("DAN"))
+            .with_province(4, Some("DAN"))
+            .build();
+        let mut adjacency = AdjacencyGraph::new();
+        adjacency.add_adjacency(1, 2);
+        adjacency.add_adjacency(1, 3);
+        adjacency.add_adjacency(3, 4);
+
+        // Before the first refresh candidates are computed on demand
+        assert_eq!(claim_candidates(&state, "SWE", &adjacency), vec![2, 3]);
+
+        refresh_claim_candidates(&mut state, Some(&adjacency));
+        assert_eq!(claim_candidates(&state, "SWE", &adjacency), vec![2, 3]);
+        assert_eq!(claim_candidates(&state, "DAN", &adjacency), vec![1]);
+
+        // Mid-month changes only remove candidates until the next refresh
+        add_claim(&mut state, 2, "SWE", false);
+        state.provinces.get_mut(&3).unwrap().owner = Some("SWE".to_string());
+        assert!(claim_candidates(&state, "SWE", &adjacency).is_empty());
+
+        refresh_claim_candidates(&mut state, Some(&adjacency));
+        assert_eq!(claim_candidates(&state, "SWE", &adjacency), vec![4]);
+    }
+
+    #[test]
+    fn test_fabrication_speed_follows_modifiers() {
+        let mut state = WorldStateBuilder::new().with_country("SWE").build();
+        assert_eq!(
+            fabrication_progress_per_month(&state, "SWE"),
+            Fixed::from_int(3)
+        );
+
+        state
+            .modifiers
+            .country_diplomatic_reputation
+            .insert("SWE".to_string(), Mod32::from_int(2));
+        state
+            .modifiers
+            .country_fabricate_claims_cost
+            .insert("SWE".to_string(), Mod32::from_f32(-0.25));
+        // (3 + 2/2) * 1.25
+        assert_eq!(
+            fabrication_progress_per_month(&state, "SWE"),
+            Fixed::from_int(5)
+        );
+    }
+}
@@ -1,6 +1,14 @@
+use crate::schedule::{Access, Resource, SystemDesc};
 use crate::state::WorldState;
 use tracing::instrument;
 
+/// Scheduler descriptor for [`run_colonization_tick`].
+pub const COLONIZATION_SYSTEM: SystemDesc = SystemDesc::world(
+    "colonization",
+    Access::new(&[], &[Resource::Colonies, Resource::Provinces]),
+    run_colonization_tick,
+);
+
 /// Monthly colonization tick.
 ///
 /// Progresses all active colonies.
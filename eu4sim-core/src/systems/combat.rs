@@ -1069,6 +1069,7 @@ mod tests {
             defender_score: 0,
             defender_battle_score: 0,
             pending_peace: None,
+            war_goal: None,
         };
         state.diplomacy.wars.insert(0, war);
 
@@ -1128,6 +1129,7 @@ mod tests {
             defender_score: 0,
             defender_battle_score: 0,
             pending_peace: None,
+            war_goal: None,
         };
         state.diplomacy.wars.insert(0, war);
 
@@ -1184,6 +1186,7 @@ mod tests {
             defender_score: 0,
             defender_battle_score: 0,
             pending_peace: None,
+            war_goal: None,
         };
         state.diplomacy.wars.insert(0, war);
 
@@ -4,13 +4,14 @@
 //! and removing the 75% autonomy floor for uncored provinces.
 //!
 //! ## EU4 Mechanics (Simplified)
-//! - **Cost**: 10 ADM per development point
+//! - **Cost**: 10 ADM per development point (-25% with a claim)
 //! - **Duration**: 36 months base
 //! - **Overextension**: 1% per development in uncored provinces
 //! - **Autonomy Floor**: 75% for uncored, 0% for cored
 
 use crate::fixed::Fixed;
 use crate::fixed_generic::Mod32;
+use crate::schedule::{run_system, Access, Resource, SystemDesc};
 use crate::state::{CoringProgress, Date, ProvinceId, ProvinceState, Tag, WorldState};
 use tracing::instrument;
 
@@ -92,7 +93,13 @@ pub fn start_coring(
         .get(&country)
         .copied()
         .unwrap_or(Mod32::ZERO);
-    let cost_factor = Mod32::ONE + core_creation_mod;
+    // Claimed provinces are cheaper to core
+    let claim_discount = if super::claims::has_claim(province, &country) {
+        super::claims::CLAIM_DISCOUNT
+    } else {
+        Mod32::ZERO
+    };
+    let cost_factor = Mod32::ONE + core_creation_mod - claim_discount;
     // Convert Mod32 factor to Fixed for multiplication with Fixed base_cost
     let cost = (base_cost * cost_factor.to_fixed()).max(Fixed::ONE); // Minimum cost of 1
 
@@ -137,59 +144,45 @@ pub fn start_coring(
 /// Advance coring progress for all provinces (called monthly in step_world).
 #[instrument(skip_all, name = "coring")]
 pub fn tick_coring(state: &mut WorldState) {
-    let mut completions = Vec::new();
-    let mut cancellations = Vec::new();
-    let mut progress_updates = Vec::new();
-
-    // First pass: identify completions, cancellations, and progress updates
-    for (&prov_id, province) in state.provinces.iter() {
-        if let Some(progress) = &province.coring_progress {
-            // Check owner still matches coring country
-            if province.owner.as_ref() == Some(&progress.coring_country) {
-                if progress.progress + 1 >= progress.required {
-                    completions.push((prov_id, progress.coring_country.clone()));
-                } else {
-                    // Needs progress increment
-                    progress_updates.push(prov_id);
-                }
-            } else {
-                // Lost province while coring - cancel
-                cancellations.push(prov_id);
-            }
-        }
-    }
+    run_system(state, &CORING_SYSTEM);
+}
 
-    // Apply progress increments
-    for prov_id in progress_updates {
-        if let Some(province) = state.provinces.get_mut(&prov_id) {
-            if let Some(ref mut progress) = province.coring_progress {
-                progress.progress += 1;
-            }
-        }
-    }
+/// Scheduler descriptor for [`tick_coring`]. Completed cores invalidate the
+/// owned-provinces cache in the finish step.
+pub const CORING_SYSTEM: SystemDesc = SystemDesc::per_province(
+    "coring",
+    Access::new(&[], &[Resource::Caches]),
+    progress_coring,
+    Some(|state, _completed| state.invalidate_owned_provinces_cache()),
+);
+
+/// Advance coring in one province. Returns `true` when a core completes.
+fn progress_coring(_state: &WorldState, prov_id: ProvinceId, province: &mut ProvinceState) -> bool {
+    let Some(progress) = province.coring_progress.as_mut() else {
+        return false;
+    };
 
-    // Cancel lost cores
-    for prov_id in cancellations {
-        if let Some(province) = state.provinces.get_mut(&prov_id) {
-            log::info!("Coring cancelled for province {} (owner changed)", prov_id);
-            province.coring_progress = None;
-        }
+    // Check owner still matches coring country
+    if province.owner.as_ref() != Some(&progress.coring_country) {
+        // Lost province while coring - cancel
+        log::info!("Coring cancelled for province {} (owner changed)", prov_id);
+        province.coring_progress = None;
+        return false;
     }
 
-    // Complete cores
-    let mut cache_invalidated = false;
-    for (prov_id, country) in completions {
-        if let Some(province) = state.provinces.get_mut(&prov_id) {
-            province.cores.insert(country.clone());
-            province.coring_progress = None;
-            log::info!("{} completed coring province {}", country, prov_id);
-            cache_invalidated = true;
-        }
+    if progress.progress + 1 < progress.required {
+        progress.progress += 1;
+        return false;
     }
 
-    if cache_invalidated {
-        state.invalidate_owned_provinces_cache();
-    }
+    // Complete core
+    let country = progress.coring_country.clone();
+    province.cores.insert(country.clone());
+    // The core supersedes any claim
+    province.claims.remove(&country);
+    province.coring_progress = None;
+    log::info!("{} completed coring province {}", country, prov_id);
+    true
 }
 
 /// Recalculate overextension for all countries.
@@ -274,6 +267,40 @@ mod tests {
         assert!(state.provinces.get(&1).unwrap().coring_progress.is_some());
     }
 
+    #[test]
+    fn test_claim_discounts_coring_and_is_replaced_by_core() {
+        let mut state = WorldStateBuilder::new()
+            .with_country("FRA")
+            .with_province(1, Some("FRA"))
+            .with_province(2, Some("FRA"))
+            .build();
+        for id in [1, 2] {
+            let province = state.provinces.get_mut(&id).unwrap();
+            province.cores.clear();
+            province.base_tax = Mod32::from_int(4);
+            province.base_production = Mod32::from_int(4);
+            province.base_manpower = Mod32::from_int(4);
+        }
+        crate::systems::claims::add_claim(&mut state, 2, "FRA", true);
+        state.countries.get_mut("FRA").unwrap().adm_mana = Fixed::from_int(1000);
+
+        let date = state.date;
+        start_coring(&mut state, "FRA".into(), 1, date).unwrap();
+        start_coring(&mut state, "FRA".into(), 2, date).unwrap();
+        // 120 ADM unclaimed, 90 claimed
+        assert_eq!(
+            state.countries.get("FRA").unwrap().adm_mana,
+            Fixed::from_int(790)
+        );
+
+        for _ in 0..BASE_CORING_TIME {
+            tick_coring(&mut state);
+        }
+        let province = state.provinces.get(&2).unwrap();
+        assert!(province.cores.contains("FRA"));
+        assert!(province.claims.is_empty());
+    }
+
     #[test]
     fn test_start_coring_insufficient_adm() {
         let mut state = WorldStateBuilder::new()
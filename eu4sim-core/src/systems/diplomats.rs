@@ -0,0 +1,504 @@
+//! Diplomats: a small per-country pool of envoys with travel time.
+//!
+//! Each country has `BASE_DIPLOMATS` plus its `diplomats` modifier. Sending a
+//! diplomat on a long-running task takes one out of the pool; they travel over
+//! the province graph from our capital to the target's capital and only start
+//! working on arrival. The diplomat returns to the pool when the task is done,
+//! when they are recalled, or when the mission becomes invalid (war, target gone).
+//!
+//! Short actions (alliance offers, insults, ...) still only use the
+//! one-per-day `last_diplomatic_action` limit.
+
+use crate::fixed::Fixed;
+use crate::state::{Date, DiplomatMission, DiplomatTask, ProvinceId, Tag, WorldState};
+use eu4data::adjacency::AdjacencyGraph;
+use tracing::instrument;
+
+/// Diplomats every country has before modifiers (EU4: 2).
+pub const BASE_DIPLOMATS: u8 = 2;
+
+/// Travel time per province crossed between the two capitals.
+const DAYS_PER_PROVINCE: u32 = 2;
+
+/// Travel time when no land route is known (overseas, or no graph loaded).
+const UNREACHABLE_TRAVEL_DAYS: u32 = 60;
+
+/// Aggressive expansion removed per month by a diplomat working on it,
+/// on top of the regular decay in `coalitions.rs`.
+const AE_REDUCTION_PER_MONTH: f32 = 0.5;
+
+/// Progress at which a fabricated claim is complete.
+const FABRICATION_GOAL: i64 = 100;
+
+/// Size of a country's diplomat pool.
+pub fn diplomats_total(state: &WorldState, tag: &str) -> u8 {
+    let bonus = state
+        .modifiers
+        .country_diplomats
+        .get(tag)
+        .map(|m| m.to_int())
+        .unwrap_or(0);
+    (BASE_DIPLOMATS as i32 + bonus).clamp(0, u8::MAX as i32) as u8
+}
+
+/// Diplomats at home, free to be sent out.
+pub fn diplomats_available(state: &WorldState, tag: &str) -> u8 {
+    let abroad = state
+        .countries
+        .get(tag)
+        .map(|c| c.diplomats.len())
+        .unwrap_or(0);
+    diplomats_total(state, tag).saturating_sub(abroad.min(u8::MAX as usize) as u8)
+}
+
+/// The country's capital, or its lowest-id province if none is marked.
+pub fn capital_of(state: &WorldState, tag: &str) -> Option<ProvinceId> {
+    let owned = || {
+        state
+            .provinces
+            .iter()
+            .filter(move |(_, p)| p.owner.as_deref() == Some(tag))
+    };
+    owned()
+        .filter(|(_, p)| p.is_capital)
+        .map(|(&id, _)| id)
+        .min()
+        .or_else(|| owned().map(|(&id, _)| id).min())
+}
+
+/// Days a diplomat from `from` needs to reach `to`, capital to capital.
+pub fn travel_days(
+    state: &WorldState,
+    adjacency: Option<&AdjacencyGraph>,
+    from: &str,
+    to: &str,
+) -> u32 {
+    let path = match (adjacency, capital_of(state, from), capital_of(state, to)) {
+        (Some(graph), Some(start), Some(end)) => graph.find_path(start, end),
+        _ => None,
+    };
+    match path {
+        // Path excludes the start province
+        Some(path) => (path.len() as u32 * DAYS_PER_PROVINCE).max(1),
+        None => UNREACHABLE_TRAVEL_DAYS,
+    }
+}
+
+/// Send one of `sender`'s diplomats to `target` to work on `task`.
+///
+/// Returns the arrival date. Sending a second diplomat on the same task is a
+/// no-op that returns the first one's arrival date.
+pub fn send_diplomat(
+    state: &mut WorldState,
+    adjacency: Option<&AdjacencyGraph>,
+    sender: &str,
+    target: &str,
+    task: DiplomatTask,
+) -> Result<Date, String> {
+    let country = state
+        .countries
+        .get(sender)
+        .ok_or_else(|| format!("Unknown country {}", sender))?;
+    if let Some(existing) = country
+        .diplomats
+        .iter()
+        .find(|m| m.target == target && m.task == task)
+    {
+        return Ok(existing.arrival_date);
+    }
+    if diplomats_available(state, sender) == 0 {
+        return Err(format!(
+            "All {} diplomats are busy",
+            diplomats_total(state, sender)
+        ));
+    }
+    if let DiplomatTask::FabricateClaim { province } = task {
+        let owner = state
+            .provinces
+            .get(&province)
+            .and_then(|p| p.owner.as_deref());
+        if owner != Some(target) {
+            return Err(format!("Province {} is not owned by {}", province, target));
+        }
+    }
+
+    let arrival_date = state
+        .date
+        .add_days(travel_days(state, adjacency, sender, target));
+    if let Some(country) = state.countries.get_mut(sender) {
+        country.diplomats.push(DiplomatMission {
+            target: target.to_string(),
+            task,
+            arrival_date,
+            progress: Fixed::ZERO,
+        });
+    }
+    Ok(arrival_date)
+}
+
+/// Bring every diplomat `sender` has in `target` home. Returns how many.
+pub fn recall_diplomats(state: &mut WorldState, sender: &str, target: &str) -> usize {
+    let Some(country) = state.countries.get_mut(sender) else {
+        return 0;
+    };
+    let before = country.diplomats.len();
+    country.diplomats.retain(|m| m.target != target);
+    before - country.diplomats.len()
+}
+
+/// Is a diplomat from `sender` currently working on `task` in `target`?
+pub fn is_working(state: &WorldState, sender: &str, target: &str, task: DiplomatTask) -> bool {
+    state.countries.get(sender).is_some_and(|c| {
+        c.diplomats
+            .iter()
+            .any(|m| m.target == target && m.task == task && state.date >= m.arrival_date)
+    })
+}
+
+/// Run monthly diplomat tick: drop invalid missions and advance the tasks of
+/// diplomats that have arrived.
+///
+/// Call this BEFORE the opinion tick so improved relations aren't decayed.
+#[instrument(skip_all, name = "diplomats")]
+pub fn run_diplomat_tick(state: &mut WorldState) {
+    let mut senders: Vec<Tag> = state
+        .countries
+        .iter()
+        .filter(|(_, c)| !c.diplomats.is_empty())
+        .map(|(tag, _)| tag.clone())
+        .collect();
+    senders.sort();
+
+    for sender in senders {
+        let missions = state.countries[&sender].diplomats.clone();
+        let mut remaining = Vec::with_capacity(missions.len());
+        for mut mission in missions {
+            if !mission_is_valid(state, &sender, &mission) {
+                log::debug!(
+                    "{}'s diplomat in {} returns home ({:?} no longer possible)",
+                    sender,
+                    mission.target,
+                    mission.task
+                );
+                continue;
+            }
+            if state.date < mission.arrival_date {
+                remaining.push(mission);
+                continue;
+            }
+
+            let finished = match mission.task {
+                DiplomatTask::ImproveRelations => {
+                    super::opinion::improve_relations(state, &sender, &mission.target)
+                }
+                DiplomatTask::ReduceAggressiveExpansion => {
+                    reduce_aggressive_expansion(state, &sender, &mission.target)
+                }
+                DiplomatTask::FabricateClaim { province } => {
+                    mission.progress +=
+                        super::claims::fabrication_progress_per_month(state, &sender);
+                    let done = mission.progress >= Fixed::from_int(FABRICATION_GOAL);
+                    if done {
+                        super::claims::add_claim(state, province, &sender, false);
+                        log::info!(
+                            "{} fabricated a claim on province {} ({})",
+                            sender,
+                            province,
+                            mission.target
+                        );
+                    }
+                    done
+                }
+            };
+            if finished {
+                log::debug!(
+                    "{}'s diplomat finished {:?} in {}",
+                    sender,
+                    mission.task,
+                    mission.target
+                );
+            } else {
+                remaining.push(mission);
+            }
+        }
+        if let Some(country) = state.countries.get_mut(&sender) {
+            country.diplomats = remaining;
+        }
+    }
+}
+
+/// Missions end when either side is gone or they went to war.
+fn mission_is_valid(state: &WorldState, sender: &str, mission: &DiplomatMission) -> bool {
+    if !state.countries.contains_key(&mission.target)
+        || state.diplomacy.are_at_war(sender, &mission.target)
+    {
+        return false;
+    }
+    match mission.task {
+        DiplomatTask::FabricateClaim { province } => {
+            state
+                .provinces
+                .get(&province)
+                .and_then(|p| p.owner.as_deref())
+                == Some(mission.target.as_str())
+        }
+        DiplomatTask::ReduceAggressiveExpansion => {
+            current_ae(state, &mission.target, sender) > Fixed::ZERO
+        }
+        DiplomatTask::ImproveRelations => true,
+    }
+}
+
+fn current_ae(state: &WorldState, holder: &str, towards: &str) -> Fixed {
+    state
+        .countries
+        .get(holder)
+        .and_then(|c| c.aggressive_expansion.get(towards))
+        .copied()
+        .unwrap_or(Fixed::ZERO)
+}
+
+/// Lower `target`'s AE towards `sender`. Returns true once it's gone.
+fn reduce_aggressive_expansion(state: &mut WorldState, sender: &str, target: &str) -> bool {
+    let remaining = (current_ae(state, target, sender) - Fixed::from_f32(AE_REDUCTION_PER_MONTH))
+        .max(Fixed::ZERO);
+    let Some(country) = state.countries.get_mut(target) else {
+        return true;
+    };
+    if remaining > Fixed::ZERO {
+        country
+            .aggressive_expansion
+            .insert(sender.to_string(), remaining);
+        false
+    } else {
+        country.aggressive_expansion.remove(sender);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed_generic::Mod32;
+    use crate::testing::WorldStateBuilder;
+
+    /// SWE capital at 1, DAN capital at 4, three provinces apart.
+    fn setup() -> (WorldState, AdjacencyGraph) {
+        let mut state = WorldStateBuilder::new()
+            .date(1444, 11, 11)
+            .with_country("SWE")
+            .with_country("DAN")
+            .with_province(1, Some("SWE"))
+            .with_province(2, Some("SWE"))
+            .with_province(3, Some("DAN"))
+            .with_province(4, Some("DAN"))
+            .build();
+        state.provinces.get_mut(&1).unwrap().is_capital = true;
+        state.provinces.get_mut(&4).unwrap().is_capital = true;
+
+        let mut graph = AdjacencyGraph::new();
+        graph.add_adjacency(1, 2);
+        graph.add_adjacency(2, 3);
+        graph.add_adjacency(3, 4);
+        (state, graph)
+    }
+
+    #[test]
+    fn test_pool_size_includes_modifiers() {
+        let (mut state, _) = setup();
+        assert_eq!(diplomats_total(&state, "SWE"), BASE_DIPLOMATS);
+        state
+            .modifiers
+            .country_diplomats
+            .insert("SWE".to_string(), Mod32::from_int(1));
+        assert_eq!(diplomats_total(&state, "SWE"), BASE_DIPLOMATS + 1);
+        assert_eq!(diplomats_available(&state, "SWE"), BASE_DIPLOMATS + 1);
+    }
+
+    #[test]
+    fn test_travel_time_follows_adjacency_graph() {
+        let (state, graph) = setup();
+        assert_eq!(capital_of(&state, "DAN"), Some(4));
+        assert_eq!(
+            travel_days(&state, Some(&graph), "SWE", "DAN"),
+            3 * DAYS_PER_PROVINCE
+        );
+        assert_eq!(
+            travel_days(&state, None, "SWE", "DAN"),
+            UNREACHABLE_TRAVEL_DAYS
+        );
+    }
+
+    #[test]
+    fn test_diplomats_are_limited_and_work_after_arrival() {
+        let (mut state, graph) = setup();
+        state
+            .countries
+            .insert("LUB".to_string(), Default::default());
+        state
+            .countries
+            .insert("NOR".to_string(), Default::default());
+
+        let arrival = send_diplomat(
+            &mut state,
+            Some(&graph),
+            "SWE",
+            "DAN",
+            DiplomatTask::ImproveRelations,
+        )
+        .unwrap();
+        assert_eq!(arrival, state.date.add_days(6));
+        // Same task again is a no-op
+        send_diplomat(
+            &mut state,
+            Some(&graph),
+            "SWE",
+            "DAN",
+            DiplomatTask::ImproveRelations,
+        )
+        .unwrap();
+        send_diplomat(
+            &mut state,
+            Some(&graph),
+            "SWE",
+            "LUB",
+            DiplomatTask::ImproveRelations,
+        )
+        .unwrap();
+        assert_eq!(diplomats_available(&state, "SWE"), 0);
+        assert!(send_diplomat(
+            &mut state,
+            Some(&graph),
+            "SWE",
+            "NOR",
+            DiplomatTask::ImproveRelations
+        )
+        .is_err());
+
+        // Still travelling: no effect yet
+        run_diplomat_tick(&mut state);
+        assert!(!is_working(
+            &state,
+            "SWE",
+            "DAN",
+            DiplomatTask::ImproveRelations
+        ));
+        assert_eq!(crate::systems::opinion(&state, "DAN", "SWE"), Fixed::ZERO);
+
+        state.date = arrival;
+        run_diplomat_tick(&mut state);
+        assert!(is_working(
+            &state,
+            "SWE",
+            "DAN",
+            DiplomatTask::ImproveRelations
+        ));
+        assert_eq!(
+            crate::systems::opinion(&state, "DAN", "SWE"),
+            Fixed::from_int(3)
+        );
+
+        // Recalling frees the diplomat
+        assert_eq!(recall_diplomats(&mut state, "SWE", "LUB"), 1);
+        assert_eq!(diplomats_available(&state, "SWE"), 1);
+    }
+
+    #[test]
+    fn test_ae_reduction_and_claim_fabrication_finish() {
+        let (mut state, graph) = setup();
+        state
+            .countries
+            .get_mut("DAN")
+            .unwrap()
+            .aggressive_expansion
+            .insert("SWE".to_string(), Fixed::ONE);
+
+        send_diplomat(
+            &mut state,
+            Some(&graph),
+            "SWE",
+            "DAN",
+            DiplomatTask::ReduceAggressiveExpansion,
+        )
+        .unwrap();
+        send_diplomat(
+            &mut state,
+            Some(&graph),
+            "SWE",
+            "DAN",
+            DiplomatTask::FabricateClaim { province: 3 },
+        )
+        .unwrap();
+        // Can't fabricate on a province the target doesn't own
+        state.countries.get_mut("SWE").unwrap().diplomats.pop();
+        assert!(send_diplomat(
+            &mut state,
+            Some(&graph),
+            "SWE",
+            "DAN",
+            DiplomatTask::FabricateClaim { province: 2 },
+        )
+        .is_err());
+        send_diplomat(
+            &mut state,
+            Some(&graph),
+            "SWE",
+            "DAN",
+            DiplomatTask::FabricateClaim { province: 3 },
+        )
+        .unwrap();
+
+        state.date = state.date.add_days(30);
+        run_diplomat_tick(&mut state);
+        run_diplomat_tick(&mut state);
+        // 1.0 AE gone after two months, that diplomat is home again
+        assert!(state.countries["DAN"].aggressive_expansion.is_empty());
+        assert_eq!(state.countries["SWE"].diplomats.len(), 1);
+
+        // 3 progress a month: done in the 34th month
+        for _ in 2..33 {
+            run_diplomat_tick(&mut state);
+        }
+        assert!(!crate::systems::claims::has_claim(
+            &state.provinces[&3],
+            "SWE"
+        ));
+        run_diplomat_tick(&mut state);
+        assert!(state.countries["SWE"].diplomats.is_empty());
+        assert!(crate::systems::claims::has_claim(
+            &state.provinces[&3],
+            "SWE"
+        ));
+    }
+
+    #[test]
+    fn test_war_ends_missions() {
+        let (mut state, graph) = setup();
+        send_diplomat(
+            &mut state,
+            Some(&graph),
+            "SWE",
+            "DAN",
+            DiplomatTask::ImproveRelations,
+        )
+        .unwrap();
+        state.diplomacy.wars.insert(
+            0,
+            crate::state::War {
+                id: 0,
+                name: "Test War".to_string(),
+                attackers: vec!["SWE".to_string()],
+                defenders: vec!["DAN".to_string()],
+                start_date: state.date,
+                attacker_score: 0,
+                attacker_battle_score: 0,
+                defender_score: 0,
+                defender_battle_score: 0,
+                pending_peace: None,
+                war_goal: None,
+            },
+        );
+        run_diplomat_tick(&mut state);
+        assert!(state.countries["SWE"].diplomats.is_empty());
+    }
+}
@@ -4,18 +4,23 @@
 
 use crate::estates::{EstateRegistry, EstateState, EstateTypeId, PrivilegeId};
 use crate::fixed::Fixed;
+use crate::schedule::{run_system, Access, Resource, SystemDesc};
 use crate::state::{CountryState, WorldState};
 
 /// Run monthly estate updates for all countries.
 ///
 /// This should be called once per month (when `date.day == 1`).
 pub fn run_estate_tick(state: &mut WorldState) {
-    let registry = &state.estates;
-    for (_tag, country) in state.countries.iter_mut() {
-        update_country_estates(country, registry);
-    }
+    run_system(state, &ESTATE_SYSTEM);
 }
 
+/// Scheduler descriptor for [`run_estate_tick`]. Reads the estate registry.
+pub const ESTATE_SYSTEM: SystemDesc = SystemDesc::per_country(
+    "estates",
+    Access::new(&[Resource::Registries], &[]),
+    |state, _tag, country| update_country_estates(country, &state.estates),
+);
+
 /// Update all estates for a single country.
 fn update_country_estates(country: &mut CountryState, registry: &EstateRegistry) {
     for &estate_id in &country.estates.available_estates {
@@ -14,6 +14,7 @@
 //! ```
 
 use crate::fixed::Fixed;
+use crate::schedule::{Access, Resource, SystemDesc};
 use crate::state::{ReformId, WorldState};
 use tracing::instrument;
 
@@ -105,6 +106,22 @@ impl crate::state::HREState {
     }
 }
 
+/// Scheduler descriptor for [`run_hre_tick`]. Elections and IA read member
+/// countries, their provinces and subject relations.
+pub const HRE_SYSTEM: SystemDesc = SystemDesc::world(
+    "hre",
+    Access::new(
+        &[
+            Resource::Date,
+            Resource::Countries,
+            Resource::Provinces,
+            Resource::Diplomacy,
+        ],
+        &[Resource::Global],
+    ),
+    run_hre_tick,
+);
+
 /// Run the HRE system (called monthly).
 ///
 /// Updates Imperial Authority and checks for elections:
@@ -330,13 +347,9 @@ fn calculate_vote_score(state: &WorldState, elector: &str, candidate: &str) -> i
         }
     }
 
-    // Check royal marriage (need to check both since relations map stores one type per pair)
-    // In EU4, a pair can have both alliance and RM, but our model stores one type
-    // For now, check if there's any RM relation
-    if let Some(rel) = state.diplomacy.relations.get(&pair) {
-        if *rel == crate::state::RelationType::RoyalMarriage {
-            score += election_defines::VOTE_ROYAL_MARRIAGE;
-        }
+    // Check royal marriage (can coexist with the alliance)
+    if state.diplomacy.royal_marriages.contains(&pair) {
+        score += election_defines::VOTE_ROYAL_MARRIAGE;
     }
 
     score
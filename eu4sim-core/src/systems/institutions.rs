@@ -1,7 +1,7 @@
 use crate::fixed_generic::Mod32;
-use crate::state::{InstitutionId, Tag, WorldState};
+use crate::schedule::{run_system, Access, Resource, SystemDesc};
+use crate::state::{InstitutionId, ProvinceId, ProvinceState, Tag, WorldState};
 use anyhow::{anyhow, Result};
-use std::collections::HashSet;
 use tracing::instrument;
 
 /// Executes the monthly institution spread tick.
@@ -11,52 +11,59 @@ use tracing::instrument;
 /// 2. If a province has 100% presence, it becomes "present" in that province.
 #[instrument(skip_all, name = "institutions")]
 pub fn tick_institution_spread(state: &mut WorldState) {
+    run_system(state, &INSTITUTION_SYSTEM);
+}
+
+/// Scheduler descriptor for [`tick_institution_spread`]. Spread only depends
+/// on the province itself and the date.
+pub const INSTITUTION_SYSTEM: SystemDesc = SystemDesc::per_province(
+    "institutions",
+    Access::new(&[Resource::Date], &[]),
+    spread_in_province,
+    None,
+);
+
+fn spread_in_province(
+    state: &WorldState,
+    province_id: ProvinceId,
+    province: &mut ProvinceState,
+) -> bool {
     // For the mid-term goal, we'll implement a very simple spread mechanism.
     // In a real EU4 simulation, this would depend on many factors (trade, adjacency, etc.)
 
-    let mut presence_updates: Vec<(u32, InstitutionId, f32)> = Vec::new();
+    let mut presence_updates: Vec<(InstitutionId, f32)> = Vec::new();
 
-    // Collect all provinces where institutions have already reached 100% and the owner has embraced it.
-    let mut embraced_by_tag: HashSet<(Tag, InstitutionId)> = HashSet::new();
-    for (tag, country) in &state.countries {
-        for inst in &country.embraced_institutions {
-            embraced_by_tag.insert((tag.clone(), inst.clone()));
-        }
-    }
+    let total_dev =
+        (province.base_tax + province.base_production + province.base_manpower).to_f32();
 
-    for (&province_id, province) in &state.provinces {
-        let total_dev =
-            (province.base_tax + province.base_production + province.base_manpower).to_f32();
-
-        // Spread chance: 1% per 100 dev per month (very slow)
-        // Simplified: if neighbor has embraced, grow by dev/1000 per month.
-        let spread_rate = (total_dev / 1000.0).max(0.1); // minimum 0.1% per month
-
-        // We'll hardcode some starting institutions if they don't exist yet
-        // In a real sim, these would fire via events.
-        if state.date.year >= 1450 && !province.institution_presence.contains_key("renaissance") {
-            // Renaissance origin simulation (e.g. province 1 is Florence in a real map)
-            if province_id == 112 {
-                // Randomly picked "origin" for now
-                presence_updates.push((province_id, "renaissance".to_string(), spread_rate * 10.0));
-            }
-        }
+    // Spread chance: 1% per 100 dev per month (very slow)
+    // Simplified: if neighbor has embraced, grow by dev/1000 per month.
+    let spread_rate = (total_dev / 1000.0).max(0.1); // minimum 0.1% per month
 
-        // Logic for spreading from embraced neighbors would go here if we had an adjacency graph in the state.
-        // For now, let's just make it grow linearly in high-dev provinces to simulate "innovation".
-        for (inst, presence) in &province.institution_presence {
-            if *presence < 100.0 {
-                presence_updates.push((province_id, inst.clone(), spread_rate));
-            }
+    // We'll hardcode some starting institutions if they don't exist yet
+    // In a real sim, these would fire via events.
+    if state.date.year >= 1450 && !province.institution_presence.contains_key("renaissance") {
+        // Renaissance origin simulation (e.g. province 1 is Florence in a real map)
+        if province_id == 112 {
+            // Randomly picked "origin" for now
+            presence_updates.push(("renaissance".to_string(), spread_rate * 10.0));
         }
     }
 
-    for (pid, inst, delta) in presence_updates {
-        if let Some(province) = state.provinces.get_mut(&pid) {
-            let entry = province.institution_presence.entry(inst).or_insert(0.0);
-            *entry = (*entry + delta).min(100.0);
+    // Logic for spreading from embraced neighbors would go here if we had an adjacency graph in the state.
+    // For now, let's just make it grow linearly in high-dev provinces to simulate "innovation".
+    for (inst, presence) in &province.institution_presence {
+        if *presence < 100.0 {
+            presence_updates.push((inst.clone(), spread_rate));
         }
     }
+
+    for (inst, delta) in presence_updates {
+        let entry = province.institution_presence.entry(inst).or_insert(0.0);
+        *entry = (*entry + delta).min(100.0);
+    }
+
+    false
 }
 
 /// Executes the EmbraceInstitution command.
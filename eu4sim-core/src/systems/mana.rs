@@ -1,5 +1,6 @@
 use crate::fixed::Fixed;
-use crate::state::{Advisor, AdvisorType, WorldState};
+use crate::schedule::{run_system, Access, SystemDesc};
+use crate::state::{Advisor, AdvisorType, CountryState, WorldState};
 use tracing::instrument;
 
 /// Generates monarch power for all countries based on ruler stats and advisors.
@@ -13,40 +14,43 @@ use tracing::instrument;
 /// Power is capped at 999 by default (can be higher with unembraced institutions).
 #[instrument(skip_all, name = "mana")]
 pub fn run_mana_tick(state: &mut WorldState) {
+    run_system(state, &MANA_SYSTEM);
+}
+
+/// Scheduler descriptor for [`run_mana_tick`]. Only touches the country itself.
+pub const MANA_SYSTEM: SystemDesc =
+    SystemDesc::per_country("mana", Access::new(&[], &[]), update_country_mana);
+
+fn update_country_mana(_state: &WorldState, tag: &str, country: &mut CountryState) {
     // Default cap is 999, but increases with unembraced institutions
     // TODO: Calculate dynamic cap based on tech penalty from institutions
     const MAX_MANA: Fixed = Fixed::from_int(999);
     const BASE_GAIN: i64 = 3;
 
-    let country_tags: Vec<String> = state.countries.keys().cloned().collect();
-    for tag in country_tags {
-        if let Some(country) = state.countries.get_mut(&tag) {
-            // Sum advisor skill levels by type (skill level = mana contribution)
-            let (adm_skill, dip_skill, mil_skill) = sum_advisor_skills(&country.advisors);
-
-            let adm_gain = Fixed::from_int(BASE_GAIN + country.ruler_adm as i64 + adm_skill);
-            let dip_gain = Fixed::from_int(BASE_GAIN + country.ruler_dip as i64 + dip_skill);
-            let mil_gain = Fixed::from_int(BASE_GAIN + country.ruler_mil as i64 + mil_skill);
-
-            country.adm_mana = (country.adm_mana + adm_gain).min(MAX_MANA);
-            country.dip_mana = (country.dip_mana + dip_gain).min(MAX_MANA);
-            country.mil_mana = (country.mil_mana + mil_gain).min(MAX_MANA);
-
-            log::trace!(
-                "Mana tick for {}: +{}/+{}/+{} (base 3 + ruler {}/{}/{} + advisor {}/{}/{})",
-                tag,
-                adm_gain,
-                dip_gain,
-                mil_gain,
-                country.ruler_adm,
-                country.ruler_dip,
-                country.ruler_mil,
-                adm_skill,
-                dip_skill,
-                mil_skill
-            );
-        }
-    }
+    // Sum advisor skill levels by type (skill level = mana contribution)
+    let (adm_skill, dip_skill, mil_skill) = sum_advisor_skills(&country.advisors);
+
+    let adm_gain = Fixed::from_int(BASE_GAIN + country.ruler_adm as i64 + adm_skill);
+    let dip_gain = Fixed::from_int(BASE_GAIN + country.ruler_dip as i64 + dip_skill);
+    let mil_gain = Fixed::from_int(BASE_GAIN + country.ruler_mil as i64 + mil_skill);
+
+    country.adm_mana = (country.adm_mana + adm_gain).min(MAX_MANA);
+    country.dip_mana = (country.dip_mana + dip_gain).min(MAX_MANA);
+    country.mil_mana = (country.mil_mana + mil_gain).min(MAX_MANA);
+
+    log::trace!(
+        "Mana tick for {}: +{}/+{}/+{} (base 3 + ruler {}/{}/{} + advisor {}/{}/{})",
+        tag,
+        adm_gain,
+        dip_gain,
+        mil_gain,
+        country.ruler_adm,
+        country.ruler_dip,
+        country.ruler_mil,
+        adm_skill,
+        dip_skill,
+        mil_skill
+    );
 }
 
 /// Returns (adm_skill, dip_skill, mil_skill) - sum of skill levels by advisor type.
@@ -5,11 +5,13 @@ pub mod alliance;
 pub mod attrition;
 pub mod buildings;
 pub mod celestial;
+pub mod claims;
 pub mod coalitions;
 pub mod colonization;
 pub mod combat;
 pub mod coring;
 pub mod development;
+pub mod diplomats;
 pub mod estates;
 pub mod expenses;
 pub mod force_limits;
@@ -20,6 +22,7 @@ pub mod mana;
 pub mod manpower;
 pub mod movement;
 pub mod naval_combat;
+pub mod opinion;
 pub mod policies;
 pub mod production;
 pub mod reformation;
@@ -49,6 +52,7 @@ pub use celestial::{
     calculate_advisor_cost_modifier, calculate_corruption_reduction, run_celestial_tick,
     run_meritocracy_tick,
 };
+pub use claims::{add_claim, refresh_claim_candidates, run_claim_tick};
 pub use coalitions::run_coalition_tick;
 pub use colonization::run_colonization_tick;
 pub use combat::run_combat_tick;
@@ -56,6 +60,9 @@ pub use coring::{
     calculate_coring_cost, effective_autonomy, recalculate_overextension, start_coring, tick_coring,
 };
 pub use development::develop_province;
+pub use diplomats::{
+    diplomats_available, diplomats_total, recall_diplomats, run_diplomat_tick, send_diplomat,
+};
 pub use estates::{
     grant_privilege, revoke_privilege, run_estate_tick, sale_land, seize_land, CrownLandError,
     PrivilegeError,
@@ -76,6 +83,10 @@ pub use mana::run_mana_tick;
 pub use manpower::run_manpower_tick;
 pub use movement::run_movement_tick;
 pub use naval_combat::run_naval_combat_tick;
+pub use opinion::{
+    add_opinion_modifier, alliance_acceptance, military_access_acceptance, opinion,
+    royal_marriage_acceptance, run_opinion_tick,
+};
 pub use policies::{
     apply_policy_modifiers, calculate_policy_slots, can_enable_policy, disable_policy,
     enable_policy, PolicyCategory, PolicyDef, PolicyError, PolicyId, PolicyRegistry,
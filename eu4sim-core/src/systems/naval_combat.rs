@@ -593,6 +593,7 @@ mod tests {
             defender_score: 0,
             defender_battle_score: 0,
             pending_peace: None,
+            war_goal: None,
         };
         state.diplomacy.wars.insert(1, war);
 
@@ -618,6 +619,7 @@ mod tests {
                 institution_presence: Default::default(),
                 trade: Default::default(),
                 cores: Default::default(),
+                claims: Default::default(),
                 coring_progress: None,
                 buildings: Default::default(),
                 building_construction: None,
@@ -732,6 +734,7 @@ mod tests {
             defender_score: 0,
             defender_battle_score: 0,
             pending_peace: None,
+            war_goal: None,
         };
         state.diplomacy.wars.insert(1, war);
 
@@ -757,6 +760,7 @@ mod tests {
                 institution_presence: Default::default(),
                 trade: Default::default(),
                 cores: Default::default(),
+                claims: Default::default(),
                 coring_progress: None,
                 buildings: Default::default(),
                 building_construction: None,
@@ -831,6 +835,7 @@ mod tests {
                 institution_presence: Default::default(),
                 trade: Default::default(),
                 cores: Default::default(),
+                claims: Default::default(),
                 coring_progress: None,
                 buildings: Default::default(),
                 building_construction: None,
@@ -0,0 +1,444 @@
+//! Opinion between countries and AI acceptance of diplomatic offers.
+//!
+//! Opinion is directional (how `holder` feels about `towards`) and is the sum of:
+//! - stored modifiers that decay monthly (insults, war declarations, improved relations)
+//! - live terms derived from state (royal marriage, aggressive expansion)
+//!
+//! Clamped to ±200 as in EU4. The acceptance functions turn opinion, trust and
+//! rivalries into a score: positive means the AI would accept.
+
+use super::diplomats::is_working;
+use crate::fixed::Fixed;
+use crate::state::{DiplomatTask, OpinionModifier, OpinionModifierKind, Tag, WorldState};
+use std::collections::BTreeMap;
+use tracing::instrument;
+
+/// Opinion bounds.
+const MAX_OPINION: i64 = 200;
+
+/// Opinion from an active royal marriage (both directions).
+const ROYAL_MARRIAGE_OPINION: i64 = 25;
+
+/// Opinion gained per month while a diplomat improves relations.
+const IMPROVE_RELATIONS_PER_MONTH: i64 = 3;
+
+/// Improved relations stop growing here; the diplomat then returns home.
+const IMPROVE_RELATIONS_CAP: i64 = 100;
+
+/// Opinion levels that are logged when crossed (see `GameEvent::OpinionThresholdCrossed`).
+pub const OPINION_THRESHOLDS: [i32; 4] = [-100, -50, 50, 100];
+
+/// Acceptance score meaning "never" (rivals, enemies).
+const REFUSE: i32 = -1000;
+
+impl OpinionModifierKind {
+    /// Value when the modifier is applied.
+    fn initial_value(self) -> Fixed {
+        match self {
+            OpinionModifierKind::Insulted => Fixed::from_int(-50),
+            OpinionModifierKind::DeclaredWarOnUs => Fixed::from_int(-100),
+            OpinionModifierKind::ImprovedRelations => Fixed::from_int(IMPROVE_RELATIONS_PER_MONTH),
+        }
+    }
+
+    /// How far the value moves towards zero each month.
+    fn decay_per_month(self) -> Fixed {
+        match self {
+            OpinionModifierKind::Insulted => Fixed::ONE,
+            OpinionModifierKind::DeclaredWarOnUs => Fixed::ONE,
+            OpinionModifierKind::ImprovedRelations => Fixed::ONE,
+        }
+    }
+}
+
+/// How `holder` feels about `towards`.
+pub fn opinion(state: &WorldState, holder: &str, towards: &str) -> Fixed {
+    let mut total = state
+        .diplomacy
+        .opinion_modifiers
+        .get(&(holder.to_string(), towards.to_string()))
+        .map(|mods| mods.iter().fold(Fixed::ZERO, |acc, m| acc + m.value))
+        .unwrap_or(Fixed::ZERO);
+
+    if state.diplomacy.has_royal_marriage(holder, towards) {
+        total += Fixed::from_int(ROYAL_MARRIAGE_OPINION);
+    }
+
+    if let Some(ae) = state
+        .countries
+        .get(holder)
+        .and_then(|c| c.aggressive_expansion.get(towards))
+    {
+        total -= *ae;
+    }
+
+    total.clamp(Fixed::from_int(-MAX_OPINION), Fixed::from_int(MAX_OPINION))
+}
+
+/// Every directed pair whose opinion may differ from zero, with its opinion.
+pub fn tracked_opinions(state: &WorldState) -> BTreeMap<(Tag, Tag), Fixed> {
+    let mut pairs: Vec<(Tag, Tag)> = state.diplomacy.opinion_modifiers.keys().cloned().collect();
+    for (a, b) in &state.diplomacy.royal_marriages {
+        pairs.push((a.clone(), b.clone()));
+        pairs.push((b.clone(), a.clone()));
+    }
+    for (holder, country) in &state.countries {
+        pairs.extend(
+            country
+                .aggressive_expansion
+                .keys()
+                .map(|towards| (holder.clone(), towards.clone())),
+        );
+    }
+    pairs
+        .into_iter()
+        .map(|(holder, towards)| {
+            let value = opinion(state, &holder, &towards);
+            ((holder, towards), value)
+        })
+        .collect()
+}
+
+/// Signed threshold level of `opinion`: +n at or above the n-th positive
+/// threshold, -n at or below the n-th negative one, 0 in between.
+pub fn opinion_level(opinion: Fixed) -> i32 {
+    OPINION_THRESHOLDS
+        .iter()
+        .map(|&t| match t.signum() {
+            -1 if opinion <= Fixed::from_int(t as i64) => -1,
+            1 if opinion >= Fixed::from_int(t as i64) => 1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Outermost threshold passed when opinion moves from level `before` to
+/// `after` (the highest one when rising, the lowest when falling).
+pub fn crossed_threshold(before: i32, after: i32) -> Option<i32> {
+    let negatives = OPINION_THRESHOLDS.iter().filter(|&&t| t < 0).count() as i32;
+    let at_level = |level: i32| {
+        let index = if level < 0 {
+            negatives + level
+        } else {
+            negatives + level - 1
+        };
+        OPINION_THRESHOLDS[index as usize]
+    };
+    match after.cmp(&before) {
+        std::cmp::Ordering::Equal => None,
+        std::cmp::Ordering::Greater if after > 0 => Some(at_level(after)),
+        std::cmp::Ordering::Greater => Some(at_level(after - 1)),
+        std::cmp::Ordering::Less if after < 0 => Some(at_level(after)),
+        std::cmp::Ordering::Less => Some(at_level(after + 1)),
+    }
+}
+
+/// Apply a fresh modifier of `kind` to `holder`'s opinion of `towards`.
+///
+/// Reapplying a kind refreshes it to its initial value rather than stacking.
+pub fn add_opinion_modifier(
+    state: &mut WorldState,
+    holder: &str,
+    towards: &str,
+    kind: OpinionModifierKind,
+) {
+    let key = (holder.to_string(), towards.to_string());
+    let mut mods = state
+        .diplomacy
+        .opinion_modifiers
+        .get(&key)
+        .cloned()
+        .unwrap_or_default();
+    mods.retain(|m| m.kind != kind);
+    mods.push(OpinionModifier {
+        kind,
+        value: kind.initial_value(),
+    });
+    state.diplomacy.opinion_modifiers.insert(key, mods);
+}
+
+/// One month of a diplomat from `sender` improving relations with `target`.
+///
+/// Returns true once the modifier reaches its cap and the diplomat is done.
+pub(crate) fn improve_relations(state: &mut WorldState, sender: &str, target: &str) -> bool {
+    // The target's opinion of the sender improves
+    let key = (target.to_string(), sender.to_string());
+    let mut mods = state
+        .diplomacy
+        .opinion_modifiers
+        .get(&key)
+        .cloned()
+        .unwrap_or_default();
+    let cap = Fixed::from_int(IMPROVE_RELATIONS_CAP);
+    let value = match mods
+        .iter_mut()
+        .find(|m| m.kind == OpinionModifierKind::ImprovedRelations)
+    {
+        Some(m) => {
+            m.value = (m.value + Fixed::from_int(IMPROVE_RELATIONS_PER_MONTH)).min(cap);
+            m.value
+        }
+        None => {
+            let kind = OpinionModifierKind::ImprovedRelations;
+            mods.push(OpinionModifier {
+                kind,
+                value: kind.initial_value(),
+            });
+            kind.initial_value()
+        }
+    };
+    state.diplomacy.opinion_modifiers.insert(key, mods);
+    value >= cap
+}
+
+/// Run monthly opinion tick: decay modifiers.
+///
+/// Improved relations don't decay while a diplomat is still working on them
+/// (see [`super::diplomats`]).
+#[instrument(skip_all, name = "opinion")]
+pub fn run_opinion_tick(state: &mut WorldState) {
+    // Decay everything except modifiers a diplomat is actively growing
+    let keys: Vec<(Tag, Tag)> = state.diplomacy.opinion_modifiers.keys().cloned().collect();
+    for key in keys {
+        let (holder, towards) = &key;
+        let being_improved = is_working(state, towards, holder, DiplomatTask::ImproveRelations);
+        let Some(mut mods) = state.diplomacy.opinion_modifiers.get(&key).cloned() else {
+            continue;
+        };
+        for m in mods.iter_mut() {
+            if being_improved && m.kind == OpinionModifierKind::ImprovedRelations {
+                continue;
+            }
+            let decay = m.kind.decay_per_month();
+            m.value = if m.value > Fixed::ZERO {
+                (m.value - decay).max(Fixed::ZERO)
+            } else {
+                (m.value + decay).min(Fixed::ZERO)
+            };
+        }
+        mods.retain(|m| m.value != Fixed::ZERO);
+        if mods.is_empty() {
+            state.diplomacy.opinion_modifiers.remove(&key);
+        } else {
+            state.diplomacy.opinion_modifiers.insert(key, mods);
+        }
+    }
+}
+
+/// Terms every acceptance shares: opinion, trust, rivalry and war.
+fn base_acceptance(state: &WorldState, evaluator: &str, proposer: &str) -> Option<i32> {
+    let is_rival = |a: &str, b: &str| state.countries.get(a).is_some_and(|c| c.rivals.contains(b));
+    if is_rival(evaluator, proposer)
+        || is_rival(proposer, evaluator)
+        || state.diplomacy.are_at_war(evaluator, proposer)
+    {
+        return None;
+    }
+    let opinion = opinion(state, evaluator, proposer).to_int() as i32;
+    let trust = state.diplomacy.get_trust(evaluator, proposer).to_int() as i32;
+    Some(opinion / 4 + (trust - 50) / 2)
+}
+
+/// Would `evaluator` accept an alliance offered by `proposer`? (> 0 = yes)
+pub fn alliance_acceptance(state: &WorldState, evaluator: &str, proposer: &str) -> i32 {
+    let Some(mut score) = base_acceptance(state, evaluator, proposer) else {
+        return REFUSE;
+    };
+    // Base reluctance
+    score -= 10;
+
+    // Common rivals make natural allies
+    let shared_rival = match (
+        state.countries.get(evaluator),
+        state.countries.get(proposer),
+    ) {
+        (Some(e), Some(p)) => e.rivals.iter().any(|r| p.rivals.contains(r)),
+        _ => false,
+    };
+    if shared_rival {
+        score += 25;
+    }
+
+    // Wary of being dragged into the proposer's wars
+    if !state.diplomacy.get_wars_for_country(proposer).is_empty() {
+        score -= 25;
+    }
+
+    // Each existing alliance makes another one less attractive
+    score -= 10 * state.diplomacy.get_allies(evaluator).len() as i32;
+    score
+}
+
+/// Would `evaluator` accept a royal marriage offered by `proposer`? (> 0 = yes)
+pub fn royal_marriage_acceptance(state: &WorldState, evaluator: &str, proposer: &str) -> i32 {
+    let Some(mut score) = base_acceptance(state, evaluator, proposer) else {
+        return REFUSE;
+    };
+    let religions = (
+        state
+            .countries
+            .get(evaluator)
+            .and_then(|c| c.religion.as_ref()),
+        state
+            .countries
+            .get(proposer)
+            .and_then(|c| c.religion.as_ref()),
+    );
+    if let (Some(ours), Some(theirs)) = religions {
+        score += if ours == theirs { 10 } else { -10 };
+    }
+    score
+}
+
+/// Would `evaluator` grant military access requested by `proposer`? (> 0 = yes)
+pub fn military_access_acceptance(state: &WorldState, evaluator: &str, proposer: &str) -> i32 {
+    let Some(mut score) = base_acceptance(state, evaluator, proposer) else {
+        return REFUSE;
+    };
+    // Never let armies through to attack our allies
+    let allies = state.diplomacy.get_allies(evaluator);
+    if allies
+        .iter()
+        .any(|ally| state.diplomacy.are_at_war(proposer, ally))
+    {
+        return REFUSE;
+    }
+    // Access is cheap to give
+    score += 10;
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::DiplomacyState;
+    use crate::testing::WorldStateBuilder;
+
+    fn two_countries() -> WorldState {
+        WorldStateBuilder::new()
+            .with_country("SWE")
+            .with_country("DAN")
+            .build()
+    }
+
+    #[test]
+    fn test_opinion_sums_modifiers_and_live_terms() {
+        let mut state = two_countries();
+        assert_eq!(opinion(&state, "DAN", "SWE"), Fixed::ZERO);
+
+        add_opinion_modifier(&mut state, "DAN", "SWE", OpinionModifierKind::Insulted);
+        state
+            .diplomacy
+            .royal_marriages
+            .insert(DiplomacyState::sorted_pair("DAN", "SWE"));
+        state
+            .countries
+            .get_mut("DAN")
+            .unwrap()
+            .aggressive_expansion
+            .insert("SWE".to_string(), Fixed::from_int(10));
+
+        // -50 insult + 25 RM - 10 AE; Sweden's view only gets the RM
+        assert_eq!(opinion(&state, "DAN", "SWE"), Fixed::from_int(-35));
+        assert_eq!(opinion(&state, "SWE", "DAN"), Fixed::from_int(25));
+
+        // Reapplying refreshes instead of stacking
+        add_opinion_modifier(&mut state, "DAN", "SWE", OpinionModifierKind::Insulted);
+        assert_eq!(opinion(&state, "DAN", "SWE"), Fixed::from_int(-35));
+
+        let tracked = tracked_opinions(&state);
+        assert_eq!(tracked.len(), 2);
+        assert_eq!(
+            tracked[&("SWE".to_string(), "DAN".to_string())],
+            Fixed::from_int(25)
+        );
+    }
+
+    #[test]
+    fn test_modifiers_decay_to_zero() {
+        let mut state = two_countries();
+        add_opinion_modifier(&mut state, "DAN", "SWE", OpinionModifierKind::Insulted);
+        run_opinion_tick(&mut state);
+        assert_eq!(opinion(&state, "DAN", "SWE"), Fixed::from_int(-49));
+
+        for _ in 0..49 {
+            run_opinion_tick(&mut state);
+        }
+        assert_eq!(opinion(&state, "DAN", "SWE"), Fixed::ZERO);
+        assert!(state.diplomacy.opinion_modifiers.is_empty());
+    }
+
+    #[test]
+    fn test_improve_relations_grows_to_cap_then_returns() {
+        use crate::systems::diplomats::{run_diplomat_tick, send_diplomat};
+
+        let mut state = two_countries();
+        let month = |state: &mut WorldState| {
+            run_diplomat_tick(state);
+            run_opinion_tick(state);
+        };
+        state.date = send_diplomat(
+            &mut state,
+            None,
+            "SWE",
+            "DAN",
+            DiplomatTask::ImproveRelations,
+        )
+        .unwrap();
+        month(&mut state);
+        month(&mut state);
+        // Denmark's opinion of Sweden grows; not the other way round
+        assert_eq!(opinion(&state, "DAN", "SWE"), Fixed::from_int(6));
+        assert_eq!(opinion(&state, "SWE", "DAN"), Fixed::ZERO);
+
+        for _ in 0..40 {
+            month(&mut state);
+        }
+        assert!(state.countries["SWE"].diplomats.is_empty());
+        // Capped at 100, then decaying since the diplomat left
+        let value = opinion(&state, "DAN", "SWE");
+        assert!(value < Fixed::from_int(100) && value > Fixed::from_int(90));
+    }
+
+    #[test]
+    fn test_acceptance_follows_opinion() {
+        let mut state = two_countries();
+        assert!(alliance_acceptance(&state, "DAN", "SWE") <= 0);
+        assert!(military_access_acceptance(&state, "DAN", "SWE") > 0);
+
+        let key = ("DAN".to_string(), "SWE".to_string());
+        state.diplomacy.opinion_modifiers.insert(
+            key,
+            vec![OpinionModifier {
+                kind: OpinionModifierKind::ImprovedRelations,
+                value: Fixed::from_int(100),
+            }],
+        );
+        assert!(alliance_acceptance(&state, "DAN", "SWE") > 0);
+        assert!(royal_marriage_acceptance(&state, "DAN", "SWE") > 0);
+
+        // Rivals refuse everything
+        state
+            .countries
+            .get_mut("DAN")
+            .unwrap()
+            .rivals
+            .insert("SWE".to_string());
+        assert_eq!(alliance_acceptance(&state, "DAN", "SWE"), REFUSE);
+        assert_eq!(military_access_acceptance(&state, "DAN", "SWE"), REFUSE);
+    }
+
+    #[test]
+    fn test_opinion_levels_and_crossings() {
+        assert_eq!(opinion_level(Fixed::from_int(-150)), -2);
+        assert_eq!(opinion_level(Fixed::from_int(-50)), -1);
+        assert_eq!(opinion_level(Fixed::from_int(-49)), 0);
+        assert_eq!(opinion_level(Fixed::from_int(100)), 2);
+
+        assert_eq!(crossed_threshold(0, 0), None);
+        assert_eq!(crossed_threshold(0, -2), Some(-100));
+        assert_eq!(crossed_threshold(-2, 0), Some(-50));
+        assert_eq!(crossed_threshold(-1, 1), Some(50));
+        assert_eq!(crossed_threshold(2, 0), Some(50));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::fixed::Fixed;
 use crate::fixed_generic::Mod32;
-use crate::state::WorldState;
+use crate::schedule::{run_system, Access, Resource, SystemDesc};
+use crate::state::{CountryState, WorldState};
 
 /// Monthly decay rates (EU4 approximations)
 /// EU4 Standard: 5% yearly decay
@@ -17,27 +18,33 @@ const DECAY_RATE: Fixed = Fixed::from_raw(42);
 /// Everything in the world eventually decays toward its foundation. ✧
 /// Pride fades into history (prestige) and strength returns to the soil (tradition). 🛡️
 pub fn run_stats_tick(state: &mut WorldState) {
-    let tags: Vec<String> = state.countries.keys().cloned().collect();
-    for tag in tags {
-        if let Some(country) = state.countries.get_mut(&tag) {
-            // Apply monthly prestige gain from modifiers
-            let prestige_gain = state
-                .modifiers
-                .country_prestige
-                .get(&tag)
-                .copied()
-                .unwrap_or(Mod32::ZERO);
-            country.prestige.add(prestige_gain.to_fixed());
+    run_system(state, &STATS_SYSTEM);
+}
 
-            // Prestige decays toward 0 - Fame is but a shadow that shrinks as the sun moves.
-            country.prestige.decay_toward(Fixed::ZERO, DECAY_RATE);
+/// Scheduler descriptor for [`run_stats_tick`]. Reads prestige modifiers.
+pub const STATS_SYSTEM: SystemDesc = SystemDesc::per_country(
+    "stats",
+    Access::new(&[Resource::Modifiers], &[]),
+    update_country_stats,
+);
 
-            // Army tradition decays toward 0 - Even the sharpest blade rusts if it is not used in battle.
-            country.army_tradition.decay_toward(Fixed::ZERO, DECAY_RATE);
+fn update_country_stats(state: &WorldState, tag: &str, country: &mut CountryState) {
+    // Apply monthly prestige gain from modifiers
+    let prestige_gain = state
+        .modifiers
+        .country_prestige
+        .get(tag)
+        .copied()
+        .unwrap_or(Mod32::ZERO);
+    country.prestige.add(prestige_gain.to_fixed());
 
-            // Stability does NOT decay (only events change it) - Peace is a fragile truth that must be broken to change.
-        }
-    }
+    // Prestige decays toward 0 - Fame is but a shadow that shrinks as the sun moves.
+    country.prestige.decay_toward(Fixed::ZERO, DECAY_RATE);
+
+    // Army tradition decays toward 0 - Even the sharpest blade rusts if it is not used in battle.
+    country.army_tradition.decay_toward(Fixed::ZERO, DECAY_RATE);
+
+    // Stability does NOT decay (only events change it) - Peace is a fragile truth that must be broken to change.
 }
 
 #[cfg(test)]
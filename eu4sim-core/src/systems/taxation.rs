@@ -62,7 +62,7 @@ pub fn run_taxation_tick(state: &mut WorldState) {
 
             // Update national modifier only when owner changes (cache is sorted!)
             if owner_id != current_owner_id {
-                let tag_str = state.tags.resolve(owner_id);
+                let tag_str = state.countries.registry().resolve(owner_id);
                 current_national_mod = country_tax_mod.get(tag_str).copied().unwrap_or(Mod32::ZERO);
                 current_owner_id = owner_id;
             }
@@ -117,27 +117,28 @@ pub fn run_taxation_tick(state: &mut WorldState) {
     };
 
     // PHASE 3: Aggregate results by owner
-    // Grouping by TagId allows using an integer key (fast aggregation)
-    let mut country_totals: std::collections::HashMap<TagId, Mod32> =
-        std::collections::HashMap::with_capacity(state.countries.len());
+    // TagId is the country's slot index, so totals live in a dense array
+    let mut country_totals: Vec<Option<Mod32>> = vec![None; state.countries.registry().len()];
 
     {
         let _span = tracing::info_span!("taxation_aggregate").entered();
         for ((owner_id, _, _), output) in province_data.iter().zip(outputs.iter()) {
-            *country_totals.entry(*owner_id).or_insert(Mod32::ZERO) +=
+            *country_totals[owner_id.0 as usize].get_or_insert(Mod32::ZERO) +=
                 Mod32::from_raw(output.monthly_income);
         }
     }
 
     // PHASE 4: Update World State (Treasury)
-    // Resolve TagId back to string just once per country
+    // Direct slot access by TagId, no string lookups
     {
         let _span = tracing::info_span!("taxation_apply").entered();
         let base_monthly_income = Mod32::ONE;
 
-        for (owner_id, total_tax) in country_totals {
-            let tag_str = state.tags.resolve(owner_id);
-            if let Some(country) = state.countries.get_mut(tag_str) {
+        for (index, total_tax) in country_totals.into_iter().enumerate() {
+            let Some(total_tax) = total_tax else {
+                continue;
+            };
+            if let Some(country) = state.countries.get_by_id_mut(TagId(index as u16)) {
                 // Add base income (every country with provinces gets 1 ducat/month)
                 let total_income = total_tax + base_monthly_income;
 
@@ -1,5 +1,6 @@
+use super::claims::claim_factor;
 use crate::fixed_generic::Mod32;
-use crate::state::{Tag, War, WorldState};
+use crate::state::{ProvinceState, Tag, War, WorldState};
 use tracing::instrument;
 
 /// Maximum war score from battles alone (40%)
@@ -11,6 +12,20 @@ const SCORE_PER_BATTLE: u8 = 5;
 /// Maximum war score from occupation (60%)  
 const MAX_OCCUPATION_SCORE: u8 = 60;
 
+/// War score `taker` pays to demand a province: half its development,
+/// 25% less if `taker` has a claim on it.
+pub fn province_peace_cost(province: &ProvinceState, taker: &str) -> u32 {
+    let dev = province.base_tax + province.base_production + province.base_manpower;
+    (dev.to_f32() * claim_factor(province, taker).to_f32() / 2.0).ceil() as u32
+}
+
+/// Development-weighted AE base for `conqueror` taking a province,
+/// 25% less if it has a claim on it.
+pub fn province_aggressive_expansion(province: &ProvinceState, conqueror: &str) -> Mod32 {
+    let dev = province.base_tax + province.base_production + province.base_manpower;
+    dev * claim_factor(province, conqueror)
+}
+
 /// Awards battle score to the winning side of a battle.
 /// Call this after combat resolution when one side clearly won.
 pub fn award_battle_score(war: &mut War, attacker_won: bool) {
@@ -172,6 +187,7 @@ mod tests {
             defender_score: 0,
             defender_battle_score: 0,
             pending_peace: None,
+            war_goal: None,
         };
 
         // Win 3 battles as attacker
@@ -196,6 +212,7 @@ mod tests {
             defender_score: 0,
             defender_battle_score: 0,
             pending_peace: None,
+            war_goal: None,
         };
 
         // Win 10 battles (should cap at 40)
@@ -206,6 +223,34 @@ mod tests {
         assert_eq!(war.attacker_battle_score, 40); // Capped
     }
 
+    #[test]
+    fn test_claims_discount_peace_cost_and_ae() {
+        let mut province = ProvinceState {
+            owner: Some("DAN".into()),
+            base_tax: Mod32::from_int(4),
+            base_production: Mod32::from_int(4),
+            base_manpower: Mod32::from_int(4),
+            ..Default::default()
+        };
+        assert_eq!(province_peace_cost(&province, "SWE"), 6);
+        assert_eq!(
+            province_aggressive_expansion(&province, "SWE"),
+            Mod32::from_int(12)
+        );
+
+        province
+            .claims
+            .insert("SWE".into(), crate::state::Claim { expires: None });
+        // 12 dev * 0.75 / 2 = 4.5, rounded up
+        assert_eq!(province_peace_cost(&province, "SWE"), 5);
+        assert_eq!(
+            province_aggressive_expansion(&province, "SWE"),
+            Mod32::from_int(9)
+        );
+        // Only the claimant benefits
+        assert_eq!(province_peace_cost(&province, "NOR"), 6);
+    }
+
     #[test]
     fn test_occupation_score_calculation() {
         let mut state = WorldStateBuilder::new()
@@ -227,6 +272,7 @@ mod tests {
             defender_score: 0,
             defender_battle_score: 0,
             pending_peace: None,
+            war_goal: None,
         };
         state.diplomacy.wars.insert(0, war);
 
@@ -287,6 +333,7 @@ mod tests {
                     defender_score: 0,
                     defender_battle_score: 0,
                     pending_peace: None,
+                    war_goal: None,
                 };
 
                 // Award battle scores
@@ -335,6 +382,7 @@ mod tests {
                     defender_score: 0,
                     defender_battle_score: 0,
                     pending_peace: None,
+                    war_goal: None,
                 };
 
                 state.diplomacy.wars.insert(0, war);
@@ -397,6 +445,7 @@ mod tests {
                     defender_score: 0,
                     defender_battle_score: 0,
                     pending_peace: None,
+                    war_goal: None,
                 };
                 state_less.diplomacy.wars.insert(0, war.clone());
                 recalculate_war_scores(&mut state_less);
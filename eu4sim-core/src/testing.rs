@@ -74,6 +74,7 @@ impl WorldStateBuilder {
                 institution_presence: HashMap::default(),
                 trade: ProvinceTradeState::default(),
                 cores,
+                claims: Default::default(),
                 coring_progress: None,
                 buildings: BuildingSet::default(),
                 building_construction: None,
@@ -132,6 +133,7 @@ impl WorldStateBuilder {
                 institution_presence: HashMap::default(),
                 trade: ProvinceTradeState::default(),
                 cores,
+                claims: Default::default(),
                 coring_progress: None,
                 buildings: BuildingSet::default(),
                 building_construction: None,
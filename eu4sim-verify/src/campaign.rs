@@ -0,0 +1,666 @@
+//! Statistical verification across a campaign's autosaves
+//!
+//! [`crate::predict`] checks one pair of saves for one country. A campaign run
+//! walks a directory of saves, orders them by in-game date, predicts every
+//! consecutive pair once for all countries, and aggregates the results:
+//! - error distribution per metric
+//! - worst individual predictions
+//! - mean error per target date, to spot drift over the campaign
+
+use crate::diff::infer_actions;
+use crate::hydrate::GameBase;
+use crate::parse::{load_save, read_save_date};
+use crate::predict::{
+    compare_metrics, days_between, parse_date, PredictionResult, PredictionStatus,
+};
+use crate::replay::{ReplayOutcome, ReplayPlan};
+use crate::ExtractedState;
+use anyhow::{Context, Result};
+use eu4sim_core::config::SimConfig;
+use eu4sim_core::step::step_world_mut;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Options for [`run_campaign`]
+#[derive(Debug, Clone, Default)]
+pub struct CampaignOptions {
+    /// Replay player actions inferred from each pair's diff
+    pub replay_actions: bool,
+    /// Only compare these countries (all countries with provinces when empty)
+    pub countries: Vec<String>,
+    /// Number of worst predictions to list in the report
+    pub worst: usize,
+}
+
+/// A save file and its in-game date
+#[derive(Debug, Clone)]
+pub struct SaveEntry {
+    pub path: PathBuf,
+    pub date: String,
+}
+
+/// Predictions for one country over one pair of consecutive saves
+#[derive(Debug, Clone, Serialize)]
+pub struct CountryPrediction {
+    pub from_date: String,
+    pub to_date: String,
+    pub days: u32,
+    pub country: String,
+    pub results: Vec<PredictionResult>,
+}
+
+/// Summary statistics of a metric's percentage errors
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ErrorDistribution {
+    pub mean: f64,
+    pub median: f64,
+    pub p90: f64,
+    pub max: f64,
+}
+
+impl ErrorDistribution {
+    /// Percentiles use the nearest-rank method.
+    pub fn from_samples(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len() as f64;
+        let rank = |p: f64| sorted[((p * n).ceil() as usize).clamp(1, sorted.len()) - 1];
+        Self {
+            mean: sorted.iter().sum::<f64>() / n,
+            median: rank(0.5),
+            p90: rank(0.9),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+/// Aggregate results for one metric across all pairs and countries
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricStats {
+    pub metric: String,
+    pub samples: usize,
+    pub pass: usize,
+    pub close: usize,
+    pub fail: usize,
+    /// Distribution of percentage errors (see [`PredictionResult::pct_error`])
+    pub pct_error: ErrorDistribution,
+    pub mean_abs_delta: f64,
+}
+
+/// A single prediction, listed among the worst of the campaign
+#[derive(Debug, Clone, Serialize)]
+pub struct Offender {
+    pub metric: String,
+    pub country: String,
+    pub from_date: String,
+    pub to_date: String,
+    pub predicted: f64,
+    pub actual: f64,
+    pub delta: f64,
+    pub pct_error: f64,
+}
+
+/// Mean error for one metric over all countries at one target date
+#[derive(Debug, Clone, Serialize)]
+pub struct TrendPoint {
+    pub date: String,
+    pub metric: String,
+    pub samples: usize,
+    pub mean_pct_error: f64,
+    pub fail_rate: f64,
+}
+
+/// Aggregate report over a campaign
+#[derive(Debug, Clone, Serialize)]
+pub struct CampaignReport {
+    pub saves: usize,
+    pub pairs: usize,
+    pub first_date: Option<String>,
+    pub last_date: Option<String>,
+    /// Country × pair comparisons
+    pub comparisons: usize,
+    pub metrics: Vec<MetricStats>,
+    pub worst: Vec<Offender>,
+    pub trend: Vec<TrendPoint>,
+}
+
+/// Find `.eu4` saves in `dir`, ordered by in-game date
+pub fn discover_saves(dir: &Path) -> Result<Vec<SaveEntry>> {
+    let mut saves = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read save directory: {}", dir.display()))?
+    {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "eu4") {
+            continue;
+        }
+        match read_save_date(&path) {
+            Ok(date) => saves.push(SaveEntry { path, date }),
+            Err(e) => log::warn!("Skipping {}: {:#}", path.display(), e),
+        }
+    }
+    sort_by_date(saves)
+}
+
+/// Order saves by in-game date (file name breaks ties)
+pub fn sort_by_date(saves: Vec<SaveEntry>) -> Result<Vec<SaveEntry>> {
+    let mut keyed = saves
+        .into_iter()
+        .map(|save| Ok((parse_date(&save.date)?, save)))
+        .collect::<Result<Vec<_>>>()?;
+    keyed.sort_by(|(a, a_save), (b, b_save)| a.cmp(b).then_with(|| a_save.path.cmp(&b_save.path)));
+    Ok(keyed.into_iter().map(|(_, save)| save).collect())
+}
+
+/// Predict every consecutive pair of saves in `dir` and aggregate the results
+pub fn run_campaign(
+    game_path: &Path,
+    dir: &Path,
+    options: &CampaignOptions,
+) -> Result<CampaignReport> {
+    let saves = discover_saves(dir)?;
+    if saves.len() < 2 {
+        anyhow::bail!(
+            "Need at least two saves in {}, found {}",
+            dir.display(),
+            saves.len()
+        );
+    }
+    log::info!(
+        "Campaign: {} saves from {} to {}",
+        saves.len(),
+        saves[0].date,
+        saves[saves.len() - 1].date
+    );
+
+    // Game files are loaded once, at the first pair's date; each pair
+    // hydrates a fresh copy
+    let mut base: Option<GameBase> = None;
+    let mut predictions = Vec::new();
+    let mut pairs = 0;
+    let mut from_state: Option<ExtractedState> = None;
+    for (i, save) in saves.iter().enumerate() {
+        // A save that fails to load drops the pairs on either side of it
+        let to_state = match load_save(&save.path) {
+            Ok(state) => state,
+            Err(e) => {
+                log::warn!("Skipping {}: {:#}", save.path.display(), e);
+                from_state = None;
+                continue;
+            }
+        };
+        if let Some(from) = &from_state {
+            log::info!(
+                "[{}/{}] {} -> {}",
+                i,
+                saves.len() - 1,
+                from.meta.date,
+                to_state.meta.date
+            );
+            if base.is_none() {
+                base = Some(GameBase::load(game_path, parse_date(&from.meta.date)?)?);
+            }
+            let base = base.as_ref().expect("loaded above");
+            match predict_pair(base, from, &to_state, options) {
+                Ok(pair) => {
+                    pairs += 1;
+                    predictions.extend(pair);
+                }
+                Err(e) => log::warn!(
+                    "Skipping {} -> {}: {:#}",
+                    from.meta.date,
+                    to_state.meta.date,
+                    e
+                ),
+            }
+        }
+        from_state = Some(to_state);
+    }
+
+    Ok(CampaignReport::new(
+        &saves,
+        pairs,
+        &predictions,
+        options.worst,
+    ))
+}
+
+/// Hydrate `from` onto `base`, simulate to `to`'s date, and compare every country
+fn predict_pair(
+    base: &GameBase,
+    from: &ExtractedState,
+    to: &ExtractedState,
+    options: &CampaignOptions,
+) -> Result<Vec<CountryPrediction>> {
+    let days = days_between(&parse_date(&from.meta.date)?, &parse_date(&to.meta.date)?);
+    if days == 0 {
+        anyhow::bail!("target save is not later than source save");
+    }
+
+    let mut world = base.hydrate(from)?;
+    let adjacency = &base.adjacency;
+
+    let plan = options
+        .replay_actions
+        .then(|| ReplayPlan::new(&infer_actions(from, to).actions, &world, days));
+    let mut replay = plan.as_ref().map(ReplayOutcome::new);
+
+    let config = SimConfig {
+        checksum_frequency: 0,
+    };
+    for day in 0..days {
+        let inputs = match (&plan, replay.as_mut()) {
+            (Some(plan), Some(outcome)) => outcome.inputs_for_day(plan, day, &world, adjacency),
+            _ => Vec::new(),
+        };
+        step_world_mut(&mut world, &inputs, Some(adjacency), &config, None);
+    }
+
+    // Countries that exist at the target date
+    let mut tags: Vec<&String> = to
+        .countries
+        .iter()
+        .filter(|(tag, country)| {
+            !country.owned_province_ids.is_empty()
+                && (options.countries.is_empty() || options.countries.contains(tag))
+        })
+        .map(|(tag, _)| tag)
+        .collect();
+    tags.sort();
+
+    Ok(tags
+        .into_iter()
+        .filter_map(|tag| {
+            let predicted = world.countries.get(tag)?;
+            Some(CountryPrediction {
+                from_date: from.meta.date.clone(),
+                to_date: to.meta.date.clone(),
+                days,
+                country: tag.clone(),
+                results: compare_metrics(predicted, &to.countries[tag]),
+            })
+        })
+        .collect())
+}
+
+impl CampaignReport {
+    /// Aggregate predictions, which must be in chronological order of pairs.
+    pub fn new(
+        saves: &[SaveEntry],
+        pairs: usize,
+        predictions: &[CountryPrediction],
+        worst: usize,
+    ) -> Self {
+        let scored: Vec<(&CountryPrediction, &PredictionResult)> = predictions
+            .iter()
+            .flat_map(|p| p.results.iter().map(move |r| (p, r)))
+            .filter(|(_, r)| r.status != PredictionStatus::Skip)
+            .collect();
+
+        // Metrics and dates in first-seen order
+        let mut metric_names: Vec<&str> = Vec::new();
+        let mut dates: Vec<&str> = Vec::new();
+        for (prediction, result) in &scored {
+            if !metric_names.contains(&result.metric.as_str()) {
+                metric_names.push(&result.metric);
+            }
+            if !dates.contains(&prediction.to_date.as_str()) {
+                dates.push(&prediction.to_date);
+            }
+        }
+
+        let metrics = metric_names
+            .iter()
+            .map(|&metric| {
+                let results: Vec<&PredictionResult> = scored
+                    .iter()
+                    .filter(|(_, r)| r.metric == metric)
+                    .map(|(_, r)| *r)
+                    .collect();
+                let count = |status| results.iter().filter(|r| r.status == status).count();
+                let errors: Vec<f64> = results.iter().map(|r| r.pct_error()).collect();
+                MetricStats {
+                    metric: metric.to_string(),
+                    samples: results.len(),
+                    pass: count(PredictionStatus::Pass),
+                    close: count(PredictionStatus::Close),
+                    fail: count(PredictionStatus::Fail),
+                    pct_error: ErrorDistribution::from_samples(&errors),
+                    mean_abs_delta: results.iter().map(|r| r.delta.abs()).sum::<f64>()
+                        / results.len() as f64,
+                }
+            })
+            .collect();
+
+        let mut offenders: Vec<Offender> = scored
+            .iter()
+            .map(|(p, r)| Offender {
+                metric: r.metric.clone(),
+                country: p.country.clone(),
+                from_date: p.from_date.clone(),
+                to_date: p.to_date.clone(),
+                predicted: r.predicted,
+                actual: r.actual,
+                delta: r.delta,
+                pct_error: r.pct_error(),
+            })
+            .collect();
+        offenders.sort_by(|a, b| b.pct_error.total_cmp(&a.pct_error));
+        offenders.truncate(worst);
+
+        let trend = dates
+            .iter()
+            .flat_map(|&date| {
+                let scored = &scored;
+                metric_names.iter().filter_map(move |&metric| {
+                    let results: Vec<&PredictionResult> = scored
+                        .iter()
+                        .filter(|(p, r)| p.to_date == date && r.metric == metric)
+                        .map(|(_, r)| *r)
+                        .collect();
+                    if results.is_empty() {
+                        return None;
+                    }
+                    let n = results.len() as f64;
+                    Some(TrendPoint {
+                        date: date.to_string(),
+                        metric: metric.to_string(),
+                        samples: results.len(),
+                        mean_pct_error: results.iter().map(|r| r.pct_error()).sum::<f64>() / n,
+                        fail_rate: results
+                            .iter()
+                            .filter(|r| r.status == PredictionStatus::Fail)
+                            .count() as f64
+                            / n,
+                    })
+                })
+            })
+            .collect();
+
+        Self {
+            saves: saves.len(),
+            pairs,
+            first_date: saves.first().map(|s| s.date.clone()),
+            last_date: saves.last().map(|s| s.date.clone()),
+            comparisons: predictions.len(),
+            metrics,
+            worst: offenders,
+            trend,
+        }
+    }
+
+    /// Render the report as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        // Writing to a String can't fail
+        let _ = self.write_markdown(&mut md);
+        md
+    }
+
+    fn write_markdown(&self, md: &mut String) -> std::fmt::Result {
+        writeln!(md, "# Verification campaign")?;
+        writeln!(md)?;
+        writeln!(
+            md,
+            "{} saves, {} pairs ({} → {}), {} country comparisons.",
+            self.saves,
+            self.pairs,
+            self.first_date.as_deref().unwrap_or("-"),
+            self.last_date.as_deref().unwrap_or("-"),
+            self.comparisons
+        )?;
+
+        writeln!(md)?;
+        writeln!(md, "## Error by metric")?;
+        writeln!(md)?;
+        writeln!(
+            md,
+            "| Metric | Samples | Pass | Close | Fail | Mean % | Median % | P90 % | Max % | Mean abs Δ |"
+        )?;
+        writeln!(md, "|---|---:|---:|---:|---:|---:|---:|---:|---:|---:|")?;
+        for m in &self.metrics {
+            writeln!(
+                md,
+                "| {} | {} | {} | {} | {} | {:.1} | {:.1} | {:.1} | {:.1} | {:.2} |",
+                m.metric,
+                m.samples,
+                m.pass,
+                m.close,
+                m.fail,
+                m.pct_error.mean,
+                m.pct_error.median,
+                m.pct_error.p90,
+                m.pct_error.max,
+                m.mean_abs_delta
+            )?;
+        }
+
+        writeln!(md)?;
+        writeln!(md, "## Worst predictions")?;
+        writeln!(md)?;
+        writeln!(
+            md,
+            "| # | Metric | Country | Window | Predicted | Actual | Delta | Error % |"
+        )?;
+        writeln!(md, "|---:|---|---|---|---:|---:|---:|---:|")?;
+        for (i, o) in self.worst.iter().enumerate() {
+            writeln!(
+                md,
+                "| {} | {} | {} | {} → {} | {:.2} | {:.2} | {:+.2} | {:.1} |",
+                i + 1,
+                o.metric,
+                o.country,
+                o.from_date,
+                o.to_date,
+                o.predicted,
+                o.actual,
+                o.delta,
+                o.pct_error
+            )?;
+        }
+
+        writeln!(md)?;
+        writeln!(md, "## Trend (mean % error by target date)")?;
+        writeln!(md)?;
+        let metrics: Vec<&str> = self.metrics.iter().map(|m| m.metric.as_str()).collect();
+        writeln!(md, "| Date | {} |", metrics.join(" | "))?;
+        writeln!(md, "|---|{}", "---:|".repeat(metrics.len()))?;
+        let mut dates: Vec<&str> = Vec::new();
+        for point in &self.trend {
+            if !dates.contains(&point.date.as_str()) {
+                dates.push(&point.date);
+            }
+        }
+        for date in dates {
+            let cells: Vec<String> = metrics
+                .iter()
+                .map(|&metric| {
+                    self.trend
+                        .iter()
+                        .find(|p| p.date == date && p.metric == metric)
+                        .map_or_else(|| "-".to_string(), |p| format!("{:.1}", p.mean_pct_error))
+                })
+                .collect();
+            writeln!(md, "| {} | {} |", date, cells.join(" | "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Serialize a campaign report as pretty-printed JSON
+pub fn json_report(report: &CampaignReport) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::predict::compare_metric;
+
+    fn save(path: &str, date: &str) -> SaveEntry {
+        SaveEntry {
+            path: PathBuf::from(path),
+            date: date.to_string(),
+        }
+    }
+
+    fn prediction(
+        from: &str,
+        to: &str,
+        country: &str,
+        results: Vec<PredictionResult>,
+    ) -> CountryPrediction {
+        CountryPrediction {
+            from_date: from.to_string(),
+            to_date: to.to_string(),
+            days: 30,
+            country: country.to_string(),
+            results,
+        }
+    }
+
+    fn sample_report() -> CampaignReport {
+        let saves = [
+            save("a.eu4", "1444.11.11"),
+            save("b.eu4", "1444.12.1"),
+            save("c.eu4", "1445.1.1"),
+        ];
+        let predictions = [
+            prediction(
+                "1444.11.11",
+                "1444.12.1",
+                "FRA",
+                vec![
+                    compare_metric("Treasury", 100.0, 100.0),
+                    compare_metric("Manpower", 10_000.0, 9_000.0),
+                ],
+            ),
+            prediction(
+                "1444.11.11",
+                "1444.12.1",
+                "ENG",
+                vec![compare_metric("Treasury", 52.0, 50.0)],
+            ),
+            prediction(
+                "1444.12.1",
+                "1445.1.1",
+                "FRA",
+                vec![compare_metric("Treasury", 150.0, 100.0)],
+            ),
+        ];
+        CampaignReport::new(&saves, 2, &predictions, 2)
+    }
+
+    /// Zip save whose date reads fine but whose gamestate is corrupt
+    fn write_corrupt_save(dir: &Path, name: &str, date: &str) {
+        use std::io::Write;
+        let options = zip::write::SimpleFileOptions::default();
+        let file = std::fs::File::create(dir.join(name)).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        zip.start_file("meta", options).unwrap();
+        write!(zip, "EU4txt\ndate={}\n", date).unwrap();
+        zip.start_file("gamestate", options).unwrap();
+        zip.write_all(b"EU4bin\xff\xff\xff\xff").unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_campaign_skips_saves_that_fail_to_load() {
+        let dir = tempfile::tempdir().unwrap();
+        write_corrupt_save(dir.path(), "a.eu4", "1444.11.11");
+        write_corrupt_save(dir.path(), "b.eu4", "1444.12.1");
+
+        // Every pair is skipped before game data is needed
+        let report = run_campaign(
+            Path::new("/nonexistent"),
+            dir.path(),
+            &CampaignOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(report.saves, 2);
+        assert_eq!(report.pairs, 0);
+        assert_eq!(report.comparisons, 0);
+    }
+
+    #[test]
+    fn test_sort_by_date_uses_in_game_date() {
+        let saves = vec![
+            save("autosave.eu4", "1445.1.1"),
+            save("b.eu4", "1444.12.1"),
+            save("a.eu4", "1444.12.1"),
+            save("old.eu4", "1444.11.11"),
+        ];
+
+        let sorted = sort_by_date(saves).unwrap();
+
+        let paths: Vec<_> = sorted.iter().map(|s| s.path.to_str().unwrap()).collect();
+        assert_eq!(paths, ["old.eu4", "a.eu4", "b.eu4", "autosave.eu4"]);
+    }
+
+    #[test]
+    fn test_metric_stats() {
+        let report = sample_report();
+
+        assert_eq!(report.comparisons, 3);
+        assert_eq!(report.metrics.len(), 2);
+        let treasury = &report.metrics[0];
+        assert_eq!(treasury.metric, "Treasury");
+        assert_eq!(treasury.samples, 3);
+        assert_eq!((treasury.pass, treasury.close, treasury.fail), (2, 0, 1));
+        // Errors of 0%, 4% and 50%
+        assert!((treasury.pct_error.mean - 18.0).abs() < 1e-9);
+        assert_eq!(treasury.pct_error.median, 4.0);
+        assert_eq!(treasury.pct_error.max, 50.0);
+        assert!((treasury.mean_abs_delta - 52.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_worst_offenders_sorted_and_truncated() {
+        let report = sample_report();
+
+        assert_eq!(report.worst.len(), 2);
+        assert_eq!(report.worst[0].metric, "Treasury");
+        assert_eq!(report.worst[0].to_date, "1445.1.1");
+        assert_eq!(report.worst[1].metric, "Manpower");
+        assert_eq!(report.worst[1].country, "FRA");
+    }
+
+    #[test]
+    fn test_trend_per_target_date() {
+        let report = sample_report();
+
+        let treasury: Vec<_> = report
+            .trend
+            .iter()
+            .filter(|p| p.metric == "Treasury")
+            .collect();
+        assert_eq!(treasury.len(), 2);
+        assert_eq!(treasury[0].date, "1444.12.1");
+        assert_eq!(treasury[0].samples, 2);
+        assert!((treasury[0].mean_pct_error - 2.0).abs() < 1e-9);
+        assert_eq!(treasury[1].fail_rate, 1.0);
+    }
+
+    #[test]
+    fn test_markdown_and_json_reports() {
+        let report = sample_report();
+
+        let md = report.to_markdown();
+        assert!(md.contains("3 saves, 2 pairs (1444.11.11 → 1445.1.1), 3 country comparisons."));
+        assert!(md.contains("| Treasury | 3 | 2 | 0 | 1 |"));
+        assert!(md.contains("| Date | Treasury | Manpower |"));
+        assert!(md.contains("| 1445.1.1 | 50.0 | - |"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&json_report(&report).unwrap()).expect("valid JSON");
+        assert_eq!(json["pairs"], 2);
+        assert_eq!(json["metrics"][0]["pct_error"]["max"], 50.0);
+        assert_eq!(json["worst"][0]["country"], "FRA");
+    }
+}
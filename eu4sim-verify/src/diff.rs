@@ -572,6 +572,11 @@ mod tests {
             subjects: HashMap::new(),
             celestial_empire: None,
             trade_nodes: HashMap::new(),
+            armies: Vec::new(),
+            fleets: Vec::new(),
+            wars: Vec::new(),
+            diplomacy: Default::default(),
+            military_and_diplomacy_extracted: false,
         }
     }
 
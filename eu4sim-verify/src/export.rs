@@ -0,0 +1,698 @@
+//! Export WorldState as an EU4 plaintext save
+//!
+//! The reverse of [`crate::parse`] + [`crate::hydrate`]: writes provinces,
+//! countries, diplomacy, wars and armies as Clausewitz text, so simulated futures
+//! can be opened in the game and in third-party save analyzers.
+//!
+//! Only state the sim tracks is written. Everything else (province history,
+//! ledgers, trade, estates, ...) is left out and falls back to game defaults on
+//! load. Some sim state is coarser than the save's:
+//! - Units only have a category, so each category is written as one
+//!   representative unit type (see [`regiment_unit_type`] / [`ship_unit_type`]).
+//! - Truces store their expiry; saves store when the war ended, which is written
+//!   as expiry minus the sim's 5-year truce length.
+//! - Advisor types come from advisor names hydrated from saves
+//!   (`"treasurer (skill 2)"`), otherwise a representative type per category.
+
+use anyhow::{Context, Result};
+use eu4sim_core::state::{
+    AdvisorType, CountryState, Date, ProvinceState, RegimentType, RelationType, ShipType, War,
+};
+use eu4sim_core::WorldState;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io::Write as _;
+use std::path::Path;
+
+/// Save game version written to exported saves
+const SAVE_GAME_VERSION: (u32, u32, u32, u32) = (1, 37, 0, 0);
+
+/// Truce length the sim gives new truces (see `hydrate_diplomacy`)
+const TRUCE_YEARS: i32 = 5;
+
+/// Save-file object type for unit ids (`id={ id=N type=54 }`)
+const UNIT_ID_TYPE: u32 = 54;
+
+/// Save-file object type for leader ids (`id={ id=N type=49 }`)
+const LEADER_ID_TYPE: u32 = 49;
+
+/// Options for [`export_save`]
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Country the save is played as (None for an observer save)
+    pub player: Option<String>,
+    /// Write a zip with separate `meta` and `gamestate` entries, like the game's
+    /// compressed saves, instead of a single plain text file
+    pub compress: bool,
+}
+
+/// Write `world` to `path` as an EU4 text save.
+pub fn export_save(world: &WorldState, path: &Path, options: &ExportOptions) -> Result<()> {
+    let gamestate = gamestate_text(world, options);
+
+    if options.compress {
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to create save file: {}", path.display()))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let file_options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        for (name, content) in [
+            ("meta", meta_text(world, options)),
+            ("gamestate", gamestate),
+        ] {
+            zip.start_file(name, file_options)?;
+            zip.write_all(content.as_bytes())?;
+        }
+        zip.finish()?;
+    } else {
+        std::fs::write(path, gamestate)
+            .with_context(|| format!("Failed to write save file: {}", path.display()))?;
+    }
+
+    log::info!(
+        "Exported {} ({} provinces, {} countries) to {}",
+        save_date(world.date),
+        world.provinces.len(),
+        world.countries.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// The `meta` entry of a compressed save: header fields only.
+pub fn meta_text(world: &WorldState, options: &ExportOptions) -> String {
+    let mut w = SaveWriter::default();
+    w.line("EU4txt");
+    write_meta(&mut w, world, options);
+    w.out
+}
+
+/// A full gamestate, usable on its own as a plain (uncompressed) save.
+pub fn gamestate_text(world: &WorldState, options: &ExportOptions) -> String {
+    let mut w = SaveWriter::default();
+    w.line("EU4txt");
+    write_meta(&mut w, world, options);
+    w.field("start_date", "1444.11.11");
+
+    let leader_ids = LeaderIds::new(world);
+    write_provinces(&mut w, world);
+    write_countries(&mut w, world, &leader_ids);
+    write_diplomacy(&mut w, world);
+
+    let mut wars: Vec<&War> = world.diplomacy.wars.values().collect();
+    wars.sort_by_key(|war| war.id);
+    for war in wars {
+        write_war(&mut w, war);
+    }
+
+    w.out
+}
+
+// =========================================================================
+// Writer
+// =========================================================================
+
+/// Minimal Clausewitz text writer with tab indentation
+#[derive(Default)]
+struct SaveWriter {
+    out: String,
+    depth: usize,
+}
+
+impl SaveWriter {
+    fn line(&mut self, text: impl Display) {
+        for _ in 0..self.depth {
+            self.out.push('\t');
+        }
+        self.out.push_str(&text.to_string());
+        self.out.push('\n');
+    }
+
+    fn field(&mut self, key: &str, value: impl Display) {
+        self.line(format_args!("{}={}", key, value));
+    }
+
+    fn quoted(&mut self, key: &str, value: &str) {
+        self.line(format_args!("{}=\"{}\"", key, value));
+    }
+
+    fn num(&mut self, key: &str, value: f64) {
+        self.line(format_args!("{}={:.3}", key, value));
+    }
+
+    fn open(&mut self, key: impl Display) {
+        self.line(format_args!("{}={{", key));
+        self.depth += 1;
+    }
+
+    fn close(&mut self) {
+        self.depth -= 1;
+        self.line("}");
+    }
+
+    /// `key={ "A" "B" }` on separate lines
+    fn tag_list<'a>(&mut self, key: &str, tags: impl IntoIterator<Item = &'a String>) {
+        self.open(key);
+        for tag in tags {
+            self.line(format_args!("\"{}\"", tag));
+        }
+        self.close();
+    }
+}
+
+/// Save dates are unpadded (`1445.1.1`), unlike [`Date`]'s `Display`
+fn save_date(date: Date) -> String {
+    format!("{}.{}.{}", date.year, date.month, date.day)
+}
+
+// =========================================================================
+// Sections
+// =========================================================================
+
+fn write_meta(w: &mut SaveWriter, world: &WorldState, options: &ExportOptions) {
+    let (first, second, third, fourth) = SAVE_GAME_VERSION;
+
+    w.field("date", save_date(world.date));
+    if let Some(player) = &options.player {
+        w.quoted("player", player);
+        w.quoted("displayed_country_name", player);
+    }
+    w.open("savegame_version");
+    w.field("first", first);
+    w.field("second", second);
+    w.field("third", third);
+    // Paradox's spelling
+    w.field("forth", fourth);
+    w.quoted("name", "eu4sim");
+    w.close();
+    w.open("savegame_versions");
+    w.line(format_args!(
+        "\"{}.{}.{}.{}\"",
+        first, second, third, fourth
+    ));
+    w.close();
+    w.open("dlc_enabled");
+    w.close();
+    w.field("multi_player", "no");
+    w.field(
+        "not_observer",
+        if options.player.is_some() {
+            "yes"
+        } else {
+            "no"
+        },
+    );
+    w.field("is_random_new_world", "no");
+    w.field("ironman", "no");
+}
+
+/// Provinces are keyed by negative id at column 0, as the game writes them
+fn write_provinces(w: &mut SaveWriter, world: &WorldState) {
+    let mut provinces: Vec<(&u32, &ProvinceState)> =
+        world.provinces.iter().filter(|(_, p)| !p.is_sea).collect();
+    provinces.sort_by_key(|(id, _)| **id);
+
+    w.open("provinces");
+    for (&id, province) in provinces {
+        w.depth = 0;
+        w.line(format_args!("-{}={{", id));
+        w.depth = 2;
+
+        // Owner goes first: parsers take the first `owner=` they see, and
+        // later keys such as `original_owner=` contain it
+        if let Some(owner) = &province.owner {
+            w.quoted("owner", owner);
+        }
+        if let Some(controller) = &province.controller {
+            w.quoted("controller", controller);
+        }
+        if !province.cores.is_empty() {
+            let mut cores: Vec<&String> = province.cores.iter().collect();
+            cores.sort();
+            w.tag_list("cores", cores);
+        }
+        if let Some(culture) = &province.culture {
+            w.field("culture", culture);
+        }
+        if let Some(religion) = &province.religion {
+            w.field("religion", religion);
+        }
+        w.num("base_tax", province.base_tax.to_f32() as f64);
+        w.num("base_production", province.base_production.to_f32() as f64);
+        w.num("base_manpower", province.base_manpower.to_f32() as f64);
+
+        let mut buildings: Vec<&str> = province
+            .buildings
+            .iter()
+            .filter_map(|id| world.building_defs.get(&id).map(|def| def.name.as_str()))
+            .collect();
+        if !buildings.is_empty() {
+            buildings.sort();
+            w.open("buildings");
+            for building in buildings {
+                w.field(building, "yes");
+            }
+            w.close();
+        }
+
+        if province.is_in_hre {
+            w.field("hre", "yes");
+        }
+        let devastation = province.devastation.to_f32();
+        if devastation > 0.0 {
+            w.num("devastation", devastation as f64);
+        }
+        w.close();
+    }
+    w.depth = 1;
+    w.close();
+}
+
+fn write_countries(w: &mut SaveWriter, world: &WorldState, leader_ids: &LeaderIds) {
+    let mut unit_id = 0u32;
+
+    w.open("countries");
+    for (tag, country) in world.countries.iter() {
+        w.open(tag);
+        write_country(w, world, tag, country);
+        write_country_history(w, world, tag, country, leader_ids);
+        write_relations(w, world, tag);
+        write_units(w, world, tag, leader_ids, &mut unit_id);
+        w.close();
+    }
+    w.close();
+}
+
+fn write_country(w: &mut SaveWriter, world: &WorldState, tag: &str, country: &CountryState) {
+    w.field("government_rank", country.government_rank);
+    if let Some(group) = &country.technology_group {
+        w.field("technology_group", group);
+    }
+    if let Some(religion) = &country.religion {
+        w.field("religion", religion);
+    }
+    if let Some(capital) = world
+        .provinces
+        .iter()
+        .filter(|(_, p)| p.is_capital && p.owner.as_deref() == Some(tag))
+        .map(|(id, _)| *id)
+        .min()
+    {
+        w.field("capital", capital);
+    }
+
+    w.num("treasury", country.treasury.to_f32() as f64);
+    // Saves count manpower in thousands of men
+    w.num("manpower", country.manpower.to_f32() as f64 / 1000.0);
+    w.num("stability", country.stability.get() as f64);
+    w.num("prestige", country.prestige.get().to_f32() as f64);
+    w.num(
+        "army_tradition",
+        country.army_tradition.get().to_f32() as f64,
+    );
+    if let Some(tribute_type) = country.tribute_type {
+        w.field("tribute_type", tribute_type as i32);
+    }
+    if country.loans > 0 {
+        w.field("loans", country.loans);
+    }
+
+    w.open("technology");
+    w.field("adm_tech", country.adm_tech);
+    w.field("dip_tech", country.dip_tech);
+    w.field("mil_tech", country.mil_tech);
+    w.close();
+
+    // Stored monarch power is a whole non-negative number in saves
+    let power = |mana: eu4sim_core::Fixed| mana.to_f32().round().max(0.0) as i64;
+    w.open("powers");
+    w.line(format_args!(
+        "{} {} {}",
+        power(country.adm_mana),
+        power(country.dip_mana),
+        power(country.mil_mana)
+    ));
+    w.close();
+
+    let mut idea_groups: Vec<(&str, u8)> = country
+        .ideas
+        .groups
+        .iter()
+        .filter_map(|(id, &unlocked)| Some((world.idea_groups.get(*id)?.name.as_str(), unlocked)))
+        .collect();
+    if let Some(national) = country
+        .ideas
+        .national_ideas
+        .and_then(|id| world.idea_groups.get(id))
+    {
+        idea_groups.push((&national.name, country.ideas.national_ideas_progress));
+    }
+    if !idea_groups.is_empty() {
+        idea_groups.sort();
+        w.open("active_idea_groups");
+        for (name, unlocked) in idea_groups {
+            w.field(name, unlocked);
+        }
+        w.close();
+    }
+
+    // The sim only keeps hired advisors
+    if !country.advisors.is_empty() {
+        w.open("advisors");
+        for (i, advisor) in country.advisors.iter().enumerate() {
+            w.line(format_args!(
+                "{{ id={} type=\"{}\" skill={} }}",
+                i + 1,
+                advisor_type_name(&advisor.name, advisor.advisor_type),
+                advisor.skill
+            ));
+        }
+        w.close();
+        w.open("active_advisors");
+        let ids: Vec<String> = (1..=country.advisors.len())
+            .map(|i| i.to_string())
+            .collect();
+        w.line(ids.join(" "));
+        w.close();
+    }
+
+    let mut rivals: Vec<&String> = country.rivals.iter().collect();
+    rivals.sort();
+    for rival in rivals {
+        w.open("rival");
+        w.quoted("country", rival);
+        w.close();
+    }
+}
+
+/// Ruler and leader definitions, dated today
+fn write_country_history(
+    w: &mut SaveWriter,
+    world: &WorldState,
+    tag: &str,
+    country: &CountryState,
+    leader_ids: &LeaderIds,
+) {
+    let mut generals: Vec<_> = world.generals.values().filter(|g| g.owner == tag).collect();
+    generals.sort_by_key(|g| g.id);
+    let mut admirals: Vec<_> = world.admirals.values().filter(|a| a.owner == tag).collect();
+    admirals.sort_by_key(|a| a.id);
+
+    let leaders: Vec<_> = generals
+        .iter()
+        .map(|g| {
+            let pips = (g.fire, g.shock, g.maneuver, g.siege);
+            (&g.name, "general", pips, leader_ids.general(g.id))
+        })
+        .chain(admirals.iter().map(|a| {
+            let pips = (a.fire, a.shock, a.maneuver, a.siege);
+            (&a.name, "admiral", pips, leader_ids.admiral(a.id))
+        }))
+        .collect();
+
+    let ruler_date = country.ruler_instated.unwrap_or(world.date);
+
+    w.open("history");
+    w.open(save_date(ruler_date));
+    w.open("monarch");
+    if let Some(name) = &country.ruler_name {
+        w.quoted("name", name);
+    }
+    if let Some(dynasty) = &country.ruler_dynasty {
+        w.quoted("dynasty", dynasty);
+    }
+    w.field("ADM", country.ruler_adm);
+    w.field("DIP", country.ruler_dip);
+    w.field("MIL", country.ruler_mil);
+    w.close();
+
+    if !leaders.is_empty() {
+        // One block per date
+        if ruler_date != world.date {
+            w.close();
+            w.open(save_date(world.date));
+        }
+        for (name, leader_type, (fire, shock, maneuver, siege), id) in leaders {
+            w.open("leader");
+            w.quoted("name", name);
+            w.field("type", leader_type);
+            // Paradox's spelling
+            w.field("manuever", maneuver);
+            w.field("fire", fire);
+            w.field("shock", shock);
+            w.field("siege", siege);
+            w.line(format_args!("id={{ id={} type={} }}", id, LEADER_ID_TYPE));
+            w.close();
+        }
+    }
+    w.close();
+    w.close();
+}
+
+/// Truces, stored per country as `active_relations={ TAG={ truce=yes ... } }`
+fn write_relations(w: &mut SaveWriter, world: &WorldState, tag: &str) {
+    let truces: BTreeMap<&str, Date> = world
+        .diplomacy
+        .truces
+        .iter()
+        .filter_map(|((a, b), &expiry)| {
+            if a == tag {
+                Some((b.as_str(), expiry))
+            } else if b == tag {
+                Some((a.as_str(), expiry))
+            } else {
+                None
+            }
+        })
+        .collect();
+    if truces.is_empty() {
+        return;
+    }
+
+    w.open("active_relations");
+    for (other, expiry) in truces {
+        w.open(other);
+        w.field("truce", "yes");
+        w.field("last_war", save_date(expiry.add_years(-TRUCE_YEARS)));
+        w.close();
+    }
+    w.close();
+}
+
+fn write_units(
+    w: &mut SaveWriter,
+    world: &WorldState,
+    tag: &str,
+    leader_ids: &LeaderIds,
+    unit_id: &mut u32,
+) {
+    let mut next_id = || {
+        *unit_id += 1;
+        format!("id={{ id={} type={} }}", *unit_id, UNIT_ID_TYPE)
+    };
+
+    let mut armies: Vec<_> = world.armies.values().filter(|a| a.owner == tag).collect();
+    armies.sort_by_key(|a| a.id);
+    for army in armies {
+        w.open("army");
+        w.line(next_id());
+        w.quoted("name", &army.name);
+        w.field("location", army.location);
+        for regiment in &army.regiments {
+            w.open("regiment");
+            w.line(next_id());
+            w.quoted("type", regiment_unit_type(regiment.type_));
+            w.num("morale", regiment.morale.to_f32() as f64);
+            // Saves store strength as a fraction of a full 1000-man regiment
+            w.num("strength", regiment.strength.to_f32() as f64 / 1000.0);
+            w.close();
+        }
+        if let Some(general) = army.general {
+            w.line(format_args!(
+                "leader={{ id={} type={} }}",
+                leader_ids.general(general),
+                LEADER_ID_TYPE
+            ));
+        }
+        w.close();
+    }
+
+    let mut fleets: Vec<_> = world.fleets.values().filter(|f| f.owner == tag).collect();
+    fleets.sort_by_key(|f| f.id);
+    for fleet in fleets {
+        w.open("navy");
+        w.line(next_id());
+        w.quoted("name", &fleet.name);
+        w.field("location", fleet.location);
+        for ship in &fleet.ships {
+            w.open("ship");
+            w.line(next_id());
+            w.quoted("type", ship_unit_type(ship.type_));
+            // Hull is a percentage in the sim, a fraction in saves
+            w.num("strength", ship.hull.to_f32() as f64 / 100.0);
+            w.close();
+        }
+        if let Some(admiral) = fleet.admiral {
+            w.line(format_args!(
+                "leader={{ id={} type={} }}",
+                leader_ids.admiral(admiral),
+                LEADER_ID_TYPE
+            ));
+        }
+        w.close();
+    }
+}
+
+/// Subjects, alliances and royal marriages
+fn write_diplomacy(w: &mut SaveWriter, world: &WorldState) {
+    w.depth = 0;
+    w.open("diplomacy");
+
+    let mut subjects: Vec<_> = world.diplomacy.subjects.values().collect();
+    subjects.sort_by(|a, b| a.subject.cmp(&b.subject));
+    for subject in subjects {
+        let Some(subject_type) = world.subject_types.get(subject.subject_type) else {
+            log::debug!(
+                "Skipping {} -> {}: unknown subject type",
+                subject.overlord,
+                subject.subject
+            );
+            continue;
+        };
+        w.open("dependency");
+        w.quoted("first", &subject.overlord);
+        w.quoted("second", &subject.subject);
+        w.quoted("subject_type", &subject_type.name);
+        w.field("start_date", save_date(subject.start_date));
+        w.close();
+    }
+
+    let mut relations: Vec<_> = world
+        .diplomacy
+        .relations
+        .iter()
+        .filter_map(|((a, b), relation)| match relation {
+            RelationType::Alliance => Some(("alliance", a, b)),
+            // Rivals are written per country
+            RelationType::Rival => None,
+        })
+        .chain(
+            world
+                .diplomacy
+                .royal_marriages
+                .iter()
+                .map(|(a, b)| ("royal_marriage", a, b)),
+        )
+        .collect();
+    relations.sort();
+    for (key, first, second) in relations {
+        w.open(key);
+        w.quoted("first", first);
+        w.quoted("second", second);
+        w.close();
+    }
+
+    w.close();
+}
+
+fn write_war(w: &mut SaveWriter, war: &War) {
+    w.depth = 0;
+    w.open("active_war");
+    w.quoted("name", &war.name);
+
+    w.open("history");
+    w.quoted("name", &war.name);
+    if let Some(goal) = &war.war_goal {
+        w.open("war_goal");
+        w.quoted("type", &goal.goal_type);
+        w.quoted("casus_belli", &goal.casus_belli);
+        if let Some(province) = goal.province {
+            w.field("province", province);
+        }
+        w.close();
+    }
+    w.open(save_date(war.start_date));
+    for attacker in &war.attackers {
+        w.quoted("add_attacker", attacker);
+    }
+    for defender in &war.defenders {
+        w.quoted("add_defender", defender);
+    }
+    w.close();
+    w.close();
+
+    w.tag_list("attackers", &war.attackers);
+    w.tag_list("defenders", &war.defenders);
+    if let Some(attacker) = war.attackers.first() {
+        w.quoted("original_attacker", attacker);
+    }
+    if let Some(defender) = war.defenders.first() {
+        w.quoted("original_defender", defender);
+    }
+    w.field("action", save_date(war.start_date));
+    w.close();
+}
+
+// =========================================================================
+// Mappings
+// =========================================================================
+
+/// Representative save unit type for a sim regiment category
+pub fn regiment_unit_type(regiment_type: RegimentType) -> &'static str {
+    match regiment_type {
+        RegimentType::Infantry => "western_medieval_infantry",
+        RegimentType::Cavalry => "western_medieval_knights",
+        RegimentType::Artillery => "large_cast_bronze_mortar",
+    }
+}
+
+/// Representative save unit type for a sim ship category
+pub fn ship_unit_type(ship_type: ShipType) -> &'static str {
+    match ship_type {
+        ShipType::HeavyShip => "carrack",
+        ShipType::LightShip => "barque",
+        ShipType::Galley => "galley",
+        ShipType::Transport => "cog",
+    }
+}
+
+/// Save advisor type for a sim advisor
+///
+/// Advisors hydrated from saves are named `"<type> (skill N)"`; others get a
+/// representative type for their category.
+fn advisor_type_name(name: &str, advisor_type: AdvisorType) -> &str {
+    match name.split_once(" (skill ") {
+        Some((save_type, _)) if !save_type.is_empty() && !save_type.contains(' ') => save_type,
+        _ => match advisor_type {
+            AdvisorType::Administrative => "philosopher",
+            AdvisorType::Diplomatic => "statesman",
+            AdvisorType::Military => "army_reformer",
+        },
+    }
+}
+
+/// Save leader ids. Generals and admirals have separate id spaces in the sim
+/// but share one in saves, so admirals are numbered after the last general.
+struct LeaderIds {
+    admiral_offset: u32,
+}
+
+impl LeaderIds {
+    fn new(world: &WorldState) -> Self {
+        Self {
+            admiral_offset: world.generals.keys().copied().max().unwrap_or(0),
+        }
+    }
+
+    fn general(&self, id: u32) -> u32 {
+        id
+    }
+
+    fn admiral(&self, id: u32) -> u32 {
+        self.admiral_offset + id
+    }
+}
+
+#[cfg(test)]
+#[path = "export_tests.rs"]
+mod tests;
@@ -0,0 +1,469 @@
+use super::*;
+use crate::{
+    ExtractedAdvisor, ExtractedArmy, ExtractedCountry, ExtractedFleet, ExtractedLeader,
+    ExtractedProvince, ExtractedState, ExtractedSubject, ExtractedTruce, ExtractedUnit,
+    ExtractedWar, ExtractedWarGoal, SaveMeta,
+};
+use eu4sim_core::buildings::BuildingDef;
+use eu4sim_core::modifiers::BuildingId;
+use eu4sim_core::state::DiplomacyState;
+use eu4sim_core::subjects::SubjectTypeDef;
+use eu4sim_core::testing::WorldStateBuilder;
+use eu4sim_core::Fixed;
+use std::collections::{BTreeSet, HashMap};
+use std::io::Read as _;
+
+const SEA: u32 = 1000;
+
+fn tags(tags: &[&str]) -> Vec<String> {
+    tags.iter().map(|t| t.to_string()).collect()
+}
+
+fn unit(unit_type: &str, strength: f64, morale: f64) -> ExtractedUnit {
+    ExtractedUnit {
+        unit_type: unit_type.to_string(),
+        strength,
+        morale,
+    }
+}
+
+fn leader(name: &str, fire: u8, shock: u8, maneuver: u8, siege: u8) -> ExtractedLeader {
+    ExtractedLeader {
+        name: name.to_string(),
+        fire,
+        shock,
+        maneuver,
+        siege,
+    }
+}
+
+fn province(id: u32, owner: &str, dev: f64, buildings: &[&str]) -> ExtractedProvince {
+    ExtractedProvince {
+        id,
+        owner: Some(owner.to_string()),
+        base_tax: Some(dev),
+        base_production: Some(dev + 1.0),
+        base_manpower: Some(dev - 1.0),
+        buildings: tags(buildings),
+        ..Default::default()
+    }
+}
+
+fn marketplace() -> BuildingDef {
+    BuildingDef {
+        id: BuildingId(0),
+        name: "marketplace".to_string(),
+        cost: Fixed::from_int(100),
+        time: 12,
+        adm_tech: None,
+        dip_tech: None,
+        mil_tech: None,
+        requires_port: false,
+        manufactory_goods: None,
+        replaces_building: None,
+        local_tax_modifier: None,
+        local_production_efficiency: None,
+        local_trade_power: None,
+        local_manpower_modifier: None,
+        local_sailors_modifier: None,
+        local_defensiveness: None,
+        local_ship_repair: None,
+        local_ship_cost: None,
+        land_forcelimit: None,
+        naval_forcelimit: None,
+        ship_recruit_speed: None,
+        fort_level: None,
+        trade_goods_size: None,
+    }
+}
+
+/// A save touching every section the exporter writes
+fn sample_save() -> ExtractedState {
+    let mut countries = HashMap::new();
+    countries.insert(
+        "FRA".to_string(),
+        ExtractedCountry {
+            tag: "FRA".to_string(),
+            treasury: Some(212.5),
+            current_manpower: Some(24.75),
+            adm_power: Some(150.0),
+            dip_power: Some(80.0),
+            mil_power: Some(45.0),
+            ruler_adm: Some(4),
+            ruler_dip: Some(3),
+            ruler_mil: Some(5),
+            advisors: vec![
+                ExtractedAdvisor {
+                    advisor_type: "treasurer".to_string(),
+                    skill: 2,
+                    is_hired: true,
+                },
+                ExtractedAdvisor {
+                    advisor_type: "army_reformer".to_string(),
+                    skill: 1,
+                    is_hired: true,
+                },
+            ],
+            rivals: tags(&["ENG"]),
+            ..Default::default()
+        },
+    );
+    countries.insert(
+        "ENG".to_string(),
+        ExtractedCountry {
+            tag: "ENG".to_string(),
+            treasury: Some(-15.0),
+            current_manpower: Some(10.0),
+            adm_power: Some(0.0),
+            dip_power: Some(20.0),
+            mil_power: Some(300.0),
+            ruler_adm: Some(1),
+            ruler_dip: Some(2),
+            ruler_mil: Some(6),
+            rivals: tags(&["FRA", "SCO"]),
+            ..Default::default()
+        },
+    );
+    for tag in ["SCO", "PRO"] {
+        countries.insert(
+            tag.to_string(),
+            ExtractedCountry {
+                tag: tag.to_string(),
+                treasury: Some(10.0),
+                current_manpower: Some(2.0),
+                tribute_type: (tag == "PRO").then_some(2),
+                ..Default::default()
+            },
+        );
+    }
+
+    let provinces = [
+        province(183, "FRA", 6.0, &["marketplace"]),
+        province(236, "ENG", 5.0, &[]),
+        province(248, "SCO", 3.0, &[]),
+        province(201, "PRO", 4.0, &[]),
+    ]
+    .into_iter()
+    .map(|p| (p.id, p))
+    .collect();
+
+    let subjects = HashMap::from([(
+        "PRO".to_string(),
+        ExtractedSubject {
+            overlord: "FRA".to_string(),
+            subject: "PRO".to_string(),
+            subject_type: "vassal".to_string(),
+            start_date: Some("1444.11.11".to_string()),
+        },
+    )]);
+
+    ExtractedState {
+        meta: SaveMeta {
+            date: "1450.3.1".to_string(),
+            player: Some("FRA".to_string()),
+            ironman: false,
+            save_version: None,
+        },
+        countries,
+        provinces,
+        subjects,
+        celestial_empire: None,
+        trade_nodes: HashMap::new(),
+        armies: vec![ExtractedArmy {
+            owner: "FRA".to_string(),
+            name: "Armee de Paris".to_string(),
+            location: 183,
+            regiments: vec![
+                unit("western_medieval_infantry", 0.75, 2.5),
+                unit("western_medieval_knights", 1.0, 3.0),
+                unit("large_cast_bronze_mortar", 0.5, 1.25),
+            ],
+            general: Some(leader("Jean de Dunois", 2, 4, 3, 1)),
+        }],
+        fleets: vec![ExtractedFleet {
+            owner: "ENG".to_string(),
+            name: "Channel Fleet".to_string(),
+            location: SEA,
+            ships: vec![
+                unit("carrack", 0.5, 0.0),
+                unit("barque", 1.0, 0.0),
+                unit("cog", 0.25, 0.0),
+            ],
+            admiral: Some(leader("Edward Howard", 3, 2, 4, 0)),
+        }],
+        wars: vec![ExtractedWar {
+            name: "Hundred Years' War".to_string(),
+            attackers: tags(&["ENG"]),
+            defenders: tags(&["FRA", "PRO"]),
+            start_date: Some("1449.9.15".to_string()),
+            war_goal: Some(ExtractedWarGoal {
+                goal_type: "take_claim".to_string(),
+                casus_belli: "cb_claim".to_string(),
+                province: Some(183),
+            }),
+        }],
+        diplomacy: crate::ExtractedDiplomacy {
+            alliances: vec![("FRA".to_string(), "SCO".to_string())],
+            // France and Scotland are both allied and married
+            royal_marriages: vec![
+                ("ENG".to_string(), "SCO".to_string()),
+                ("FRA".to_string(), "SCO".to_string()),
+            ],
+            truces: vec![ExtractedTruce {
+                first: "SCO".to_string(),
+                second: "ENG".to_string(),
+                last_war: "1448.6.1".to_string(),
+            }],
+        },
+        military_and_diplomacy_extracted: true,
+    }
+}
+
+/// Base world with the countries, provinces and definitions the sample save references
+fn hydrated_world(save: &ExtractedState) -> WorldState {
+    let mut builder = WorldStateBuilder::new();
+    let mut countries: Vec<&String> = save.countries.keys().collect();
+    countries.sort();
+    for tag in countries {
+        builder = builder.with_country(tag);
+    }
+    for &id in save.provinces.keys() {
+        builder = builder.with_province(id, None);
+    }
+    let sea = ProvinceState {
+        is_sea: true,
+        ..Default::default()
+    };
+    let mut world = builder.with_province_state(SEA, sea).build();
+
+    let def = marketplace();
+    world.building_name_to_id.insert(def.name.clone(), def.id);
+    world.building_defs.insert(def.id, def);
+    world.subject_types.add(SubjectTypeDef {
+        name: "vassal".to_string(),
+        ..Default::default()
+    });
+
+    crate::hydrate::apply_save(&mut world, save, &HashMap::new()).unwrap();
+    world
+}
+
+fn assert_close(what: &str, expected: Option<f64>, actual: Option<f64>) {
+    let (Some(expected), Some(actual)) = (expected, actual) else {
+        panic!("{}: expected {:?}, got {:?}", what, expected, actual);
+    };
+    assert!(
+        (expected - actual).abs() < 0.01,
+        "{}: expected {}, got {}",
+        what,
+        expected,
+        actual
+    );
+}
+
+fn sorted_pairs(pairs: &[(String, String)]) -> BTreeSet<(String, String)> {
+    pairs
+        .iter()
+        .map(|(a, b)| DiplomacyState::sorted_pair(a, b))
+        .collect()
+}
+
+fn units(units: &[ExtractedUnit]) -> Vec<(String, i64)> {
+    units
+        .iter()
+        .map(|u| (u.unit_type.clone(), (u.strength * 1000.0).round() as i64))
+        .collect()
+}
+
+#[test]
+fn test_round_trip_through_text_save() {
+    let original = sample_save();
+    let world = hydrated_world(&original);
+    let options = ExportOptions {
+        player: Some("FRA".to_string()),
+        compress: false,
+    };
+
+    let text = gamestate_text(&world, &options);
+    let parsed = crate::parse::parse_text_content(&text).unwrap();
+
+    assert_eq!(parsed.meta.date, original.meta.date);
+    assert_eq!(parsed.meta.player, original.meta.player);
+
+    // Provinces (the sea province isn't written)
+    assert_eq!(parsed.provinces.len(), original.provinces.len());
+    for (id, expected) in &original.provinces {
+        let actual = &parsed.provinces[id];
+        assert_eq!(actual.owner, expected.owner, "province {} owner", id);
+        assert_close("base_tax", expected.base_tax, actual.base_tax);
+        assert_close(
+            "base_production",
+            expected.base_production,
+            actual.base_production,
+        );
+        assert_close(
+            "base_manpower",
+            expected.base_manpower,
+            actual.base_manpower,
+        );
+        assert_eq!(actual.buildings, expected.buildings, "province {}", id);
+    }
+
+    // Countries
+    assert_eq!(parsed.countries.len(), original.countries.len());
+    for (tag, expected) in &original.countries {
+        let actual = &parsed.countries[tag];
+        assert_close("treasury", expected.treasury, actual.treasury);
+        assert_close(
+            "manpower",
+            expected.current_manpower,
+            actual.current_manpower,
+        );
+        assert_close(
+            "adm_power",
+            expected.adm_power.or(Some(0.0)),
+            actual.adm_power,
+        );
+        assert_close(
+            "dip_power",
+            expected.dip_power.or(Some(0.0)),
+            actual.dip_power,
+        );
+        assert_close(
+            "mil_power",
+            expected.mil_power.or(Some(0.0)),
+            actual.mil_power,
+        );
+        if expected.ruler_adm.is_some() {
+            assert_eq!(
+                (actual.ruler_adm, actual.ruler_dip, actual.ruler_mil),
+                (expected.ruler_adm, expected.ruler_dip, expected.ruler_mil),
+                "{} ruler",
+                tag
+            );
+        }
+        assert_eq!(actual.tribute_type, expected.tribute_type, "{}", tag);
+        assert_eq!(actual.advisors, expected.advisors, "{} advisors", tag);
+
+        let mut rivals = actual.rivals.clone();
+        rivals.sort();
+        assert_eq!(rivals, expected.rivals, "{} rivals", tag);
+    }
+    assert_eq!(parsed.countries["FRA"].owned_province_ids, vec![183]);
+
+    // Subjects
+    let subject = &parsed.subjects["PRO"];
+    assert_eq!(subject.overlord, "FRA");
+    assert_eq!(subject.subject_type, "vassal");
+    assert_eq!(subject.start_date.as_deref(), Some("1444.11.11"));
+
+    // Armies and fleets
+    assert_eq!(parsed.armies.len(), 1);
+    let (expected, actual) = (&original.armies[0], &parsed.armies[0]);
+    assert_eq!(
+        (&actual.owner, &actual.name),
+        (&expected.owner, &expected.name)
+    );
+    assert_eq!(actual.location, expected.location);
+    assert_eq!(units(&actual.regiments), units(&expected.regiments));
+    for (e, a) in expected.regiments.iter().zip(&actual.regiments) {
+        assert_close("morale", Some(e.morale), Some(a.morale));
+    }
+    assert_eq!(actual.general, expected.general);
+
+    assert_eq!(parsed.fleets.len(), 1);
+    let (expected, actual) = (&original.fleets[0], &parsed.fleets[0]);
+    assert_eq!(
+        (&actual.owner, &actual.name),
+        (&expected.owner, &expected.name)
+    );
+    assert_eq!(actual.location, expected.location);
+    assert_eq!(units(&actual.ships), units(&expected.ships));
+    assert_eq!(actual.admiral, expected.admiral);
+
+    // Wars
+    assert_eq!(parsed.wars.len(), 1);
+    let (expected, actual) = (&original.wars[0], &parsed.wars[0]);
+    assert_eq!(actual.name, expected.name);
+    assert_eq!(actual.attackers, expected.attackers);
+    assert_eq!(actual.defenders, expected.defenders);
+    assert_eq!(actual.start_date, expected.start_date);
+    assert_eq!(actual.war_goal, expected.war_goal);
+
+    // Diplomacy
+    assert_eq!(
+        sorted_pairs(&parsed.diplomacy.alliances),
+        sorted_pairs(&original.diplomacy.alliances)
+    );
+    assert_eq!(
+        sorted_pairs(&parsed.diplomacy.royal_marriages),
+        sorted_pairs(&original.diplomacy.royal_marriages)
+    );
+    // Truces are written on both sides
+    let truces: BTreeSet<_> = parsed
+        .diplomacy
+        .truces
+        .iter()
+        .map(|t| {
+            (
+                DiplomacyState::sorted_pair(&t.first, &t.second),
+                &t.last_war,
+            )
+        })
+        .collect();
+    let truce = &original.diplomacy.truces[0];
+    assert_eq!(parsed.diplomacy.truces.len(), 2);
+    assert_eq!(
+        truces.into_iter().collect::<Vec<_>>(),
+        vec![(
+            DiplomacyState::sorted_pair(&truce.first, &truce.second),
+            &truce.last_war
+        )]
+    );
+}
+
+#[test]
+fn test_round_trip_is_stable() {
+    // A second hydrate -> export pass reproduces the first export
+    let world = hydrated_world(&sample_save());
+    let options = ExportOptions::default();
+    let first = gamestate_text(&world, &options);
+
+    let reparsed = crate::parse::parse_text_content(&first).unwrap();
+    let second = gamestate_text(&hydrated_world(&reparsed), &options);
+
+    assert_eq!(first, second);
+}
+
+#[test]
+fn test_compressed_save_has_meta_and_gamestate() {
+    let world = hydrated_world(&sample_save());
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("future.eu4");
+    let options = ExportOptions {
+        player: Some("FRA".to_string()),
+        compress: true,
+    };
+
+    export_save(&world, &path, &options).unwrap();
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    let mut entry = |name: &str| {
+        let mut text = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    };
+
+    let meta = entry("meta");
+    assert!(meta.starts_with("EU4txt\n"));
+    assert!(meta.contains("date=1450.3.1\n"));
+    assert!(meta.contains("player=\"FRA\"\n"));
+    assert!(!meta.contains("provinces={"));
+
+    let gamestate = entry("gamestate");
+    assert_eq!(gamestate, gamestate_text(&world, &options));
+}
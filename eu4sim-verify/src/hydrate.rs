@@ -3,12 +3,15 @@
 //! Converts parsed save data into a sim-compatible WorldState by:
 //! 1. Loading static data from game files (adjacency, terrain, trade nodes)
 //! 2. Overriding provinces/countries with save file values
-//! 3. Stubbing out armies/fleets for passive simulation
+//! 3. Replacing armies, fleets, wars and relations with those in the save
 
-use crate::{ExtractedState, ExtractedTradeNode};
+use crate::{ExtractedLeader, ExtractedState, ExtractedTradeNode};
 use anyhow::Result;
 use eu4data::adjacency::AdjacencyGraph;
-use eu4sim_core::state::Date;
+use eu4sim_core::state::{
+    Admiral, Army, Date, DiplomacyState, Fleet, General, Regiment, RegimentType, RelationType,
+    Ship, ShipType, War, WarGoal,
+};
 use eu4sim_core::trade::{MerchantAction, MerchantState};
 use eu4sim_core::{Fixed, Mod32, WorldState};
 use std::collections::HashMap;
@@ -19,23 +22,69 @@ use std::path::Path;
 /// This function:
 /// 1. Loads base state from EU4 game files (via eu4sim::loader)
 /// 2. Overrides province/country data with values from the save
-/// 3. Clears armies/fleets for passive (no-AI) simulation
+/// 3. Replaces armies, fleets, wars, alliances, royal marriages, truces and rivals
+///    with those in the save
 pub fn hydrate_from_save(
     game_path: &Path,
     save: &ExtractedState,
 ) -> Result<(WorldState, AdjacencyGraph)> {
-    // Parse date from save meta
-    let date = parse_date(&save.meta.date)?;
+    let base = GameBase::load(game_path, parse_date(&save.meta.date)?)?;
+    let world = base.hydrate(save)?;
+    Ok((world, base.adjacency))
+}
 
-    // Load base state from game files
-    log::info!("Loading game data from {:?}", game_path);
-    let (mut world, adjacency) = eu4sim::loader::load_initial_state(game_path, date, 0)?;
+/// Game-file data loaded once and reused for many saves
+///
+/// Loading the game files dominates [`hydrate_from_save`]; callers hydrating
+/// a series of saves (see [`crate::campaign`]) load a `GameBase` once and
+/// [`hydrate`](Self::hydrate) each save onto a copy of it.
+pub struct GameBase {
+    /// State from game files at the load date, before any save is applied
+    pub world: WorldState,
+    pub adjacency: AdjacencyGraph,
+    /// Unit name -> `common/units` type, for [`apply_save`]
+    pub unit_categories: HashMap<String, String>,
+}
 
-    log::info!(
-        "Base state loaded: {} provinces, {} countries",
-        world.provinces.len(),
-        world.countries.len()
-    );
+impl GameBase {
+    /// Load static data from the game files at `date`
+    pub fn load(game_path: &Path, date: Date) -> Result<Self> {
+        log::info!("Loading game data from {:?}", game_path);
+        let (world, adjacency) = eu4sim::loader::load_initial_state(game_path, date, 0)?;
+
+        log::info!(
+            "Base state loaded: {} provinces, {} countries",
+            world.provinces.len(),
+            world.countries.len()
+        );
+
+        Ok(Self {
+            world,
+            adjacency,
+            unit_categories: load_unit_categories(game_path),
+        })
+    }
+
+    /// A fresh copy of the base state with `save` applied
+    pub fn hydrate(&self, save: &ExtractedState) -> Result<WorldState> {
+        let mut world = self.world.clone();
+        apply_save(&mut world, save, &self.unit_categories)?;
+        Ok(world)
+    }
+}
+
+/// Override a base WorldState with save data
+///
+/// This is the part of [`hydrate_from_save`] that doesn't touch game files, so
+/// it can also be applied to hand-built states. `unit_categories` maps unit
+/// names to their `common/units` type; unknown names are guessed from the name.
+pub fn apply_save(
+    world: &mut WorldState,
+    save: &ExtractedState,
+    unit_categories: &HashMap<String, String>,
+) -> Result<()> {
+    let date = parse_date(&save.meta.date)?;
+    world.date = date;
 
     // Override provinces with save data
     let mut provinces_updated = 0;
@@ -327,19 +376,22 @@ pub fn hydrate_from_save(
         subjects_updated
     );
 
-    // Use total expenses MINUS fort maintenance as fixed expenses
-    // Fort maintenance is calculated from provinces, but everything else
-    // (army, navy, state maintenance, etc.) comes from the save ledger
+    // Use total expenses MINUS fort, army and navy maintenance as fixed expenses
+    // Fort maintenance is calculated from provinces and unit maintenance from the
+    // hydrated armies/fleets, but everything else (state maintenance, etc.)
+    // comes from the save ledger
     for (tag, save_country) in &save.countries {
         if let Some(country) = world.countries.get_mut(tag) {
             let total_expenses = save_country.total_monthly_expenses.unwrap_or(0.0);
             let fort_maint = save_country.fort_maintenance.unwrap_or(0.0);
+            let unit_maint = save_country.army_maintenance.unwrap_or(0.0)
+                + save_country.navy_maintenance.unwrap_or(0.0);
 
-            // Fixed expenses = total - fort_maintenance
-            // Fort maintenance is calculated from province.fort_level in expense system
-            // Everything else (army, navy, state maintenance, advisors, corruption, etc.)
+            // Fixed expenses = total - fort - army - navy maintenance
+            // Those three are recalculated by the expense system each month.
+            // Everything else (state maintenance, advisors, corruption, etc.)
             // is baked into the ledger total and applied as fixed expense
-            let fixed_expenses = total_expenses - fort_maint;
+            let fixed_expenses = (total_expenses - fort_maint - unit_maint).max(0.0);
 
             country.fixed_expenses = Fixed::from_f32(fixed_expenses as f32);
 
@@ -373,28 +425,18 @@ pub fn hydrate_from_save(
         }
     }
 
-    // Clear armies and fleets for passive simulation to avoid combat/movement AI
-    let armies_cleared = world.armies.len();
-    let fleets_cleared = world.fleets.len();
-
-    // Log fleet details for debugging
-    let korea_fleets: Vec<_> = world.fleets.values().filter(|f| f.owner == "KOR").collect();
-    log::info!(
-        "Korea has {} fleets with {} total ships",
-        korea_fleets.len(),
-        korea_fleets.iter().map(|f| f.ships.len()).sum::<usize>()
-    );
+    if save.military_and_diplomacy_extracted {
+        // Replace generated armies/fleets with the ones in the save
+        hydrate_military(world, save, unit_categories);
 
-    world.armies.clear();
-    world.fleets.clear();
-    log::info!(
-        "Cleared {} armies and {} fleets for passive simulation (maintenance costs preserved)",
-        armies_cleared,
-        fleets_cleared
-    );
+        // Replace wars and bilateral relations with the ones in the save
+        hydrate_diplomacy(world, save, date);
+    } else {
+        log::warn!("Save has no army, war or relation data; keeping the generated ones");
+    }
 
     // Hydrate trade state from save (merchants, power, node values)
-    hydrate_trade_state(&mut world, &save.trade_nodes);
+    hydrate_trade_state(world, &save.trade_nodes);
 
     // Hydrate celestial empire state from save
     if let Some(ref ce) = save.celestial_empire {
@@ -428,7 +470,7 @@ pub fn hydrate_from_save(
         }
     }
 
-    Ok((world, adjacency))
+    Ok(())
 }
 
 /// Map celestial reform name string to CelestialReformId
@@ -537,6 +579,397 @@ fn hydrate_trade_state(world: &mut WorldState, trade_nodes: &HashMap<String, Ext
     );
 }
 
+/// Load unit definition name -> category ("infantry", "heavy_ship", ...) from
+/// `common/units/*.txt`.
+///
+/// Returns an empty map if the directory can't be read; callers fall back to
+/// guessing the category from the unit name.
+fn load_unit_categories(game_path: &Path) -> HashMap<String, String> {
+    let mut categories = HashMap::new();
+    let Ok(entries) = std::fs::read_dir(game_path.join("common/units")) else {
+        log::debug!("No unit definitions found, guessing unit types from names");
+        return categories;
+    };
+
+    let type_re = regex::Regex::new(r"(?m)^\s*type\s*=\s*(\w+)").unwrap();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let (Some(name), Ok(bytes)) = (
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .map(str::to_string),
+            std::fs::read(&path),
+        ) else {
+            continue;
+        };
+        let text = String::from_utf8_lossy(&bytes);
+        if let Some(category) = type_re.captures(&text).and_then(|c| c.get(1)) {
+            categories.insert(name, category.as_str().to_string());
+        }
+    }
+
+    log::debug!("Loaded {} unit definitions", categories.len());
+    categories
+}
+
+/// Resolve a save regiment type (e.g. "western_medieval_knights") to a RegimentType.
+fn regiment_type(unit_type: &str, categories: &HashMap<String, String>) -> RegimentType {
+    match categories.get(unit_type).map(String::as_str) {
+        Some("cavalry") => RegimentType::Cavalry,
+        Some("artillery") => RegimentType::Artillery,
+        Some(_) => RegimentType::Infantry,
+        None => {
+            const ARTILLERY: &[&str] = &["artillery", "cannon", "bombard", "mortar", "culverin"];
+            const CAVALRY: &[&str] = &[
+                "cavalry",
+                "knights",
+                "horse",
+                "hussar",
+                "cuirassier",
+                "dragoon",
+                "lancer",
+                "chevauchee",
+                "carabinier",
+                "raider",
+                "rider",
+                "archer",
+                "cossack",
+            ];
+            if ARTILLERY.iter().any(|w| unit_type.contains(w)) {
+                RegimentType::Artillery
+            } else if CAVALRY.iter().any(|w| unit_type.contains(w)) {
+                RegimentType::Cavalry
+            } else {
+                RegimentType::Infantry
+            }
+        }
+    }
+}
+
+/// Resolve a save ship type (e.g. "carrack") to a ShipType.
+fn ship_type(unit_type: &str, categories: &HashMap<String, String>) -> ShipType {
+    match categories.get(unit_type).map(String::as_str) {
+        Some("light_ship") => ShipType::LightShip,
+        Some("galley") => ShipType::Galley,
+        Some("transport") => ShipType::Transport,
+        Some(_) => ShipType::HeavyShip,
+        None => {
+            const TRANSPORT: &[&str] = &["transport", "cog", "flute", "brig", "merchantman"];
+            const GALLEY: &[&str] = &["galley", "galleass", "chebeck", "galiot", "archipelago"];
+            const LIGHT: &[&str] = &["barque", "caravel", "frigate", "light"];
+            if TRANSPORT.iter().any(|w| unit_type.contains(w)) {
+                ShipType::Transport
+            } else if GALLEY.iter().any(|w| unit_type.contains(w)) {
+                ShipType::Galley
+            } else if LIGHT.iter().any(|w| unit_type.contains(w)) {
+                ShipType::LightShip
+            } else {
+                ShipType::HeavyShip
+            }
+        }
+    }
+}
+
+/// Replace armies, fleets and their leaders with those in the save.
+///
+/// Units owned by unknown countries or located in unknown provinces are dropped.
+/// Armies standing in a sea province are embarked on a fleet of the same owner
+/// in that province. Battles and sieges aren't in the extracted state, so they
+/// start fresh and are re-engaged by the combat/siege systems.
+fn hydrate_military(
+    world: &mut WorldState,
+    save: &ExtractedState,
+    unit_categories: &HashMap<String, String>,
+) {
+    world.armies.clear();
+    world.fleets.clear();
+    world.generals.clear();
+    world.admirals.clear();
+    world.battles.clear();
+    world.naval_battles.clear();
+    world.sieges.clear();
+    world.next_army_id = 1;
+    world.next_fleet_id = 1;
+    world.next_general_id = 1;
+    world.next_admiral_id = 1;
+
+    for save_fleet in &save.fleets {
+        if !world.countries.contains_key(&save_fleet.owner)
+            || !world.provinces.contains_key(&save_fleet.location)
+            || save_fleet.ships.is_empty()
+        {
+            log::trace!(
+                "Skipping fleet '{}' of {} at {}",
+                save_fleet.name,
+                save_fleet.owner,
+                save_fleet.location
+            );
+            continue;
+        }
+
+        let admiral = save_fleet
+            .admiral
+            .as_ref()
+            .map(|leader| add_admiral(world, &save_fleet.owner, leader));
+
+        let id = world.next_fleet_id;
+        world.next_fleet_id += 1;
+        world.fleets.insert(
+            id,
+            Fleet {
+                id,
+                name: save_fleet.name.clone(),
+                owner: save_fleet.owner.clone(),
+                location: save_fleet.location,
+                ships: save_fleet
+                    .ships
+                    .iter()
+                    .map(|ship| Ship {
+                        type_: ship_type(&ship.unit_type, unit_categories),
+                        hull: Fixed::from_f32((ship.strength * 100.0) as f32),
+                        durability: Fixed::from_int(100),
+                    })
+                    .collect(),
+                embarked_armies: Vec::new(),
+                movement: None,
+                admiral,
+                in_battle: None,
+            },
+        );
+    }
+
+    for save_army in &save.armies {
+        if !world.countries.contains_key(&save_army.owner)
+            || !world.provinces.contains_key(&save_army.location)
+            || save_army.regiments.is_empty()
+        {
+            log::trace!(
+                "Skipping army '{}' of {} at {}",
+                save_army.name,
+                save_army.owner,
+                save_army.location
+            );
+            continue;
+        }
+
+        let regiments = save_army
+            .regiments
+            .iter()
+            .map(|reg| Regiment {
+                type_: regiment_type(&reg.unit_type, unit_categories),
+                // Save strength is a fraction of a full 1000-man regiment
+                strength: Fixed::from_f32((reg.strength * 1000.0) as f32),
+                morale: Fixed::from_f32(reg.morale as f32),
+            })
+            .collect();
+
+        let id = world.next_army_id;
+        world.next_army_id += 1;
+        let mut army = Army::new(
+            id,
+            save_army.name.clone(),
+            save_army.owner.clone(),
+            save_army.location,
+            regiments,
+        );
+
+        army.general = save_army
+            .general
+            .as_ref()
+            .map(|leader| add_general(world, &save_army.owner, leader));
+
+        let at_sea = world
+            .provinces
+            .get(&save_army.location)
+            .is_some_and(|p| p.is_sea);
+        if at_sea {
+            let transport = world
+                .fleets
+                .values()
+                .filter(|f| f.owner == save_army.owner && f.location == save_army.location)
+                .map(|f| f.id)
+                .min();
+            if let Some(fleet_id) = transport {
+                army.embarked_on = Some(fleet_id);
+                if let Some(fleet) = world.fleets.get_mut(&fleet_id) {
+                    fleet.embarked_armies.push(id);
+                }
+            }
+        }
+
+        world.armies.insert(id, army);
+    }
+
+    log::info!(
+        "Hydrated {} armies ({} generals) and {} fleets ({} admirals) from save",
+        world.armies.len(),
+        world.generals.len(),
+        world.fleets.len(),
+        world.admirals.len()
+    );
+}
+
+/// Register a general for `owner` and return its ID.
+fn add_general(world: &mut WorldState, owner: &str, leader: &ExtractedLeader) -> u32 {
+    let id = world.next_general_id;
+    world.next_general_id += 1;
+    world.generals.insert(
+        id,
+        General {
+            id,
+            name: leader.name.clone(),
+            owner: owner.to_string(),
+            fire: leader.fire.min(6),
+            shock: leader.shock.min(6),
+            maneuver: leader.maneuver.min(6),
+            siege: leader.siege.min(6),
+        },
+    );
+    id
+}
+
+/// Register an admiral for `owner` and return its ID.
+fn add_admiral(world: &mut WorldState, owner: &str, leader: &ExtractedLeader) -> u32 {
+    let id = world.next_admiral_id;
+    world.next_admiral_id += 1;
+    world.admirals.insert(
+        id,
+        Admiral {
+            id,
+            name: leader.name.clone(),
+            owner: owner.to_string(),
+            fire: leader.fire.min(6),
+            shock: leader.shock.min(6),
+            maneuver: leader.maneuver.min(6),
+            siege: leader.siege.min(6),
+        },
+    );
+    id
+}
+
+/// Replace wars, alliances, royal marriages, truces and rivals with those in the save.
+///
+/// War scores start at zero: occupation score is recomputed monthly, but battle
+/// score history isn't in the extracted state.
+fn hydrate_diplomacy(world: &mut WorldState, save: &ExtractedState, date: Date) {
+    // Wars
+    world.diplomacy.wars.clear();
+    for save_war in &save.wars {
+        let known = |tags: &[String]| -> Vec<String> {
+            tags.iter()
+                .filter(|t| world.countries.contains_key(t.as_str()))
+                .cloned()
+                .collect()
+        };
+        let attackers = known(&save_war.attackers);
+        let defenders = known(&save_war.defenders);
+        if attackers.is_empty() || defenders.is_empty() {
+            log::debug!("Skipping war '{}': no known participants", save_war.name);
+            continue;
+        }
+
+        let id = world.diplomacy.next_war_id;
+        world.diplomacy.next_war_id += 1;
+        world.diplomacy.wars.insert(
+            id,
+            War {
+                id,
+                name: save_war.name.clone(),
+                attackers,
+                defenders,
+                start_date: save_war
+                    .start_date
+                    .as_deref()
+                    .and_then(|d| parse_date(d).ok())
+                    .unwrap_or(date),
+                attacker_score: 0,
+                attacker_battle_score: 0,
+                defender_score: 0,
+                defender_battle_score: 0,
+                pending_peace: None,
+                war_goal: save_war.war_goal.as_ref().map(|goal| WarGoal {
+                    casus_belli: goal.casus_belli.clone(),
+                    goal_type: goal.goal_type.clone(),
+                    province: goal.province,
+                }),
+            },
+        );
+    }
+
+    // Alliances and royal marriages (a pair can be both). Rival relations
+    // are rebuilt from the save's rivals below.
+    world.diplomacy.relations.clear();
+    world.diplomacy.royal_marriages.clear();
+    let known = |(first, second): &(String, String)| {
+        (world.countries.contains_key(first) && world.countries.contains_key(second))
+            .then(|| DiplomacyState::sorted_pair(first, second))
+    };
+    let alliances: Vec<_> = save.diplomacy.alliances.iter().filter_map(known).collect();
+    let marriages: Vec<_> = save
+        .diplomacy
+        .royal_marriages
+        .iter()
+        .filter_map(known)
+        .collect();
+    for pair in alliances {
+        world
+            .diplomacy
+            .relations
+            .insert(pair, RelationType::Alliance);
+    }
+    world.diplomacy.royal_marriages.extend(marriages);
+
+    // Truces: saves record when the war ended, the sim stores expiry.
+    // Use the same 5-year length the sim gives new truces.
+    world.diplomacy.truces.clear();
+    for truce in &save.diplomacy.truces {
+        let Ok(last_war) = parse_date(&truce.last_war) else {
+            continue;
+        };
+        let expiry = last_war.add_years(5);
+        if expiry > date {
+            world
+                .diplomacy
+                .create_truce(&truce.first, &truce.second, expiry);
+        }
+    }
+
+    // Rivals, kept on each country and as a relation per pair. The relation
+    // map holds one type per pair, so an alliance isn't overwritten.
+    for country in world.countries.values_mut() {
+        country.rivals.clear();
+    }
+    for (tag, save_country) in &save.countries {
+        if !world.countries.contains_key(tag) {
+            continue;
+        }
+        let rivals: Vec<String> = save_country
+            .rivals
+            .iter()
+            .filter(|r| world.countries.contains_key(r.as_str()))
+            .cloned()
+            .collect();
+        for rival in &rivals {
+            world
+                .diplomacy
+                .relations
+                .entry(DiplomacyState::sorted_pair(tag, rival))
+                .or_insert(RelationType::Rival);
+        }
+        if let Some(country) = world.countries.get_mut(tag) {
+            country.rivals = rivals.into_iter().collect();
+        }
+    }
+
+    log::info!(
+        "Hydrated {} wars, {} relations, {} royal marriages and {} truces from save",
+        world.diplomacy.wars.len(),
+        world.diplomacy.relations.len(),
+        world.diplomacy.royal_marriages.len(),
+        world.diplomacy.truces.len()
+    );
+}
+
 /// Parse date string "YYYY.MM.DD" into Date
 fn parse_date(date_str: &str) -> Result<Date> {
     let parts: Vec<&str> = date_str.split('.').collect();
@@ -604,4 +1037,260 @@ mod tests {
         assert!(parse_date("1444-11-11").is_err());
         assert!(parse_date("1444.11").is_err());
     }
+
+    fn save_with(
+        armies: Vec<crate::ExtractedArmy>,
+        fleets: Vec<crate::ExtractedFleet>,
+    ) -> ExtractedState {
+        ExtractedState {
+            meta: crate::SaveMeta {
+                date: "1450.1.1".to_string(),
+                player: None,
+                ironman: false,
+                save_version: None,
+            },
+            countries: HashMap::new(),
+            provinces: HashMap::new(),
+            subjects: HashMap::new(),
+            celestial_empire: None,
+            trade_nodes: HashMap::new(),
+            armies,
+            fleets,
+            wars: Vec::new(),
+            diplomacy: Default::default(),
+            military_and_diplomacy_extracted: true,
+        }
+    }
+
+    fn unit(unit_type: &str, strength: f64) -> crate::ExtractedUnit {
+        crate::ExtractedUnit {
+            unit_type: unit_type.to_string(),
+            strength,
+            morale: 2.5,
+        }
+    }
+
+    #[test]
+    fn test_unit_type_fallbacks() {
+        let none = HashMap::new();
+        assert_eq!(
+            regiment_type("western_medieval_knights", &none),
+            RegimentType::Cavalry
+        );
+        assert_eq!(
+            regiment_type("large_cast_bronze_mortar", &none),
+            RegimentType::Artillery
+        );
+        assert_eq!(
+            regiment_type("western_medieval_infantry", &none),
+            RegimentType::Infantry
+        );
+        assert_eq!(ship_type("carrack", &none), ShipType::HeavyShip);
+        assert_eq!(ship_type("cog", &none), ShipType::Transport);
+
+        // Game data wins over name heuristics
+        let defs = HashMap::from([("odd_cog".to_string(), "galley".to_string())]);
+        assert_eq!(ship_type("odd_cog", &defs), ShipType::Galley);
+    }
+
+    #[test]
+    fn test_hydrate_military() {
+        let sea = eu4sim_core::state::ProvinceState {
+            is_sea: true,
+            ..Default::default()
+        };
+        let mut world = eu4sim_core::testing::WorldStateBuilder::new()
+            .with_country("FRA")
+            .with_province(183, Some("FRA"))
+            .with_province_state(1275, sea)
+            .build();
+        world.armies.insert(
+            99,
+            Army::new(99, "Generated".to_string(), "FRA".to_string(), 183, vec![]),
+        );
+
+        let leader = ExtractedLeader {
+            name: "Jean de Dunois".to_string(),
+            fire: 2,
+            shock: 4,
+            maneuver: 3,
+            siege: 1,
+        };
+        let save = save_with(
+            vec![
+                crate::ExtractedArmy {
+                    owner: "FRA".to_string(),
+                    name: "Armee de Paris".to_string(),
+                    location: 183,
+                    regiments: vec![
+                        unit("western_medieval_infantry", 0.5),
+                        unit("western_medieval_knights", 1.0),
+                    ],
+                    general: Some(leader),
+                },
+                crate::ExtractedArmy {
+                    owner: "FRA".to_string(),
+                    name: "Expedition".to_string(),
+                    location: 1275,
+                    regiments: vec![unit("western_medieval_infantry", 1.0)],
+                    general: None,
+                },
+                // Unknown owner is dropped
+                crate::ExtractedArmy {
+                    owner: "XXX".to_string(),
+                    name: "Ghosts".to_string(),
+                    location: 183,
+                    regiments: vec![unit("western_medieval_infantry", 1.0)],
+                    general: None,
+                },
+            ],
+            vec![crate::ExtractedFleet {
+                owner: "FRA".to_string(),
+                name: "1st Fleet".to_string(),
+                location: 1275,
+                ships: vec![unit("carrack", 0.5), unit("cog", 1.0)],
+                admiral: None,
+            }],
+        );
+
+        hydrate_military(&mut world, &save, &HashMap::new());
+
+        assert_eq!(world.armies.len(), 2);
+        let army = world.armies.values().find(|a| a.location == 183).unwrap();
+        assert_eq!(army.name, "Armee de Paris");
+        assert_eq!(army.composition(), (1, 1, 0));
+        assert_eq!(army.regiments[0].strength, Fixed::from_int(500));
+        let general = &world.generals[&army.general.unwrap()];
+        assert_eq!((general.fire, general.shock, general.maneuver), (2, 4, 3));
+
+        let fleet = world.fleets.values().next().unwrap();
+        assert_eq!(fleet.ships[0].type_, ShipType::HeavyShip);
+        assert_eq!(fleet.ships[0].hull, Fixed::from_int(50));
+        assert_eq!(fleet.ships[1].type_, ShipType::Transport);
+
+        // The army at sea is aboard the fleet in its province
+        let expedition = world.armies.values().find(|a| a.location == 1275).unwrap();
+        assert_eq!(expedition.embarked_on, Some(fleet.id));
+        assert_eq!(fleet.embarked_armies, vec![expedition.id]);
+    }
+
+    #[test]
+    fn test_apply_save_keeps_units_and_relations_it_did_not_extract() {
+        let mut world = eu4sim_core::testing::WorldStateBuilder::new()
+            .with_country("FRA")
+            .with_country("SCO")
+            .with_province(183, Some("FRA"))
+            .build();
+        world.armies.insert(
+            1,
+            Army::new(1, "Generated".to_string(), "FRA".to_string(), 183, vec![]),
+        );
+        world.diplomacy.relations.insert(
+            DiplomacyState::sorted_pair("FRA", "SCO"),
+            RelationType::Alliance,
+        );
+
+        // A binary save that couldn't be melted
+        let mut save = save_with(Vec::new(), Vec::new());
+        save.military_and_diplomacy_extracted = false;
+        apply_save(&mut world, &save, &HashMap::new()).unwrap();
+
+        assert_eq!(world.armies.len(), 1);
+        assert_eq!(world.diplomacy.get_allies("FRA"), vec!["SCO".to_string()]);
+    }
+
+    #[test]
+    fn test_hydrate_diplomacy() {
+        let mut world = eu4sim_core::testing::WorldStateBuilder::new()
+            .with_country("FRA")
+            .with_country("ENG")
+            .with_country("SCO")
+            .with_country("CAS")
+            .with_country("ARA")
+            .build();
+        let date = Date::new(1450, 1, 1);
+        // A 1444 rivalry the save no longer has
+        world.diplomacy.relations.insert(
+            DiplomacyState::sorted_pair("ENG", "SCO"),
+            RelationType::Rival,
+        );
+        world
+            .countries
+            .get_mut("SCO")
+            .unwrap()
+            .rivals
+            .insert("ENG".to_string());
+
+        let mut save = save_with(Vec::new(), Vec::new());
+        save.wars.push(crate::ExtractedWar {
+            name: "Hundred Years' War".to_string(),
+            attackers: vec!["ENG".to_string(), "XXX".to_string()],
+            defenders: vec!["FRA".to_string()],
+            start_date: Some("1449.9.15".to_string()),
+            war_goal: Some(crate::ExtractedWarGoal {
+                goal_type: "take_claim".to_string(),
+                casus_belli: "cb_claim".to_string(),
+                province: Some(183),
+            }),
+        });
+        save.diplomacy.alliances = vec![
+            ("FRA".to_string(), "SCO".to_string()),
+            ("CAS".to_string(), "ARA".to_string()),
+        ];
+        save.diplomacy.royal_marriages = vec![("ARA".to_string(), "CAS".to_string())];
+        save.diplomacy.truces = vec![
+            crate::ExtractedTruce {
+                first: "SCO".to_string(),
+                second: "ENG".to_string(),
+                last_war: "1448.6.1".to_string(),
+            },
+            // Already expired
+            crate::ExtractedTruce {
+                first: "CAS".to_string(),
+                second: "FRA".to_string(),
+                last_war: "1440.1.1".to_string(),
+            },
+        ];
+        save.countries.insert(
+            "FRA".to_string(),
+            crate::ExtractedCountry {
+                tag: "FRA".to_string(),
+                rivals: vec!["ENG".to_string(), "XXX".to_string()],
+                ..Default::default()
+            },
+        );
+
+        hydrate_diplomacy(&mut world, &save, date);
+
+        assert_eq!(world.diplomacy.wars.len(), 1);
+        let war = world.diplomacy.wars.values().next().unwrap();
+        assert_eq!(war.attackers, vec!["ENG".to_string()]);
+        assert_eq!(war.defenders, vec!["FRA".to_string()]);
+        assert_eq!(war.start_date, Date::new(1449, 9, 15));
+        assert_eq!(war.war_goal.as_ref().unwrap().province, Some(183));
+        assert!(world.diplomacy.are_at_war("FRA", "ENG"));
+
+        let relation = |a: &str, b: &str| {
+            world
+                .diplomacy
+                .relations
+                .get(&DiplomacyState::sorted_pair(a, b))
+                .copied()
+        };
+        assert_eq!(relation("FRA", "SCO"), Some(RelationType::Alliance));
+        // Castile and Aragon are both allied and married
+        assert_eq!(relation("ARA", "CAS"), Some(RelationType::Alliance));
+        assert!(world.diplomacy.has_royal_marriage("CAS", "ARA"));
+        assert!(!world.diplomacy.has_royal_marriage("FRA", "SCO"));
+
+        assert!(world.diplomacy.has_active_truce("ENG", "SCO", date));
+        assert!(!world.diplomacy.has_active_truce("CAS", "FRA", date));
+
+        let rivals = &world.countries.get("FRA").unwrap().rivals;
+        assert!(rivals.contains("ENG"));
+        assert_eq!(rivals.len(), 1);
+        assert_eq!(relation("ENG", "FRA"), Some(RelationType::Rival));
+        assert!(world.countries.get("SCO").unwrap().rivals.is_empty());
+        assert_eq!(relation("ENG", "SCO"), None);
+    }
 }
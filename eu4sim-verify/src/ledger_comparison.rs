@@ -1,13 +1,289 @@
 //! Ledger comparison utilities for debugging income/expense deltas
+//!
+//! Lines up a save's last-month ledger (`lastmonthincometable` /
+//! `lastmonthexpensetable`) against the sim's [`IncomeBreakdown`] for the month
+//! it just ticked, category by category. Categories the sim doesn't track
+//! separately have no sim value, so they show up as unmodelled rather than as a
+//! spurious delta. For the same reason the income total only sums the modelled
+//! categories on both sides.
+//!
+//! [`IncomeBreakdown`]: eu4sim_core::state::IncomeBreakdown
 
-use anyhow::Result;
-use std::path::Path;
+use crate::ExtractedCountry;
+use eu4sim_core::state::CountryState;
+use serde::Serialize;
 
-/// Print full ledger breakdown from a save file for analysis
-///
-/// DISABLED: Version compatibility issues with eu4save library
-pub fn print_ledger_comparison(_from_save: &Path, _to_save: &Path, _country: &str) -> Result<()> {
-    log::info!("Ledger comparison disabled due to version compatibility issues");
-    log::info!("Alternative: Use direct save file inspection or game UI comparison");
-    Ok(())
+/// Which side of the ledger a line belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerSide {
+    Income,
+    Expense,
+}
+
+/// One ledger category, as recorded in the save and as simulated
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerLine {
+    pub category: String,
+    pub side: LedgerSide,
+    /// Monthly amount from the save ledger (None if the save lacks it)
+    pub save: Option<f64>,
+    /// Monthly amount from the sim (None if the sim doesn't model it separately)
+    pub sim: Option<f64>,
+    /// `sim - save`, when both are known
+    pub delta: Option<f64>,
+}
+
+impl LedgerLine {
+    fn new(category: &str, side: LedgerSide, save: Option<f64>, sim: Option<f64>) -> Self {
+        Self {
+            category: category.to_string(),
+            side,
+            save,
+            sim,
+            delta: save.zip(sim).map(|(save, sim)| sim - save),
+        }
+    }
+}
+
+/// Line-by-line ledger comparison for one country
+#[derive(Debug, Clone, Serialize)]
+pub struct LedgerComparison {
+    pub country: String,
+    /// Date of the save the ledger was read from
+    pub date: String,
+    pub lines: Vec<LedgerLine>,
+    /// Taxation + production + trade; the save's other income isn't simulated
+    pub modelled_income: LedgerLine,
+    pub total_expenses: LedgerLine,
+}
+
+impl LedgerComparison {
+    /// Compare a save's ledger against the sim's income tracking for the same month.
+    ///
+    /// `sim` should be the country after stepping to the save's date, so its
+    /// `income` holds the month the save's ledger describes.
+    pub fn new(date: &str, save: &ExtractedCountry, sim: &CountryState) -> Self {
+        use LedgerSide::{Expense, Income};
+
+        let income = save.monthly_income.as_ref();
+        let sim_income = &sim.income;
+
+        let lines = vec![
+            LedgerLine::new(
+                "Taxation",
+                Income,
+                income.map(|i| i.tax),
+                Some(sim_income.taxation.to_f32() as f64),
+            ),
+            LedgerLine::new(
+                "Production",
+                Income,
+                income.map(|i| i.production),
+                Some(sim_income.production.to_f32() as f64),
+            ),
+            LedgerLine::new(
+                "Trade",
+                Income,
+                income.map(|i| i.trade),
+                Some(sim_income.trade.to_f32() as f64),
+            ),
+            LedgerLine::new("Gold", Income, income.map(|i| i.gold), None),
+            LedgerLine::new("Tariffs", Income, income.map(|i| i.tariffs), None),
+            LedgerLine::new("Vassals", Income, income.map(|i| i.vassals), None),
+            LedgerLine::new("Subsidies", Income, income.map(|i| i.subsidies), None),
+            LedgerLine::new("Army maintenance", Expense, save.army_maintenance, None),
+            LedgerLine::new("Navy maintenance", Expense, save.navy_maintenance, None),
+            LedgerLine::new("Fort maintenance", Expense, save.fort_maintenance, None),
+            LedgerLine::new("State maintenance", Expense, save.state_maintenance, None),
+            LedgerLine::new("Interest", Expense, save.interest_expense, None),
+            LedgerLine::new("Advisors", Expense, save.advisor_maintenance, None),
+            LedgerLine::new(
+                "Root out corruption",
+                Expense,
+                save.root_out_corruption,
+                None,
+            ),
+        ];
+
+        let save_modelled_income = income.map(|i| i.tax + i.production + i.trade);
+        let sim_modelled_income = sim_income.taxation + sim_income.production + sim_income.trade;
+
+        Self {
+            country: save.tag.clone(),
+            date: date.to_string(),
+            lines,
+            modelled_income: LedgerLine::new(
+                "Modelled income",
+                Income,
+                save_modelled_income,
+                Some(sim_modelled_income.to_f32() as f64),
+            ),
+            total_expenses: LedgerLine::new(
+                "Total expenses",
+                Expense,
+                save.total_monthly_expenses,
+                Some(sim_income.expenses.to_f32() as f64),
+            ),
+        }
+    }
+
+    /// The modelled line with the largest absolute delta, if any
+    pub fn worst_line(&self) -> Option<&LedgerLine> {
+        self.lines
+            .iter()
+            .filter(|l| l.delta.is_some())
+            .max_by(|a, b| {
+                let a = a.delta.unwrap_or(0.0).abs();
+                let b = b.delta.unwrap_or(0.0).abs();
+                a.total_cmp(&b)
+            })
+    }
+}
+
+/// Serialize a ledger comparison as pretty-printed JSON
+pub fn json_report(comparison: &LedgerComparison) -> serde_json::Result<String> {
+    serde_json::to_string_pretty(comparison)
+}
+
+/// Print a ledger comparison table to stdout
+pub fn print_ledger_comparison(comparison: &LedgerComparison) {
+    fn amount(value: Option<f64>) -> String {
+        value.map_or_else(|| "-".to_string(), |v| format!("{:.2}", v))
+    }
+
+    println!(
+        "=== Ledger: {} ({}) ===",
+        comparison.country, comparison.date
+    );
+    println!(
+        "{:<20} {:>12} {:>12} {:>12}",
+        "Category", "Save", "Sim", "Delta"
+    );
+    println!("{}", "─".repeat(59));
+
+    let mut current_side = None;
+    for line in comparison
+        .lines
+        .iter()
+        .chain([&comparison.modelled_income, &comparison.total_expenses])
+    {
+        if current_side != Some(line.side) && line.side == LedgerSide::Expense {
+            println!("{}", "─".repeat(59));
+        }
+        current_side = Some(line.side);
+
+        let delta = match (line.delta, line.sim) {
+            (Some(delta), _) => format!("{:+.2}", delta),
+            (None, None) => "unmodelled".to_string(),
+            (None, Some(_)) => "-".to_string(),
+        };
+        println!(
+            "{:<20} {:>12} {:>12} {:>12}",
+            line.category,
+            amount(line.save),
+            amount(line.sim),
+            delta
+        );
+    }
+
+    if let Some(worst) = comparison.worst_line() {
+        println!();
+        println!(
+            "Largest modelled delta: {} ({:+.2} ducats/month)",
+            worst.category,
+            worst.delta.unwrap_or(0.0)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MonthlyIncome;
+    use eu4sim_core::state::IncomeBreakdown;
+    use eu4sim_core::Fixed;
+
+    fn save_country() -> ExtractedCountry {
+        ExtractedCountry {
+            tag: "KOR".to_string(),
+            monthly_income: Some(MonthlyIncome {
+                tax: 4.0,
+                production: 3.0,
+                trade: 2.0,
+                gold: 0.0,
+                tariffs: 0.0,
+                vassals: 1.5,
+                subsidies: 0.0,
+                total: 10.5,
+            }),
+            army_maintenance: Some(2.5),
+            total_monthly_expenses: Some(6.0),
+            ..Default::default()
+        }
+    }
+
+    fn sim_country() -> CountryState {
+        CountryState {
+            income: IncomeBreakdown {
+                taxation: Fixed::from_f32(4.0),
+                production: Fixed::from_f32(2.0),
+                trade: Fixed::from_f32(2.5),
+                expenses: Fixed::from_f32(5.0),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn line<'a>(comparison: &'a LedgerComparison, category: &str) -> &'a LedgerLine {
+        comparison
+            .lines
+            .iter()
+            .find(|l| l.category == category)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_modelled_lines_have_deltas() {
+        let comparison = LedgerComparison::new("1445.1.1", &save_country(), &sim_country());
+
+        let production = line(&comparison, "Production");
+        assert_eq!(production.save, Some(3.0));
+        assert!((production.delta.unwrap() + 1.0).abs() < 0.01);
+
+        // Vassal income is in the save total but not simulated
+        let modelled = &comparison.modelled_income;
+        assert_eq!(modelled.save, Some(9.0));
+        assert!((modelled.delta.unwrap() + 0.5).abs() < 0.01);
+        assert!((comparison.total_expenses.delta.unwrap() + 1.0).abs() < 0.01);
+        assert_eq!(comparison.worst_line().unwrap().category, "Production");
+    }
+
+    #[test]
+    fn test_unmodelled_lines_keep_save_value_without_delta() {
+        let comparison = LedgerComparison::new("1445.1.1", &save_country(), &sim_country());
+
+        let vassals = line(&comparison, "Vassals");
+        assert_eq!(vassals.save, Some(1.5));
+        assert_eq!(vassals.sim, None);
+        assert_eq!(vassals.delta, None);
+
+        let army = line(&comparison, "Army maintenance");
+        assert_eq!(army.side, LedgerSide::Expense);
+        assert_eq!(army.save, Some(2.5));
+        assert_eq!(army.delta, None);
+    }
+
+    #[test]
+    fn test_json_report_includes_deltas() {
+        let comparison = LedgerComparison::new("1445.1.1", &save_country(), &sim_country());
+        let json: serde_json::Value =
+            serde_json::from_str(&json_report(&comparison).unwrap()).expect("valid JSON");
+
+        assert_eq!(json["country"], "KOR");
+        assert_eq!(json["lines"][0]["category"], "Taxation");
+        assert_eq!(json["lines"][0]["side"], "income");
+        assert!(json["lines"][0]["delta"].is_number());
+        assert!(json["lines"][3]["sim"].is_null());
+    }
 }
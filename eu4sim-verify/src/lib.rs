@@ -1,12 +1,16 @@
+pub mod campaign;
 pub mod coverage;
 pub mod diff;
+pub mod export;
 pub mod extract;
 pub mod hydrate;
 pub mod ledger_comparison;
 pub mod melt;
 pub mod parse;
 pub mod predict;
+pub mod replay;
 pub mod report;
+pub mod tokens;
 pub mod verify;
 
 use serde::{Deserialize, Serialize};
@@ -24,6 +28,17 @@ pub struct ExtractedState {
     pub celestial_empire: Option<ExtractedCelestialEmpire>,
     /// Trade node data from save
     pub trade_nodes: HashMap<String, ExtractedTradeNode>,
+    /// Land armies from save (all countries)
+    pub armies: Vec<ExtractedArmy>,
+    /// Navies from save (all countries)
+    pub fleets: Vec<ExtractedFleet>,
+    /// Active wars from save
+    pub wars: Vec<ExtractedWar>,
+    /// Alliances, royal marriages and truces from save
+    pub diplomacy: ExtractedDiplomacy,
+    /// Whether `armies`, `fleets`, `wars`, `diplomacy` and country `rivals`
+    /// were extracted (hydration keeps the generated ones otherwise)
+    pub military_and_diplomacy_extracted: bool,
 }
 
 /// Celestial Empire (Emperor of China) state extracted from save
@@ -82,6 +97,110 @@ pub struct ExtractedCountryTradeData {
     pub action: Option<u8>,
 }
 
+/// Military leader (general or admiral) extracted from save
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractedLeader {
+    /// Leader name
+    pub name: String,
+    /// Fire pips
+    pub fire: u8,
+    /// Shock pips
+    pub shock: u8,
+    /// Maneuver pips (spelled "manuever" in saves)
+    pub maneuver: u8,
+    /// Siege pips
+    pub siege: u8,
+}
+
+/// Regiment or ship extracted from save
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractedUnit {
+    /// Unit definition name (e.g., "western_medieval_infantry", "carrack")
+    pub unit_type: String,
+    /// Strength as a fraction of full strength (0.0-1.0)
+    pub strength: f64,
+    /// Current morale
+    pub morale: f64,
+}
+
+/// Land army extracted from save
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedArmy {
+    /// Owner country tag
+    pub owner: String,
+    /// Army name (e.g., "1st Army")
+    pub name: String,
+    /// Current province
+    pub location: u32,
+    /// Regiments in this army
+    pub regiments: Vec<ExtractedUnit>,
+    /// Commanding general (if any)
+    pub general: Option<ExtractedLeader>,
+}
+
+/// Navy extracted from save
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedFleet {
+    /// Owner country tag
+    pub owner: String,
+    /// Fleet name (e.g., "1st Fleet")
+    pub name: String,
+    /// Current (sea) province
+    pub location: u32,
+    /// Ships in this fleet
+    pub ships: Vec<ExtractedUnit>,
+    /// Commanding admiral (if any)
+    pub admiral: Option<ExtractedLeader>,
+}
+
+/// War goal extracted from save
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractedWarGoal {
+    /// War goal type (e.g., "take_claim", "superiority")
+    pub goal_type: String,
+    /// Casus belli (e.g., "cb_claim")
+    pub casus_belli: String,
+    /// Target province, for province-based war goals
+    pub province: Option<u32>,
+}
+
+/// Active war extracted from save
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedWar {
+    /// War name (e.g., "Castilian-Portuguese War")
+    pub name: String,
+    /// Attacker tags (war leader first)
+    pub attackers: Vec<String>,
+    /// Defender tags (war leader first)
+    pub defenders: Vec<String>,
+    /// Earliest dated entry in the war history (YYYY.M.D format)
+    pub start_date: Option<String>,
+    /// Original war goal
+    pub war_goal: Option<ExtractedWarGoal>,
+}
+
+/// Truce between two countries extracted from save
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtractedTruce {
+    /// Country holding the relation
+    pub first: String,
+    /// Country the truce is with
+    pub second: String,
+    /// Date the war between them ended (YYYY.M.D format)
+    pub last_war: String,
+}
+
+/// Bilateral diplomatic relations extracted from save
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedDiplomacy {
+    /// Alliances as (first, second) tag pairs
+    pub alliances: Vec<(String, String)>,
+    /// Royal marriages as (first, second) tag pairs
+    pub royal_marriages: Vec<(String, String)>,
+    /// Active truces
+    pub truces: Vec<ExtractedTruce>,
+}
+
 /// Save file metadata
 #[derive(Debug, Clone)]
 pub struct SaveMeta {
@@ -127,6 +246,8 @@ pub struct ExtractedCountry {
     pub fort_maintenance: Option<f64>,
     pub state_maintenance: Option<f64>,
     pub root_out_corruption: Option<f64>,
+    pub advisor_maintenance: Option<f64>,
+    pub interest_expense: Option<f64>,
 
     // Force limits (cached values from save)
     pub land_force_limit: Option<f64>,
@@ -143,6 +264,9 @@ pub struct ExtractedCountry {
 
     // For recalculation
     pub owned_province_ids: Vec<u32>,
+
+    // Rival country tags
+    pub rivals: Vec<String>,
 }
 
 /// Advisor state extracted from save
@@ -184,6 +308,8 @@ pub struct MonthlyIncome {
     pub trade: f64,
     pub gold: f64,
     pub tariffs: f64,
+    /// Vassal and tributary payments received
+    pub vassals: f64,
     pub subsidies: f64,
     pub total: f64,
 }
@@ -211,6 +337,10 @@ pub struct ExtractedProvince {
 
     // Trade good produced (e.g., "grain", "cloth", "silk")
     pub trade_good: Option<String>,
+
+    // Current religion and culture (e.g., "catholic", "swedish")
+    pub religion: Option<String>,
+    pub culture: Option<String>,
 }
 
 /// Result of verifying a single metric
@@ -2,7 +2,10 @@ use anyhow::Result;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
-use eu4sim_verify::{coverage, diff, extract, melt, parse, predict, report, verify};
+use eu4sim_verify::{
+    campaign, coverage, diff, export, extract, hydrate, ledger_comparison, melt, parse, predict,
+    report, tokens, verify,
+};
 
 #[derive(Parser)]
 #[command(name = "eu4sim-verify")]
@@ -14,6 +17,10 @@ struct Cli {
     /// Enable verbose logging
     #[arg(short, long, global = true)]
     verbose: bool,
+
+    /// Token file for binary (Ironman) saves (default: assets/tokens/eu4.txt)
+    #[arg(long, global = true, env = "EU4_IRONMAN_TOKENS")]
+    tokens: Option<PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -54,7 +61,7 @@ enum Commands {
         save_path: PathBuf,
     },
 
-    /// Melt a binary save to text format (unknown tokens as hex)
+    /// Melt a binary save to text format (unresolved tokens as hex)
     Melt {
         /// Path to the EU4 save file (.eu4)
         save_path: PathBuf,
@@ -85,6 +92,66 @@ enum Commands {
         /// Path to EU4 game directory
         #[arg(long, env = "EU4_GAME_PATH")]
         game_path: PathBuf,
+
+        /// Replay player actions inferred from the save diff (builds, dev clicks)
+        #[arg(long)]
+        replay_actions: bool,
+
+        /// Write the line-by-line ledger comparison as JSON to this file
+        #[arg(long)]
+        ledger_json: Option<PathBuf>,
+    },
+
+    /// Predict every consecutive pair in a directory of saves and aggregate the errors
+    Campaign {
+        /// Directory containing the campaign's saves (.eu4)
+        dir: PathBuf,
+
+        /// Path to EU4 game directory
+        #[arg(long, env = "EU4_GAME_PATH")]
+        game_path: PathBuf,
+
+        /// Only compare these countries (comma-separated tags)
+        #[arg(short, long)]
+        country: Option<String>,
+
+        /// Replay player actions inferred from each pair's diff
+        #[arg(long)]
+        replay_actions: bool,
+
+        /// Number of worst predictions to list
+        #[arg(long, default_value = "20")]
+        worst: usize,
+
+        /// Write the report as JSON to this file
+        #[arg(long)]
+        json: Option<PathBuf>,
+
+        /// Write the report as Markdown to this file (default: stdout)
+        #[arg(long)]
+        markdown: Option<PathBuf>,
+    },
+
+    /// Hydrate a save, optionally simulate forward, and write the result as a save
+    Export {
+        /// Path to the source save file
+        save_path: PathBuf,
+
+        /// Output save file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Days to simulate before exporting (no player inputs)
+        #[arg(long, default_value = "0")]
+        days: u32,
+
+        /// Write a zip with meta and gamestate entries instead of plain text
+        #[arg(long)]
+        compress: bool,
+
+        /// Path to EU4 game directory
+        #[arg(long, env = "EU4_GAME_PATH")]
+        game_path: PathBuf,
     },
 
     /// Infer actions between two sequential saves (Phase 3)
@@ -129,6 +196,12 @@ fn main() -> Result<()> {
     let log_level = if cli.verbose { "debug" } else { "info" };
     env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
 
+    // Binary save loading (including eu4save's EnvTokens) finds the token file
+    // through the environment
+    if let Some(path) = &cli.tokens {
+        std::env::set_var(tokens::TOKENS_ENV, path);
+    }
+
     match cli.command {
         Commands::Check {
             save_path,
@@ -302,11 +375,15 @@ fn main() -> Result<()> {
             }
 
             // Melt to text
+            let token_table = tokens::TokenTable::discover()?;
+            if token_table.is_none() {
+                log::warn!("No token file found; field names will be written as hex ids");
+            }
             let mut melted = Vec::new();
-            let stats = melt::melt_save(&data, &mut melted)?;
+            let stats = melt::melt_save(&data, token_table.as_ref(), &mut melted)?;
 
             log::info!(
-                "Melted {} tokens ({} unknown)",
+                "Melted {} tokens ({} unresolved)",
                 stats.total_tokens,
                 stats.unknown_tokens
             );
@@ -330,12 +407,25 @@ fn main() -> Result<()> {
             to,
             country,
             game_path,
+            replay_actions,
+            ledger_json,
         } => {
             log::info!("Running prediction from {:?} to {:?}", from, to);
 
-            let summary = predict::run_prediction(&game_path, &from, &to, &country)?;
+            let summary =
+                predict::run_prediction(&game_path, &from, &to, &country, replay_actions)?;
             predict::print_prediction_report(&summary);
 
+            if let Some(path) = ledger_json {
+                match &summary.ledger {
+                    Some(ledger) => {
+                        std::fs::write(&path, ledger_comparison::json_report(ledger)?)?;
+                        println!("Ledger comparison written to: {}", path.display());
+                    }
+                    None => log::warn!("{} not found in both states, no ledger written", country),
+                }
+            }
+
             // Count failures
             let failures = summary
                 .results
@@ -348,6 +438,67 @@ fn main() -> Result<()> {
             }
         }
 
+        Commands::Campaign {
+            dir,
+            game_path,
+            country,
+            replay_actions,
+            worst,
+            json,
+            markdown,
+        } => {
+            let options = campaign::CampaignOptions {
+                replay_actions,
+                countries: country
+                    .map(|tags| tags.split(',').map(|t| t.trim().to_string()).collect())
+                    .unwrap_or_default(),
+                worst,
+            };
+            let report = campaign::run_campaign(&game_path, &dir, &options)?;
+
+            if let Some(path) = json {
+                std::fs::write(&path, campaign::json_report(&report)?)?;
+                println!("JSON report written to: {}", path.display());
+            }
+            match markdown {
+                Some(path) => {
+                    std::fs::write(&path, report.to_markdown())?;
+                    println!("Markdown report written to: {}", path.display());
+                }
+                None => print!("{}", report.to_markdown()),
+            }
+        }
+
+        Commands::Export {
+            save_path,
+            output,
+            days,
+            compress,
+            game_path,
+        } => {
+            let save = parse::load_save(&save_path)?;
+            let (mut world, adjacency) = hydrate::hydrate_from_save(&game_path, &save)?;
+
+            let config = eu4sim_core::SimConfig {
+                checksum_frequency: 0,
+            };
+            for _ in 0..days {
+                eu4sim_core::step::step_world_mut(&mut world, &[], Some(&adjacency), &config, None);
+            }
+
+            let options = export::ExportOptions {
+                player: save.meta.player.clone(),
+                compress,
+            };
+            export::export_save(&world, &output, &options)?;
+            println!(
+                "Exported {} -> {} ({} days simulated)",
+                save.meta.date,
+                output.display(),
+                days
+            );
+        }
+
         Commands::Diff {
             before,
             after,
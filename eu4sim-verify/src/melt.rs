@@ -5,8 +5,17 @@ use jomini::binary::{Lexer, Token};
 use jomini::Windows1252Encoding;
 use std::io::Write;
 
+use crate::tokens::TokenTable;
+
 /// Melt a binary EU4 save to text format
-pub fn melt_save(data: &[u8], output: &mut impl Write) -> Result<MeltStats> {
+///
+/// Field ids are resolved through `tokens`; ids missing from the table (or all
+/// of them, without a table) are written as `__0x1234`.
+pub fn melt_save(
+    data: &[u8],
+    tokens: Option<&TokenTable>,
+    output: &mut impl Write,
+) -> Result<MeltStats> {
     // Check for EU4bin header
     let content = if data.starts_with(b"EU4bin") {
         &data[6..]
@@ -17,103 +26,118 @@ pub fn melt_save(data: &[u8], output: &mut impl Write) -> Result<MeltStats> {
     // Parse the binary format
     let mut lexer = Lexer::new(content);
     let mut stats = MeltStats::default();
-    let mut depth: usize = 0;
-    let mut need_newline = false;
+    let mut writer = MeltWriter {
+        output,
+        depth: 0,
+        need_newline: false,
+        after_equal: false,
+    };
 
     while let Some(token) = lexer.next_token().context("Failed to read token")? {
         match token {
-            Token::Open => {
-                if need_newline {
-                    writeln!(output)?;
-                }
-                writeln!(output, "{{")?;
-                depth += 1;
-                need_newline = false;
-            }
-            Token::Close => {
-                if need_newline {
-                    writeln!(output)?;
-                }
-                depth = depth.saturating_sub(1);
-                write_indent(output, depth)?;
-                writeln!(output, "}}")?;
-                need_newline = false;
-            }
-            Token::Equal => {
-                write!(output, "=")?;
-                need_newline = false;
-            }
+            Token::Open => writer.open()?,
+            Token::Close => writer.close()?,
+            Token::Equal => writer.equal()?,
             Token::Id(id) => {
                 stats.total_tokens += 1;
-                stats.unknown_tokens += 1;
-                if need_newline {
-                    writeln!(output)?;
+                match tokens.and_then(|t| t.resolve(id)) {
+                    Some(name) => writer.scalar(name)?,
+                    None => {
+                        stats.unknown_tokens += 1;
+                        writer.scalar(&format!("__0x{:04x}", id))?;
+                    }
                 }
-                write_indent(output, depth)?;
-                write!(output, "__0x{:04x}", id)?;
-                need_newline = false;
             }
             Token::Quoted(s) => {
                 let decoded = Windows1252Encoding::decode(s.as_bytes());
-                write!(output, "\"{}\"", escape_string(&decoded))?;
-                need_newline = true;
+                writer.scalar(&format!("\"{}\"", escape_string(&decoded)))?;
             }
             Token::Unquoted(s) => {
                 let decoded = Windows1252Encoding::decode(s.as_bytes());
-                write!(output, "{}", decoded)?;
-                need_newline = true;
-            }
-            Token::I32(v) => {
-                write!(output, "{}", v)?;
-                need_newline = true;
-            }
-            Token::U32(v) => {
-                write!(output, "{}", v)?;
-                need_newline = true;
-            }
-            Token::I64(v) => {
-                write!(output, "{}", v)?;
-                need_newline = true;
-            }
-            Token::U64(v) => {
-                write!(output, "{}", v)?;
-                need_newline = true;
-            }
-            Token::Bool(v) => {
-                write!(output, "{}", if v { "yes" } else { "no" })?;
-                need_newline = true;
-            }
-            Token::F32(v) => {
-                // v is [u8; 4], convert to f32
-                let val = f32::from_le_bytes(v);
-                write!(output, "{:.5}", val)?;
-                need_newline = true;
-            }
-            Token::F64(v) => {
-                // v is [u8; 8], convert to f64
-                let val = f64::from_le_bytes(v);
-                write!(output, "{:.5}", val)?;
-                need_newline = true;
-            }
-            Token::Rgb(r) => {
-                write!(output, "rgb {{ {} {} {} }}", r.r, r.g, r.b)?;
-                need_newline = true;
+                writer.scalar(&decoded)?;
             }
+            Token::I32(v) => match binary_date(v) {
+                Some(date) => writer.scalar(&date)?,
+                None => writer.scalar(&v.to_string())?,
+            },
+            Token::U32(v) => writer.scalar(&v.to_string())?,
+            Token::I64(v) => writer.scalar(&v.to_string())?,
+            Token::U64(v) => writer.scalar(&v.to_string())?,
+            Token::Bool(v) => writer.scalar(if v { "yes" } else { "no" })?,
+            Token::F32(v) => writer.scalar(&format!("{:.3}", fixed_point_f32(v)))?,
+            Token::F64(v) => writer.scalar(&format!("{:.5}", fixed_point_f64(v)))?,
+            Token::Rgb(r) => writer.scalar(&format!("rgb {{ {} {} {} }}", r.r, r.g, r.b))?,
             Token::Lookup(v) => {
-                // Lookup references - output as hex
-                write!(output, "lookup:{}", v)?;
-                need_newline = true;
+                // Lookup references - output as index
+                writer.scalar(&format!("lookup:{}", v))?;
             }
         }
     }
 
-    if need_newline {
-        writeln!(output)?;
+    if writer.need_newline {
+        writeln!(writer.output)?;
     }
 
     Ok(stats)
 }
 
+/// Tracks layout while melting: values after `=` stay on the key's line,
+/// keys and array elements start a new indented line.
+struct MeltWriter<'a, W: Write> {
+    output: &'a mut W,
+    depth: usize,
+    need_newline: bool,
+    after_equal: bool,
+}
+
+impl<W: Write> MeltWriter<'_, W> {
+    fn scalar(&mut self, text: &str) -> std::io::Result<()> {
+        if !self.after_equal {
+            self.start_line()?;
+        }
+        write!(self.output, "{}", text)?;
+        self.need_newline = true;
+        self.after_equal = false;
+        Ok(())
+    }
+
+    fn equal(&mut self) -> std::io::Result<()> {
+        write!(self.output, "=")?;
+        self.after_equal = true;
+        Ok(())
+    }
+
+    fn open(&mut self) -> std::io::Result<()> {
+        if !self.after_equal {
+            self.start_line()?;
+        }
+        writeln!(self.output, "{{")?;
+        self.depth += 1;
+        self.need_newline = false;
+        self.after_equal = false;
+        Ok(())
+    }
+
+    fn close(&mut self) -> std::io::Result<()> {
+        if self.need_newline {
+            writeln!(self.output)?;
+        }
+        self.depth = self.depth.saturating_sub(1);
+        write_indent(self.output, self.depth)?;
+        writeln!(self.output, "}}")?;
+        self.need_newline = false;
+        self.after_equal = false;
+        Ok(())
+    }
+
+    fn start_line(&mut self) -> std::io::Result<()> {
+        if self.need_newline {
+            writeln!(self.output)?;
+        }
+        write_indent(self.output, self.depth)
+    }
+}
+
 fn write_indent(output: &mut impl Write, depth: usize) -> std::io::Result<()> {
     for _ in 0..depth {
         write!(output, "\t")?;
@@ -128,6 +152,47 @@ fn escape_string(s: &str) -> String {
         .replace('\r', "\\r")
 }
 
+/// Binary dates are hours since 5000 BC in a 365-day calendar
+const BINARY_DATE_EPOCH: i32 = 43_800_000;
+
+const DAYS_IN_MONTH: [i32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+/// Decode an i32 that looks like a binary date into `Y.M.D`
+///
+/// Binary saves don't tag dates, so any value past year 1 with a zero hour
+/// component is treated as one (the same heuristic jomini uses).
+fn binary_date(value: i32) -> Option<String> {
+    if value < BINARY_DATE_EPOCH + 365 * 24 {
+        return None;
+    }
+    let hours = value - BINARY_DATE_EPOCH;
+    if hours % 24 != 0 {
+        return None;
+    }
+    let days = hours / 24;
+    let year = days / 365;
+    let mut day_of_year = days % 365;
+    let mut month = 1;
+    for len in DAYS_IN_MONTH {
+        if day_of_year < len {
+            break;
+        }
+        day_of_year -= len;
+        month += 1;
+    }
+    Some(format!("{}.{}.{}", year, month, day_of_year + 1))
+}
+
+/// EU4 stores 32-bit decimals as fixed point with three decimal places
+fn fixed_point_f32(bytes: [u8; 4]) -> f64 {
+    i32::from_le_bytes(bytes) as f64 / 1000.0
+}
+
+/// EU4 stores 64-bit decimals as Q49.15 fixed point
+fn fixed_point_f64(bytes: [u8; 8]) -> f64 {
+    i64::from_le_bytes(bytes) as f64 / 32768.0
+}
+
 #[derive(Default, Debug)]
 pub struct MeltStats {
     pub total_tokens: usize,
@@ -143,4 +208,67 @@ mod tests {
         assert_eq!(escape_string("hello"), "hello");
         assert_eq!(escape_string("hello\"world"), "hello\\\"world");
     }
+
+    /// Encode a date the way binary saves do
+    fn encode_date(year: i32, month: usize, day: i32) -> i32 {
+        let day_of_year: i32 = DAYS_IN_MONTH[..month - 1].iter().sum::<i32>() + day - 1;
+        BINARY_DATE_EPOCH + (year * 365 + day_of_year) * 24
+    }
+
+    #[test]
+    fn test_binary_date() {
+        assert_eq!(
+            binary_date(encode_date(1444, 11, 11)).as_deref(),
+            Some("1444.11.11")
+        );
+        assert_eq!(
+            binary_date(encode_date(1821, 1, 1)).as_deref(),
+            Some("1821.1.1")
+        );
+        assert_eq!(
+            binary_date(encode_date(1600, 12, 31)).as_deref(),
+            Some("1600.12.31")
+        );
+        // Plain integers and non-zero hours aren't dates
+        assert_eq!(binary_date(1000), None);
+        assert_eq!(binary_date(encode_date(1444, 11, 11) + 5), None);
+    }
+
+    #[test]
+    fn test_fixed_point_decimals() {
+        assert_eq!(fixed_point_f32(12_345i32.to_le_bytes()), 12.345);
+        assert_eq!(fixed_point_f32((-500i32).to_le_bytes()), -0.5);
+        assert_eq!(fixed_point_f64((3 * 32768i64).to_le_bytes()), 3.0);
+        assert_eq!(fixed_point_f64(16384i64.to_le_bytes()), 0.5);
+    }
+
+    #[test]
+    fn test_melt_layout() {
+        let mut out = Vec::new();
+        let mut writer = MeltWriter {
+            output: &mut out,
+            depth: 0,
+            need_newline: false,
+            after_equal: false,
+        };
+        // date=1444.11.11 countries={ powers={ 1 2 } }
+        writer.scalar("date").unwrap();
+        writer.equal().unwrap();
+        writer.scalar("1444.11.11").unwrap();
+        writer.scalar("countries").unwrap();
+        writer.equal().unwrap();
+        writer.open().unwrap();
+        writer.scalar("powers").unwrap();
+        writer.equal().unwrap();
+        writer.open().unwrap();
+        writer.scalar("1").unwrap();
+        writer.scalar("2").unwrap();
+        writer.close().unwrap();
+        writer.close().unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "date=1444.11.11\ncountries={\n\tpowers={\n\t\t1\n\t\t2\n\t}\n}\n"
+        );
+    }
 }
@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::tokens::{find_tokens_file, TokenTable, TOKENS_ENV};
 use crate::{ExtractedState, SaveMeta};
 
 /// Load and parse an EU4 save file
@@ -60,6 +61,32 @@ pub fn load_save(path: &Path) -> Result<ExtractedState> {
     }
 }
 
+/// Read just the in-game date of a save
+///
+/// Uses the text `meta` entry or header when there is one, so most saves don't
+/// need a full parse. Binary (ironman) saves fall back to [`load_save`].
+pub fn read_save_date(path: &Path) -> Result<String> {
+    let data = std::fs::read(path)
+        .with_context(|| format!("Failed to read save file: {}", path.display()))?;
+
+    let date = if data.starts_with(b"PK") {
+        zip::ZipArchive::new(std::io::Cursor::new(&data))
+            .ok()
+            .and_then(|mut archive| read_meta(&mut archive))
+            .map(|(date, _)| date)
+    } else if data.starts_with(b"EU4txt") {
+        // The date is the first key of the header
+        extract_date(&String::from_utf8_lossy(&data[..data.len().min(4096)]))
+    } else {
+        None
+    };
+
+    match date {
+        Some(date) => Ok(date),
+        None => Ok(load_save(path)?.meta.date),
+    }
+}
+
 fn read_gamestate<R: std::io::Read + std::io::Seek>(
     archive: &mut zip::ZipArchive<R>,
 ) -> Result<Vec<u8>> {
@@ -133,28 +160,24 @@ fn parse_binary_gamestate(data: &[u8]) -> Result<ExtractedState> {
     use eu4save::{EnvTokens, Eu4File, PdsDate};
 
     // Check for tokens
-    let tokens_path = find_tokens_file();
-    if tokens_path.is_none() && std::env::var("EU4_IRONMAN_TOKENS").is_err() {
+    let Some(tokens_path) = find_tokens_file() else {
         anyhow::bail!(
             "Binary (Ironman) save requires token file.\n\n\
             The token file maps binary field IDs to names. Options:\n\n\
-            1. Set EU4_IRONMAN_TOKENS=/path/to/eu4.txt\n\
+            1. Pass --tokens /path/to/eu4.txt (or set EU4_IRONMAN_TOKENS)\n\
             2. Place tokens at assets/tokens/eu4.txt\n\n\
             Token sources:\n\
-            - pdx-tools (https://pdx.tools) - use their Rakaly CLI\n\
-            - PDX-Unlimiter can extract from eu4.exe\n\
-            - Our eu4tokens tool extracts strings but not IDs (WIP)\n\n\
+            - Our eu4tokens tool: eu4tokens derive <eu4 binary>\n\
+            - pdx-tools (https://pdx.tools) - use their Rakaly CLI\n\n\
             Note: Token IDs change between game versions. Use tokens\n\
             matching your save file version."
         );
-    }
+    };
 
-    // Set environment variable if we found a local tokens file
-    if let Some(path) = tokens_path {
-        if std::env::var("EU4_IRONMAN_TOKENS").is_err() {
-            log::info!("Using tokens from: {}", path.display());
-            std::env::set_var("EU4_IRONMAN_TOKENS", &path);
-        }
+    // eu4save's EnvTokens reads the token file from the environment
+    if std::env::var_os(TOKENS_ENV).is_none() {
+        log::info!("Using tokens from: {}", tokens_path.display());
+        std::env::set_var(TOKENS_ENV, &tokens_path);
     }
 
     log::info!("Parsing binary save with eu4save...");
@@ -166,6 +189,15 @@ fn parse_binary_gamestate(data: &[u8]) -> Result<ExtractedState> {
     let save = match file.deserializer().build_save(&EnvTokens) {
         Ok(save) => save,
         Err(e) => {
+            log::warn!(
+                "Failed to deserialize binary save: {}, falling back to melted text parsing",
+                e
+            );
+            match parse_melted_gamestate(data, &tokens_path) {
+                Ok(state) => return Ok(state),
+                Err(melt_err) => log::warn!("Melted text parsing failed: {:#}", melt_err),
+            }
+
             // Provide helpful error for token mismatch
             let msg = format!("{}", e);
             if msg.contains("missing field") || msg.contains("unknown token") {
@@ -230,6 +262,8 @@ fn parse_binary_gamestate(data: &[u8]) -> Result<ExtractedState> {
             local_autonomy: Some(province.local_autonomy.into()),
             buildings,
             trade_good: province.trade_goods.clone(),
+            religion: province.religion.clone(),
+            culture: province.culture.clone(),
         };
 
         provinces.insert(id_u32, extracted);
@@ -292,6 +326,7 @@ fn parse_binary_gamestate(data: &[u8]) -> Result<ExtractedState> {
             trade: income_ledger.trade as f64,
             gold: income_ledger.gold as f64,
             tariffs: income_ledger.tariffs as f64,
+            vassals: income_ledger.vassals as f64,
             subsidies: income_ledger.subsidies as f64,
             total: total_income as f64,
         });
@@ -319,12 +354,15 @@ fn parse_binary_gamestate(data: &[u8]) -> Result<ExtractedState> {
             fort_maintenance: Some(expense_ledger.fort_maintenance as f64),
             state_maintenance: None, // TODO: Extract when ledger has detailed breakdown
             root_out_corruption: None, // TODO: Extract when ledger has detailed breakdown
-            land_force_limit: None,  // TODO: Extract when eu4save exposes this field
+            advisor_maintenance: Some(expense_ledger.advisor_maintenance as f64),
+            interest_expense: None, // TODO: Extract when ledger has detailed breakdown
+            land_force_limit: None, // TODO: Extract when eu4save exposes this field
             naval_force_limit: None, // TODO: Extract when eu4save exposes this field
             advisors,
             ideas,
             active_modifiers: vec![], // TODO: Extract from eu4save when deserialization works
             owned_province_ids: owned,
+            rivals: vec![], // Filled from text by extract_military_and_diplomacy
         };
 
         countries.insert(tag_str, extracted);
@@ -352,14 +390,31 @@ fn parse_binary_gamestate(data: &[u8]) -> Result<ExtractedState> {
     // Extract celestial empire data from query (if available)
     let celestial_empire = extract_celestial_empire_from_query(&query);
 
-    Ok(ExtractedState {
+    let mut state = ExtractedState {
         meta,
         countries,
         provinces,
         subjects,
         celestial_empire,
         trade_nodes: HashMap::new(), // TODO: Extract from Query API
-    })
+        armies: Vec::new(),
+        fleets: Vec::new(),
+        wars: Vec::new(),
+        diplomacy: Default::default(),
+        military_and_diplomacy_extracted: false,
+    };
+
+    // eu4save's models don't cover armies, wars and relations, so melt the
+    // save and pick them up from the text like a text save
+    match melt_gamestate(data, &tokens_path) {
+        Ok(text) => extract_military_and_diplomacy(&text, &mut state)?,
+        Err(e) => log::warn!(
+            "Failed to melt binary save, armies, wars and relations not extracted: {:#}",
+            e
+        ),
+    }
+
+    Ok(state)
 }
 
 /// Extract celestial empire data from eu4save query
@@ -510,33 +565,28 @@ fn extract_ruler_stats(country: &eu4save::models::Country) -> ExtractedMonarch {
     }
 }
 
-/// Find tokens file in standard locations
-fn find_tokens_file() -> Option<std::path::PathBuf> {
-    // Check relative to current directory
-    let candidates = [
-        "assets/tokens/eu4.txt",
-        "../assets/tokens/eu4.txt",
-        "eu4.tokens.txt",
-    ];
-
-    for candidate in candidates {
-        let path = std::path::PathBuf::from(candidate);
-        if path.exists() {
-            return path.canonicalize().ok();
-        }
-    }
-
-    // Check relative to executable
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            let path = exe_dir.join("assets/tokens/eu4.txt");
-            if path.exists() {
-                return Some(path);
-            }
-        }
-    }
+/// Melt a binary gamestate with the token table and parse the text
+///
+/// Used when eu4save can't deserialize the save (e.g. fields it doesn't know
+/// about); the regex parser tolerates missing and unexpected fields.
+fn parse_melted_gamestate(data: &[u8], tokens_path: &Path) -> Result<ExtractedState> {
+    let text = melt_gamestate(data, tokens_path)?;
+    let mut state = parse_text_content(&text)?;
+    state.meta.ironman = true;
+    Ok(state)
+}
 
-    None
+/// Melt a binary gamestate to text with the token table
+fn melt_gamestate(data: &[u8], tokens_path: &Path) -> Result<String> {
+    let tokens = TokenTable::load(tokens_path)?;
+    let mut melted = Vec::new();
+    let stats = crate::melt::melt_save(data, Some(&tokens), &mut melted)?;
+    log::info!(
+        "Melted {} tokens ({} unresolved)",
+        stats.total_tokens,
+        stats.unknown_tokens
+    );
+    Ok(String::from_utf8_lossy(&melted).into_owned())
 }
 
 fn parse_text_gamestate(data: &[u8]) -> Result<ExtractedState> {
@@ -553,7 +603,18 @@ fn parse_text_gamestate(data: &[u8]) -> Result<ExtractedState> {
                 Ok(save) => {
                     log::debug!("Deserialized successfully, using Query API for ledger data");
                     let query = eu4save::query::Query::from_save(save);
-                    parse_with_query(query)
+                    let mut state = parse_with_query(query)?;
+
+                    // Armies, wars and relations aren't taken from the Query API,
+                    // so pick them up from the text directly
+                    let content = if data.starts_with(b"EU4txt") {
+                        &data[6..]
+                    } else {
+                        data
+                    };
+                    let text = String::from_utf8_lossy(content);
+                    extract_military_and_diplomacy(&text, &mut state)?;
+                    Ok(state)
                 }
                 Err(e) => {
                     log::warn!(
@@ -641,6 +702,8 @@ fn parse_with_query(query: eu4save::query::Query) -> Result<ExtractedState> {
             local_autonomy: Some(province.local_autonomy.into()),
             buildings,
             trade_good: province.trade_goods.clone(),
+            religion: province.religion.clone(),
+            culture: province.culture.clone(),
         };
 
         provinces.insert(id_u32, extracted);
@@ -685,6 +748,7 @@ fn parse_with_query(query: eu4save::query::Query) -> Result<ExtractedState> {
             trade: income_ledger.trade as f64,
             gold: income_ledger.gold as f64,
             tariffs: income_ledger.tariffs as f64,
+            vassals: income_ledger.vassals as f64,
             subsidies: income_ledger.subsidies as f64,
             total: total_income as f64,
         });
@@ -712,12 +776,15 @@ fn parse_with_query(query: eu4save::query::Query) -> Result<ExtractedState> {
             fort_maintenance: Some(expense_ledger.fort_maintenance as f64),
             state_maintenance: None, // TODO: Extract when ledger has detailed breakdown
             root_out_corruption: None, // TODO: Extract when ledger has detailed breakdown
-            land_force_limit: None,  // TODO: Extract when eu4save exposes this field
+            advisor_maintenance: Some(expense_ledger.advisor_maintenance as f64),
+            interest_expense: None, // TODO: Extract when ledger has detailed breakdown
+            land_force_limit: None, // TODO: Extract when eu4save exposes this field
             naval_force_limit: None, // TODO: Extract when eu4save exposes this field
             advisors,
             ideas,
             active_modifiers: vec![], // TODO: Extract from eu4save when deserialization works
             owned_province_ids: owned,
+            rivals: vec![], // Filled from text by extract_military_and_diplomacy
         };
 
         countries.insert(tag_str, extracted);
@@ -748,11 +815,16 @@ fn parse_with_query(query: eu4save::query::Query) -> Result<ExtractedState> {
         subjects,
         celestial_empire,
         trade_nodes: HashMap::new(), // TODO: Extract from binary save
+        armies: Vec::new(),
+        fleets: Vec::new(),
+        wars: Vec::new(),
+        diplomacy: Default::default(),
+        military_and_diplomacy_extracted: false,
     })
 }
 
 /// Parse text content (shared between text saves and melted binary)
-fn parse_text_content(text: &str) -> Result<ExtractedState> {
+pub(crate) fn parse_text_content(text: &str) -> Result<ExtractedState> {
     log::info!("Parsing text gamestate ({} chars)", text.len());
 
     // Basic extraction - look for key patterns
@@ -768,6 +840,11 @@ fn parse_text_content(text: &str) -> Result<ExtractedState> {
         subjects: HashMap::new(),
         celestial_empire: None,
         trade_nodes: HashMap::new(),
+        armies: Vec::new(),
+        fleets: Vec::new(),
+        wars: Vec::new(),
+        diplomacy: Default::default(),
+        military_and_diplomacy_extracted: false,
     };
 
     // Extract country data
@@ -785,12 +862,18 @@ fn parse_text_content(text: &str) -> Result<ExtractedState> {
     // Extract trade node data
     extract_trade_nodes(text, &mut state);
 
+    // Extract armies, fleets, wars, alliances, marriages, truces and rivals
+    extract_military_and_diplomacy(text, &mut state)?;
+
     log::info!(
-        "Extracted {} countries, {} provinces, {} subjects, {} trade nodes",
+        "Extracted {} countries, {} provinces, {} subjects, {} trade nodes, {} armies, {} fleets, {} wars",
         state.countries.len(),
         state.provinces.len(),
         state.subjects.len(),
-        state.trade_nodes.len()
+        state.trade_nodes.len(),
+        state.armies.len(),
+        state.fleets.len(),
+        state.wars.len()
     );
 
     Ok(state)
@@ -1026,6 +1109,7 @@ fn parse_country_block(tag: &str, content: &str) -> crate::ExtractedCountry {
                 trade: income_array[2],
                 gold: income_array[3],
                 tariffs: income_array[4],
+                vassals: income_array[5],
                 subsidies: income_array[7], // Subsidies is at index 7
                 total: income_array.iter().sum(),
             });
@@ -1077,6 +1161,8 @@ fn parse_country_block(tag: &str, content: &str) -> crate::ExtractedCountry {
         // Extract expense data
         // Expense array indices: 2 = State maintenance, 6 = Army maintenance, 7 = Fleet maintenance, 8 = Fort maintenance, 27 = Root out corruption
         if expense_array.len() >= 9 {
+            country.advisor_maintenance = Some(expense_array[0]);
+            country.interest_expense = Some(expense_array[1]);
             country.state_maintenance = Some(expense_array[2]);
             country.army_maintenance = Some(expense_array[6]);
             country.navy_maintenance = Some(expense_array[7]);
@@ -1179,9 +1265,10 @@ fn extract_advisors(content: &str, tag: &str) -> Vec<crate::ExtractedAdvisor> {
             std::collections::HashSet::new()
         };
 
-    // Find the advisors block
-    if let Some(advisors_start) = content.find("\n\tadvisors={") {
-        let block_start = advisors_start + "\n\tadvisors={".len();
+    // Find the advisors block (at any indentation, but not active_advisors)
+    let advisors_re = regex::Regex::new(r"(?m)^\s*advisors=\{").unwrap();
+    if let Some(advisors_start) = advisors_re.find(content) {
+        let block_start = advisors_start.end();
         if let Some(advisors_block) = extract_block(&content[block_start..]) {
             // Parse individual advisor entries
             // Each advisor is in a block like: { id=123 type="philosopher" skill=2 ... }
@@ -1568,9 +1655,23 @@ fn parse_province_block(id: u32, content: &str) -> crate::ExtractedProvince {
         province.trade_good = caps.get(1).map(|m| m.as_str().to_string());
     }
 
+    // Current religion/culture - skip the original_* and former_* variants
+    province.religion = extract_province_string(content, "religion");
+    province.culture = extract_province_string(content, "culture");
+
     province
 }
 
+/// First `key=value` line in a province block, quoted or not
+///
+/// The current values come before the `history` block, so the first match wins.
+fn extract_province_string(content: &str, key: &str) -> Option<String> {
+    let re = regex::Regex::new(&format!(r#"(?m)^\s*{}="?([a-z_]+)"?"#, key)).ok()?;
+    re.captures(content)
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str().to_string())
+}
+
 /// Extract subject relationships from diplomacy section
 /// Dependencies appear as: dependency={ first="FRA" second="PRO" subject_type="vassal" ... }
 fn extract_subjects(text: &str, state: &mut ExtractedState) -> Result<()> {
@@ -1925,6 +2026,369 @@ fn extract_trade_nodes(text: &str, state: &mut ExtractedState) {
     log::debug!("Extracted {} trade nodes", state.trade_nodes.len());
 }
 
+/// Extract armies, fleets, wars and bilateral relations from a text save
+///
+/// Country-level data (armies, navies, leaders, rivals, truces) lives inside each
+/// country block; wars are top-level `active_war={...}` blocks; alliances and
+/// royal marriages live in the `diplomacy={...}` section next to dependencies.
+fn extract_military_and_diplomacy(text: &str, state: &mut ExtractedState) -> Result<()> {
+    extract_country_military(text, state)?;
+    extract_wars(text, state);
+    extract_relations(text, state);
+    state.military_and_diplomacy_extracted = true;
+
+    log::info!(
+        "Extracted {} armies, {} fleets, {} wars, {} alliances, {} royal marriages, {} truces",
+        state.armies.len(),
+        state.fleets.len(),
+        state.wars.len(),
+        state.diplomacy.alliances.len(),
+        state.diplomacy.royal_marriages.len(),
+        state.diplomacy.truces.len()
+    );
+    Ok(())
+}
+
+/// Return the text of a block with all nested `{...}` blocks removed
+///
+/// Lets field regexes match only the block's own top-level keys, e.g. an army's
+/// `name=` rather than one of its regiments' names.
+fn strip_nested_blocks(block: &str) -> String {
+    let mut out = String::with_capacity(block.len());
+    let mut depth = 0usize;
+    for c in block.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Iterate the contents of every `key={...}` block matched by `pattern`
+fn blocks<'a>(pattern: &regex::Regex, text: &'a str) -> Vec<&'a str> {
+    pattern
+        .find_iter(text)
+        .filter_map(|m| extract_block(&text[m.end()..]))
+        .collect()
+}
+
+/// First capture group of `pattern` in `text`
+fn capture<'a>(pattern: &regex::Regex, text: &'a str) -> Option<&'a str> {
+    pattern
+        .captures(text)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str())
+}
+
+/// Extract armies, navies, rivals and truces from each country block
+///
+/// ```text
+/// FRA={
+///     history={ 1450.1.1={ leader={ name="X" type=general manuever=2 fire=1 shock=3 siege=0 id={ id=7 type=49 } } } }
+///     active_relations={ ENG={ truce=yes last_war=1450.5.1 } }
+///     rival={ country="ENG" date=1445.1.1 }
+///     army={ name="1st Army" location=183 leader={ id=7 type=49 }
+///         regiment={ type="western_medieval_infantry" morale=2.500 strength=0.850 } }
+///     navy={ name="1st Fleet" location=1275 ship={ type="carrack" morale=2.000 strength=1.000 } }
+/// }
+/// ```
+fn extract_country_military(text: &str, state: &mut ExtractedState) -> Result<()> {
+    use regex::Regex;
+
+    let Some(countries_start) = text.find("\ncountries={") else {
+        return Ok(());
+    };
+    let section_start = countries_start + "\ncountries={".len();
+    let Some(countries_section) = extract_block(&text[section_start..]) else {
+        return Ok(());
+    };
+
+    let tag_re = Regex::new(r"\n\t([A-Z]{3})=\{").context("Failed to compile tag regex")?;
+    let army_re = Regex::new(r"(?m)^\s*army=\{").unwrap();
+    let navy_re = Regex::new(r"(?m)^\s*navy=\{").unwrap();
+    let regiment_re = Regex::new(r"(?m)^\s*regiment=\{").unwrap();
+    let ship_re = Regex::new(r"(?m)^\s*ship=\{").unwrap();
+    let leader_re = Regex::new(r"leader=\{").unwrap();
+    let rival_re = Regex::new(r"(?m)^\s*rival=\{").unwrap();
+    let relations_re = Regex::new(r"active_relations=\{").unwrap();
+    let relation_tag_re = Regex::new(r"(?m)^\s*([A-Z]{3})=\{").unwrap();
+
+    let name_re = Regex::new(r#"name="([^"]*)""#).unwrap();
+    let type_re = Regex::new(r#"type="?([A-Za-z0-9_]+)"?"#).unwrap();
+    let id_re = Regex::new(r"id=\{\s*id=(\d+)").unwrap();
+    let country_re = Regex::new(r#"country="([A-Z]{3})""#).unwrap();
+    let last_war_re = Regex::new(r"last_war=(\d+\.\d+\.\d+)").unwrap();
+
+    let pip = |block: &str, key: &str| -> u8 {
+        extract_int_value(block, &format!("{}=", key)).unwrap_or(0) as u8
+    };
+
+    let parse_unit = |block: &str| -> Option<crate::ExtractedUnit> {
+        let own = strip_nested_blocks(block);
+        Some(crate::ExtractedUnit {
+            unit_type: capture(&type_re, &own)?.to_string(),
+            // Full-strength units may omit the field
+            strength: extract_float_value(&own, "strength=").unwrap_or(1.0),
+            morale: extract_float_value(&own, "morale=").unwrap_or(0.0),
+        })
+    };
+
+    for cap in tag_re.captures_iter(countries_section) {
+        let tag = cap.get(1).unwrap().as_str().to_string();
+        let block_start = cap.get(0).unwrap().end();
+        let Some(country_block) = extract_block(&countries_section[block_start..]) else {
+            continue;
+        };
+
+        // Leader definitions (anything with a name); army/navy references only carry an id
+        let mut leaders: HashMap<u32, crate::ExtractedLeader> = HashMap::new();
+        for leader_block in blocks(&leader_re, country_block) {
+            let own = strip_nested_blocks(leader_block);
+            let (Some(name), Some(id)) = (
+                capture(&name_re, &own),
+                capture(&id_re, leader_block).and_then(|v| v.parse::<u32>().ok()),
+            ) else {
+                continue;
+            };
+            leaders.insert(
+                id,
+                crate::ExtractedLeader {
+                    name: name.to_string(),
+                    fire: pip(&own, "fire"),
+                    shock: pip(&own, "shock"),
+                    // Paradox's spelling
+                    maneuver: pip(&own, "manuever"),
+                    siege: pip(&own, "siege"),
+                },
+            );
+        }
+
+        let unit_leader = |block: &str| -> Option<crate::ExtractedLeader> {
+            let leader_block = blocks(&leader_re, block)
+                .into_iter()
+                .find(|b| !b.contains("name="))?;
+            let id = extract_int_value(leader_block, "id=")?;
+            leaders.get(&(id as u32)).cloned()
+        };
+
+        for army_block in blocks(&army_re, country_block) {
+            let own = strip_nested_blocks(army_block);
+            let Some(location) = extract_int_value(&own, "location=") else {
+                continue;
+            };
+            state.armies.push(crate::ExtractedArmy {
+                owner: tag.clone(),
+                name: capture(&name_re, &own).unwrap_or_default().to_string(),
+                location: location as u32,
+                regiments: blocks(&regiment_re, army_block)
+                    .into_iter()
+                    .filter_map(parse_unit)
+                    .collect(),
+                general: unit_leader(army_block),
+            });
+        }
+
+        for navy_block in blocks(&navy_re, country_block) {
+            let own = strip_nested_blocks(navy_block);
+            let Some(location) = extract_int_value(&own, "location=") else {
+                continue;
+            };
+            state.fleets.push(crate::ExtractedFleet {
+                owner: tag.clone(),
+                name: capture(&name_re, &own).unwrap_or_default().to_string(),
+                location: location as u32,
+                ships: blocks(&ship_re, navy_block)
+                    .into_iter()
+                    .filter_map(parse_unit)
+                    .collect(),
+                admiral: unit_leader(navy_block),
+            });
+        }
+
+        if let Some(relations) = blocks(&relations_re, country_block).first() {
+            for m in relation_tag_re.captures_iter(relations) {
+                let other = m.get(1).unwrap().as_str();
+                let Some(relation) = extract_block(&relations[m.get(0).unwrap().end()..]) else {
+                    continue;
+                };
+                if !relation.contains("truce=yes") {
+                    continue;
+                }
+                if let Some(last_war) = capture(&last_war_re, relation) {
+                    state.diplomacy.truces.push(crate::ExtractedTruce {
+                        first: tag.clone(),
+                        second: other.to_string(),
+                        last_war: last_war.to_string(),
+                    });
+                }
+            }
+        }
+
+        let rivals: Vec<String> = blocks(&rival_re, country_block)
+            .into_iter()
+            .filter_map(|b| capture(&country_re, b).map(str::to_string))
+            .collect();
+        if let Some(country) = state.countries.get_mut(&tag) {
+            country.rivals = rivals;
+        }
+    }
+
+    Ok(())
+}
+
+/// Extract active wars
+///
+/// ```text
+/// active_war={
+///     name="Franco-English War"
+///     history={
+///         war_goal={ type="take_claim" casus_belli="cb_claim" province=183 }
+///         1444.11.12={ add_attacker="FRA" add_defender="ENG" }
+///     }
+///     attackers={ "FRA" }
+///     defenders={ "ENG" }
+///     original_attacker="FRA"
+///     original_defender="ENG"
+/// }
+/// ```
+///
+/// Participants come from the `attackers`/`defenders` lists when present, otherwise
+/// from replaying `add_*`/`rem_*` entries in the war history.
+fn extract_wars(text: &str, state: &mut ExtractedState) {
+    use regex::Regex;
+
+    let war_re = Regex::new(r"\nactive_war=\{").unwrap();
+    let name_re = Regex::new(r#"name="([^"]*)""#).unwrap();
+    let history_re = Regex::new(r"history=\{").unwrap();
+    let war_goal_re = Regex::new(r"war_goal=\{").unwrap();
+    let date_key_re = Regex::new(r"(\d+)\.(\d+)\.(\d+)=\{").unwrap();
+    let event_re =
+        Regex::new(r#"(add_attacker|add_defender|rem_attacker|rem_defender)="([A-Z]{3})""#)
+            .unwrap();
+    let list_tag_re = Regex::new(r#""([A-Z]{3})""#).unwrap();
+    let goal_type_re = Regex::new(r#"(?:^|\s)type="([^"]+)""#).unwrap();
+    let cb_re = Regex::new(r#"casus_belli="([^"]+)""#).unwrap();
+    let province_re = Regex::new(r"province=(\d+)").unwrap();
+    let original_attacker_re = Regex::new(r#"original_attacker="([A-Z]{3})""#).unwrap();
+    let original_defender_re = Regex::new(r#"original_defender="([A-Z]{3})""#).unwrap();
+
+    let tag_list = |block: &str, key: &str| -> Option<Vec<String>> {
+        let re = Regex::new(&format!(r"(?m)^\s*{}=\{{", key)).unwrap();
+        let list = blocks(&re, block).into_iter().next()?;
+        Some(
+            list_tag_re
+                .captures_iter(list)
+                .map(|c| c[1].to_string())
+                .collect(),
+        )
+    };
+
+    for war_block in blocks(&war_re, text) {
+        let own = strip_nested_blocks(war_block);
+        let history = blocks(&history_re, war_block)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+
+        // Replay history for participants and start date
+        let mut attackers: Vec<String> = Vec::new();
+        let mut defenders: Vec<String> = Vec::new();
+        for c in event_re.captures_iter(history) {
+            let tag = c[2].to_string();
+            match &c[1] {
+                "add_attacker" if !attackers.contains(&tag) => attackers.push(tag),
+                "add_defender" if !defenders.contains(&tag) => defenders.push(tag),
+                "rem_attacker" => attackers.retain(|t| *t != tag),
+                "rem_defender" => defenders.retain(|t| *t != tag),
+                _ => {}
+            }
+        }
+        if let Some(listed) = tag_list(war_block, "attackers") {
+            attackers = listed;
+        }
+        if let Some(listed) = tag_list(war_block, "defenders") {
+            defenders = listed;
+        }
+
+        // War leaders first
+        let lead_first = |side: &mut Vec<String>, leader: Option<&str>| {
+            if let Some(pos) = leader.and_then(|l| side.iter().position(|t| t == l)) {
+                let leader = side.remove(pos);
+                side.insert(0, leader);
+            }
+        };
+        lead_first(&mut attackers, capture(&original_attacker_re, &own));
+        lead_first(&mut defenders, capture(&original_defender_re, &own));
+
+        let start_date = date_key_re
+            .captures_iter(history)
+            .filter_map(|c| {
+                let ymd: (i32, u8, u8) =
+                    (c[1].parse().ok()?, c[2].parse().ok()?, c[3].parse().ok()?);
+                Some((ymd, format!("{}.{}.{}", &c[1], &c[2], &c[3])))
+            })
+            .min_by_key(|(ymd, _)| *ymd)
+            .map(|(_, s)| s);
+
+        let war_goal = blocks(&war_goal_re, war_block)
+            .into_iter()
+            .next()
+            .and_then(|goal| {
+                Some(crate::ExtractedWarGoal {
+                    goal_type: capture(&goal_type_re, goal)?.to_string(),
+                    casus_belli: capture(&cb_re, goal).unwrap_or_default().to_string(),
+                    province: capture(&province_re, goal).and_then(|v| v.parse().ok()),
+                })
+            });
+
+        state.wars.push(crate::ExtractedWar {
+            name: capture(&name_re, &own).unwrap_or_default().to_string(),
+            attackers,
+            defenders,
+            start_date,
+            war_goal,
+        });
+    }
+}
+
+/// Extract alliances and royal marriages from the diplomacy section
+///
+/// Both appear next to dependencies: `alliance={ first="FRA" second="SCO" ... }`
+fn extract_relations(text: &str, state: &mut ExtractedState) {
+    use regex::Regex;
+
+    let Some(diplomacy_start) = text.find("\ndiplomacy={") else {
+        return;
+    };
+    let section_start = diplomacy_start + "\ndiplomacy={".len();
+    let Some(diplomacy_section) = extract_block(&text[section_start..]) else {
+        return;
+    };
+
+    let first_re = Regex::new(r#"first="([A-Z]{3})""#).unwrap();
+    let second_re = Regex::new(r#"second="([A-Z]{3})""#).unwrap();
+    let pair = |block: &str| -> Option<(String, String)> {
+        Some((
+            capture(&first_re, block)?.to_string(),
+            capture(&second_re, block)?.to_string(),
+        ))
+    };
+
+    let alliance_re = Regex::new(r"(?m)^\s*alliance=\{").unwrap();
+    let marriage_re = Regex::new(r"(?m)^\s*royal_marriage=\{").unwrap();
+    state.diplomacy.alliances = blocks(&alliance_re, diplomacy_section)
+        .into_iter()
+        .filter_map(pair)
+        .collect();
+    state.diplomacy.royal_marriages = blocks(&marriage_re, diplomacy_section)
+        .into_iter()
+        .filter_map(pair)
+        .collect();
+}
+
 #[cfg(test)]
 #[path = "parse_tests.rs"]
 mod tests;
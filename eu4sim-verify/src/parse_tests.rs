@@ -211,6 +211,27 @@ fn test_extract_buildings_empty() {
     assert!(buildings.is_empty());
 }
 
+#[test]
+fn test_parse_province_block_religion_and_culture() {
+    let content = r#"
+        name="Stockholm"
+        owner="SWE"
+        original_culture=swedish
+        culture=finnish
+        religion="protestant"
+        original_religion=catholic
+        history={
+            1444.11.11={
+                religion=catholic
+            }
+        }
+    "#;
+    let province = parse_province_block(1, content);
+    assert_eq!(province.owner.as_deref(), Some("SWE"));
+    assert_eq!(province.culture.as_deref(), Some("finnish"));
+    assert_eq!(province.religion.as_deref(), Some("protestant"));
+}
+
 // -------------------------------------------------------------------------
 // Gamestate format detection tests
 // -------------------------------------------------------------------------
@@ -294,3 +315,238 @@ fn test_extract_country_modifiers_empty() {
     let modifiers = extract_country_modifiers(content);
     assert!(modifiers.is_empty());
 }
+
+// -------------------------------------------------------------------------
+// Military and diplomacy extraction tests
+// -------------------------------------------------------------------------
+
+fn empty_state() -> ExtractedState {
+    ExtractedState {
+        meta: SaveMeta {
+            date: "1450.1.1".to_string(),
+            player: None,
+            ironman: false,
+            save_version: None,
+        },
+        countries: HashMap::new(),
+        provinces: HashMap::new(),
+        subjects: HashMap::new(),
+        celestial_empire: None,
+        trade_nodes: HashMap::new(),
+        armies: Vec::new(),
+        fleets: Vec::new(),
+        wars: Vec::new(),
+        diplomacy: Default::default(),
+        military_and_diplomacy_extracted: false,
+    }
+}
+
+#[test]
+fn test_strip_nested_blocks() {
+    let block = r#" name="Army" regiment={ name="Reg" } location=183 "#;
+    let own = strip_nested_blocks(block);
+    assert!(own.contains(r#"name="Army""#));
+    assert!(own.contains("location=183"));
+    assert!(!own.contains("Reg"));
+}
+
+const MILITARY_SAVE: &str = "
+countries={
+\tFRA={
+\t\thistory={
+\t\t\t1449.3.1={
+\t\t\t\tleader={
+\t\t\t\t\tname=\"Jean de Dunois\"
+\t\t\t\t\ttype=general
+\t\t\t\t\tmanuever=3
+\t\t\t\t\tfire=2
+\t\t\t\t\tshock=4
+\t\t\t\t\tsiege=1
+\t\t\t\t\tid={
+\t\t\t\t\t\tid=7
+\t\t\t\t\t\ttype=49
+\t\t\t\t\t}
+\t\t\t\t}
+\t\t\t}
+\t\t}
+\t\tactive_relations={
+\t\t\tENG={
+\t\t\t\ttruce=yes
+\t\t\t\tlast_war=1449.6.1
+\t\t\t}
+\t\t\tBUR={
+\t\t\t\tlast_war=1440.1.1
+\t\t\t}
+\t\t}
+\t\trival={
+\t\t\tcountry=\"ENG\"
+\t\t\tdate=1445.1.1
+\t\t}
+\t\tarmy={
+\t\t\tid={
+\t\t\t\tid=100
+\t\t\t\ttype=54
+\t\t\t}
+\t\t\tname=\"Armee de Paris\"
+\t\t\tlocation=183
+\t\t\tregiment={
+\t\t\t\tname=\"Paris's Regiment\"
+\t\t\t\thome=183
+\t\t\t\ttype=\"western_medieval_infantry\"
+\t\t\t\tmorale=2.500
+\t\t\t\tstrength=0.850
+\t\t\t}
+\t\t\tregiment={
+\t\t\t\tname=\"Orleans's Regiment\"
+\t\t\t\thome=184
+\t\t\t\ttype=\"western_medieval_knights\"
+\t\t\t\tmorale=2.500
+\t\t\t}
+\t\t\tleader={
+\t\t\t\tid=7
+\t\t\t\ttype=49
+\t\t\t}
+\t\t}
+\t\tnavy={
+\t\t\tname=\"1st Fleet\"
+\t\t\tlocation=1275
+\t\t\tship={
+\t\t\t\tname=\"La Belle\"
+\t\t\t\ttype=\"carrack\"
+\t\t\t\tmorale=2.000
+\t\t\t\tstrength=0.500
+\t\t\t}
+\t\t}
+\t}
+}
+";
+
+#[test]
+fn test_extract_country_military() {
+    let mut state = empty_state();
+    state.countries.insert(
+        "FRA".to_string(),
+        crate::ExtractedCountry {
+            tag: "FRA".to_string(),
+            ..Default::default()
+        },
+    );
+    extract_country_military(MILITARY_SAVE, &mut state).unwrap();
+
+    assert_eq!(state.armies.len(), 1);
+    let army = &state.armies[0];
+    assert_eq!(army.owner, "FRA");
+    assert_eq!(army.name, "Armee de Paris");
+    assert_eq!(army.location, 183);
+    assert_eq!(army.regiments.len(), 2);
+    assert_eq!(army.regiments[0].unit_type, "western_medieval_infantry");
+    assert!((army.regiments[0].strength - 0.85).abs() < 1e-9);
+    // Missing strength means full strength
+    assert!((army.regiments[1].strength - 1.0).abs() < 1e-9);
+    let general = army
+        .general
+        .as_ref()
+        .expect("general resolved from history");
+    assert_eq!(general.name, "Jean de Dunois");
+    assert_eq!(
+        (general.fire, general.shock, general.maneuver, general.siege),
+        (2, 4, 3, 1)
+    );
+
+    assert_eq!(state.fleets.len(), 1);
+    assert_eq!(state.fleets[0].location, 1275);
+    assert_eq!(state.fleets[0].ships[0].unit_type, "carrack");
+    assert!(state.fleets[0].admiral.is_none());
+
+    // Only relations flagged truce=yes count
+    assert_eq!(
+        state.diplomacy.truces,
+        vec![crate::ExtractedTruce {
+            first: "FRA".to_string(),
+            second: "ENG".to_string(),
+            last_war: "1449.6.1".to_string(),
+        }]
+    );
+    assert_eq!(state.countries["FRA"].rivals, vec!["ENG".to_string()]);
+}
+
+#[test]
+fn test_extract_wars() {
+    let text = "
+active_war={
+\tname=\"Hundred Years' War\"
+\thistory={
+\t\tname=\"Hundred Years' War\"
+\t\twar_goal={
+\t\t\ttype=\"take_claim\"
+\t\t\tcasus_belli=\"cb_claim\"
+\t\t\tprovince=183
+\t\t}
+\t\t1449.10.2={
+\t\t\tadd_attacker=\"SCO\"
+\t\t}
+\t\t1449.9.15={
+\t\t\tadd_attacker=\"ENG\"
+\t\t\tadd_defender=\"FRA\"
+\t\t}
+\t\t1449.11.1={
+\t\t\tadd_defender=\"BRI\"
+\t\t\trem_defender=\"BRI\"
+\t\t}
+\t}
+\toriginal_attacker=\"ENG\"
+\toriginal_defender=\"FRA\"
+}
+";
+    let mut state = empty_state();
+    extract_wars(text, &mut state);
+
+    assert_eq!(state.wars.len(), 1);
+    let war = &state.wars[0];
+    assert_eq!(war.name, "Hundred Years' War");
+    // War leader first, removed participants dropped
+    assert_eq!(war.attackers, vec!["ENG".to_string(), "SCO".to_string()]);
+    assert_eq!(war.defenders, vec!["FRA".to_string()]);
+    assert_eq!(war.start_date.as_deref(), Some("1449.9.15"));
+    assert_eq!(
+        war.war_goal,
+        Some(crate::ExtractedWarGoal {
+            goal_type: "take_claim".to_string(),
+            casus_belli: "cb_claim".to_string(),
+            province: Some(183),
+        })
+    );
+}
+
+#[test]
+fn test_extract_relations() {
+    let text = "
+diplomacy={
+\tdependency={
+\t\tfirst=\"FRA\"
+\t\tsecond=\"PRO\"
+\t\tsubject_type=\"vassal\"
+\t}
+\talliance={
+\t\tfirst=\"FRA\"
+\t\tsecond=\"SCO\"
+\t\tstart_date=1444.11.11
+\t}
+\troyal_marriage={
+\t\tfirst=\"CAS\"
+\t\tsecond=\"ARA\"
+\t}
+}
+";
+    let mut state = empty_state();
+    extract_relations(text, &mut state);
+
+    assert_eq!(
+        state.diplomacy.alliances,
+        vec![("FRA".to_string(), "SCO".to_string())]
+    );
+    assert_eq!(
+        state.diplomacy.royal_marriages,
+        vec![("CAS".to_string(), "ARA".to_string())]
+    );
+}
@@ -3,22 +3,26 @@
 //! Validates simulation stepping by:
 //! 1. Loading save at time T
 //! 2. Hydrating to WorldState
-//! 3. Running step_world() N times
+//! 3. Running step_world() N times, optionally replaying player actions
+//!    inferred from the save diff (see [`crate::replay`])
 //! 4. Comparing predicted state to save at time T+N
 
+use crate::diff::infer_actions;
 use crate::hydrate::hydrate_from_save;
-use crate::ledger_comparison::print_ledger_comparison;
+use crate::ledger_comparison::{print_ledger_comparison, LedgerComparison};
 use crate::parse::load_save;
-use crate::ExtractedState;
+use crate::replay::{ReplayOutcome, ReplayPlan};
+use crate::{ExtractedCountry, ExtractedState};
 use anyhow::Result;
 use eu4sim_core::config::SimConfig;
-use eu4sim_core::state::Date;
-use eu4sim_core::step::step_world;
+use eu4sim_core::state::{CountryState, Date};
+use eu4sim_core::step::step_world_mut;
 use eu4sim_core::WorldState;
+use serde::Serialize;
 use std::path::Path;
 
 /// Result of a single metric prediction
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PredictionResult {
     pub metric: String,
     pub predicted: f64,
@@ -27,7 +31,18 @@ pub struct PredictionResult {
     pub status: PredictionStatus,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+impl PredictionResult {
+    /// Relative error in percent (absolute delta × 100 when actual is ~0)
+    pub fn pct_error(&self) -> f64 {
+        if self.actual.abs() > 0.001 {
+            (self.delta / self.actual).abs() * 100.0
+        } else {
+            self.delta.abs() * 100.0
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub enum PredictionStatus {
     Pass,  // Within 5%
     Close, // Within 10%
@@ -43,18 +58,23 @@ pub struct PredictionSummary {
     pub days_simulated: u32,
     pub country: String,
     pub results: Vec<PredictionResult>,
+    /// Replayed player actions (None for passive prediction)
+    pub replay: Option<ReplayOutcome>,
+    /// Target save's ledger vs the sim's last simulated month
+    pub ledger: Option<LedgerComparison>,
 }
 
 /// Run prediction from save T to save T+N
+///
+/// With `replay_actions`, actions inferred by diffing the two saves are injected
+/// as player commands during the run.
 pub fn run_prediction(
     game_path: &Path,
     from_save: &Path,
     to_save: &Path,
     country: &str,
+    replay_actions: bool,
 ) -> Result<PredictionSummary> {
-    // 0. Print ledger comparison for debugging
-    print_ledger_comparison(from_save, to_save, country)?;
-
     // 1. Load and parse both saves
     log::info!("Loading source save: {:?}", from_save);
     let from_state = load_save(from_save)?;
@@ -90,7 +110,20 @@ pub fn run_prediction(
         );
     }
 
-    // 4. Run simulation for N days (passive - no inputs)
+    // 4. Run simulation for N days (passive, or replaying inferred actions)
+    let plan = replay_actions.then(|| {
+        let diff = infer_actions(&from_state, &to_state);
+        let plan = ReplayPlan::new(&diff.actions, &world, days);
+        log::info!(
+            "Replaying {} commands from {} inferred actions ({} not replayable)",
+            plan.scheduled.len(),
+            diff.actions.len(),
+            plan.skipped.len()
+        );
+        plan
+    });
+    let mut replay = plan.as_ref().map(ReplayOutcome::new);
+
     let config = SimConfig {
         checksum_frequency: 0, // Disable checksums for speed
     };
@@ -112,7 +145,11 @@ pub fn run_prediction(
             0.0
         };
 
-        world = step_world(&world, &[], Some(&adjacency), &config, None);
+        let inputs = match (&plan, replay.as_mut()) {
+            (Some(plan), Some(outcome)) => outcome.inputs_for_day(plan, day, &world, &adjacency),
+            _ => Vec::new(),
+        };
+        step_world_mut(&mut world, &inputs, Some(&adjacency), &config, None);
 
         let new_treasury = if let Some(c) = world.countries.get(country) {
             c.treasury.to_f32()
@@ -180,6 +217,11 @@ pub fn run_prediction(
     }
 
     let results = compare_country(&world, &to_state, country);
+    let ledger = to_state
+        .countries
+        .get(country)
+        .zip(world.countries.get(country))
+        .map(|(actual, predicted)| LedgerComparison::new(&to_date, actual, predicted));
 
     Ok(PredictionSummary {
         from_date,
@@ -187,6 +229,8 @@ pub fn run_prediction(
         days_simulated: days,
         country: country.to_string(),
         results,
+        replay,
+        ledger,
     })
 }
 
@@ -196,8 +240,6 @@ fn compare_country(
     actual: &ExtractedState,
     tag: &str,
 ) -> Vec<PredictionResult> {
-    let mut results = Vec::new();
-
     // Get predicted country state
     let pred_country = match predicted.countries.get(tag) {
         Some(c) => c,
@@ -282,6 +324,18 @@ fn compare_country(
         pred_country.income.expenses.to_f32()
     );
 
+    compare_metrics(pred_country, actual_country)
+}
+
+/// Compare a predicted country against its state in the target save
+///
+/// Covers manpower, treasury and monarch power; metrics the save lacks are left out.
+pub fn compare_metrics(
+    pred_country: &CountryState,
+    actual_country: &ExtractedCountry,
+) -> Vec<PredictionResult> {
+    let mut results = Vec::new();
+
     // Compare manpower (sim stores raw men, save stores thousands)
     // Display as raw men for clarity
     if let Some(actual_mp) = actual_country.current_manpower {
@@ -297,7 +351,7 @@ fn compare_country(
         // Debug: Show income breakdown
         log::debug!(
             "{} income breakdown - Tax: {}, Prod: {}, Trade: {}, Expenses: {}",
-            tag,
+            actual_country.tag,
             pred_country.income.taxation,
             pred_country.income.production,
             pred_country.income.trade,
@@ -325,33 +379,28 @@ fn compare_country(
 }
 
 /// Compare a single metric and determine status
-fn compare_metric(name: &str, predicted: f64, actual: f64) -> PredictionResult {
-    let delta = predicted - actual;
-    let pct_diff = if actual.abs() > 0.001 {
-        (delta / actual).abs() * 100.0
-    } else {
-        delta.abs() * 100.0
+pub(crate) fn compare_metric(name: &str, predicted: f64, actual: f64) -> PredictionResult {
+    let mut result = PredictionResult {
+        metric: name.to_string(),
+        predicted,
+        actual,
+        delta: predicted - actual,
+        status: PredictionStatus::Skip,
     };
 
-    let status = if pct_diff <= 5.0 {
+    let pct_diff = result.pct_error();
+    result.status = if pct_diff <= 5.0 {
         PredictionStatus::Pass
     } else if pct_diff <= 10.0 {
         PredictionStatus::Close
     } else {
         PredictionStatus::Fail
     };
-
-    PredictionResult {
-        metric: name.to_string(),
-        predicted,
-        actual,
-        delta,
-        status,
-    }
+    result
 }
 
 /// Parse date string "YYYY.MM.DD" into Date
-fn parse_date(date_str: &str) -> Result<Date> {
+pub(crate) fn parse_date(date_str: &str) -> Result<Date> {
     let parts: Vec<&str> = date_str.split('.').collect();
     if parts.len() != 3 {
         anyhow::bail!("Invalid date format: {}", date_str);
@@ -365,7 +414,7 @@ fn parse_date(date_str: &str) -> Result<Date> {
 }
 
 /// Calculate days between two dates
-fn days_between(from: &Date, to: &Date) -> u32 {
+pub(crate) fn days_between(from: &Date, to: &Date) -> u32 {
     let from_days = from.days_from_epoch();
     let to_days = to.days_from_epoch();
 
@@ -405,6 +454,45 @@ pub fn print_prediction_report(summary: &PredictionSummary) {
         );
     }
 
+    if let Some(ledger) = &summary.ledger {
+        println!();
+        print_ledger_comparison(ledger);
+    }
+
+    if let Some(replay) = &summary.replay {
+        println!();
+        println!(
+            "Replayed actions: {} applied, {} rejected by sim, {} not replayable",
+            replay.applied.len(),
+            replay.rejected.len(),
+            replay.skipped.len()
+        );
+        for cmd in &replay.applied {
+            println!("  [day {:>3}] {}", cmd.day, cmd.source);
+        }
+        for cmd in &replay.rejected {
+            println!("  [day {:>3}] REJECTED {}", cmd.day, cmd.source);
+        }
+        for skipped in &replay.skipped {
+            println!("  SKIPPED {} ({})", skipped.action, skipped.reason);
+        }
+
+        let remaining: Vec<&str> = summary
+            .results
+            .iter()
+            .filter(|r| r.status == PredictionStatus::Fail)
+            .map(|r| r.metric.as_str())
+            .collect();
+        if remaining.is_empty() {
+            println!("No mismatches remain after accounting for player actions.");
+        } else {
+            println!(
+                "Mismatches remaining after accounting for player actions: {}",
+                remaining.join(", ")
+            );
+        }
+    }
+
     println!();
 }
 
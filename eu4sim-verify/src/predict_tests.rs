@@ -163,6 +163,8 @@ fn test_prediction_summary_creation() {
         days_simulated: 20,
         country: "TUR".to_string(),
         results: vec![compare_metric("Treasury", 500.0, 500.0)],
+        replay: None,
+        ledger: None,
     };
 
     assert_eq!(summary.from_date, "1444.11.11");
@@ -0,0 +1,350 @@
+//! Action replay: feed diff-inferred player actions into prediction
+//!
+//! Passive prediction steps the sim with no inputs, so any window where the
+//! player built, dev-clicked, etc. fails verification. This module converts the
+//! [`InferredAction`]s found by [`crate::diff::infer_actions`] into sim
+//! [`Command`]s and schedules them on the day they must have happened, so only
+//! mismatches the player's actions can't explain remain.
+//!
+//! Saves don't record *when* inside the window an action happened, so timing is
+//! the earliest day consistent with the target save:
+//! - Dev clicks take effect immediately, so they replay on the first day.
+//! - Buildings are paid for when ordered and appear after their build time, so
+//!   they replay `build time` before the target date. A building that must have
+//!   been ordered before the source save was already paid for and isn't replayed.
+
+use crate::diff::{DevType, InferredAction};
+use eu4data::adjacency::AdjacencyGraph;
+use eu4sim_core::input::{Command, DevType as SimDevType, PlayerInputs};
+use eu4sim_core::step::execute_command;
+use eu4sim_core::WorldState;
+use std::collections::BTreeMap;
+
+/// A command scheduled for injection on a given day of the prediction window.
+#[derive(Debug, Clone)]
+pub struct ScheduledCommand {
+    /// Step index (0 = first simulated day)
+    pub day: u32,
+    /// Country issuing the command
+    pub country: String,
+    pub command: Command,
+    /// The inferred action this command replays
+    pub source: InferredAction,
+}
+
+/// An inferred action that could not be replayed, with the reason.
+#[derive(Debug, Clone)]
+pub struct SkippedAction {
+    pub action: InferredAction,
+    pub reason: String,
+}
+
+/// Commands to inject during prediction, derived from inferred actions.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayPlan {
+    /// Commands in day order
+    pub scheduled: Vec<ScheduledCommand>,
+    /// Actions with no command equivalent (or impossible timing)
+    pub skipped: Vec<SkippedAction>,
+}
+
+impl ReplayPlan {
+    /// Build a replay plan for a prediction window of `days` days starting at `world`.
+    pub fn new(actions: &[InferredAction], world: &WorldState, days: u32) -> Self {
+        let mut plan = ReplayPlan::default();
+
+        for action in actions {
+            match action {
+                InferredAction::DevelopProvince {
+                    province_id,
+                    dev_type,
+                    from,
+                    to,
+                    owner,
+                    ..
+                } => {
+                    let Some(country) = owner
+                        .clone()
+                        .or_else(|| province_owner(world, *province_id))
+                    else {
+                        plan.skip(action, "province has no owner");
+                        continue;
+                    };
+                    // One command per development click
+                    let clicks = (to - from).round().max(0.0) as u32;
+                    for _ in 0..clicks {
+                        plan.scheduled.push(ScheduledCommand {
+                            day: 0,
+                            country: country.clone(),
+                            command: Command::DevelopProvince {
+                                province: *province_id,
+                                dev_type: sim_dev_type(*dev_type),
+                            },
+                            source: action.clone(),
+                        });
+                    }
+                }
+                InferredAction::BuildBuilding {
+                    province_id,
+                    building,
+                    owner,
+                    ..
+                } => {
+                    let Some(country) = owner
+                        .clone()
+                        .or_else(|| province_owner(world, *province_id))
+                    else {
+                        plan.skip(action, "province has no owner");
+                        continue;
+                    };
+                    let Some(def) = world
+                        .building_name_to_id
+                        .get(building)
+                        .and_then(|id| world.building_defs.get(id))
+                    else {
+                        plan.skip(action, "building not defined in game data");
+                        continue;
+                    };
+                    let build_days = def.time as u32 * 30;
+                    if build_days > days {
+                        plan.skip(
+                            action,
+                            &format!(
+                                "ordered before the source save ({} day build time)",
+                                build_days
+                            ),
+                        );
+                        continue;
+                    }
+                    plan.scheduled.push(ScheduledCommand {
+                        day: days - build_days,
+                        country,
+                        command: Command::BuildInProvince {
+                            province: *province_id,
+                            building: building.clone(),
+                        },
+                        source: action.clone(),
+                    });
+                }
+                InferredAction::HireAdvisor { .. } | InferredAction::DismissAdvisor { .. } => {
+                    plan.skip(action, "no advisor commands in the sim")
+                }
+                InferredAction::Vassalize { .. } | InferredAction::ReleaseSubject { .. } => {
+                    plan.skip(action, "no subject diplomacy commands in the sim")
+                }
+                // Effects of other actions, not actions themselves
+                InferredAction::SpendMana { .. } | InferredAction::TreasuryChange { .. } => {}
+            }
+        }
+
+        plan.scheduled.sort_by_key(|c| c.day);
+        plan
+    }
+
+    fn skip(&mut self, action: &InferredAction, reason: &str) {
+        self.skipped.push(SkippedAction {
+            action: action.clone(),
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Commands due on `day`.
+    pub fn due(&self, day: u32) -> impl Iterator<Item = &ScheduledCommand> {
+        self.scheduled.iter().filter(move |c| c.day == day)
+    }
+}
+
+/// Outcome of injecting a replay plan during prediction.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayOutcome {
+    /// Commands the sim accepted
+    pub applied: Vec<ScheduledCommand>,
+    /// Commands the sim would not allow on their scheduled day
+    pub rejected: Vec<ScheduledCommand>,
+    /// Actions that were never scheduled
+    pub skipped: Vec<SkippedAction>,
+}
+
+impl ReplayOutcome {
+    pub fn new(plan: &ReplayPlan) -> Self {
+        Self {
+            skipped: plan.skipped.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Build the inputs for `day`, keeping only commands the sim currently allows.
+    ///
+    /// Rejected commands are recorded rather than sent, since the sim would
+    /// silently drop them. Each command is checked against the state left by
+    /// the ones accepted before it that day, so clicks share one mana pool.
+    pub fn inputs_for_day(
+        &mut self,
+        plan: &ReplayPlan,
+        day: u32,
+        world: &WorldState,
+        adjacency: &AdjacencyGraph,
+    ) -> Vec<PlayerInputs> {
+        let mut by_country: BTreeMap<&str, Vec<Command>> = BTreeMap::new();
+        // Running state with the day's accepted commands applied
+        let mut running: Option<WorldState> = None;
+
+        for scheduled in plan.due(day) {
+            let state = running.get_or_insert_with(|| world.clone());
+            let accepted = state
+                .available_commands(&scheduled.country, Some(adjacency))
+                .contains(&scheduled.command)
+                && execute_command(
+                    state,
+                    &scheduled.country,
+                    &scheduled.command,
+                    Some(adjacency),
+                )
+                .is_ok();
+            if accepted {
+                by_country
+                    .entry(&scheduled.country)
+                    .or_default()
+                    .push(scheduled.command.clone());
+                self.applied.push(scheduled.clone());
+            } else {
+                self.rejected.push(scheduled.clone());
+            }
+        }
+
+        by_country
+            .into_iter()
+            .map(|(country, commands)| PlayerInputs {
+                country: country.to_string(),
+                commands,
+                available_commands: Vec::new(),
+                visible_state: None,
+            })
+            .collect()
+    }
+}
+
+fn province_owner(world: &WorldState, province: u32) -> Option<String> {
+    world.provinces.get(&province)?.owner.clone()
+}
+
+fn sim_dev_type(dev_type: DevType) -> SimDevType {
+    match dev_type {
+        DevType::Tax => SimDevType::Tax,
+        DevType::Production => SimDevType::Production,
+        DevType::Manpower => SimDevType::Manpower,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eu4sim_core::testing::WorldStateBuilder;
+
+    fn dev_click(province_id: u32, from: f64, to: f64) -> InferredAction {
+        InferredAction::DevelopProvince {
+            province_id,
+            province_name: None,
+            dev_type: DevType::Tax,
+            from,
+            to,
+            owner: None,
+        }
+    }
+
+    #[test]
+    fn test_dev_clicks_replay_on_first_day() {
+        let world = WorldStateBuilder::new()
+            .with_country("KOR")
+            .with_province(735, Some("KOR"))
+            .build();
+
+        let plan = ReplayPlan::new(&[dev_click(735, 3.0, 5.0)], &world, 30);
+
+        assert_eq!(plan.scheduled.len(), 2);
+        assert!(plan
+            .scheduled
+            .iter()
+            .all(|c| c.day == 0 && c.country == "KOR"));
+        assert_eq!(
+            plan.scheduled[0].command,
+            Command::DevelopProvince {
+                province: 735,
+                dev_type: SimDevType::Tax,
+            }
+        );
+    }
+
+    #[test]
+    fn test_unreplayable_actions_are_reported() {
+        let world = WorldStateBuilder::new().with_province(735, None).build();
+        let actions = [
+            dev_click(735, 3.0, 4.0),
+            InferredAction::HireAdvisor {
+                country: "KOR".to_string(),
+                advisor_type: "treasurer".to_string(),
+                skill: 1,
+            },
+            InferredAction::BuildBuilding {
+                province_id: 735,
+                province_name: None,
+                building: "not_a_building".to_string(),
+                owner: Some("KOR".to_string()),
+            },
+            InferredAction::TreasuryChange {
+                country: "KOR".to_string(),
+                delta: -100.0,
+                likely_cause: String::new(),
+            },
+        ];
+
+        let plan = ReplayPlan::new(&actions, &world, 30);
+
+        assert!(plan.scheduled.is_empty());
+        // TreasuryChange is an effect, not an action, so it isn't listed
+        assert_eq!(plan.skipped.len(), 3);
+        assert_eq!(plan.skipped[0].reason, "province has no owner");
+    }
+
+    #[test]
+    fn test_disallowed_commands_are_rejected_not_sent() {
+        // No admin mana, so the dev click isn't available
+        let world = WorldStateBuilder::new()
+            .with_country("KOR")
+            .with_province(735, Some("KOR"))
+            .build();
+        let plan = ReplayPlan::new(&[dev_click(735, 3.0, 4.0)], &world, 30);
+        let mut outcome = ReplayOutcome::new(&plan);
+
+        let inputs = outcome.inputs_for_day(&plan, 0, &world, &AdjacencyGraph::new());
+
+        assert!(inputs.is_empty());
+        assert!(outcome.applied.is_empty());
+        assert_eq!(outcome.rejected.len(), 1);
+    }
+
+    #[test]
+    fn test_same_day_clicks_share_one_mana_pool() {
+        // Enough admin mana for one 50-mana click, not three
+        let mut world = WorldStateBuilder::new()
+            .with_country("KOR")
+            .with_province(735, Some("KOR"))
+            .build();
+        world.countries.get_mut("KOR").unwrap().adm_mana = eu4sim_core::Fixed::from_int(60);
+        let plan = ReplayPlan::new(&[dev_click(735, 3.0, 6.0)], &world, 30);
+        assert_eq!(plan.scheduled.len(), 3);
+        let mut outcome = ReplayOutcome::new(&plan);
+
+        let inputs = outcome.inputs_for_day(&plan, 0, &world, &AdjacencyGraph::new());
+
+        assert_eq!(outcome.applied.len(), 1);
+        assert_eq!(outcome.rejected.len(), 2);
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].commands.len(), 1);
+        // The caller's state is untouched; the sim applies the sent click
+        assert_eq!(
+            world.countries["KOR"].adm_mana,
+            eu4sim_core::Fixed::from_int(60)
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Binary token table for ironman saves
+//!
+//! Binary saves store field names as 16-bit token ids. `eu4tokens` writes the
+//! id → name table to `assets/tokens/eu4.txt` as `0x1234 name` lines; this module
+//! loads it for [`crate::melt`] and for parsing binary saves in [`crate::parse`].
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the token file (also read by eu4save's `EnvTokens`)
+pub const TOKENS_ENV: &str = "EU4_IRONMAN_TOKENS";
+
+/// Token id → field name table
+#[derive(Debug, Clone, Default)]
+pub struct TokenTable {
+    names: HashMap<u16, String>,
+}
+
+impl TokenTable {
+    /// Parse `ID NAME` lines. Ids are hex with a `0x` prefix (as written by
+    /// `eu4tokens`) or decimal; blank lines and `#` comments are skipped.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut names = HashMap::new();
+        for (line_num, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (id, name) = line.split_once(char::is_whitespace).with_context(|| {
+                format!("Invalid token line {}: expected 'ID NAME'", line_num + 1)
+            })?;
+            let id = match id.strip_prefix("0x").or_else(|| id.strip_prefix("0X")) {
+                Some(hex) => u16::from_str_radix(hex, 16),
+                None => id.parse(),
+            }
+            .with_context(|| format!("Invalid token ID on line {}: {}", line_num + 1, id))?;
+            names.insert(id, name.trim().to_string());
+        }
+        Ok(Self { names })
+    }
+
+    /// Load a token file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read tokens file: {}", path.display()))?;
+        let table = Self::parse(&content)?;
+        log::info!("Loaded {} tokens from {}", table.len(), path.display());
+        Ok(table)
+    }
+
+    /// Load the token file found by [`find_tokens_file`], if any
+    pub fn discover() -> Result<Option<Self>> {
+        find_tokens_file().map(|path| Self::load(&path)).transpose()
+    }
+
+    /// Field name for a token id
+    pub fn resolve(&self, id: u16) -> Option<&str> {
+        self.names.get(&id).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+/// Locate the token file
+///
+/// Checks `EU4_IRONMAN_TOKENS` (set by the `--tokens` flag), then
+/// `assets/tokens/eu4.txt` relative to the working directory and the executable.
+pub fn find_tokens_file() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(TOKENS_ENV) {
+        return Some(PathBuf::from(path));
+    }
+
+    let candidates = [
+        "assets/tokens/eu4.txt",
+        "../assets/tokens/eu4.txt",
+        "eu4.tokens.txt",
+    ];
+    for candidate in candidates {
+        let path = PathBuf::from(candidate);
+        if path.exists() {
+            return path.canonicalize().ok();
+        }
+    }
+
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let path = exe_dir.join("assets/tokens/eu4.txt");
+    path.exists().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_and_decimal_ids() {
+        let table = TokenTable::parse(
+            "# eu4 1.37\n\
+             0x2c69 date\n\
+             \n\
+             0x00e1 treasury\n\
+             284 countries\n",
+        )
+        .unwrap();
+
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.resolve(0x2c69), Some("date"));
+        assert_eq!(table.resolve(0x00e1), Some("treasury"));
+        assert_eq!(table.resolve(284), Some("countries"));
+        assert_eq!(table.resolve(0x1234), None);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_lines() {
+        assert!(TokenTable::parse("0x2c69").is_err());
+        assert!(TokenTable::parse("0xzzzz date").is_err());
+        assert!(TokenTable::parse("70000 date").is_err());
+    }
+}
@@ -0,0 +1,76 @@
+//! Per-tick AI input generation.
+//!
+//! Builds each AI's fog-of-war [`VisibleWorldState`](eu4sim_core::ai::VisibleWorldState),
+//! asks it for commands, and returns the resulting [`PlayerInputs`]. Shared by
+//! the interactive loop and `eu4sim batch`.
+
+use eu4sim_core::{PlayerInputs, WorldState};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+/// Generate inputs for every country in `ais` (in parallel).
+///
+/// In gp-only mode, countries without an AI auto-accept peace offers so wars
+/// against passive countries can still end.
+pub fn generate_ai_inputs(
+    state: &WorldState,
+    adjacency: &eu4data::adjacency::AdjacencyGraph,
+    ais: &mut BTreeMap<String, Box<dyn eu4sim_core::AiPlayer>>,
+    gp_only: bool,
+) -> Vec<PlayerInputs> {
+    // Per-tick data shared by every AI's view (army strength, neighbours)
+    let visibility = eu4sim_core::ai::VisibilityIndex::new(state, Some(adjacency));
+
+    // Generate AI commands for all countries (parallel)
+    // Returns PlayerInputs for ALL countries so datagen can use precomputed available_commands
+    let mut inputs: Vec<PlayerInputs> = ais
+        .par_iter_mut()
+        .map(|(tag, ai)| {
+            // Build visible state with fog-of-war filtered intelligence
+            let visible_state = visibility.visible_state(state, tag, Some(adjacency));
+
+            // Compute available commands once - reused by AI and datagen
+            let available = state.available_commands(tag, Some(adjacency));
+            ai.observe_world(state);
+            let cmds = ai.decide(&visible_state, &available);
+
+            PlayerInputs {
+                country: tag.clone(),
+                commands: cmds,
+                available_commands: available,
+                visible_state: Some(visible_state),
+            }
+        })
+        .collect();
+
+    // In gp-only mode: auto-accept peace offers for passive countries (no AI)
+    if gp_only {
+        let ai_tags: std::collections::HashSet<_> = ais.keys().cloned().collect();
+        for war in state.diplomacy.wars.values() {
+            if let Some(pending) = &war.pending_peace {
+                // Find the target of the peace offer (opposite side from offerer)
+                let target_side = if pending.from_attacker {
+                    &war.defenders
+                } else {
+                    &war.attackers
+                };
+
+                // If any target country is passive (no AI), auto-accept
+                for target_tag in target_side {
+                    if !ai_tags.contains(target_tag) {
+                        inputs.push(PlayerInputs {
+                            country: target_tag.clone(),
+                            commands: vec![eu4sim_core::Command::AcceptPeace { war_id: war.id }],
+                            available_commands: vec![],
+                            visible_state: None,
+                        });
+                        log::info!("[AUTO-ACCEPT] {} accepts peace in {}", target_tag, war.name);
+                        break; // Only need one country to accept
+                    }
+                }
+            }
+        }
+    }
+
+    inputs
+}
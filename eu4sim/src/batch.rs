@@ -0,0 +1,662 @@
+//! `eu4sim batch`: Monte Carlo runs over many seeds in one process.
+//!
+//! Game data is loaded once. Each run forks the initial [`WorldState`] with its
+//! own seed and runs to the target date on the rayon pool. Events are
+//! collected with an in-memory [`EventLogObserver`]. The per-run outcomes are
+//! reduced into one [`BatchReport`] (country survival, development
+//! distribution, war counts), which is written as JSON or CSV.
+
+use anyhow::{Context, Result};
+use eu4sim_core::observer::event_log::{EventLogObserver, GameEvent};
+use eu4sim_core::state::Date;
+use eu4sim_core::{step_world_mut, ObserverRegistry, SimConfig, Snapshot, WorldState};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+#[derive(clap::Args, Debug)]
+pub struct BatchArgs {
+    /// Number of runs. Run `i` uses seed `--seed + i`.
+    #[arg(long, default_value_t = 16)]
+    pub runs: u64,
+
+    /// Run each simulation until this date (YYYY or YYYY.MM.DD)
+    #[arg(long, default_value = "1454.1.1", value_parser = parse_date)]
+    pub until: Date,
+
+    /// Worker threads (default: one per core)
+    #[arg(long)]
+    pub threads: Option<usize>,
+
+    /// Report format
+    #[arg(long, value_enum, default_value_t = ReportFormat::Json)]
+    pub format: ReportFormat,
+
+    /// Write the report to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Include every run's summary in the JSON report
+    #[arg(long)]
+    pub per_run: bool,
+
+    /// Also write each run's event log to `<DIR>/seed-<SEED>.jsonl`
+    #[arg(long, value_name = "DIR")]
+    pub event_log_dir: Option<PathBuf>,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Full report as pretty-printed JSON
+    Json,
+    /// One row per country
+    Csv,
+}
+
+/// Parse `YYYY` or `YYYY.MM.DD`.
+pub(crate) fn parse_date(s: &str) -> Result<Date, String> {
+    let parts: Vec<&str> = s.split('.').collect();
+    let parse = |p: &str, what: &str| {
+        p.parse::<i32>()
+            .map_err(|_| format!("invalid {} '{}' in date '{}'", what, p, s))
+    };
+    match parts.as_slice() {
+        [year] => Ok(Date::new(parse(year, "year")?, 1, 1)),
+        [year, month, day] => {
+            let month = parse(month, "month")?;
+            let day = parse(day, "day")?;
+            if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+                return Err(format!("date out of range: '{}'", s));
+            }
+            Ok(Date::new(parse(year, "year")?, month as u8, day as u8))
+        }
+        _ => Err(format!("expected YYYY or YYYY.MM.DD, got '{}'", s)),
+    }
+}
+
+/// Settings shared by every run in the batch.
+struct RunSettings<'a> {
+    until: Date,
+    ai: &'a str,
+    greedy_count: usize,
+    event_log_dir: Option<&'a std::path::Path>,
+}
+
+/// Outcome of a single country at the end of a run.
+#[derive(Debug, Clone, Serialize)]
+pub struct CountryOutcome {
+    /// Still owns at least one province
+    pub alive: bool,
+    pub development: f64,
+    pub provinces: u32,
+    /// Wars this country declared (as war leader)
+    pub wars_declared: u32,
+}
+
+/// Counts of notable events from one run's event log.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EventCounts {
+    pub wars_declared: u32,
+    pub white_peaces: u32,
+    pub province_peaces: u32,
+    pub annexations: u32,
+    pub eliminations: u32,
+    pub battles: u32,
+    pub sieges: u32,
+}
+
+/// Summary of a single run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub seed: u64,
+    pub end_date: String,
+    pub ticks: u64,
+    pub events: EventCounts,
+    pub countries: BTreeMap<String, CountryOutcome>,
+}
+
+/// Summary statistics of a sample.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Distribution {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub p10: f64,
+    pub median: f64,
+    pub p90: f64,
+    pub max: f64,
+}
+
+impl Distribution {
+    /// Population statistics; percentiles use the nearest-rank method.
+    pub fn from_samples(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / n;
+        let variance = sorted.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+        let rank = |p: f64| sorted[((p * n).ceil() as usize).clamp(1, sorted.len()) - 1];
+        Self {
+            mean,
+            std_dev: variance.sqrt(),
+            min: sorted[0],
+            p10: rank(0.1),
+            median: rank(0.5),
+            p90: rank(0.9),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+/// Aggregate statistics for one country across all runs.
+#[derive(Debug, Clone, Serialize)]
+pub struct CountryStats {
+    /// Fraction of runs in which the country survived
+    pub survival_rate: f64,
+    /// End-of-run development (0 when the country died)
+    pub development: Distribution,
+    pub wars_declared_mean: f64,
+}
+
+/// Aggregate report over all runs.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchReport {
+    pub runs: usize,
+    pub start_date: String,
+    pub end_date: String,
+    pub ai: String,
+    pub seeds: Vec<u64>,
+    pub wars_declared: Distribution,
+    pub battles: Distribution,
+    pub annexations: Distribution,
+    pub countries: BTreeMap<String, CountryStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_run: Option<Vec<RunSummary>>,
+}
+
+impl BatchReport {
+    pub fn from_runs(start: Date, ai: &str, runs: Vec<RunSummary>, keep_runs: bool) -> Self {
+        let sample = |f: &dyn Fn(&RunSummary) -> u32| -> Distribution {
+            let values: Vec<f64> = runs.iter().map(|r| f(r) as f64).collect();
+            Distribution::from_samples(&values)
+        };
+
+        let tags: std::collections::BTreeSet<&String> =
+            runs.iter().flat_map(|r| r.countries.keys()).collect();
+        let countries = tags
+            .into_iter()
+            .map(|tag| {
+                let outcomes: Vec<Option<&CountryOutcome>> =
+                    runs.iter().map(|r| r.countries.get(tag)).collect();
+                let n = outcomes.len().max(1) as f64;
+                let alive = outcomes
+                    .iter()
+                    .filter(|o| o.is_some_and(|o| o.alive))
+                    .count();
+                let dev: Vec<f64> = outcomes
+                    .iter()
+                    .map(|o| o.filter(|o| o.alive).map_or(0.0, |o| o.development))
+                    .collect();
+                let wars: u32 = outcomes.iter().flatten().map(|o| o.wars_declared).sum();
+                (
+                    tag.clone(),
+                    CountryStats {
+                        survival_rate: alive as f64 / n,
+                        development: Distribution::from_samples(&dev),
+                        wars_declared_mean: wars as f64 / n,
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            runs: runs.len(),
+            start_date: start.to_string(),
+            end_date: runs.first().map(|r| r.end_date.clone()).unwrap_or_default(),
+            ai: ai.to_string(),
+            seeds: runs.iter().map(|r| r.seed).collect(),
+            wars_declared: sample(&|r| r.events.wars_declared),
+            battles: sample(&|r| r.events.battles),
+            annexations: sample(&|r| r.events.annexations),
+            countries,
+            per_run: keep_runs.then_some(runs),
+        }
+    }
+
+    /// One row per country, sorted by tag.
+    pub fn write_csv(&self, out: &mut dyn Write) -> std::io::Result<()> {
+        writeln!(
+            out,
+            "tag,survival_rate,dev_mean,dev_std,dev_min,dev_p10,dev_median,dev_p90,dev_max,wars_declared_mean"
+        )?;
+        for (tag, stats) in &self.countries {
+            let d = &stats.development;
+            writeln!(
+                out,
+                "{},{:.4},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2},{:.2},{:.4}",
+                tag,
+                stats.survival_rate,
+                d.mean,
+                d.std_dev,
+                d.min,
+                d.p10,
+                d.median,
+                d.p90,
+                d.max,
+                stats.wars_declared_mean
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// `Write` into a shared in-memory buffer, so the event log can be read back
+/// after the observer (which owns its writer) is done.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("event buffer poisoned"))?
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// `--ai` modes [`build_ais`] understands.
+const AI_MODES: [&str; 5] = ["random", "greedy", "hybrid", "gp-only", "search"];
+
+/// Reject `--ai` modes batch runs can't play, once per invocation.
+///
+/// Hybrid still runs, but without its LLM seat: batch runs are meant to be
+/// cheap and reproducible, so that country gets GreedyAI like the other
+/// great powers.
+pub(crate) fn check_ai_mode(mode: &str) -> Result<()> {
+    if mode.starts_with("llm") {
+        anyhow::bail!("--ai {}: the LLM AI is not supported in batch runs", mode);
+    }
+    if !AI_MODES.contains(&mode) {
+        anyhow::bail!(
+            "unknown --ai mode '{}' (expected one of: {})",
+            mode,
+            AI_MODES.join(", ")
+        );
+    }
+    if mode == "hybrid" {
+        log::warn!(
+            "LLM AI is not supported in batch runs; hybrid mode plays its GPs with GreedyAI"
+        );
+    }
+    Ok(())
+}
+
+/// Build the AI map for one run. Same modes as the interactive loop, minus
+/// the LLM (see [`check_ai_mode`]).
+pub(crate) fn build_ais(
+    state: &WorldState,
+    adjacency: &Arc<eu4data::adjacency::AdjacencyGraph>,
+    mode: &str,
+    greedy_count: usize,
+    seed: u64,
+) -> Result<BTreeMap<String, Box<dyn eu4sim_core::AiPlayer>>> {
+    let greedy_tags: HashSet<String> = match mode {
+        "greedy" => state.countries.keys().cloned().collect(),
+        "hybrid" | "gp-only" | "search" => super::calculate_top_countries(state, greedy_count),
+        "random" => HashSet::new(),
+        other => anyhow::bail!("unknown --ai mode '{}'", other),
+    };
+
+    let mut ais: BTreeMap<String, Box<dyn eu4sim_core::AiPlayer>> = BTreeMap::new();
+    for tag in state.countries.keys() {
+        if greedy_tags.contains(tag) && mode == "search" {
+            let config = eu4sim_core::SearchConfig {
+                seed,
+                ..Default::default()
+            };
+            let ai = eu4sim_core::SearchAI::new(config).with_adjacency(adjacency.clone());
+            ais.insert(tag.clone(), Box::new(ai));
+        } else if greedy_tags.contains(tag) {
+            ais.insert(tag.clone(), Box::new(eu4sim_core::GreedyAI::new()));
+        } else if mode != "gp-only" {
+            // Hash tag into seed for diversity
+            let tag_hash: u64 = tag.as_bytes().iter().map(|&b| b as u64).sum();
+            ais.insert(
+                tag.clone(),
+                Box::new(eu4sim_core::RandomAi::new(seed.wrapping_add(tag_hash))),
+            );
+        }
+    }
+    Ok(ais)
+}
+
+/// Count events and per-country war declarations from a JSONL event log.
+fn tally_events(event_log: &[u8]) -> (EventCounts, BTreeMap<String, u32>) {
+    let mut counts = EventCounts::default();
+    let mut declared_by: BTreeMap<String, u32> = BTreeMap::new();
+
+    for line in event_log.split(|&b| b == b'\n').filter(|l| !l.is_empty()) {
+        let Ok(event) = serde_json::from_slice::<GameEvent>(line) else {
+            log::warn!(
+                "Skipping unparseable event: {}",
+                String::from_utf8_lossy(line)
+            );
+            continue;
+        };
+        match event {
+            GameEvent::WarDeclared { attacker, .. } => {
+                counts.wars_declared += 1;
+                *declared_by.entry(attacker).or_default() += 1;
+            }
+            GameEvent::PeaceWhite { .. } => counts.white_peaces += 1,
+            GameEvent::PeaceProvinces { .. } => counts.province_peaces += 1,
+            GameEvent::PeaceAnnexation { .. } => counts.annexations += 1,
+            GameEvent::CountryEliminated { .. } => counts.eliminations += 1,
+            GameEvent::BattleFought { .. } => counts.battles += 1,
+            GameEvent::SiegeCompleted { .. } => counts.sieges += 1,
+            _ => {}
+        }
+    }
+
+    (counts, declared_by)
+}
+
+/// Run one fork of `base` with `seed` to the target date.
+fn run_one(
+    base: &WorldState,
+    adjacency: &Arc<eu4data::adjacency::AdjacencyGraph>,
+    seed: u64,
+    settings: &RunSettings,
+) -> Result<RunSummary> {
+    let mut state = base.clone();
+    state.rng_seed = seed;
+    state.rng_state = 0;
+
+    let tracked: Vec<String> = state.countries.keys().cloned().collect();
+    let mut ais = build_ais(&state, adjacency, settings.ai, settings.greedy_count, seed)?;
+
+    let buffer = SharedBuffer::default();
+    let mut observers = ObserverRegistry::new();
+    observers.register(Box::new(EventLogObserver::new(Box::new(buffer.clone()))));
+
+    let config = SimConfig::default();
+    let gp_only = settings.ai == "gp-only";
+    let mut tick: u64 = 0;
+    let mut last_reassign_year = state.date.year;
+
+    // Baseline for event detection
+    observers.notify(&Snapshot::new(state.clone(), tick, 0));
+
+    while state.date < settings.until {
+        let inputs = super::ai_inputs::generate_ai_inputs(&state, adjacency, &mut ais, gp_only);
+        step_world_mut(&mut state, &inputs, Some(adjacency), &config, None);
+        tick += 1;
+
+        if settings.ai == "hybrid" && state.date.year > last_reassign_year {
+            last_reassign_year = state.date.year;
+            super::reassign_hybrid_ais(&mut ais, &state, settings.greedy_count, seed);
+        }
+
+        if observers.wants_snapshot(tick, state.date) {
+            let snapshot = Snapshot::new(state.clone(), tick, 0);
+            observers.notify_with_inputs(&snapshot, &inputs);
+        }
+    }
+    observers.shutdown();
+
+    let event_log = std::mem::take(
+        &mut *buffer
+            .0
+            .lock()
+            .map_err(|_| anyhow::anyhow!("event buffer poisoned"))?,
+    );
+    if let Some(dir) = settings.event_log_dir {
+        let path = dir.join(format!("seed-{}.jsonl", seed));
+        std::fs::write(&path, &event_log).with_context(|| format!("writing {}", path.display()))?;
+    }
+    let (events, declared_by) = tally_events(&event_log);
+
+    let mut countries: BTreeMap<String, CountryOutcome> = tracked
+        .into_iter()
+        .map(|tag| {
+            let wars_declared = declared_by.get(&tag).copied().unwrap_or(0);
+            (
+                tag,
+                CountryOutcome {
+                    alive: false,
+                    development: 0.0,
+                    provinces: 0,
+                    wars_declared,
+                },
+            )
+        })
+        .collect();
+    for province in state.provinces.values() {
+        let Some(outcome) = province.owner.as_ref().and_then(|o| countries.get_mut(o)) else {
+            continue;
+        };
+        outcome.alive = true;
+        outcome.provinces += 1;
+        outcome.development +=
+            (province.base_tax + province.base_production + province.base_manpower).to_f32() as f64;
+    }
+
+    log::info!(
+        "Run seed={} finished at {} ({} ticks)",
+        seed,
+        state.date,
+        tick
+    );
+    Ok(RunSummary {
+        seed,
+        end_date: state.date.to_string(),
+        ticks: tick,
+        events,
+        countries,
+    })
+}
+
+/// Entry point for `eu4sim batch`.
+pub fn run(
+    base: WorldState,
+    adjacency: eu4data::adjacency::AdjacencyGraph,
+    base_seed: u64,
+    ai: &str,
+    greedy_count: usize,
+    args: &BatchArgs,
+) -> Result<()> {
+    if args.until <= base.date {
+        anyhow::bail!(
+            "--until {} is not after the start date {}",
+            args.until,
+            base.date
+        );
+    }
+    check_ai_mode(ai)?;
+    if let Some(dir) = &args.event_log_dir {
+        std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    }
+
+    let settings = RunSettings {
+        until: args.until,
+        ai,
+        greedy_count,
+        event_log_dir: args.event_log_dir.as_deref(),
+    };
+    let seeds: Vec<u64> = (0..args.runs).map(|i| base_seed.wrapping_add(i)).collect();
+
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(threads) = args.threads {
+        pool = pool.num_threads(threads);
+    }
+    let pool = pool.build()?;
+
+    let adjacency = Arc::new(adjacency);
+    let start = std::time::Instant::now();
+    log::info!(
+        "Batch: {} runs from {} to {} on {} threads",
+        seeds.len(),
+        base.date,
+        args.until,
+        pool.current_num_threads()
+    );
+    let runs: Vec<RunSummary> = pool.install(|| {
+        seeds
+            .par_iter()
+            .map(|&seed| run_one(&base, &adjacency, seed, &settings))
+            .collect::<Result<_>>()
+    })?;
+    log::info!("Batch finished in {:.2}s", start.elapsed().as_secs_f64());
+
+    let report = BatchReport::from_runs(base.date, ai, runs, args.per_run);
+
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path).with_context(|| format!("creating {}", path.display()))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    match args.format {
+        ReportFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, &report)?;
+            writeln!(out)?;
+        }
+        ReportFormat::Csv => report.write_csv(&mut out)?,
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(alive: bool, development: f64, wars_declared: u32) -> CountryOutcome {
+        CountryOutcome {
+            alive,
+            development,
+            provinces: alive as u32,
+            wars_declared,
+        }
+    }
+
+    fn summary(seed: u64, countries: Vec<(&str, CountryOutcome)>, wars: u32) -> RunSummary {
+        RunSummary {
+            seed,
+            end_date: "1450.01.01".to_string(),
+            ticks: 10,
+            events: EventCounts {
+                wars_declared: wars,
+                ..Default::default()
+            },
+            countries: countries
+                .into_iter()
+                .map(|(t, o)| (t.to_string(), o))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_check_ai_mode_rejects_unknown_and_llm_modes() {
+        for mode in AI_MODES {
+            assert!(check_ai_mode(mode).is_ok(), "{} should be accepted", mode);
+        }
+        let err = check_ai_mode("greddy").unwrap_err().to_string();
+        assert!(err.contains("unknown --ai mode 'greddy'"), "{}", err);
+        let err = check_ai_mode("llm").unwrap_err().to_string();
+        assert!(err.contains("not supported in batch runs"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1500"), Ok(Date::new(1500, 1, 1)));
+        assert_eq!(parse_date("1444.11.11"), Ok(Date::new(1444, 11, 11)));
+        assert!(parse_date("1444.13.1").is_err());
+        assert!(parse_date("1444.11").is_err());
+        assert!(parse_date("soon").is_err());
+    }
+
+    #[test]
+    fn test_distribution() {
+        let d = Distribution::from_samples(&[4.0, 1.0, 3.0, 2.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0]);
+        assert_eq!(d.mean, 5.5);
+        assert_eq!(d.min, 1.0);
+        assert_eq!(d.max, 10.0);
+        assert_eq!(d.p10, 1.0);
+        assert_eq!(d.median, 5.0);
+        assert_eq!(d.p90, 9.0);
+        assert!((d.std_dev - 8.25f64.sqrt()).abs() < 1e-9);
+        assert_eq!(Distribution::from_samples(&[]), Distribution::default());
+    }
+
+    #[test]
+    fn test_report_aggregates_runs() {
+        let runs = vec![
+            summary(
+                1,
+                vec![
+                    ("AAA", outcome(true, 30.0, 2)),
+                    ("BBB", outcome(true, 10.0, 0)),
+                ],
+                2,
+            ),
+            summary(
+                2,
+                vec![
+                    ("AAA", outcome(true, 40.0, 1)),
+                    ("BBB", outcome(false, 0.0, 0)),
+                ],
+                1,
+            ),
+        ];
+        let report = BatchReport::from_runs(Date::new(1444, 11, 11), "greedy", runs, false);
+
+        assert_eq!(report.runs, 2);
+        assert_eq!(report.seeds, vec![1, 2]);
+        assert_eq!(report.wars_declared.mean, 1.5);
+        assert!(report.per_run.is_none());
+
+        let aaa = &report.countries["AAA"];
+        assert_eq!(aaa.survival_rate, 1.0);
+        assert_eq!(aaa.development.mean, 35.0);
+        assert_eq!(aaa.wars_declared_mean, 1.5);
+
+        let bbb = &report.countries["BBB"];
+        assert_eq!(bbb.survival_rate, 0.5);
+        assert_eq!(bbb.development.mean, 5.0);
+
+        let mut csv = Vec::new();
+        report.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("tag,survival_rate,"));
+        assert!(lines[2].starts_with("BBB,0.5000,5.00,"));
+    }
+
+    #[test]
+    fn test_tally_events() {
+        let log = concat!(
+            r#"{"type":"war_declared","tick":1,"date":"1444.11.12","attacker":"AAA","defender":"BBB","war_name":"w","war_id":1}"#,
+            "\n",
+            r#"{"type":"peace_white","tick":9,"date":"1444.11.20","war_id":1,"war_name":"w","attacker_score":0,"defender_score":0}"#,
+            "\n",
+            "not json\n"
+        );
+        let (counts, declared_by) = tally_events(log.as_bytes());
+        assert_eq!(counts.wars_declared, 1);
+        assert_eq!(counts.white_peaces, 1);
+        assert_eq!(declared_by.get("AAA"), Some(&1));
+    }
+}
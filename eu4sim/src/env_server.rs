@@ -0,0 +1,160 @@
+//! `eu4sim env`: serve the RL environment to external training scripts.
+//!
+//! Speaks the newline-delimited JSON protocol of
+//! [`eu4sim_core::env::protocol`] on stdin/stdout, or on a TCP socket with
+//! `--listen`. TCP clients are served one at a time; each connection starts
+//! with whatever episode the previous one left and should `reset` first.
+//!
+//! Game data for the default start date is loaded once. Episodes that reset
+//! to another start date load that date's history from the game files; only
+//! the most recent such date is kept, so randomized start dates don't pile up
+//! whole worlds in memory.
+
+use anyhow::{Context, Result};
+use eu4sim_core::ai::OpponentPolicy;
+use eu4sim_core::env::{protocol, reward_by_name, Env, EnvConfig, Scenario, REWARD_NAMES};
+use eu4sim_core::state::Date;
+use eu4sim_core::WorldState;
+use std::io::BufReader;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+type World = (WorldState, Arc<eu4data::adjacency::AdjacencyGraph>);
+
+#[derive(clap::Args, Debug)]
+pub struct EnvArgs {
+    /// Listen on ADDR (e.g. 127.0.0.1:9877) instead of stdin/stdout
+    #[arg(long, value_name = "ADDR")]
+    pub listen: Option<String>,
+
+    /// Reward function: dev_growth, score or survival
+    #[arg(long, default_value = "dev_growth")]
+    pub reward: String,
+
+    /// Days simulated per step
+    #[arg(long, default_value_t = 30)]
+    pub days_per_step: u32,
+
+    /// Episode length in years
+    #[arg(long, default_value_t = 10)]
+    pub episode_years: u32,
+
+    /// How the countries the agent doesn't control play
+    #[arg(long, value_enum, default_value_t = Opponents::Greedy)]
+    pub opponents: Opponents,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Opponents {
+    /// No commands at all (fastest)
+    Passive,
+    /// GreedyAI
+    Greedy,
+    /// RandomAi, seeded from the episode seed
+    Random,
+}
+
+impl From<Opponents> for OpponentPolicy {
+    fn from(opponents: Opponents) -> Self {
+        match opponents {
+            Opponents::Passive => OpponentPolicy::Passive,
+            Opponents::Greedy => OpponentPolicy::Greedy,
+            Opponents::Random => OpponentPolicy::Random,
+        }
+    }
+}
+
+/// Episodes from game files: the default start date plus the last other one.
+struct GameScenario {
+    /// `None` in test mode: every episode starts from `base`
+    game_path: Option<PathBuf>,
+    base: World,
+    /// Most recently loaded start date other than the base one
+    last: Option<(Date, World)>,
+}
+
+impl Scenario for GameScenario {
+    fn load(&mut self, start_date: Option<Date>) -> Result<World, String> {
+        let (Some(date), Some(game_path)) = (start_date, &self.game_path) else {
+            return Ok(self.base.clone());
+        };
+        if date == self.base.0.date {
+            return Ok(self.base.clone());
+        }
+        if let Some((last_date, world)) = &self.last {
+            if *last_date == date {
+                return Ok(world.clone());
+            }
+        }
+        log::info!("Loading game data for {}", date);
+        // Drop the previous date's world before loading the next one
+        self.last = None;
+        let (state, adjacency) =
+            crate::loader::load_initial_state(game_path, date, self.base.0.rng_seed)
+                .map_err(|e| format!("{:#}", e))?;
+        let world = (state, Arc::new(adjacency));
+        self.last = Some((date, world.clone()));
+        Ok(world)
+    }
+}
+
+/// Entry point for `eu4sim env`.
+pub fn run(
+    state: WorldState,
+    adjacency: eu4data::adjacency::AdjacencyGraph,
+    game_path: Option<PathBuf>,
+    args: &EnvArgs,
+) -> Result<()> {
+    let reward = reward_by_name(&args.reward).with_context(|| {
+        format!(
+            "unknown reward '{}' (expected one of {})",
+            args.reward,
+            REWARD_NAMES.join(", ")
+        )
+    })?;
+    let config = EnvConfig {
+        days_per_step: args.days_per_step,
+        episode_days: args.episode_years * 360,
+        opponents: args.opponents.into(),
+        ..Default::default()
+    };
+    let scenario = GameScenario {
+        game_path,
+        base: (state, Arc::new(adjacency)),
+        last: None,
+    };
+    let mut env = Env::new(Box::new(scenario), reward, config);
+
+    let Some(addr) = &args.listen else {
+        log::info!("Env: serving on stdin/stdout");
+        let stdin = std::io::stdin().lock();
+        let stdout = std::io::stdout().lock();
+        return protocol::serve(&mut env, stdin, stdout).context("serving stdin/stdout");
+    };
+
+    let listener =
+        std::net::TcpListener::bind(addr).with_context(|| format!("binding {}", addr))?;
+    log::warn!("Env: listening on {}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        // A failed accept (e.g. out of file descriptors) only loses that client
+        let (stream, peer, reader) = match stream.and_then(|stream| {
+            let peer = stream.peer_addr()?;
+            let reader = BufReader::new(stream.try_clone()?);
+            Ok((stream, peer, reader))
+        }) {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                log::warn!("Env: failed to accept a client: {}", e);
+                // Don't spin while the error lasts
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                continue;
+            }
+        };
+        log::info!("Env: client {} connected", peer);
+        if let Err(e) = protocol::serve(&mut env, reader, stream) {
+            log::warn!("Env: client {} dropped: {}", peer, e);
+        }
+        log::info!("Env: client {} disconnected", peer);
+    }
+    Ok(())
+}
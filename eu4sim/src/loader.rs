@@ -2,8 +2,8 @@ use anyhow::Result;
 use eu4sim_core::ideas::{IdeaGroupRegistry, RawIdea, RawIdeaGroup};
 use eu4sim_core::modifiers::TradegoodId;
 use eu4sim_core::state::{
-    Army, CountryState, Date, Fleet, HashMap as ImHashMap, ProvinceState, Regiment, RegimentType,
-    Ship, ShipType, SubjectRelationship, Terrain,
+    Army, Claim, CountryState, Date, Fleet, HashMap as ImHashMap, ProvinceState, Regiment,
+    RegimentType, Ship, ShipType, SubjectRelationship, Terrain,
 };
 use eu4sim_core::subjects::{RawSubjectType, SubjectTypeRegistry};
 use eu4sim_core::systems::ideas::{recalculate_idea_modifiers, ModifierStubTracker};
@@ -102,6 +102,21 @@ fn convert_raw_subject_type(raw: eu4data::subject_types::RawSubjectType) -> RawS
     }
 }
 
+/// Convert from eu4data's RawAiPersonality to eu4sim-core's AiPersonality.
+fn convert_raw_ai_personality(
+    raw: eu4data::ai_personalities::RawAiPersonality,
+) -> eu4sim_core::ai::AiPersonality {
+    eu4sim_core::ai::AiPersonality {
+        name: raw.name,
+        chance: Fixed::from_f32(raw.chance),
+        priorities: raw
+            .priorities
+            .into_iter()
+            .map(|(key, value)| (key, Fixed::from_f32(value)))
+            .collect(),
+    }
+}
+
 /// Build estate registry from loaded raw data.
 fn build_estate_registry(
     _raw_estates: StdHashMap<String, eu4data::estates::RawEstate>,
@@ -160,7 +175,7 @@ fn parse_terrain(terrain_str: &str) -> Option<Terrain> {
 pub fn load_initial_state(
     game_path: &Path,
     start_date: Date,
-    _rng_seed: u64,
+    rng_seed: u64,
 ) -> Result<(WorldState, eu4data::adjacency::AdjacencyGraph)> {
     // 0. Load Adjacency Graph (with Strict Cache Validation)
     log::info!("Loading adjacency graph (strict mode)...");
@@ -319,6 +334,15 @@ pub fn load_initial_state(
             cores.insert(tag.clone());
         }
 
+        // Historical claims are permanent; the owner's own claims add nothing
+        let claims = hist
+            .add_claim
+            .iter()
+            .flatten()
+            .filter(|tag| !cores.contains(*tag))
+            .map(|tag| (tag.clone(), Claim { expires: None }))
+            .collect();
+
         let p = ProvinceState {
             owner: hist.owner.clone(),
             controller: hist.owner.clone(),
@@ -344,6 +368,7 @@ pub fn load_initial_state(
             institution_presence: ImHashMap::default(),
             trade: Default::default(),
             cores,
+            claims,
             coring_progress: None,
             buildings: Default::default(),
             building_construction: None,
@@ -892,6 +917,38 @@ pub fn load_initial_state(
         );
     }
 
+    // 5e. Load AI Personalities
+    log::info!("Loading AI personalities...");
+    let raw_personalities = eu4data::ai_personalities::load_ai_personalities(game_path)
+        .map_err(|e| anyhow::anyhow!("Failed to load AI personalities: {}", e))?;
+    let ai_personalities = eu4sim_core::ai::AiPersonalityRegistry::new(
+        raw_personalities
+            .into_values()
+            .map(convert_raw_ai_personality),
+    );
+    for (tag, country) in &mut countries {
+        country.ai_personality = ai_personalities.roll(tag, rng_seed).map(|p| p.name.clone());
+    }
+    let raw_attitudes = eu4data::ai_personalities::load_ai_attitudes(game_path)
+        .map_err(|e| anyhow::anyhow!("Failed to load AI attitudes: {}", e))?;
+    let ai_attitudes = eu4sim_core::ai::AiAttitudeRegistry::new(
+        eu4sim_core::ai::Attitude::ALL
+            .into_iter()
+            .filter_map(|attitude| {
+                let raw = raw_attitudes.get(attitude.key())?;
+                Some((
+                    attitude,
+                    eu4sim_core::ai::AttitudeEffects::from_flags(&raw.flags),
+                ))
+            }),
+    );
+    log::info!(
+        "Loaded {} AI personalities, {} attitudes ({} modelled)",
+        ai_personalities.len(),
+        raw_attitudes.len(),
+        ai_attitudes.len()
+    );
+
     // 6. Load Diplomatic History (subjects, alliances, etc.)
     log::info!("Loading diplomatic history...");
     let diplomacy_entries = eu4data::diplomacy::load_diplomacy_history(game_path)
@@ -1001,11 +1058,10 @@ pub fn load_initial_state(
     // 8. Assemble State
     let mut state = WorldState {
         date: start_date,
-        rng_seed: _rng_seed,
+        rng_seed,
         rng_state: 0, // Initialize RNG state
         provinces: provinces.into(),
         countries: countries.into(),
-        tags: eu4sim_core::state::TagRegistry::default(),
         base_goods_prices: base_prices.into(),
         modifiers,
         diplomacy: eu4sim_core::state::DiplomacyState {
@@ -1018,6 +1074,8 @@ pub fn load_initial_state(
         fleets: fleets.into(),
         next_fleet_id,
         colonies: ImHashMap::default(),
+        strategic_goals: ImHashMap::default(),
+        claim_candidates: None,
         // Combat system
         generals: ImHashMap::default(),
         next_general_id: 1,
@@ -1050,6 +1108,9 @@ pub fn load_initial_state(
         government_types: eu4sim_core::government::GovernmentRegistry::new(),
         // Estate system
         estates: estate_registry,
+        // AI personality system
+        ai_personalities,
+        ai_attitudes,
         // Performance caches (initialized empty, rebuilt lazily on first access)
         owned_provinces_cache: Default::default(),
         owned_provinces_cache_valid: false,
@@ -5,7 +5,7 @@ static ALLOC: tracy_client::ProfiledAllocator<std::alloc::System> =
     tracy_client::ProfiledAllocator::new(std::alloc::System, 100);
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crossterm::{
     event::{self, Event, KeyCode},
     terminal::{disable_raw_mode, enable_raw_mode},
@@ -14,13 +14,18 @@ use eu4sim_core::observer::console::ConsoleObserver;
 use eu4sim_core::observer::datagen::DataGenObserver;
 use eu4sim_core::observer::event_log::EventLogObserver;
 use eu4sim_core::state::Date;
-use eu4sim_core::{step_world, ObserverRegistry, PlayerInputs, SimConfig, Snapshot, WorldState};
-use rayon::prelude::*;
+use eu4sim_core::{
+    step_world_mut, ObserverRegistry, PlayerInputs, SimConfig, Snapshot, WorldState,
+};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 
+mod ai_inputs;
+mod batch;
+mod env_server;
 mod loader;
+mod tournament;
 mod tui;
 
 /// Create minimal mock state for CI testing (no game files needed)
@@ -123,6 +128,7 @@ fn create_mock_state(seed: u64) -> (WorldState, eu4data::adjacency::AdjacencyGra
                 institution_presence: Default::default(),
                 trade: Default::default(),
                 cores,
+                claims: Default::default(),
                 coring_progress: None,
                 buildings: Default::default(),
                 building_construction: None,
@@ -192,7 +198,6 @@ fn create_mock_state(seed: u64) -> (WorldState, eu4data::adjacency::AdjacencyGra
         rng_state: 0,
         provinces: provinces.into(),
         countries: countries.into(),
-        tags: Default::default(),
         base_goods_prices: Default::default(),
         modifiers: Default::default(),
         diplomacy: Default::default(),
@@ -202,6 +207,8 @@ fn create_mock_state(seed: u64) -> (WorldState, eu4data::adjacency::AdjacencyGra
         fleets: Default::default(),
         next_fleet_id: 1,
         colonies: Default::default(),
+        strategic_goals: Default::default(),
+        claim_candidates: None,
         // Combat system
         generals: Default::default(),
         next_general_id: 1,
@@ -234,6 +241,9 @@ fn create_mock_state(seed: u64) -> (WorldState, eu4data::adjacency::AdjacencyGra
         government_types: Default::default(),
         // Estate system
         estates: Default::default(),
+        // AI personality system
+        ai_personalities: Default::default(),
+        ai_attitudes: Default::default(),
         // Performance caches (initialized empty, rebuilt lazily on first access)
         owned_provinces_cache: Default::default(),
         owned_provinces_cache_valid: false,
@@ -257,6 +267,42 @@ fn calculate_top_countries(state: &WorldState, count: usize) -> HashSet<String>
     ranked.into_iter().take(count).map(|(t, _)| t).collect()
 }
 
+/// Load `count` Candle LLM players; more than one share a batched model.
+fn load_candle_llms(
+    base_model: &str,
+    adapter_path: Option<PathBuf>,
+    count: usize,
+) -> Result<Vec<eu4sim_ai::LlmAi>> {
+    if count <= 1 {
+        return Ok(vec![eu4sim_ai::LlmAi::new(base_model, adapter_path)?]);
+    }
+    let config = eu4sim_ai::ModelConfig {
+        base_model: base_model.to_string(),
+        adapter_path: adapter_path.unwrap_or_default(),
+        ..Default::default()
+    };
+    let batcher = eu4sim_ai::LlmBatcher::new(config, eu4sim_ai::DEFAULT_COLLECT_WINDOW)?;
+    Ok((0..count)
+        .map(|_| eu4sim_ai::LlmAi::with_batcher(&batcher))
+        .collect())
+}
+
+/// Parse the `HOST:PORT` part of a `bridge:HOST:PORT` spec (port defaults to 9876).
+fn parse_bridge_addr(addr: &str) -> (&str, u16) {
+    let parts: Vec<&str> = addr.splitn(2, ':').collect();
+    match parts.as_slice() {
+        [host, port_str] => {
+            let port = port_str.parse::<u16>().unwrap_or_else(|_| {
+                log::warn!("Invalid port '{}', using default 9876", port_str);
+                9876
+            });
+            (*host, port)
+        }
+        [host] => (*host, 9876),
+        _ => ("127.0.0.1", 9876),
+    }
+}
+
 /// Format interesting events from Great Powers for the TUI event log
 fn format_gp_events(
     inputs: &[PlayerInputs],
@@ -503,22 +549,32 @@ fn reassign_hybrid_ais(
 ) -> bool {
     let new_greedy = calculate_top_countries(state, greedy_count);
 
-    // Extract LlmAi if present (we'll reassign it to a random GP)
-    let llm_ai: Option<(String, Box<dyn eu4sim_core::AiPlayer>)> = {
-        let llm_tag = ais
+    // Extract LlmAis if present (we'll reassign them to random GPs)
+    let llm_ais: Vec<(String, Box<dyn eu4sim_core::AiPlayer>)> = {
+        let llm_tags: Vec<String> = ais
             .iter()
-            .find(|(_, ai)| ai.name() == "LlmAi")
-            .map(|(t, _)| t.clone());
-        llm_tag.map(|tag| (tag.clone(), ais.remove(&tag).unwrap()))
+            .filter(|(_, ai)| ai.name() == "LlmAi")
+            .map(|(t, _)| t.clone())
+            .collect();
+        llm_tags
+            .into_iter()
+            .map(|tag| {
+                let ai = ais.remove(&tag).unwrap();
+                (tag, ai)
+            })
+            .collect()
     };
 
-    // Pick a deterministic "random" GP for the LlmAi using seed + year
-    let llm_target: Option<String> = if llm_ai.is_some() && !new_greedy.is_empty() {
-        let greedy_vec: Vec<_> = new_greedy.iter().cloned().collect();
+    // Pick deterministic "random" GPs for the LlmAis using seed + year
+    let llm_targets: Vec<String> = if !llm_ais.is_empty() && !new_greedy.is_empty() {
+        let mut greedy_vec: Vec<_> = new_greedy.iter().cloned().collect();
+        greedy_vec.sort();
         let idx = (base_seed.wrapping_add(state.date.year as u64) as usize) % greedy_vec.len();
-        Some(greedy_vec[idx].clone())
+        greedy_vec.rotate_left(idx);
+        greedy_vec.truncate(llm_ais.len());
+        greedy_vec
     } else {
-        None
+        Vec::new()
     };
 
     // Find current greedy tags (excluding LlmAi which we extracted)
@@ -526,8 +582,8 @@ fn reassign_hybrid_ais(
 
     for (tag, ai) in ais.iter() {
         let is_greedy = ai.name() == "GreedyAI";
-        // Should be greedy if in new_greedy AND not the LlmAi target
-        let should_be_greedy = new_greedy.contains(tag) && llm_target.as_ref() != Some(tag);
+        // Should be greedy if in new_greedy AND not an LlmAi target
+        let should_be_greedy = new_greedy.contains(tag) && !llm_targets.contains(tag);
 
         if is_greedy != should_be_greedy {
             changes.push((tag.clone(), should_be_greedy));
@@ -536,7 +592,7 @@ fn reassign_hybrid_ais(
 
     // Handle new countries that don't have an AI yet
     for tag in state.countries.keys() {
-        if !ais.contains_key(tag) && llm_target.as_ref() != Some(tag) {
+        if !ais.contains_key(tag) && !llm_targets.contains(tag) {
             let should_be_greedy = new_greedy.contains(tag);
             changes.push((tag.clone(), should_be_greedy));
         }
@@ -552,15 +608,14 @@ fn reassign_hybrid_ais(
         ais.remove(tag);
     }
 
-    // Track if LlmAi needs reassignment
-    let llm_changed = llm_ai
-        .as_ref()
-        .map(|(old_tag, _)| llm_target.as_ref() != Some(old_tag))
-        .unwrap_or(false);
+    // Track if any LlmAi needs reassignment
+    let llm_changed = llm_ais
+        .iter()
+        .any(|(old_tag, _)| !llm_targets.contains(old_tag));
 
     if changes.is_empty() && dead_tags.is_empty() && !llm_changed {
-        // Put LlmAi back if no changes needed
-        if let Some((tag, ai)) = llm_ai {
+        // Put LlmAis back if no changes needed
+        for (tag, ai) in llm_ais {
             ais.insert(tag, ai);
         }
         return false;
@@ -578,18 +633,28 @@ fn reassign_hybrid_ais(
         ais.insert(tag, ai);
     }
 
-    // Reassign LlmAi to the randomly chosen GP
-    if let Some((old_tag, llm)) = llm_ai {
-        if let Some(ref target_tag) = llm_target {
+    // Reassign LlmAis to the randomly chosen GPs: those already on a target
+    // stay, the rest move to the free targets
+    let (staying, moving): (Vec<_>, Vec<_>) = llm_ais
+        .into_iter()
+        .partition(|(tag, _)| llm_targets.contains(tag));
+    let mut free_targets: Vec<&String> = llm_targets
+        .iter()
+        .filter(|target| !staying.iter().any(|(tag, _)| tag == *target))
+        .collect();
+    free_targets.reverse();
+    for (old_tag, llm) in moving {
+        if let Some(target_tag) = free_targets.pop() {
+            log::info!("LlmAi transferred: {} → {}", old_tag, target_tag);
             ais.insert(target_tag.clone(), llm);
-            if old_tag != *target_tag {
-                log::info!("LlmAi transferred: {} → {}", old_tag, target_tag);
-            }
         } else {
             // No valid GP, put it back where it was
             ais.insert(old_tag, llm);
         }
     }
+    for (tag, llm) in staying {
+        ais.insert(tag, llm);
+    }
 
     log::info!("AI pool updated: GreedyAI → {:?}", new_greedy);
     true
@@ -613,14 +678,17 @@ impl Drop for RawModeGuard {
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// Path to game data
-    #[arg(long, default_value_t = eu4data::path::detect_game_path()
+    #[arg(long, global = true, default_value_t = eu4data::path::detect_game_path()
         .and_then(|p| p.to_str().map(String::from))
         .unwrap_or_else(|| ".".to_string()))]
     game_path: String,
 
     /// Start year
-    #[arg(long, default_value_t = 1444)]
+    #[arg(long, global = true, default_value_t = 1444)]
     start_year: i32,
 
     /// Number of ticks to run
@@ -628,7 +696,7 @@ struct Args {
     ticks: u32,
 
     /// Log level (error, warn, info, debug, trace)
-    #[arg(long, default_value = "info")]
+    #[arg(long, global = true, default_value = "info")]
     log_level: String,
 
     /// Dump game data manifest and exit
@@ -659,12 +727,13 @@ struct Args {
     #[arg(long)]
     datagen: Option<String>,
 
-    /// AI type: "random", "greedy", "hybrid" (default), or "gp-only" (only top 8 get GreedyAI)
-    #[arg(long, default_value = "hybrid")]
+    /// AI type: "random", "greedy", "hybrid" (default), "gp-only" (only top 8 get GreedyAI),
+    /// or "search" (top 8 get the lookahead SearchAI, others random)
+    #[arg(long, global = true, default_value = "hybrid")]
     ai: String,
 
-    /// Number of top countries (by development) to use GreedyAI in hybrid mode
-    #[arg(long, default_value_t = 8)]
+    /// Number of top countries (by development) to use GreedyAI in hybrid mode (SearchAI in search mode)
+    #[arg(long, global = true, default_value_t = 8)]
     greedy_count: usize,
 
     /// Headless mode: disable TUI, keyboard input, and console observer
@@ -672,11 +741,11 @@ struct Args {
     headless: bool,
 
     /// Random seed for simulation reproducibility
-    #[arg(long, default_value_t = 12345)]
+    #[arg(long, global = true, default_value_t = 12345)]
     seed: u64,
 
     /// Test mode: use minimal mock state instead of loading game files (for CI)
-    #[arg(long)]
+    #[arg(long, global = true)]
     test_mode: bool,
 
     /// Use LLM AI for the top Great Power.
@@ -695,6 +764,12 @@ struct Args {
     #[arg(long, value_name = "MODEL")]
     llm_ai_base: Option<String>,
 
+    /// Number of top Great Powers played by the LLM AI.
+    /// Above 1, the players share one model and decide together in a single
+    /// batch each tick (Candle backend only; bridges always drive one country).
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    llm_gps: usize,
+
     /// Enable TUI mode
     #[arg(long)]
     tui: bool,
@@ -705,6 +780,16 @@ struct Args {
     trace_level: String,
 }
 
+#[derive(Subcommand, Debug)]
+enum Commands {
+    /// Run many seeds from one data load and report aggregate statistics
+    Batch(batch::BatchArgs),
+    /// Serve a reinforcement learning environment as newline-delimited JSON
+    Env(env_server::EnvArgs),
+    /// Pit AI players against each other and maintain an Elo table
+    Tournament(tournament::TournamentArgs),
+}
+
 use eu4sim_core::SimMetrics;
 
 fn main() -> Result<()> {
@@ -718,7 +803,11 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    let log_level = if (args.observer || args.benchmark) && args.log_level == "info" {
+    let batch_mode = matches!(
+        args.command,
+        Some(Commands::Batch(_)) | Some(Commands::Env(_)) | Some(Commands::Tournament(_))
+    );
+    let log_level = if (args.observer || args.benchmark || batch_mode) && args.log_level == "info" {
         "warn"
     } else {
         &args.log_level
@@ -766,6 +855,45 @@ fn main() -> Result<()> {
         let game_path = PathBuf::from(&args.game_path);
         loader::load_initial_state(&game_path, Date::new(args.start_year, 11, 11), args.seed)?
     };
+
+    if let Some(Commands::Batch(batch_args)) = &args.command {
+        if args.llm_ai.is_some() || args.llm_ai_base.is_some() {
+            log::warn!("LLM AI is not supported in batch runs; ignoring --llm-ai/--llm-ai-base");
+        }
+        return batch::run(
+            state,
+            adjacency_raw,
+            args.seed,
+            &args.ai,
+            args.greedy_count,
+            batch_args,
+        );
+    }
+    if let Some(Commands::Env(env_args)) = &args.command {
+        let game_path = (!args.test_mode).then(|| PathBuf::from(&args.game_path));
+        return env_server::run(state, adjacency_raw, game_path, env_args);
+    }
+    if let Some(Commands::Tournament(tournament_args)) = &args.command {
+        let llm = |spec: &str| -> Result<Box<dyn eu4sim_core::AiPlayer>> {
+            let ai = match spec.strip_prefix("bridge:") {
+                Some(addr) => {
+                    let (host, port) = parse_bridge_addr(addr);
+                    eu4sim_ai::LlmAi::with_bridge(host, port)?
+                }
+                None => eu4sim_ai::LlmAi::with_default_bridge()?,
+            };
+            Ok(Box::new(ai))
+        };
+        return tournament::run(
+            state,
+            adjacency_raw,
+            args.seed,
+            args.greedy_count,
+            tournament_args,
+            &llm,
+        );
+    }
+
     let adjacency = Arc::new(adjacency_raw);
 
     log::info!("Initial State Date: {}", state.date);
@@ -792,14 +920,24 @@ fn main() -> Result<()> {
         let use_llm = (args.ai == "hybrid" || args.llm_ai.is_some() || args.llm_ai_base.is_some())
             && args.datagen.is_none(); // Skip LLM when generating training data
 
-        // When LLM is enabled: LLM gets #1 country, GreedyAI gets next greedy_count
+        // Bridges serve one country; Candle can batch several
+        let llm_count = match args.llm_ai.as_deref() {
+            Some(spec) if spec.starts_with("bridge") || spec.starts_with("rocm") => {
+                if args.llm_gps > 1 {
+                    log::warn!("--llm-gps needs the Candle backend; using 1 LLM country");
+                }
+                1
+            }
+            _ => args.llm_gps.max(1),
+        };
+
+        // When LLM is enabled: LLM gets the top llm_count countries, GreedyAI gets next greedy_count
         // When LLM is disabled: GreedyAI gets top greedy_count countries
         // This ensures greedy_count specifies exactly how many GreedyAIs we get
-        let llm_tag: Option<String> = if use_llm {
-            let top = calculate_top_countries(&state, 1);
-            top.into_iter().next()
+        let llm_tags: HashSet<String> = if use_llm {
+            calculate_top_countries(&state, llm_count)
         } else {
-            None
+            HashSet::new()
         };
 
         // Determine which tags get GreedyAI (excluding the LLM tag if present)
@@ -807,17 +945,14 @@ fn main() -> Result<()> {
         let greedy_tags: HashSet<String> = match args.ai.as_str() {
             "greedy" => state.countries.keys().cloned().collect(),
             "hybrid" => {
-                // Get top (greedy_count + 1 if LLM) countries, then exclude LLM tag
-                let extra = if use_llm { 1 } else { 0 };
-                let mut top = calculate_top_countries(&state, args.greedy_count + extra);
-                if let Some(ref llm) = llm_tag {
-                    top.remove(llm);
-                }
+                // Get top (greedy_count + LLM count) countries, then exclude LLM tags
+                let mut top = calculate_top_countries(&state, args.greedy_count + llm_tags.len());
+                top.retain(|tag| !llm_tags.contains(tag));
                 log::info!(
                     "Hybrid mode: {} GreedyAI + {} LLM = {} smart AIs: {:?}",
                     top.len(),
-                    if llm_tag.is_some() { 1 } else { 0 },
-                    top.len() + if llm_tag.is_some() { 1 } else { 0 },
+                    llm_tags.len(),
+                    top.len() + llm_tags.len(),
                     top
                 );
                 top
@@ -832,36 +967,30 @@ fn main() -> Result<()> {
                 );
                 top
             }
+            "search" => {
+                // Top greedy_count countries search ahead, everyone else is random
+                let top = calculate_top_countries(&state, args.greedy_count);
+                log::info!("Search mode: {} SearchAIs: {:?}", top.len(), top);
+                top
+            }
             _ => HashSet::new(), // random mode: no greedy
         };
 
         // Initialize LLM AI (in hybrid mode or if explicitly requested, but NOT for datagen)
-        let llm_ai: Option<Box<dyn eu4sim_core::AiPlayer>> = if use_llm {
+        let llm_ais: Vec<Box<dyn eu4sim_core::AiPlayer>> = if use_llm {
             let result = if let Some(llm_spec) = &args.llm_ai {
                 // Check if using bridge mode (Python inference server for ROCm)
                 if llm_spec == "bridge" {
                     log::info!("Loading LLM AI via bridge (default: 127.0.0.1:9876)");
-                    eu4sim_ai::LlmAi::with_default_bridge()
+                    eu4sim_ai::LlmAi::with_default_bridge().map(|ai| vec![ai])
                 } else if let Some(addr) = llm_spec.strip_prefix("bridge:") {
-                    // Parse "bridge:HOST:PORT" format
-                    let parts: Vec<&str> = addr.splitn(2, ':').collect();
-                    let (host, port) = match parts.as_slice() {
-                        [host, port_str] => {
-                            let port = port_str.parse::<u16>().unwrap_or_else(|_| {
-                                log::warn!("Invalid port '{}', using default 9876", port_str);
-                                9876
-                            });
-                            (*host, port)
-                        }
-                        [host] => (*host, 9876),
-                        _ => ("127.0.0.1", 9876),
-                    };
+                    let (host, port) = parse_bridge_addr(addr);
                     log::info!("Loading LLM AI via bridge ({}:{})", host, port);
-                    eu4sim_ai::LlmAi::with_bridge(host, port)
+                    eu4sim_ai::LlmAi::with_bridge(host, port).map(|ai| vec![ai])
                 } else if llm_spec == "rocm" {
                     // Auto-spawn ROCm inference server (no adapter)
                     log::info!("Auto-spawning ROCm inference server (no adapter)");
-                    eu4sim_ai::LlmAi::with_auto_bridge(None)
+                    eu4sim_ai::LlmAi::with_auto_bridge(None).map(|ai| vec![ai])
                 } else if let Some(adapter_str) = llm_spec.strip_prefix("rocm:") {
                     // Auto-spawn ROCm inference server with adapter
                     let adapter_path = PathBuf::from(adapter_str);
@@ -869,7 +998,7 @@ fn main() -> Result<()> {
                         "Auto-spawning ROCm inference server with adapter: {:?}",
                         adapter_path
                     );
-                    eu4sim_ai::LlmAi::with_auto_bridge(Some(adapter_path))
+                    eu4sim_ai::LlmAi::with_auto_bridge(Some(adapter_path)).map(|ai| vec![ai])
                 } else {
                     // Candle backend with adapter path
                     let adapter_path = PathBuf::from(llm_spec);
@@ -883,7 +1012,7 @@ fn main() -> Result<()> {
                         adapter_path,
                         base_model
                     );
-                    eu4sim_ai::LlmAi::new(base_model, Some(adapter_path))
+                    load_candle_llms(base_model, Some(adapter_path), llm_count)
                 }
             } else {
                 // No --llm-ai specified, use base model only
@@ -893,34 +1022,47 @@ fn main() -> Result<()> {
                     Some(other) => other,
                 };
                 log::info!("Loading LLM AI with base model: {}", base_model);
-                eu4sim_ai::LlmAi::new(base_model, None)
+                load_candle_llms(base_model, None, llm_count)
             };
 
             match result {
-                Ok(ai) => {
-                    log::info!("LLM AI loaded successfully for: {:?}", llm_tag);
+                Ok(ais) => {
+                    log::info!("LLM AI loaded successfully for: {:?}", llm_tags);
                     // Set up TUI sender for LLM I/O display and store receiver
                     llm_rx_for_tui = Some(llm_rx);
-                    Some(Box::new(ai.with_tui_sender(llm_tx)))
+                    ais.into_iter()
+                        .map(|ai| {
+                            Box::new(ai.with_tui_sender(llm_tx.clone()))
+                                as Box<dyn eu4sim_core::AiPlayer>
+                        })
+                        .collect()
                 }
                 Err(e) => {
                     log::error!("Failed to load LLM AI: {}. Falling back to GreedyAI.", e);
-                    None
+                    Vec::new()
                 }
             }
         } else {
-            None
+            Vec::new()
         };
 
         // Build AI map
         let mut ai_map: BTreeMap<String, Box<dyn eu4sim_core::AiPlayer>> = BTreeMap::new();
-        let mut llm_ai = llm_ai; // Make it mutable so we can take() from it
+        let mut llm_ais = llm_ais; // Make it mutable so we can pop() from it
 
         for tag in state.countries.keys() {
             let ai: Option<Box<dyn eu4sim_core::AiPlayer>> =
-                if llm_tag.as_ref() == Some(tag) && llm_ai.is_some() {
-                    // Use LLM AI for the top GP
-                    Some(llm_ai.take().unwrap())
+                if llm_tags.contains(tag) && !llm_ais.is_empty() {
+                    // Use LLM AI for the top GPs
+                    llm_ais.pop()
+                } else if greedy_tags.contains(tag) && args.ai == "search" {
+                    let config = eu4sim_core::SearchConfig {
+                        seed: args.seed,
+                        ..Default::default()
+                    };
+                    Some(Box::new(
+                        eu4sim_core::SearchAI::new(config).with_adjacency(adjacency.clone()),
+                    ))
                 } else if greedy_tags.contains(tag) {
                     Some(Box::new(eu4sim_core::GreedyAI::new()))
                 } else if gp_only_mode {
@@ -1140,349 +1282,8 @@ fn main() -> Result<()> {
         if args.observer {
             let ai_start = std::time::Instant::now();
 
-            // Pre-compute global army strength (O(armies), shared across all AIs)
-            let global_strength: HashMap<String, u32> =
-                state.armies.values().fold(HashMap::new(), |mut acc, army| {
-                    *acc.entry(army.owner.clone()).or_default() += army.regiment_count();
-                    acc
-                });
-
-            // Pre-compute neighbor countries for each country (fog of war)
-            // A country "knows" another if they share a province border
-            let neighbor_countries: HashMap<String, HashSet<String>> = {
-                // First, get owned provinces per country
-                let mut country_provinces: HashMap<String, Vec<u32>> = HashMap::new();
-                for (prov_id, prov) in &state.provinces {
-                    if let Some(owner) = &prov.owner {
-                        country_provinces
-                            .entry(owner.clone())
-                            .or_default()
-                            .push(*prov_id);
-                    }
-                }
-
-                // Then find neighbors via adjacency graph
-                let mut neighbors: HashMap<String, HashSet<String>> = HashMap::new();
-                for (tag, provs) in &country_provinces {
-                    let mut known = HashSet::new();
-                    for &prov_id in provs {
-                        for neighbor_id in adjacency.neighbors(prov_id) {
-                            if let Some(neighbor_prov) = state.provinces.get(&neighbor_id) {
-                                if let Some(neighbor_owner) = &neighbor_prov.owner {
-                                    if neighbor_owner != tag {
-                                        known.insert(neighbor_owner.clone());
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    neighbors.insert(tag.clone(), known);
-                }
-                neighbors
-            };
-
-            // Generate AI commands for all countries (parallel)
-            // Returns PlayerInputs for ALL countries so datagen can use precomputed available_commands
-            inputs = ais
-                .par_iter_mut()
-                .map(|(tag, ai)| {
-                    // Start with neighbor countries (fog of war baseline)
-                    let mut known_countries: HashSet<String> =
-                        neighbor_countries.get(tag).cloned().unwrap_or_default();
-
-                    // Check if this country is at war with anyone
-                    let at_war = state
-                        .diplomacy
-                        .wars
-                        .values()
-                        .any(|war| war.attackers.contains(tag) || war.defenders.contains(tag));
-
-                    // Calculate war scores and enemy provinces for this country
-                    let mut our_war_score = std::collections::HashMap::new();
-                    let mut enemy_provinces = std::collections::HashSet::new();
-                    for war in state.diplomacy.wars.values() {
-                        let is_attacker = war.attackers.contains(tag);
-                        let is_defender = war.defenders.contains(tag);
-
-                        if is_attacker || is_defender {
-                            // Add all war participants to known countries
-                            for participant in war.attackers.iter().chain(war.defenders.iter()) {
-                                known_countries.insert(participant.clone());
-                            }
-
-                            // Calculate relative war score (positive = winning, negative = losing)
-                            let score = if is_attacker {
-                                eu4sim_core::fixed::Fixed::from_int(war.attacker_score as i64)
-                                    - eu4sim_core::fixed::Fixed::from_int(war.defender_score as i64)
-                            } else {
-                                eu4sim_core::fixed::Fixed::from_int(war.defender_score as i64)
-                                    - eu4sim_core::fixed::Fixed::from_int(war.attacker_score as i64)
-                            };
-                            our_war_score.insert(war.id, score);
-
-                            // Collect enemy provinces (only for known enemies)
-                            let enemy_tags: Vec<&String> = if is_attacker {
-                                war.defenders.iter().collect()
-                            } else {
-                                war.attackers.iter().collect()
-                            };
-
-                            for (prov_id, prov) in &state.provinces {
-                                if let Some(owner) = &prov.owner {
-                                    if enemy_tags.contains(&owner) {
-                                        enemy_provinces.insert(*prov_id);
-                                    }
-                                }
-                            }
-                        }
-                    }
-
-                    // Calculate total strength of all current war enemies
-                    let current_war_enemy_strength: u32 = state
-                        .diplomacy
-                        .wars
-                        .values()
-                        .filter(|war| war.attackers.contains(tag) || war.defenders.contains(tag))
-                        .flat_map(|war| {
-                            let is_attacker = war.attackers.contains(tag);
-                            if is_attacker {
-                                war.defenders.iter().collect::<Vec<_>>()
-                            } else {
-                                war.attackers.iter().collect()
-                            }
-                        })
-                        .filter_map(|enemy| global_strength.get(enemy))
-                        .sum();
-
-                    // Filter strength to only known countries (fog of war)
-                    let known_country_strength: HashMap<String, u32> = global_strength
-                        .iter()
-                        .filter(|(country, _)| known_countries.contains(*country))
-                        .map(|(k, v)| (k.clone(), *v))
-                        .collect();
-
-                    // Build warfare-aware fields
-                    let own_generals: Vec<eu4sim_core::ai::GeneralSummary> = state
-                        .generals
-                        .values()
-                        .filter(|g| g.owner == *tag)
-                        .map(|g| {
-                            let assigned_to = state
-                                .armies
-                                .values()
-                                .find(|a| a.general == Some(g.id))
-                                .map(|a| a.id);
-                            eu4sim_core::ai::GeneralSummary {
-                                id: g.id,
-                                fire: g.fire,
-                                shock: g.shock,
-                                maneuver: g.maneuver,
-                                siege: g.siege,
-                                assigned_to,
-                            }
-                        })
-                        .collect();
-
-                    let armies_without_general: Vec<u32> = state
-                        .armies
-                        .values()
-                        .filter(|a| a.owner == *tag && a.general.is_none())
-                        .map(|a| a.id)
-                        .collect();
-
-                    let own_fleets: Vec<eu4sim_core::ai::FleetSummary> = state
-                        .fleets
-                        .values()
-                        .filter(|f| f.owner == *tag)
-                        .map(|f| eu4sim_core::ai::FleetSummary {
-                            id: f.id,
-                            location: f.location,
-                            ship_count: f.ships.len() as u32,
-                            transport_capacity: f.ships.len() as u32, // Simplified
-                            in_battle: f.in_battle.is_some(),
-                        })
-                        .collect();
-
-                    // Blocked straits (simplified - would need adjacency graph for full implementation)
-                    let blocked_straits = std::collections::HashSet::new();
-
-                    // Province supply limits and army locations
-                    let province_supply: std::collections::HashMap<u32, u32> = state
-                        .provinces
-                        .iter()
-                        .map(|(id, p)| {
-                            let dev = p.base_tax + p.base_production + p.base_manpower;
-                            (*id, dev.to_int() as u32)
-                        })
-                        .collect();
-
-                    let mut army_locations = std::collections::HashMap::new();
-                    for army in state.armies.values() {
-                        *army_locations.entry(army.location).or_insert(0) += army.regiment_count();
-                    }
-
-                    // AE and coalitions (convert im::HashMap to std::HashMap)
-                    let own_ae: std::collections::HashMap<String, eu4sim_core::fixed::Fixed> =
-                        state
-                            .countries
-                            .get(tag)
-                            .map(|c| {
-                                c.aggressive_expansion
-                                    .iter()
-                                    .map(|(k, v)| (k.clone(), *v))
-                                    .collect()
-                            })
-                            .unwrap_or_default();
-
-                    let coalition_against_us = state
-                        .diplomacy
-                        .coalitions
-                        .get(tag)
-                        .map(|c| c.members.clone());
-
-                    // Fort provinces (enemy provinces with forts)
-                    let fort_provinces: std::collections::HashSet<u32> = state
-                        .provinces
-                        .iter()
-                        .filter(|(id, p)| enemy_provinces.contains(id) && p.fort_level > 0)
-                        .map(|(id, _)| *id)
-                        .collect();
-
-                    // Active sieges
-                    let active_sieges: Vec<eu4sim_core::ai::SiegeSummary> = state
-                        .sieges
-                        .values()
-                        .filter(|s| s.attacker == *tag)
-                        .map(|s| {
-                            // Rough estimate: average 30 days per phase, 12 phases max
-                            let phases_left = 12 - s.progress_modifier.min(12);
-                            let days_estimate = (phases_left * 30) as u32;
-                            eu4sim_core::ai::SiegeSummary {
-                                province: s.province,
-                                fort_level: s.fort_level,
-                                progress_modifier: s.progress_modifier,
-                                days_remaining_estimate: days_estimate,
-                            }
-                        })
-                        .collect();
-
-                    // Pending calls-to-arms (simplified - would need relationship tracking)
-                    let pending_call_to_arms = vec![];
-
-                    // Our army sizes - for Move scoring (don't move small stacks into enemy territory)
-                    let our_army_sizes: std::collections::HashMap<_, _> = state
-                        .armies
-                        .iter()
-                        .filter(|(_, a)| a.owner == *tag)
-                        .map(|(&id, a)| (id, a.regiment_count()))
-                        .collect();
-
-                    // Our army provinces - for consolidation bonus (gravitate toward friendly stacks)
-                    let our_army_provinces: std::collections::HashMap<_, _> = state
-                        .armies
-                        .iter()
-                        .filter(|(_, a)| {
-                            a.owner == *tag && a.in_battle.is_none() && a.embarked_on.is_none()
-                        })
-                        .fold(std::collections::HashMap::new(), |mut acc, (_, a)| {
-                            *acc.entry(a.location).or_default() += a.regiment_count();
-                            acc
-                        });
-
-                    // Staging provinces - friendly provinces adjacent to enemy territory
-                    // Used for army consolidation before attack
-                    let staging_provinces: std::collections::HashSet<u32> = if at_war {
-                        state
-                            .provinces
-                            .iter()
-                            .filter(|(_, p)| p.owner.as_ref() == Some(tag))
-                            .filter(|(&prov_id, _)| {
-                                // Check if any neighbor is an enemy province
-                                adjacency
-                                    .neighbors(prov_id)
-                                    .iter()
-                                    .any(|n| enemy_provinces.contains(n))
-                            })
-                            .map(|(&id, _)| id)
-                            .collect()
-                    } else {
-                        std::collections::HashSet::new()
-                    };
-
-                    // Build visible state with fog-of-war filtered intelligence
-                    let visible_state = eu4sim_core::ai::VisibleWorldState {
-                        date: state.date,
-                        observer: tag.clone(),
-                        own_country: state.countries.get(tag).cloned().unwrap_or_default(),
-                        at_war,
-                        known_countries: known_countries.into_iter().collect(),
-                        enemy_provinces,
-                        known_country_strength,
-                        our_war_score,
-                        own_generals,
-                        armies_without_general,
-                        own_fleets,
-                        blocked_straits,
-                        province_supply,
-                        army_locations,
-                        own_ae,
-                        coalition_against_us,
-                        fort_provinces,
-                        active_sieges,
-                        pending_call_to_arms,
-                        current_war_enemy_strength,
-                        our_army_sizes,
-                        our_army_provinces,
-                        staging_provinces,
-                    };
-
-                    // Compute available commands once - reused by AI and datagen
-                    let available = state.available_commands(tag, Some(&*adjacency));
-                    let cmds = ai.decide(&visible_state, &available);
-
-                    PlayerInputs {
-                        country: tag.clone(),
-                        commands: cmds,
-                        available_commands: available,
-                        visible_state: Some(visible_state),
-                    }
-                })
-                .collect();
-
-            // In gp-only mode: auto-accept peace offers for passive countries (no AI)
-            if args.ai == "gp-only" {
-                let ai_tags: std::collections::HashSet<_> = ais.keys().cloned().collect();
-                for war in state.diplomacy.wars.values() {
-                    if let Some(pending) = &war.pending_peace {
-                        // Find the target of the peace offer (opposite side from offerer)
-                        let target_side = if pending.from_attacker {
-                            &war.defenders
-                        } else {
-                            &war.attackers
-                        };
-
-                        // If any target country is passive (no AI), auto-accept
-                        for target_tag in target_side {
-                            if !ai_tags.contains(target_tag) {
-                                inputs.push(PlayerInputs {
-                                    country: target_tag.clone(),
-                                    commands: vec![eu4sim_core::Command::AcceptPeace {
-                                        war_id: war.id,
-                                    }],
-                                    available_commands: vec![],
-                                    visible_state: None,
-                                });
-                                log::info!(
-                                    "[AUTO-ACCEPT] {} accepts peace in {}",
-                                    target_tag,
-                                    war.name
-                                );
-                                break; // Only need one country to accept
-                            }
-                        }
-                    }
-                }
-            }
+            inputs =
+                ai_inputs::generate_ai_inputs(&state, &adjacency, &mut ais, args.ai == "gp-only");
 
             if let Some(m) = metrics.as_mut() {
                 m.ai_time += ai_start.elapsed();
@@ -1506,8 +1307,8 @@ fn main() -> Result<()> {
         };
 
         let step_start = std::time::Instant::now();
-        state = step_world(
-            &state,
+        step_world_mut(
+            &mut state,
             &inputs,
             Some(&*adjacency),
             &config,
@@ -1554,9 +1355,16 @@ fn main() -> Result<()> {
             reassign_hybrid_ais(&mut ais, &state, args.greedy_count, args.seed);
         }
 
-        // Notify observers with post-step state and inputs that were processed
-        let snapshot = Snapshot::new(state.clone(), tick, 0);
-        observers.notify_with_inputs(&snapshot, &inputs);
+        // Notify observers with post-step state and inputs that were processed.
+        // Only snapshot when someone is listening this tick; the clone is otherwise wasted.
+        if observers.wants_snapshot(tick, state.date) {
+            let observer_start = std::time::Instant::now();
+            let snapshot = Snapshot::new(state.clone(), tick, 0);
+            observers.notify_with_inputs(&snapshot, &inputs);
+            if let Some(m) = metrics.as_mut() {
+                m.observer_time += observer_start.elapsed();
+            }
+        }
 
         // Speed control delay (use short sleep for TUI to keep input responsive)
         if args.tui {
@@ -1767,4 +1575,54 @@ mod tests {
         assert!(ais.contains_key("FRA"));
         assert!(!ais.contains_key("DEAD"), "Dead country should be removed");
     }
+
+    #[test]
+    fn test_reassign_hybrid_ais_moves_every_llm_ai() {
+        // Property: every LlmAi lands on a distinct top country and none are lost
+        struct FakeLlm;
+        impl eu4sim_core::AiPlayer for FakeLlm {
+            fn name(&self) -> &'static str {
+                "LlmAi"
+            }
+            fn decide(
+                &mut self,
+                _: &eu4sim_core::ai::VisibleWorldState,
+                _: &eu4sim_core::ai::AvailableCommands,
+            ) -> Vec<eu4sim_core::Command> {
+                vec![]
+            }
+        }
+
+        let countries = vec![
+            ("FRA".to_string(), 100),
+            ("SPA".to_string(), 90),
+            ("ENG".to_string(), 80),
+            ("AUS".to_string(), 70),
+            ("TUR".to_string(), 60),
+        ];
+        let state = make_test_world(&countries);
+        let mut ais: BTreeMap<String, Box<dyn eu4sim_core::AiPlayer>> = BTreeMap::new();
+        for (tag, _) in &countries {
+            ais.insert(tag.clone(), Box::new(eu4sim_core::RandomAi::new(12345)));
+        }
+        ais.insert("AUS".to_string(), Box::new(FakeLlm));
+        ais.insert("TUR".to_string(), Box::new(FakeLlm));
+
+        assert!(reassign_hybrid_ais(&mut ais, &state, 3, 12345));
+
+        let top_3 = calculate_top_countries(&state, 3);
+        let llm_tags: Vec<_> = ais
+            .iter()
+            .filter(|(_, ai)| ai.name() == "LlmAi")
+            .map(|(tag, _)| tag.clone())
+            .collect();
+        assert_eq!(llm_tags.len(), 2);
+        assert!(llm_tags.iter().all(|tag| top_3.contains(tag)));
+        let greedy = ais.values().filter(|ai| ai.name() == "GreedyAI").count();
+        assert_eq!(greedy, 1);
+        assert_eq!(ais.len(), 5);
+
+        // Stable once placed
+        assert!(!reassign_hybrid_ais(&mut ais, &state, 3, 12345));
+    }
 }
@@ -0,0 +1,571 @@
+//! `eu4sim tournament`: rank AI players against each other with Elo ratings.
+//!
+//! Each match forks the initial [`WorldState`] with its own seed and gives
+//! every participant one of the top great powers (by development). Seats
+//! rotate from match to match, so over `players.len()` seeds every participant
+//! plays every great power once. All other countries are run by the
+//! `--background` AI mode (same modes as the interactive loop, assigned once
+//! per match).
+//!
+//! Participants are single-country AIs. `hybrid` is not one: it is a way of
+//! assigning AIs to the whole world (GreedyAI great powers, an LLM seat,
+//! RandomAi elsewhere), so it can only be the background. Its per-country
+//! parts compete as `greedy` and `bridge`.
+//!
+//! At the end of a match each participant is scored on its country's
+//! development and province growth, mean war score and survival
+//! ([`ScoreWeights`]). Placements update a multiplayer Elo table (every pair
+//! of participants is one game) that is persisted as JSON, so ratings carry
+//! over between tournaments.
+
+use anyhow::{Context, Result};
+use eu4sim_core::env::reward::owned_development;
+use eu4sim_core::state::Date;
+use eu4sim_core::{step_world_mut, AiPlayer, SimConfig, WorldState};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(clap::Args, Debug)]
+pub struct TournamentArgs {
+    /// Comma-separated participants: random, greedy, search (lookahead
+    /// SearchAI), bridge or bridge:HOST:PORT (LLM via inference server).
+    /// `hybrid` assigns AIs to every country, so it is only a --background.
+    #[arg(long, value_delimiter = ',', default_value = "greedy,random")]
+    pub players: Vec<String>,
+
+    /// Number of matches. Match `i` uses seed `--seed + i`.
+    #[arg(long, default_value_t = 8)]
+    pub matches: u64,
+
+    /// Run each match until this date (YYYY or YYYY.MM.DD)
+    #[arg(long, default_value = "1454.1.1", value_parser = super::batch::parse_date)]
+    pub until: Date,
+
+    /// AI mode for the countries no participant plays
+    #[arg(long, default_value = "hybrid")]
+    pub background: String,
+
+    /// Elo table to read and update
+    #[arg(long, default_value = "tournament.json")]
+    pub ratings: PathBuf,
+
+    /// Worker threads (default: one per core). Use 1 with LLM players that
+    /// share one inference server.
+    #[arg(long)]
+    pub threads: Option<usize>,
+
+    /// Write the match report to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Rating given to a player's first match.
+pub const INITIAL_ELO: f64 = 1500.0;
+
+/// Elo K-factor per match, split across the pairwise games.
+const K_FACTOR: f64 = 32.0;
+
+/// Builds an LLM player from a `bridge[:HOST:PORT]` spec.
+pub type LlmFactory<'a> = &'a (dyn Fn(&str) -> Result<Box<dyn AiPlayer>> + Sync);
+
+/// How a match result turns into one number.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreWeights {
+    /// Per unit of end/start development ratio
+    pub development_growth: f64,
+    /// Per unit of end/start province-count ratio
+    pub province_growth: f64,
+    /// Per point of mean monthly war score
+    pub war_score: f64,
+}
+
+impl Default for ScoreWeights {
+    fn default() -> Self {
+        Self {
+            development_growth: 100.0,
+            province_growth: 20.0,
+            war_score: 0.5,
+        }
+    }
+}
+
+/// One participant's outcome in one match.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerResult {
+    pub player: String,
+    pub tag: String,
+    pub alive: bool,
+    pub start_development: f64,
+    pub development: f64,
+    pub start_provinces: u32,
+    pub provinces: u32,
+    /// Relative war score summed over the country's wars, averaged per month
+    pub mean_war_score: f64,
+    pub score: f64,
+    /// 1 = best; tied scores share a placement
+    pub placement: usize,
+}
+
+impl PlayerResult {
+    /// Weighted score; an eliminated country scores 0.
+    fn compute_score(&self, weights: &ScoreWeights) -> f64 {
+        if !self.alive {
+            return 0.0;
+        }
+        let ratio = |end: f64, start: f64| if start > 0.0 { end / start } else { 1.0 };
+        weights.development_growth * ratio(self.development, self.start_development)
+            + weights.province_growth * ratio(self.provinces as f64, self.start_provinces as f64)
+            + weights.war_score * self.mean_war_score
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchResult {
+    pub seed: u64,
+    pub results: Vec<PlayerResult>,
+}
+
+/// A player's entry in the persisted table.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Rating {
+    pub elo: f64,
+    pub matches: u32,
+    /// Matches finished in first place (shared firsts count)
+    pub wins: u32,
+    /// Running mean of the match score
+    pub mean_score: f64,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            elo: INITIAL_ELO,
+            matches: 0,
+            wins: 0,
+            mean_score: 0.0,
+        }
+    }
+}
+
+/// Elo table persisted between tournaments.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Ratings {
+    pub players: BTreeMap<String, Rating>,
+}
+
+impl Ratings {
+    /// Read the table, or start an empty one if the file doesn't exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        serde_json::from_str(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json + "\n").with_context(|| format!("writing {}", path.display()))
+    }
+
+    /// Apply one match: every pair of participants is a game won by the
+    /// better placement. Deltas use pre-match ratings.
+    pub fn record(&mut self, results: &[PlayerResult]) {
+        let elo = |name: &str| self.players.get(name).map_or(INITIAL_ELO, |r| r.elo);
+        let pre: Vec<f64> = results.iter().map(|r| elo(&r.player)).collect();
+        let k = K_FACTOR / (results.len().max(2) - 1) as f64;
+
+        let mut deltas = vec![0.0; results.len()];
+        for i in 0..results.len() {
+            for j in (i + 1)..results.len() {
+                let actual = match results[i].placement.cmp(&results[j].placement) {
+                    std::cmp::Ordering::Less => 1.0,
+                    std::cmp::Ordering::Equal => 0.5,
+                    std::cmp::Ordering::Greater => 0.0,
+                };
+                let expected = 1.0 / (1.0 + 10f64.powf((pre[j] - pre[i]) / 400.0));
+                deltas[i] += k * (actual - expected);
+                deltas[j] -= k * (actual - expected);
+            }
+        }
+
+        for (result, delta) in results.iter().zip(deltas) {
+            let rating = self.players.entry(result.player.clone()).or_default();
+            rating.elo += delta;
+            rating.matches += 1;
+            if result.placement == 1 {
+                rating.wins += 1;
+            }
+            rating.mean_score += (result.score - rating.mean_score) / rating.matches as f64;
+        }
+    }
+
+    /// Players sorted by rating, best first.
+    pub fn standings(&self) -> Vec<(&String, &Rating)> {
+        let mut rows: Vec<_> = self.players.iter().collect();
+        rows.sort_by(|a, b| b.1.elo.total_cmp(&a.1.elo).then(a.0.cmp(b.0)));
+        rows
+    }
+}
+
+/// Full output of a tournament run.
+#[derive(Debug, Clone, Serialize)]
+pub struct TournamentReport {
+    pub start_date: String,
+    pub end_date: String,
+    pub background: String,
+    pub weights: ScoreWeights,
+    pub matches: Vec<MatchResult>,
+    pub ratings: Ratings,
+}
+
+/// Set placements from scores (higher is better, ties share a place).
+fn assign_placements(results: &mut [PlayerResult]) {
+    let scores: Vec<f64> = results.iter().map(|r| r.score).collect();
+    for result in results.iter_mut() {
+        result.placement = 1 + scores.iter().filter(|&&s| s > result.score).count();
+    }
+}
+
+fn build_player(
+    spec: &str,
+    seed: u64,
+    adjacency: &Arc<eu4data::adjacency::AdjacencyGraph>,
+    llm: LlmFactory,
+) -> Result<Box<dyn AiPlayer>> {
+    Ok(match spec {
+        "random" => Box::new(eu4sim_core::RandomAi::new(seed)),
+        "greedy" => Box::new(eu4sim_core::GreedyAI::new()),
+        "search" => Box::new(
+            eu4sim_core::SearchAI::new(eu4sim_core::SearchConfig {
+                seed,
+                ..Default::default()
+            })
+            .with_adjacency(adjacency.clone()),
+        ),
+        _ if spec == "bridge" || spec.starts_with("bridge:") => llm(spec)?,
+        "hybrid" => anyhow::bail!(
+            "'hybrid' mixes AIs across all countries and can't play a single one; \
+             use it as --background and enter greedy or bridge as players"
+        ),
+        _ => anyhow::bail!(
+            "unknown player '{}' (expected random, greedy, search, bridge or bridge:HOST:PORT)",
+            spec
+        ),
+    })
+}
+
+/// Development and province count of `tag`.
+fn holdings(state: &WorldState, tag: &str) -> (f64, u32) {
+    let provinces = state
+        .provinces
+        .values()
+        .filter(|p| p.owner.as_deref() == Some(tag))
+        .count();
+    (owned_development(state, tag), provinces as u32)
+}
+
+/// Relative war score of `tag`, summed over its wars.
+fn war_score(state: &WorldState, tag: &str) -> f64 {
+    state
+        .diplomacy
+        .wars
+        .values()
+        .map(|war| {
+            let relative = war.attacker_score as f64 - war.defender_score as f64;
+            if war.attackers.iter().any(|t| t == tag) {
+                relative
+            } else if war.defenders.iter().any(|t| t == tag) {
+                -relative
+            } else {
+                0.0
+            }
+        })
+        .sum()
+}
+
+/// Play one match with `seats[i]` controlled by `players[i]`.
+#[allow(clippy::too_many_arguments)]
+fn run_match(
+    base: &WorldState,
+    adjacency: &Arc<eu4data::adjacency::AdjacencyGraph>,
+    seed: u64,
+    players: &[String],
+    seats: &[String],
+    args: &TournamentArgs,
+    greedy_count: usize,
+    weights: &ScoreWeights,
+    llm: LlmFactory,
+) -> Result<MatchResult> {
+    let mut state = base.clone();
+    state.rng_seed = seed;
+    state.rng_state = 0;
+
+    let mut ais = super::batch::build_ais(&state, adjacency, &args.background, greedy_count, seed)?;
+    for (i, (player, tag)) in players.iter().zip(seats).enumerate() {
+        let ai = build_player(player, seed.wrapping_add(i as u64), adjacency, llm)?;
+        ais.insert(tag.clone(), ai);
+    }
+
+    let start: Vec<(f64, u32)> = seats.iter().map(|tag| holdings(&state, tag)).collect();
+    let mut war_totals = vec![0.0; seats.len()];
+    let mut months = 0u32;
+
+    let config = SimConfig::default();
+    let gp_only = args.background == "gp-only";
+    while state.date < args.until {
+        let inputs = super::ai_inputs::generate_ai_inputs(&state, adjacency, &mut ais, gp_only);
+        step_world_mut(&mut state, &inputs, Some(adjacency), &config, None);
+        if state.date.day == 1 {
+            months += 1;
+            for (total, tag) in war_totals.iter_mut().zip(seats) {
+                *total += war_score(&state, tag);
+            }
+        }
+    }
+
+    let mut results: Vec<PlayerResult> = players
+        .iter()
+        .zip(seats)
+        .enumerate()
+        .map(|(i, (player, tag))| {
+            let (development, provinces) = holdings(&state, tag);
+            let mut result = PlayerResult {
+                player: player.clone(),
+                tag: tag.clone(),
+                alive: state.countries.contains_key(tag) && provinces > 0,
+                start_development: start[i].0,
+                development,
+                start_provinces: start[i].1,
+                provinces,
+                mean_war_score: war_totals[i] / months.max(1) as f64,
+                score: 0.0,
+                placement: 0,
+            };
+            result.score = result.compute_score(weights);
+            result
+        })
+        .collect();
+    assign_placements(&mut results);
+
+    log::info!(
+        "Match seed={} finished: {}",
+        seed,
+        results
+            .iter()
+            .map(|r| format!("#{} {} ({}) {:.1}", r.placement, r.player, r.tag, r.score))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    Ok(MatchResult { seed, results })
+}
+
+/// Entry point for `eu4sim tournament`.
+pub fn run(
+    base: WorldState,
+    adjacency: eu4data::adjacency::AdjacencyGraph,
+    base_seed: u64,
+    greedy_count: usize,
+    args: &TournamentArgs,
+    llm: LlmFactory,
+) -> Result<()> {
+    if args.players.len() < 2 {
+        anyhow::bail!("a tournament needs at least two --players");
+    }
+    if let Some(dup) = args
+        .players
+        .iter()
+        .enumerate()
+        .find_map(|(i, p)| args.players[..i].contains(p).then_some(p))
+    {
+        anyhow::bail!("player '{}' listed twice", dup);
+    }
+    super::batch::check_ai_mode(&args.background)?;
+    if args.until <= base.date {
+        anyhow::bail!(
+            "--until {} is not after the start date {}",
+            args.until,
+            base.date
+        );
+    }
+
+    // Great powers by development, largest first, so seat rotation is stable
+    let mut great_powers: Vec<(String, f64)> =
+        super::calculate_top_countries(&base, args.players.len())
+            .into_iter()
+            .map(|tag| {
+                let dev = holdings(&base, &tag).0;
+                (tag, dev)
+            })
+            .collect();
+    great_powers.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    let great_powers: Vec<String> = great_powers.into_iter().map(|(tag, _)| tag).collect();
+    if great_powers.len() < args.players.len() {
+        anyhow::bail!(
+            "only {} countries for {} players",
+            great_powers.len(),
+            args.players.len()
+        );
+    }
+
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(threads) = args.threads {
+        pool = pool.num_threads(threads);
+    }
+    let pool = pool.build()?;
+
+    let weights = ScoreWeights::default();
+    let adjacency = Arc::new(adjacency);
+    log::info!(
+        "Tournament: {:?} on {:?}, {} matches to {}",
+        args.players,
+        great_powers,
+        args.matches,
+        args.until
+    );
+    let matches: Vec<MatchResult> = pool.install(|| {
+        (0..args.matches)
+            .into_par_iter()
+            .map(|i| {
+                let n = great_powers.len();
+                let seats: Vec<String> = (0..n)
+                    .map(|p| great_powers[(p + i as usize) % n].clone())
+                    .collect();
+                run_match(
+                    &base,
+                    &adjacency,
+                    base_seed.wrapping_add(i),
+                    &args.players,
+                    &seats,
+                    args,
+                    greedy_count,
+                    &weights,
+                    llm,
+                )
+            })
+            .collect::<Result<_>>()
+    })?;
+
+    // Ratings are updated in seed order so reruns give the same table
+    let mut ratings = Ratings::load(&args.ratings)?;
+    for m in &matches {
+        ratings.record(&m.results);
+    }
+    ratings.save(&args.ratings)?;
+
+    for (rank, (player, rating)) in ratings.standings().into_iter().enumerate() {
+        log::warn!(
+            "{:>2}. {:<24} elo {:>7.1}  matches {:>4}  wins {:>4}  mean score {:>7.1}",
+            rank + 1,
+            player,
+            rating.elo,
+            rating.matches,
+            rating.wins,
+            rating.mean_score
+        );
+    }
+
+    let report = TournamentReport {
+        start_date: base.date.to_string(),
+        end_date: args.until.to_string(),
+        background: args.background.clone(),
+        weights,
+        matches,
+        ratings,
+    };
+    let mut out: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path).with_context(|| format!("creating {}", path.display()))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    };
+    serde_json::to_writer_pretty(&mut out, &report)?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(player: &str, score: f64) -> PlayerResult {
+        PlayerResult {
+            player: player.to_string(),
+            tag: String::new(),
+            alive: true,
+            start_development: 10.0,
+            development: 10.0,
+            start_provinces: 1,
+            provinces: 1,
+            mean_war_score: 0.0,
+            score,
+            placement: 0,
+        }
+    }
+
+    #[test]
+    fn test_build_player_rejects_world_modes() {
+        let adjacency = Arc::new(eu4data::adjacency::AdjacencyGraph::default());
+        let llm = |_: &str| -> Result<Box<dyn AiPlayer>> { unreachable!() };
+        assert!(build_player("greedy", 0, &adjacency, &llm).is_ok());
+        let Err(err) = build_player("hybrid", 0, &adjacency, &llm) else {
+            panic!("hybrid should not be a player");
+        };
+        assert!(err.to_string().contains("--background"), "{}", err);
+    }
+
+    #[test]
+    fn test_score_rewards_growth_and_punishes_elimination() {
+        let weights = ScoreWeights::default();
+        let mut r = result("greedy", 0.0);
+        assert_eq!(r.compute_score(&weights), 120.0);
+
+        r.development = 20.0;
+        r.provinces = 2;
+        r.mean_war_score = 10.0;
+        assert_eq!(r.compute_score(&weights), 245.0);
+
+        r.alive = false;
+        assert_eq!(r.compute_score(&weights), 0.0);
+    }
+
+    #[test]
+    fn test_placements_share_ties() {
+        let mut results = vec![result("a", 5.0), result("b", 9.0), result("c", 5.0)];
+        assign_placements(&mut results);
+        let placements: Vec<usize> = results.iter().map(|r| r.placement).collect();
+        assert_eq!(placements, vec![2, 1, 2]);
+    }
+
+    #[test]
+    fn test_elo_moves_toward_winner_and_round_trips() {
+        let mut results = vec![result("greedy", 200.0), result("random", 100.0)];
+        assign_placements(&mut results);
+
+        let mut ratings = Ratings::default();
+        ratings.record(&results);
+        let greedy = &ratings.players["greedy"];
+        let random = &ratings.players["random"];
+        assert_eq!(greedy.elo, INITIAL_ELO + K_FACTOR / 2.0);
+        assert_eq!(random.elo, INITIAL_ELO - K_FACTOR / 2.0);
+        assert_eq!((greedy.wins, random.wins), (1, 0));
+        assert_eq!(greedy.mean_score, 200.0);
+
+        // A second win against a weaker opponent gains less
+        ratings.record(&results);
+        let gain = ratings.players["greedy"].elo - (INITIAL_ELO + K_FACTOR / 2.0);
+        assert!(gain > 0.0 && gain < K_FACTOR / 2.0);
+        assert_eq!(ratings.standings()[0].0, "greedy");
+
+        let path = std::env::temp_dir().join(format!("eu4sim-ratings-{}.json", std::process::id()));
+        ratings.save(&path).unwrap();
+        assert_eq!(Ratings::load(&path).unwrap(), ratings);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
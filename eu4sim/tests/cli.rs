@@ -43,3 +43,53 @@ fn test_help_flag() {
     assert!(stdout.contains("Usage:"));
     assert!(stdout.contains("--game-path"));
 }
+
+#[test]
+fn test_batch_mode_reports_all_runs() {
+    let dir = std::env::temp_dir().join(format!("eu4sim-batch-{}", std::process::id()));
+    let mut cmd = Command::new(cargo_bin("eu4sim"));
+    let output = cmd
+        .args(["--test-mode", "--ai", "greedy", "batch"])
+        .args(["--runs", "3", "--until", "1445.2.1", "--per-run"])
+        .arg("--event-log-dir")
+        .arg(&dir)
+        .output()
+        .expect("failed to execute");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("batch report should be JSON");
+    assert_eq!(report["runs"], 3);
+    assert_eq!(report["seeds"], serde_json::json!([12345, 12346, 12347]));
+    assert_eq!(report["per_run"].as_array().map(Vec::len), Some(3));
+    for tag in ["AAA", "BBB", "CCC"] {
+        let survival = report["countries"][tag]["survival_rate"].as_f64();
+        assert!(
+            survival.is_some_and(|s| (0.0..=1.0).contains(&s)),
+            "{}",
+            tag
+        );
+    }
+    assert!(dir.join("seed-12346.jsonl").exists());
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_batch_mode_csv() {
+    let mut cmd = Command::new(cargo_bin("eu4sim"));
+    let output = cmd
+        .args(["--test-mode", "batch", "--runs", "2", "--until", "1445"])
+        .args(["--format", "csv", "--threads", "2"])
+        .output()
+        .expect("failed to execute");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines[0].starts_with("tag,survival_rate,dev_mean"));
+    assert_eq!(lines.len(), 4, "header + one row per mock country");
+}
@@ -137,7 +137,7 @@ fn find_token_table(data: &[u8], _base_offset: usize) -> Result<Vec<(u16, String
 
 /// Try to read a valid Clausewitz identifier from data
 /// Returns (identifier, bytes_consumed) or None
-fn try_read_identifier(data: &[u8]) -> Option<(String, usize)> {
+pub(crate) fn try_read_identifier(data: &[u8]) -> Option<(String, usize)> {
     if data.is_empty() {
         return None;
     }
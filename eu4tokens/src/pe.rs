@@ -1,20 +1,475 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use goblin::pe::PE;
+
+use crate::elf::try_read_identifier;
+
+/// Minimum number of consecutive records before a run counts as the token table
+///
+/// The real table has thousands of entries; short runs are usually unrelated
+/// pointer/integer pairs (vtables, small lookup tables).
+const MIN_TABLE_RUN: usize = 32;
 
 /// Extract token mappings from a PE binary (Windows EU4)
 ///
-/// **Not yet implemented.** Windows/PE support is planned for a future release.
+/// Token format in binary:
+/// - A static array of records pairing a name pointer with a 16-bit ID
+/// - Each record is two pointer-sized slots: `{ name, id }` or `{ id, name }`
+/// - Name pointers are virtual addresses of null-terminated identifiers,
+///   usually in `.rdata`; the array itself lives in `.rdata` or `.data`
+pub fn extract_tokens(data: &[u8]) -> Result<Vec<(u16, String)>> {
+    let pe = PE::parse(data).context("Failed to parse PE binary")?;
+
+    log::info!(
+        "PE binary: {} sections, {}-bit, image base 0x{:x}",
+        pe.sections.len(),
+        if pe.is_64 { 64 } else { 32 },
+        pe.image_base
+    );
+
+    let image = Image::new(&pe, data);
+    for section in &image.sections {
+        log::debug!(
+            "Section {}: (rva: 0x{:x}, offset: 0x{:x}, size: 0x{:x})",
+            section.name,
+            section.rva,
+            section.offset,
+            section.size
+        );
+    }
+
+    // Look for the record array in data sections
+    let candidate_sections = [".rdata", ".data"];
+    let mut all_tokens = Vec::new();
+
+    for section_name in candidate_sections {
+        let Some(section) = image.sections.iter().find(|s| s.name == section_name) else {
+            continue;
+        };
+        let tokens = find_token_records(&image, section);
+        if !tokens.is_empty() {
+            log::info!(
+                "Found {} candidate tokens in {}",
+                tokens.len(),
+                section_name
+            );
+            all_tokens.extend(tokens);
+        }
+    }
+
+    if all_tokens.is_empty() {
+        anyhow::bail!(
+            "No token table found in PE binary.\n\n\
+            Expected a run of at least {} (name pointer, ID) records in .rdata or .data.\n\
+            The executable may be packed or from an unsupported game version.",
+            MIN_TABLE_RUN
+        );
+    }
+
+    // Deduplicate and sort by ID
+    all_tokens.sort_by_key(|(id, _)| *id);
+    all_tokens.dedup_by_key(|(id, _)| *id);
+
+    Ok(all_tokens)
+}
+
+/// File-backed section of the image
+struct Section {
+    name: String,
+    rva: usize,
+    offset: usize,
+    size: usize,
+}
+
+/// Minimal view of a mapped PE image: enough to follow absolute pointers
+struct Image<'a> {
+    data: &'a [u8],
+    image_base: u64,
+    pointer_size: usize,
+    sections: Vec<Section>,
+}
+
+impl<'a> Image<'a> {
+    fn new(pe: &PE, data: &'a [u8]) -> Self {
+        let sections = pe
+            .sections
+            .iter()
+            .filter_map(|sh| {
+                let offset = sh.pointer_to_raw_data as usize;
+                // Only the raw data is in the file; any uninitialized tail isn't
+                let size = sh.size_of_raw_data as usize;
+                if offset.checked_add(size)? > data.len() {
+                    log::warn!(
+                        "Section {} extends beyond file bounds",
+                        sh.name().unwrap_or("<unknown>")
+                    );
+                    return None;
+                }
+                Some(Section {
+                    name: sh.name().unwrap_or("<unknown>").to_string(),
+                    rva: sh.virtual_address as usize,
+                    offset,
+                    size,
+                })
+            })
+            .collect();
+
+        Self {
+            data,
+            image_base: pe.image_base as u64,
+            pointer_size: if pe.is_64 { 8 } else { 4 },
+            sections,
+        }
+    }
+
+    fn bytes(&self, section: &Section) -> &'a [u8] {
+        &self.data[section.offset..section.offset + section.size]
+    }
+
+    /// Read a pointer-sized little-endian slot
+    fn read_slot(&self, bytes: &[u8], at: usize) -> Option<u64> {
+        let slot = bytes.get(at..at + self.pointer_size)?;
+        let mut buf = [0u8; 8];
+        buf[..self.pointer_size].copy_from_slice(slot);
+        Some(u64::from_le_bytes(buf))
+    }
+
+    /// Map an absolute virtual address to a file offset
+    fn va_to_offset(&self, va: u64) -> Option<usize> {
+        let rva = usize::try_from(va.checked_sub(self.image_base)?).ok()?;
+        self.sections
+            .iter()
+            .find(|s| rva >= s.rva && rva - s.rva < s.size)
+            .map(|s| s.offset + (rva - s.rva))
+    }
+
+    /// Identifier string at a virtual address
+    fn identifier_at(&self, va: u64) -> Option<String> {
+        let offset = self.va_to_offset(va)?;
+        // Names start at a string boundary, not mid-string
+        if offset > 0 && self.data[offset - 1] != 0 {
+            return None;
+        }
+        try_read_identifier(&self.data[offset..]).map(|(name, _)| name)
+    }
+}
+
+/// Slot order within a token record
+#[derive(Debug, Clone, Copy)]
+enum RecordLayout {
+    NameFirst,
+    IdFirst,
+}
+
+/// Consecutive records decoded from one section
+struct RecordRun {
+    start: usize,
+    end: usize,
+    tokens: Vec<(u16, String)>,
+}
+
+/// Scan a section for runs of `(name pointer, id)` records
 ///
-/// For now, you can:
-/// 1. Use the `use` subcommand with an existing tokens.txt file
-/// 2. Run token extraction on a Linux system with the Linux EU4 binary
-/// 3. Use tokens from pdx-tools or similar projects
-pub fn extract_tokens(_data: &[u8]) -> Result<Vec<(u16, String)>> {
-    anyhow::bail!(
-        "Windows PE binary support is not yet implemented.\n\n\
-        Alternatives:\n\
-        1. Use 'eu4tokens use <tokens.txt>' with an existing tokens file\n\
-        2. Run extraction on Linux with the Linux EU4 binary\n\
-        3. Obtain tokens from pdx-tools or similar projects\n\n\
-        PE support is planned for a future release."
-    )
+/// Every pointer-aligned phase and both slot orders are tried. A misaligned
+/// read of a real table pairs each ID with the next record's name and comes
+/// out one record shorter, so overlapping runs are resolved longest-first.
+fn find_token_records(image: &Image, section: &Section) -> Vec<(u16, String)> {
+    let bytes = image.bytes(section);
+    let slot = image.pointer_size;
+    let stride = slot * 2;
+
+    let mut runs = Vec::new();
+    for layout in [RecordLayout::NameFirst, RecordLayout::IdFirst] {
+        for phase in [0, slot] {
+            let mut current: Option<RecordRun> = None;
+            let mut at = phase;
+            while at + stride <= bytes.len() {
+                match read_record(image, bytes, at, layout) {
+                    Some(token) => {
+                        let run = current.get_or_insert_with(|| RecordRun {
+                            start: at,
+                            end: at,
+                            tokens: Vec::new(),
+                        });
+                        run.tokens.push(token);
+                        run.end = at + stride;
+                    }
+                    None => {
+                        if let Some(run) = current.take() {
+                            if run.tokens.len() >= MIN_TABLE_RUN {
+                                runs.push(run);
+                            }
+                        }
+                    }
+                }
+                at += stride;
+            }
+            if let Some(run) = current.take() {
+                if run.tokens.len() >= MIN_TABLE_RUN {
+                    runs.push(run);
+                }
+            }
+        }
+    }
+
+    // Longest runs win; drop runs overlapping one already taken
+    runs.sort_by_key(|run| std::cmp::Reverse(run.tokens.len()));
+    let mut taken: Vec<RecordRun> = Vec::new();
+    for run in runs {
+        if taken
+            .iter()
+            .all(|t| run.end <= t.start || run.start >= t.end)
+        {
+            log::debug!(
+                "Token records at 0x{:x}..0x{:x} ({} entries)",
+                section.offset + run.start,
+                section.offset + run.end,
+                run.tokens.len()
+            );
+            taken.push(run);
+        }
+    }
+
+    taken.into_iter().flat_map(|run| run.tokens).collect()
+}
+
+/// Decode one record, requiring a valid identifier pointer and a non-zero ID
+/// slot that holds nothing above 16 bits (zero slots are padding or the
+/// terminating record, and would let a misaligned read extend a run)
+fn read_record(
+    image: &Image,
+    bytes: &[u8],
+    at: usize,
+    layout: RecordLayout,
+) -> Option<(u16, String)> {
+    let slot = image.pointer_size;
+    let (name_at, id_at) = match layout {
+        RecordLayout::NameFirst => (at, at + slot),
+        RecordLayout::IdFirst => (at + slot, at),
+    };
+    let id = u16::try_from(image.read_slot(bytes, id_at)?)
+        .ok()
+        .filter(|&id| id != 0)?;
+    let name = image.identifier_at(image.read_slot(bytes, name_at)?)?;
+    Some((id, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE_BASE: u64 = 0x1_4000_0000;
+    const FILE_ALIGNMENT: usize = 0x200;
+    const SECTION_ALIGNMENT: usize = 0x1000;
+
+    fn align(value: usize, to: usize) -> usize {
+        value.div_ceil(to) * to
+    }
+
+    /// Build a minimal PE image with the given sections laid out in order
+    fn build_pe(is_64: bool, sections: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let pe_offset = 0x80;
+        let optional_size = if is_64 { 240 } else { 224 };
+        let headers_size = align(
+            pe_offset + 24 + optional_size + 40 * sections.len(),
+            FILE_ALIGNMENT,
+        );
+
+        let mut out = vec![0u8; headers_size];
+        out[0..2].copy_from_slice(b"MZ");
+        out[0x3c..0x40].copy_from_slice(&(pe_offset as u32).to_le_bytes());
+
+        // PE signature + COFF header
+        let mut h = Vec::new();
+        h.extend_from_slice(b"PE\0\0");
+        h.extend_from_slice(&(if is_64 { 0x8664u16 } else { 0x14c }).to_le_bytes());
+        h.extend_from_slice(&(sections.len() as u16).to_le_bytes());
+        h.extend_from_slice(&[0; 12]); // timestamp, symbol table, symbol count
+        h.extend_from_slice(&(optional_size as u16).to_le_bytes());
+        h.extend_from_slice(&0x22u16.to_le_bytes()); // executable, large address aware
+
+        let size_of_image = SECTION_ALIGNMENT * (sections.len() + 1);
+
+        // Optional header: standard fields
+        h.extend_from_slice(&(if is_64 { 0x20bu16 } else { 0x10b }).to_le_bytes());
+        h.extend_from_slice(&[0; 2]); // linker version
+        h.extend_from_slice(&[0; 12]); // code / data sizes
+        h.extend_from_slice(&[0; 8]); // entry point, base of code
+        if !is_64 {
+            h.extend_from_slice(&[0; 4]); // base of data
+        }
+        // Windows fields
+        if is_64 {
+            h.extend_from_slice(&IMAGE_BASE.to_le_bytes());
+        } else {
+            h.extend_from_slice(&(IMAGE_BASE as u32).to_le_bytes());
+        }
+        h.extend_from_slice(&(SECTION_ALIGNMENT as u32).to_le_bytes());
+        h.extend_from_slice(&(FILE_ALIGNMENT as u32).to_le_bytes());
+        h.extend_from_slice(&[0; 16]); // OS / image / subsystem versions, win32 version
+        h.extend_from_slice(&(size_of_image as u32).to_le_bytes());
+        h.extend_from_slice(&(headers_size as u32).to_le_bytes());
+        h.extend_from_slice(&[0; 8]); // checksum, subsystem, dll characteristics
+        h.extend_from_slice(&vec![0; if is_64 { 32 } else { 16 }]); // stack / heap sizes
+        h.extend_from_slice(&[0; 4]); // loader flags
+        h.extend_from_slice(&16u32.to_le_bytes());
+        h.extend_from_slice(&[0; 16 * 8]); // data directories
+
+        // Section headers
+        let mut raw_offset = headers_size;
+        for (i, (name, contents)) in sections.iter().enumerate() {
+            let mut name_bytes = [0u8; 8];
+            name_bytes[..name.len()].copy_from_slice(name.as_bytes());
+            h.extend_from_slice(&name_bytes);
+            h.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            h.extend_from_slice(&(section_rva(i) as u32).to_le_bytes());
+            let raw_size = align(contents.len(), FILE_ALIGNMENT);
+            h.extend_from_slice(&(raw_size as u32).to_le_bytes());
+            h.extend_from_slice(&(raw_offset as u32).to_le_bytes());
+            h.extend_from_slice(&[0; 12]); // relocations, line numbers
+            h.extend_from_slice(&0x4000_0040u32.to_le_bytes()); // initialized, readable
+            raw_offset += raw_size;
+        }
+        out[pe_offset..pe_offset + h.len()].copy_from_slice(&h);
+
+        for (_, contents) in sections {
+            let mut raw = contents.clone();
+            raw.resize(align(contents.len(), FILE_ALIGNMENT), 0);
+            out.extend_from_slice(&raw);
+        }
+        out
+    }
+
+    fn section_rva(index: usize) -> usize {
+        SECTION_ALIGNMENT * (index + 1)
+    }
+
+    /// Null-terminated strings, returning their offsets
+    fn string_table(names: &[String]) -> (Vec<u8>, Vec<usize>) {
+        let mut bytes = b"\0".to_vec();
+        let mut offsets = Vec::new();
+        for name in names {
+            offsets.push(bytes.len());
+            bytes.extend_from_slice(name.as_bytes());
+            bytes.push(0);
+        }
+        (bytes, offsets)
+    }
+
+    fn sample_tokens(count: usize) -> Vec<(u16, String)> {
+        (0..count)
+            .map(|i| (0x2c00 + (i as u16) * 3, format!("field_{}", i)))
+            .collect()
+    }
+
+    /// Record array pointing into a string section at `strings_rva`
+    fn record_table(
+        tokens: &[(u16, String)],
+        offsets: &[usize],
+        strings_rva: usize,
+        pointer_size: usize,
+        layout: RecordLayout,
+    ) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for ((id, _), offset) in tokens.iter().zip(offsets) {
+            let va = IMAGE_BASE + (strings_rva + offset) as u64;
+            let name_slot = &va.to_le_bytes()[..pointer_size];
+            let id_slot = &(*id as u64).to_le_bytes()[..pointer_size];
+            match layout {
+                RecordLayout::NameFirst => {
+                    bytes.extend_from_slice(name_slot);
+                    bytes.extend_from_slice(id_slot);
+                }
+                RecordLayout::IdFirst => {
+                    bytes.extend_from_slice(id_slot);
+                    bytes.extend_from_slice(name_slot);
+                }
+            }
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_extract_name_first_records_pe64() {
+        let tokens = sample_tokens(40);
+        let names: Vec<String> = tokens.iter().map(|(_, n)| n.clone()).collect();
+        let (strings, offsets) = string_table(&names);
+
+        // Unrelated data around the table
+        let mut data = vec![0xcc; 24];
+        data.extend(record_table(
+            &tokens,
+            &offsets,
+            section_rva(0),
+            8,
+            RecordLayout::NameFirst,
+        ));
+        data.extend_from_slice(&[0xff; 16]);
+
+        let pe = build_pe(true, &[(".rdata", strings), (".data", data)]);
+        assert_eq!(extract_tokens(&pe).unwrap(), tokens);
+    }
+
+    #[test]
+    fn test_extract_id_first_records_pe32() {
+        let tokens = sample_tokens(50);
+        let names: Vec<String> = tokens.iter().map(|(_, n)| n.clone()).collect();
+        let (strings, offsets) = string_table(&names);
+
+        // Strings and table share .rdata
+        let table_offset = align(strings.len(), 16) + 4;
+        let mut rdata = strings;
+        rdata.resize(table_offset, 0);
+        rdata.extend(record_table(
+            &tokens,
+            &offsets,
+            section_rva(0),
+            4,
+            RecordLayout::IdFirst,
+        ));
+
+        let pe = build_pe(false, &[(".rdata", rdata)]);
+        assert_eq!(extract_tokens(&pe).unwrap(), tokens);
+    }
+
+    #[test]
+    fn test_short_runs_are_ignored() {
+        let tokens = sample_tokens(MIN_TABLE_RUN - 1);
+        let names: Vec<String> = tokens.iter().map(|(_, n)| n.clone()).collect();
+        let (strings, offsets) = string_table(&names);
+        let data = record_table(
+            &tokens,
+            &offsets,
+            section_rva(0),
+            8,
+            RecordLayout::NameFirst,
+        );
+
+        let pe = build_pe(true, &[(".rdata", strings), (".data", data)]);
+        let err = extract_tokens(&pe).unwrap_err();
+        assert!(err.to_string().contains("No token table found"));
+    }
+
+    #[test]
+    fn test_pointers_must_hit_string_starts() {
+        let tokens = sample_tokens(40);
+        let names: Vec<String> = tokens.iter().map(|(_, n)| n.clone()).collect();
+        let (strings, offsets) = string_table(&names);
+        // Point one byte into every name
+        let shifted: Vec<usize> = offsets.iter().map(|o| o + 1).collect();
+        let data = record_table(
+            &tokens,
+            &shifted,
+            section_rva(0),
+            8,
+            RecordLayout::NameFirst,
+        );
+
+        let pe = build_pe(true, &[(".rdata", strings), (".data", data)]);
+        assert!(extract_tokens(&pe).is_err());
+    }
+
+    #[test]
+    fn test_rejects_non_pe() {
+        assert!(extract_tokens(b"MZ not really a PE file").is_err());
+    }
 }
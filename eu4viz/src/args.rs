@@ -1,3 +1,4 @@
+use crate::timelapse::FrameInterval;
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
@@ -21,7 +22,7 @@ pub struct Cli {
     pub log_level: String,
 
     /// Path to simulation event log (JSONL) for replay
-    #[arg(long)]
+    #[arg(long, global = true)]
     pub event_log: Option<PathBuf>,
 
     #[command(subcommand)]
@@ -54,6 +55,16 @@ pub enum Commands {
         /// The map mode to render (e.g., TradeGoods, Political).
         #[arg(long, value_enum, default_value_t = MapMode::TradeGoods)]
         mode: MapMode,
+        /// Color provinces from an EU4 save or a sim `WorldState` (.json)
+        /// instead of the 1444 history.
+        #[arg(long)]
+        source: Option<PathBuf>,
+        /// Draw province and country borders and coastlines.
+        #[arg(long)]
+        borders: bool,
+        /// Label countries with their localised names.
+        #[arg(long)]
+        labels: bool,
     },
 
     /// Open the interactive map window (default behavior).
@@ -72,6 +83,75 @@ pub enum Commands {
         /// The map mode to render (e.g., TradeGoods, Political).
         #[arg(long, value_enum, default_value_t = MapMode::Province)]
         mode: MapMode,
+
+        /// Color provinces from an EU4 save or a sim `WorldState` (.json)
+        /// instead of the 1444 history.
+        #[arg(long)]
+        source: Option<PathBuf>,
+    },
+
+    /// Render two saves or sim snapshots side by side with an owner-difference overlay.
+    ///
+    /// Example: `compare real_1600.eu4 sim_1600.json --output compare.png`
+    Compare {
+        /// Left map: an EU4 save or a sim `WorldState` (.json).
+        left: PathBuf,
+
+        /// Right map: an EU4 save or a sim `WorldState` (.json).
+        right: PathBuf,
+
+        /// The map mode to render for both sides.
+        #[arg(long, value_enum, default_value_t = MapMode::Political)]
+        mode: MapMode,
+
+        /// Output path for the side-by-side image.
+        #[arg(short, long, default_value = "compare.png")]
+        output: PathBuf,
+
+        /// Also write the owner-difference overlay at full resolution.
+        #[arg(long)]
+        overlay: Option<PathBuf>,
+
+        /// Width of each panel in pixels (0 keeps the map's size).
+        #[arg(long, default_value_t = 2048)]
+        width: u32,
+
+        /// Draw province and country borders and coastlines.
+        #[arg(long)]
+        borders: bool,
+
+        /// Label countries with their localised names.
+        #[arg(long)]
+        labels: bool,
+    },
+
+    /// Export an animated political map timelapse from `--event-log`.
+    ///
+    /// Example: `timelapse --event-log run.jsonl --every 1y --output run.gif`
+    Timelapse {
+        /// Game time between frames (e.g. 1y, 6m, 30d).
+        #[arg(long, default_value = "1y")]
+        every: FrameInterval,
+
+        /// Output path: a .gif file, or a directory for a numbered PNG sequence.
+        #[arg(short, long, default_value = "timelapse.gif")]
+        output: PathBuf,
+
+        /// Countries to highlight (comma-separated tags); others are dimmed.
+        #[arg(long, value_delimiter = ',')]
+        highlight: Vec<String>,
+
+        /// Frame width in pixels (height keeps the map's aspect ratio).
+        #[arg(long, default_value_t = 1024)]
+        width: u32,
+
+        /// Delay between frames in milliseconds.
+        #[arg(long, default_value_t = 200)]
+        frame_ms: u32,
+
+        /// Leave out the date caption.
+        #[arg(long)]
+        no_caption: bool,
     },
 
     /// Lookup a localisation key.
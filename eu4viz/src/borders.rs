@@ -0,0 +1,136 @@
+//! Border extraction from the province map for the headless renderers.
+//!
+//! A pixel is on a border when its right or lower neighbour belongs to another
+//! province. The border is classed by what the two provinces share: land next to
+//! water is coastline, land held by different owners is a country border, and
+//! anything else between two land provinces is a province border.
+
+use eu4data::history::ProvinceHistory;
+use image::{Rgb, RgbImage};
+use std::collections::{HashMap, HashSet};
+
+/// Border classes, lowest precedence first (a pixel on several keeps the highest).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BorderKind {
+    Province,
+    Country,
+    Coast,
+}
+
+/// Classify every pixel of a `width`-wide province id buffer (`u32::MAX` for
+/// unknown colours).
+///
+/// Coastlines are drawn on the land side; other borders on the left/upper pixel.
+pub fn extract_borders(
+    width: u32,
+    province_ids: &[u32],
+    water_ids: &HashSet<u32>,
+    province_history: &HashMap<u32, ProvinceHistory>,
+) -> Vec<Option<BorderKind>> {
+    let width = width as usize;
+    let mut borders = vec![None; province_ids.len()];
+    if width == 0 {
+        return borders;
+    }
+    let owner = |id: u32| province_history.get(&id).and_then(|h| h.owner.as_ref());
+
+    let mut mark = |idx: usize, kind: BorderKind| borders[idx] = borders[idx].max(Some(kind));
+
+    for (idx, &id) in province_ids.iter().enumerate() {
+        let right = (idx % width + 1 < width).then_some(idx + 1);
+        let below = Some(idx + width).filter(|&i| i < province_ids.len());
+
+        for other_idx in [right, below].into_iter().flatten() {
+            let other = province_ids[other_idx];
+            if id == other || id == u32::MAX || other == u32::MAX {
+                continue;
+            }
+            match (water_ids.contains(&id), water_ids.contains(&other)) {
+                (true, true) => {}
+                (false, true) => mark(idx, BorderKind::Coast),
+                (true, false) => mark(other_idx, BorderKind::Coast),
+                (false, false) if owner(id) != owner(other) => mark(idx, BorderKind::Country),
+                (false, false) => mark(idx, BorderKind::Province),
+            }
+        }
+    }
+    borders
+}
+
+/// Draw borders over a rendered map of the same size.
+///
+/// Province borders darken the fill so they stay subtle; country borders and
+/// coastlines are drawn solid.
+pub fn draw_borders(map: &mut RgbImage, borders: &[Option<BorderKind>]) {
+    for (pixel, border) in map.pixels_mut().zip(borders) {
+        match border {
+            Some(BorderKind::Province) => {
+                *pixel = Rgb(pixel.0.map(|c| (c as u16 * 3 / 4) as u8));
+            }
+            Some(BorderKind::Country) => *pixel = Rgb([20, 20, 20]),
+            Some(BorderKind::Coast) => *pixel = Rgb([32, 64, 96]),
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::owners;
+
+    #[test]
+    fn test_extract_borders() {
+        // 1 1 2 3
+        // 1 1 2 9
+        // 9 9 9 9
+        // Provinces 1 and 2 are Swedish, 3 is Danish, 9 is sea
+        let ids = [1, 1, 2, 3, 1, 1, 2, 9, 9, 9, 9, 9];
+        let water = HashSet::from([9]);
+        let history = owners(&[(1, "SWE"), (2, "SWE"), (3, "DAN")]);
+
+        let borders = extract_borders(4, &ids, &water, &history);
+        use BorderKind::*;
+        assert_eq!(
+            borders,
+            vec![
+                None,
+                Some(Province),
+                Some(Country),
+                Some(Coast),
+                Some(Coast),
+                Some(Coast),
+                Some(Coast),
+                None,
+                None,
+                None,
+                None,
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_pixels_have_no_border() {
+        let ids = [1, u32::MAX, 2];
+        let borders = extract_borders(3, &ids, &HashSet::new(), &HashMap::new());
+        assert_eq!(borders, vec![None; 3]);
+    }
+
+    #[test]
+    fn test_draw_borders() {
+        let mut map = RgbImage::from_pixel(4, 1, Rgb([200, 100, 40]));
+        let borders = [
+            None,
+            Some(BorderKind::Province),
+            Some(BorderKind::Country),
+            Some(BorderKind::Coast),
+        ];
+
+        draw_borders(&mut map, &borders);
+        assert_eq!(map.get_pixel(0, 0), &Rgb([200, 100, 40]));
+        assert_eq!(map.get_pixel(1, 0), &Rgb([150, 75, 30]));
+        assert_eq!(map.get_pixel(2, 0), &Rgb([20, 20, 20]));
+        assert_eq!(map.get_pixel(3, 0), &Rgb([32, 64, 96]));
+    }
+}
@@ -0,0 +1,248 @@
+//! Side-by-side map comparison of two province sources.
+//!
+//! Draws the same map mode for two sets of provinces (typically a real save and
+//! a sim snapshot at the same date) next to an overlay that dims provinces whose
+//! owners agree and marks the ones that disagree.
+
+use crate::args::MapMode;
+use crate::ops::{self, Decorations};
+use crate::text::TextRenderer;
+use crate::timelapse::{dim, draw_caption, scale_to_width};
+use crate::window::WorldData;
+use eu4data::history::ProvinceHistory;
+use eu4sim_core::state::Tag;
+use image::{Rgb, RgbImage};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Overlay colour for provinces whose owner differs.
+pub const DISAGREE_COLOR: Rgb<u8> = Rgb([255, 0, 255]);
+
+/// Gap between panels in the composite image.
+const PANEL_GAP: u32 = 8;
+
+/// One side of a comparison.
+pub struct CompareSide {
+    /// Caption drawn on the panel (e.g. the save's file name).
+    pub label: String,
+    pub history: HashMap<u32, ProvinceHistory>,
+}
+
+/// A land province owned by different countries on each side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerDiff {
+    pub province: u32,
+    pub left: Option<Tag>,
+    pub right: Option<Tag>,
+}
+
+/// Rendered comparison.
+pub struct Comparison {
+    /// Left map, right map and overlay side by side.
+    pub image: RgbImage,
+    /// The owner overlay at full map resolution.
+    pub overlay: RgbImage,
+    pub disagreements: Vec<OwnerDiff>,
+}
+
+/// Land provinces whose owner differs between the two histories, by id.
+///
+/// A province missing from one side counts as unowned there.
+pub fn owner_disagreements(
+    world_data: &WorldData,
+    left: &HashMap<u32, ProvinceHistory>,
+    right: &HashMap<u32, ProvinceHistory>,
+) -> Vec<OwnerDiff> {
+    let owner = |history: &HashMap<u32, ProvinceHistory>, id: &u32| {
+        history.get(id).and_then(|h| h.owner.clone())
+    };
+    let ids: BTreeSet<u32> = left.keys().chain(right.keys()).copied().collect();
+    ids.into_iter()
+        .filter(|id| !world_data.water_ids.contains(id))
+        .filter_map(|id| {
+            let (left, right) = (owner(left, &id), owner(right, &id));
+            (left != right).then_some(OwnerDiff {
+                province: id,
+                left,
+                right,
+            })
+        })
+        .collect()
+}
+
+/// Dim `base` and paint provinces listed in `disagreements` with [`DISAGREE_COLOR`].
+pub fn draw_owner_overlay(
+    world_data: &WorldData,
+    base: &RgbImage,
+    disagreements: &[OwnerDiff],
+) -> RgbImage {
+    let differing: HashSet<u32> = disagreements.iter().map(|d| d.province).collect();
+    let mut overlay = base.clone();
+    for (pixel, &id) in overlay.pixels_mut().zip(&world_data.province_id_buffer) {
+        if id == u32::MAX || world_data.water_ids.contains(&id) {
+            continue;
+        }
+        if differing.contains(&id) {
+            *pixel = DISAGREE_COLOR;
+        } else {
+            dim(pixel);
+        }
+    }
+    overlay
+}
+
+/// Render `mode` for both sides plus a political owner overlay.
+///
+/// Each panel gets `decorations` (owners for the overlay's come from the left
+/// side), is scaled to `panel_width` (0 keeps the map's size) and captioned.
+pub fn compare_maps(
+    world_data: &WorldData,
+    left: &CompareSide,
+    right: &CompareSide,
+    mode: MapMode,
+    panel_width: u32,
+    decorations: &Decorations,
+) -> Comparison {
+    let decorate = |map: &mut RgbImage, history: &HashMap<u32, ProvinceHistory>| {
+        ops::decorate_map(
+            map,
+            &world_data.province_id_buffer,
+            &world_data.water_ids,
+            history,
+            decorations,
+        );
+    };
+    let mut left_map = ops::render_map_mode(world_data, &left.history, mode);
+    let mut right_map = ops::render_map_mode(world_data, &right.history, mode);
+
+    let disagreements = owner_disagreements(world_data, &left.history, &right.history);
+    let overlay_base = if mode == MapMode::Political {
+        left_map.clone()
+    } else {
+        ops::render_map_mode(world_data, &left.history, MapMode::Political)
+    };
+    let mut overlay = draw_owner_overlay(world_data, &overlay_base, &disagreements);
+    decorate(&mut left_map, &left.history);
+    decorate(&mut right_map, &right.history);
+    decorate(&mut overlay, &left.history);
+    let overlay_label = format!("Owner differs: {} provinces", disagreements.len());
+
+    let renderer = TextRenderer::bundled();
+    let panels: Vec<RgbImage> = [
+        (left_map, left.label.as_str()),
+        (right_map, right.label.as_str()),
+        (overlay.clone(), overlay_label.as_str()),
+    ]
+    .into_iter()
+    .map(|(map, label)| {
+        let mut panel = scale_to_width(map, panel_width);
+        draw_caption(&mut panel, &renderer, label);
+        panel
+    })
+    .collect();
+
+    Comparison {
+        image: side_by_side(&panels),
+        overlay,
+        disagreements,
+    }
+}
+
+/// Lay images out left to right on a black background.
+pub fn side_by_side(panels: &[RgbImage]) -> RgbImage {
+    let gaps = PANEL_GAP * (panels.len() as u32).saturating_sub(1);
+    let width = panels.iter().map(RgbImage::width).sum::<u32>() + gaps;
+    let height = panels.iter().map(RgbImage::height).max().unwrap_or(0);
+
+    let mut image = RgbImage::new(width, height);
+    let mut x = 0;
+    for panel in panels {
+        image::imageops::replace(&mut image, panel, x as i64, 0);
+        x += panel.width() + PANEL_GAP;
+    }
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{owners, test_world};
+
+    #[test]
+    fn test_owner_disagreements() {
+        let world = test_world();
+        // Sweden took Danish province 3 in the sim; province 2 is unowned in
+        // the save; sea province 4 is ignored
+        let real = owners(&[(1, "SWE"), (3, "DAN"), (4, "DAN")]);
+        let sim = owners(&[(1, "SWE"), (2, "SWE"), (3, "SWE")]);
+
+        let diffs = owner_disagreements(&world, &real, &sim);
+        assert_eq!(
+            diffs,
+            vec![
+                OwnerDiff {
+                    province: 2,
+                    left: None,
+                    right: Some("SWE".to_string()),
+                },
+                OwnerDiff {
+                    province: 3,
+                    left: Some("DAN".to_string()),
+                    right: Some("SWE".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_draw_owner_overlay() {
+        let world = test_world();
+        let real = owners(&[(1, "SWE"), (2, "SWE"), (3, "DAN")]);
+        let sim = owners(&[(1, "SWE"), (2, "SWE"), (3, "SWE")]);
+        let base = ops::render_map_mode(&world, &real, MapMode::Political);
+        let diffs = owner_disagreements(&world, &real, &sim);
+
+        let overlay = draw_owner_overlay(&world, &base, &diffs);
+        // Agreeing Swedish land is dimmed, province 3 is marked, sea is untouched
+        assert_eq!(overlay.get_pixel(0, 0), &Rgb([66, 66, 151]));
+        assert_eq!(overlay.get_pixel(0, 1), &DISAGREE_COLOR);
+        assert_eq!(overlay.get_pixel(3, 1), &Rgb([64, 164, 223]));
+    }
+
+    #[test]
+    fn test_compare_maps_layout() {
+        let world = test_world();
+        let left = CompareSide {
+            label: "real.eu4".to_string(),
+            history: owners(&[(1, "SWE"), (2, "SWE"), (3, "DAN")]),
+        };
+        let right = CompareSide {
+            label: "sim.json".to_string(),
+            history: owners(&[(1, "SWE"), (2, "DAN"), (3, "DAN")]),
+        };
+
+        let comparison = compare_maps(
+            &world,
+            &left,
+            &right,
+            MapMode::Political,
+            0,
+            &Decorations::default(),
+        );
+        assert_eq!(comparison.disagreements.len(), 1);
+        assert_eq!(comparison.overlay.dimensions(), (4, 2));
+        assert_eq!(comparison.image.dimensions(), (3 * 4 + 2 * PANEL_GAP, 2));
+    }
+
+    #[test]
+    fn test_side_by_side_pads_shorter_panels() {
+        let tall = RgbImage::from_pixel(2, 3, Rgb([255, 0, 0]));
+        let short = RgbImage::from_pixel(3, 1, Rgb([0, 255, 0]));
+
+        let image = side_by_side(&[tall, short]);
+        assert_eq!(image.dimensions(), (2 + PANEL_GAP + 3, 3));
+        assert_eq!(image.get_pixel(1, 2), &Rgb([255, 0, 0]));
+        assert_eq!(image.get_pixel(2, 0), &Rgb([0, 0, 0]));
+        assert_eq!(image.get_pixel(2 + PANEL_GAP, 0), &Rgb([0, 255, 0]));
+        assert_eq!(image.get_pixel(2 + PANEL_GAP, 1), &Rgb([0, 0, 0]));
+    }
+}
@@ -0,0 +1,377 @@
+//! Country name labels for the headless renderers.
+//!
+//! Each realm is labelled once, over its largest contiguous block of provinces
+//! so colonies and exclaves don't drag the label out to sea. The label runs
+//! along the block's principal axis (the major eigenvector of its pixel
+//! covariance) and is sized to span most of the block's length without
+//! spilling far past its thickness.
+
+use crate::text::TextRenderer;
+use eu4data::history::ProvinceHistory;
+use eu4data::localisation::Localisation;
+use eu4sim_core::state::Tag;
+use image::{Rgb, RgbImage, RgbaImage};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// Labels smaller than this are skipped rather than drawn illegibly.
+const MIN_LABEL_PX: f32 = 8.0;
+
+/// Fraction of the realm's length the label spans.
+const LABEL_SPAN: f32 = 0.8;
+
+const LABEL_COLOR: Rgb<u8> = Rgb([16, 16, 16]);
+
+/// The main body of one realm, reduced to the ellipse its label is fitted to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RealmShape {
+    pub tag: Tag,
+    /// Centroid in pixels.
+    pub center: (f32, f32),
+    /// Principal axis in radians, clockwise from the x axis (y points down).
+    /// Always within ±90° so labels read left to right.
+    pub angle: f32,
+    /// Extent along the principal axis.
+    pub length: f32,
+    /// Extent across the principal axis.
+    pub thickness: f32,
+}
+
+/// Find each owner's largest contiguous block of land and fit its axis.
+///
+/// `province_ids` is a `width`-wide id buffer with `u32::MAX` for unknown pixels.
+/// Shapes are sorted by tag.
+pub fn realm_shapes(
+    width: u32,
+    province_ids: &[u32],
+    water_ids: &HashSet<u32>,
+    province_history: &HashMap<u32, ProvinceHistory>,
+) -> Vec<RealmShape> {
+    let width = width as usize;
+    if width == 0 {
+        return Vec::new();
+    }
+    let owner = |id: u32| {
+        if id == u32::MAX || water_ids.contains(&id) {
+            return None;
+        }
+        province_history.get(&id).and_then(|h| h.owner.as_ref())
+    };
+
+    // Join touching provinces with the same owner and count their pixels
+    let mut blocks = UnionFind::default();
+    let mut pixels: HashMap<u32, u64> = HashMap::new();
+    for (idx, &id) in province_ids.iter().enumerate() {
+        let Some(tag) = owner(id) else { continue };
+        *pixels.entry(id).or_default() += 1;
+        blocks.add(id);
+
+        let right = (idx % width + 1 < width).then_some(idx + 1);
+        let below = Some(idx + width).filter(|&i| i < province_ids.len());
+        for other in [right, below].into_iter().flatten() {
+            let other = province_ids[other];
+            if other != id && owner(other) == Some(tag) {
+                blocks.union(id, other);
+            }
+        }
+    }
+
+    // Largest block per owner (ties go to the lower root id)
+    let mut block_sizes: HashMap<u32, u64> = HashMap::new();
+    for (&id, &count) in &pixels {
+        *block_sizes.entry(blocks.find(id)).or_default() += count;
+    }
+    let mut main_block: HashMap<&Tag, (u64, u32)> = HashMap::new();
+    for (&root, &size) in &block_sizes {
+        let tag = owner(root).expect("blocks only hold owned provinces");
+        let best = main_block.entry(tag).or_insert((size, root));
+        if (size, std::cmp::Reverse(root)) > (best.0, std::cmp::Reverse(best.1)) {
+            *best = (size, root);
+        }
+    }
+    let in_main_block: HashMap<u32, &Tag> = pixels
+        .keys()
+        .filter_map(|&id| {
+            let tag = owner(id)?;
+            (main_block[tag].1 == blocks.find(id)).then_some((id, tag))
+        })
+        .collect();
+
+    let mut moments: BTreeMap<&Tag, Moments> = BTreeMap::new();
+    for (idx, id) in province_ids.iter().enumerate() {
+        if let Some(tag) = in_main_block.get(id) {
+            let (x, y) = ((idx % width) as f64 + 0.5, (idx / width) as f64 + 0.5);
+            moments.entry(tag).or_default().add(x, y);
+        }
+    }
+
+    moments
+        .into_iter()
+        .map(|(tag, m)| m.shape(tag.clone()))
+        .collect()
+}
+
+/// Draw each realm's name along its axis.
+///
+/// `name` maps a tag to its display name. Names are uppercased, as on the in-game map.
+pub fn draw_labels(
+    map: &mut RgbImage,
+    shapes: &[RealmShape],
+    renderer: &TextRenderer,
+    name: impl Fn(&str) -> String,
+) {
+    for shape in shapes {
+        let text = name(&shape.tag).to_uppercase();
+        let Some(size) = label_size(renderer, &text, shape) else {
+            continue;
+        };
+        let glyphs = renderer.render_line(&text, size);
+        blit_rotated(map, &glyphs, shape.center, shape.angle, LABEL_COLOR);
+    }
+}
+
+/// Country names from the game's localisation, or an empty table with a warning.
+pub fn load_country_names(base_path: &Path, language: &str) -> Localisation {
+    let mut loc = Localisation::new();
+    if let Err(e) = loc.load_from_dir(base_path.join("localisation"), language) {
+        log::warn!("Failed to load localisation, labelling with tags: {}", e);
+    }
+    loc
+}
+
+/// Font size that makes `text` span [`LABEL_SPAN`] of the realm, capped by its
+/// thickness; `None` when that would be too small to read.
+fn label_size(renderer: &TextRenderer, text: &str, shape: &RealmShape) -> Option<f32> {
+    // line_width measures at 24px and scales linearly
+    let width_at_24 = renderer.line_width(text);
+    if width_at_24 <= 0.0 {
+        return None;
+    }
+    let size = (24.0 * LABEL_SPAN * shape.length / width_at_24).min(shape.thickness * 0.9);
+    (size >= MIN_LABEL_PX).then_some(size)
+}
+
+/// Composite `glyphs` (coverage in alpha) onto `map` in `color`, rotated by
+/// `angle` about its centre and centred on `center`.
+fn blit_rotated(
+    map: &mut RgbImage,
+    glyphs: &RgbaImage,
+    center: (f32, f32),
+    angle: f32,
+    color: Rgb<u8>,
+) {
+    let (sin, cos) = angle.sin_cos();
+    let (half_w, half_h) = (glyphs.width() as f32 / 2.0, glyphs.height() as f32 / 2.0);
+    let reach_x = half_w * cos.abs() + half_h * sin.abs();
+    let reach_y = half_w * sin.abs() + half_h * cos.abs();
+
+    let x_range = (center.0 - reach_x).floor().max(0.0) as u32
+        ..((center.0 + reach_x).ceil().max(0.0) as u32).min(map.width());
+    let y_range = (center.1 - reach_y).floor().max(0.0) as u32
+        ..((center.1 + reach_y).ceil().max(0.0) as u32).min(map.height());
+
+    for y in y_range {
+        for x in x_range.clone() {
+            // Rotate the destination pixel back into glyph space
+            let (dx, dy) = (x as f32 + 0.5 - center.0, y as f32 + 0.5 - center.1);
+            let u = dx * cos + dy * sin + half_w;
+            let v = -dx * sin + dy * cos + half_h;
+            let alpha = sample_alpha(glyphs, u, v);
+            if alpha <= 0.0 {
+                continue;
+            }
+            let pixel = map.get_pixel_mut(x, y);
+            for c in 0..3 {
+                let blended = color[c] as f32 * alpha + pixel[c] as f32 * (1.0 - alpha);
+                pixel[c] = blended.round() as u8;
+            }
+        }
+    }
+}
+
+/// Bilinear alpha at pixel-space position (`u`, `v`), 0 outside the image.
+fn sample_alpha(image: &RgbaImage, u: f32, v: f32) -> f32 {
+    let (u, v) = (u - 0.5, v - 0.5);
+    let (x0, y0) = (u.floor(), v.floor());
+    let (fx, fy) = (u - x0, v - y0);
+    let alpha = |x: f32, y: f32| {
+        if x < 0.0 || y < 0.0 || x >= image.width() as f32 || y >= image.height() as f32 {
+            0.0
+        } else {
+            image.get_pixel(x as u32, y as u32)[3] as f32 / 255.0
+        }
+    };
+    let top = alpha(x0, y0) * (1.0 - fx) + alpha(x0 + 1.0, y0) * fx;
+    let bottom = alpha(x0, y0 + 1.0) * (1.0 - fx) + alpha(x0 + 1.0, y0 + 1.0) * fx;
+    top * (1.0 - fy) + bottom * fy
+}
+
+/// Running sums for a pixel centroid and covariance.
+#[derive(Default)]
+struct Moments {
+    n: f64,
+    sx: f64,
+    sy: f64,
+    sxx: f64,
+    syy: f64,
+    sxy: f64,
+}
+
+impl Moments {
+    fn add(&mut self, x: f64, y: f64) {
+        self.n += 1.0;
+        self.sx += x;
+        self.sy += y;
+        self.sxx += x * x;
+        self.syy += y * y;
+        self.sxy += x * y;
+    }
+
+    fn shape(&self, tag: Tag) -> RealmShape {
+        let (mx, my) = (self.sx / self.n, self.sy / self.n);
+        let var_x = self.sxx / self.n - mx * mx;
+        let var_y = self.syy / self.n - my * my;
+        let cov = self.sxy / self.n - mx * my;
+
+        // Eigenvalues of [[var_x, cov], [cov, var_y]]
+        let mean = (var_x + var_y) / 2.0;
+        let spread = (((var_x - var_y) / 2.0).powi(2) + cov * cov).sqrt();
+        let (major, minor) = (mean + spread, (mean - spread).max(0.0));
+        let angle = 0.5 * (2.0 * cov).atan2(var_x - var_y);
+
+        // A run of n pixels has variance (n² - 1) / 12
+        let extent = |variance: f64| (12.0 * variance + 1.0).sqrt() as f32;
+        RealmShape {
+            tag,
+            center: (mx as f32, my as f32),
+            angle: angle as f32,
+            length: extent(major),
+            thickness: extent(minor),
+        }
+    }
+}
+
+/// Province id union-find for grouping contiguous provinces.
+#[derive(Default)]
+struct UnionFind {
+    parent: HashMap<u32, u32>,
+}
+
+impl UnionFind {
+    fn add(&mut self, id: u32) {
+        self.parent.entry(id).or_insert(id);
+    }
+
+    fn find(&mut self, id: u32) -> u32 {
+        let parent = *self.parent.entry(id).or_insert(id);
+        if parent == id {
+            return id;
+        }
+        let root = self.find(parent);
+        self.parent.insert(id, root);
+        root
+    }
+
+    fn union(&mut self, a: u32, b: u32) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            // Lower id as root keeps results independent of scan order
+            self.parent.insert(a.max(b), a.min(b));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::owners;
+    use std::f32::consts::FRAC_PI_4;
+
+    #[test]
+    fn test_horizontal_realm() {
+        // A 20x4 Swedish strip made of two provinces on a 20x6 sea
+        let ids: Vec<u32> = (0..20 * 6)
+            .map(|i| match (i % 20, i / 20) {
+                (_, 0) | (_, 5) => 9,
+                (x, _) if x < 10 => 1,
+                _ => 2,
+            })
+            .collect();
+        let history = owners(&[(1, "SWE"), (2, "SWE")]);
+
+        let shapes = realm_shapes(20, &ids, &HashSet::from([9]), &history);
+        assert_eq!(shapes.len(), 1);
+        let sweden = &shapes[0];
+        assert_eq!(sweden.tag, "SWE");
+        assert_eq!(sweden.center, (10.0, 3.0));
+        assert!(sweden.angle.abs() < 1e-4);
+        assert!((sweden.length - 20.0).abs() < 0.1);
+        assert!((sweden.thickness - 4.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_diagonal_realm() {
+        // Three-pixel-wide band down the main diagonal; y points down, so the
+        // axis is +45°
+        let ids: Vec<u32> = (0..30 * 30_i32)
+            .map(|i| if (i % 30 - i / 30).abs() <= 1 { 1 } else { 9 })
+            .collect();
+        let history = owners(&[(1, "DAN")]);
+
+        let shapes = realm_shapes(30, &ids, &HashSet::from([9]), &history);
+        assert!((shapes[0].angle - FRAC_PI_4).abs() < 1e-3);
+        assert!(shapes[0].length > 5.0 * shapes[0].thickness);
+    }
+
+    #[test]
+    fn test_labels_follow_largest_block() {
+        // Sweden's 6-pixel homeland (1, 2) and a 2-pixel exclave (3) far east
+        // 1 1 1 9 9 9 3
+        // 2 2 2 9 9 9 3
+        let ids = [1, 1, 1, 9, 9, 9, 3, 2, 2, 2, 9, 9, 9, 3];
+        let history = owners(&[(1, "SWE"), (2, "SWE"), (3, "SWE")]);
+
+        let shapes = realm_shapes(7, &ids, &HashSet::from([9]), &history);
+        assert_eq!(shapes.len(), 1);
+        assert_eq!(shapes[0].center, (1.5, 1.0));
+    }
+
+    #[test]
+    fn test_draw_labels() {
+        let mut map = RgbImage::from_pixel(200, 100, Rgb([240, 240, 240]));
+        let shape = RealmShape {
+            tag: "SWE".to_string(),
+            center: (100.0, 50.0),
+            angle: 0.0,
+            length: 180.0,
+            thickness: 80.0,
+        };
+        let renderer = TextRenderer::bundled();
+
+        draw_labels(&mut map, &[shape], &renderer, |_| "Sweden".to_string());
+
+        let inked: Vec<(u32, u32)> = map
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p[0] < 128)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!inked.is_empty());
+        // The label is centred and spans roughly LABEL_SPAN of the length
+        let min_x = inked.iter().map(|p| p.0).min().unwrap();
+        let max_x = inked.iter().map(|p| p.0).max().unwrap();
+        assert!((min_x as i32 - 28).abs() <= 8, "min_x {}", min_x);
+        assert!((max_x as i32 - 172).abs() <= 8, "max_x {}", max_x);
+    }
+
+    #[test]
+    fn test_tiny_realms_are_not_labelled() {
+        let shape = RealmShape {
+            tag: "RAG".to_string(),
+            center: (5.0, 5.0),
+            angle: 0.0,
+            length: 6.0,
+            thickness: 3.0,
+        };
+        assert_eq!(label_size(&TextRenderer::bundled(), "RAGUSA", &shape), None);
+    }
+}
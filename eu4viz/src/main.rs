@@ -2,21 +2,27 @@ use clap::Parser;
 use std::path::PathBuf;
 
 mod args;
+mod borders;
 mod camera;
+mod compare;
 mod datagen;
 mod ops;
 mod window;
 
+mod labels;
 pub mod logger;
 mod renderer;
+mod sources;
 mod state;
 #[cfg(test)]
 mod testing;
 mod text;
+mod timelapse;
 mod timeline;
 mod ui;
 
 use args::{Cli, Commands, MapMode};
+use sources::MapSource;
 
 fn run(mut args: Cli) -> Result<(), String> {
     // Resolve EU4 Path
@@ -48,7 +54,18 @@ fn run(mut args: Cli) -> Result<(), String> {
                 println!("{}", eu4data::manifest::GAME_MANIFEST.dump());
                 return Ok(());
             }
-            Commands::DrawMap { output, mode } => {
+            Commands::DrawMap {
+                output,
+                mode,
+                source,
+                borders,
+                labels,
+            } => {
+                let source = source.as_deref().map(MapSource::from_path);
+                let decorations = ops::Decorations {
+                    borders: *borders,
+                    labels: labels.then(|| labels::load_country_names(&eu4_path, &args.language)),
+                };
                 let base = eu4_path;
                 match mode {
                     MapMode::All => {
@@ -57,6 +74,8 @@ fn run(mut args: Cli) -> Result<(), String> {
                             &base,
                             &PathBuf::from("map_political.png"),
                             MapMode::Political,
+                            source.as_ref(),
+                            &decorations,
                         )?;
 
                         println!("\n=== Rendering Trade Goods Map ===");
@@ -64,10 +83,12 @@ fn run(mut args: Cli) -> Result<(), String> {
                             &base,
                             &PathBuf::from("map_tradegoods.png"),
                             MapMode::TradeGoods,
+                            source.as_ref(),
+                            &decorations,
                         )?;
                     }
                     _ => {
-                        ops::draw_map(&base, output, *mode)?;
+                        ops::draw_map(&base, output, *mode, source.as_ref(), &decorations)?;
                     }
                 }
                 return Ok(());
@@ -79,12 +100,96 @@ fn run(mut args: Cli) -> Result<(), String> {
                 window::run(level, &base, args.event_log);
                 return Ok(());
             }
-            Commands::Snapshot { output, mode } => {
+            Commands::Snapshot {
+                output,
+                mode,
+                source,
+            } => {
                 let base = eu4_path;
                 let path = std::path::Path::new(output);
+                let source = source.as_deref().map(MapSource::from_path);
                 let level =
                     std::str::FromStr::from_str(&args.log_level).unwrap_or(log::LevelFilter::Info);
-                pollster::block_on(window::snapshot(&base, path, *mode, level))?;
+                pollster::block_on(window::snapshot(&base, path, *mode, source.as_ref(), level))?;
+                return Ok(());
+            }
+            Commands::Compare {
+                left,
+                right,
+                mode,
+                output,
+                overlay,
+                width,
+                borders,
+                labels,
+            } => {
+                if *mode == MapMode::All {
+                    return Err("compare renders a single map mode".to_string());
+                }
+                let world_data = ops::load_world_data(&eu4_path)?;
+                let load_side = |path: &std::path::Path| -> Result<_, String> {
+                    let source = MapSource::from_path(path);
+                    Ok(compare::CompareSide {
+                        label: source.to_string(),
+                        history: source.load_history(&eu4_path)?,
+                    })
+                };
+                let (left, right) = (load_side(left)?, load_side(right)?);
+
+                let decorations = ops::Decorations {
+                    borders: *borders,
+                    labels: labels.then(|| labels::load_country_names(&eu4_path, &args.language)),
+                };
+
+                let comparison =
+                    compare::compare_maps(&world_data, &left, &right, *mode, *width, &decorations);
+                comparison.image.save(output).map_err(|e| e.to_string())?;
+                println!("Saved {:?}", output);
+                if let Some(overlay) = overlay {
+                    comparison
+                        .overlay
+                        .save(overlay)
+                        .map_err(|e| e.to_string())?;
+                    println!("Saved {:?}", overlay);
+                }
+
+                println!(
+                    "{} provinces have different owners:",
+                    comparison.disagreements.len()
+                );
+                for diff in &comparison.disagreements {
+                    println!(
+                        "  {:>5}: {} -> {}",
+                        diff.province,
+                        diff.left.as_deref().unwrap_or("---"),
+                        diff.right.as_deref().unwrap_or("---")
+                    );
+                }
+                return Ok(());
+            }
+            Commands::Timelapse {
+                every,
+                output,
+                highlight,
+                width,
+                frame_ms,
+                no_caption,
+            } => {
+                let Some(event_log) = &args.event_log else {
+                    return Err("timelapse requires --event-log <PATH>".to_string());
+                };
+                let mut timeline = timeline::Timeline::from_file(event_log)?;
+                let world_data = ops::load_world_data(&eu4_path)?;
+                let opts = timelapse::TimelapseOptions {
+                    every: *every,
+                    output: output.clone(),
+                    highlight: highlight.iter().map(|t| t.trim().to_uppercase()).collect(),
+                    width: *width,
+                    frame_ms: *frame_ms,
+                    caption: !no_caption,
+                };
+                let frames = timelapse::render_timelapse(&world_data, &mut timeline, &opts)?;
+                println!("Wrote {} frames to {}", frames, output.display());
                 return Ok(());
             }
             Commands::Lookup { key } => {
@@ -1,13 +1,17 @@
 use crate::args::MapMode;
-use crate::window;
+use crate::sources::MapSource;
+use crate::text::TextRenderer;
+use crate::{borders, labels, window};
 use eu4data::{
     Tradegoods,
     countries::Country,
     cultures::Culture,
     history::ProvinceHistory,
+    localisation::Localisation,
     map::{DefaultMap, load_definitions},
     religions::Religion,
 };
+use eu4sim_core::state::{ProvinceId, Tag};
 use eu4txt::{DefaultEU4Txt, EU4Txt, from_node};
 use image::{Rgb, RgbImage};
 use std::collections::{HashMap, HashSet};
@@ -32,7 +36,29 @@ pub fn dump_tradegoods(base_path: &std::path::Path) -> Result<(), String> {
     Ok(())
 }
 
-pub fn draw_map(base_path: &Path, output_path: &Path, mode: MapMode) -> Result<(), String> {
+/// Borders and country labels drawn over a rendered map.
+#[derive(Default)]
+pub struct Decorations {
+    pub borders: bool,
+    /// Country names to label realms with; `None` leaves labels off.
+    pub labels: Option<Localisation>,
+}
+
+impl Decorations {
+    pub fn is_empty(&self) -> bool {
+        !self.borders && self.labels.is_none()
+    }
+}
+
+/// Render `mode` to `output_path`, colouring provinces from `source` when given
+/// and from the 1444 province history otherwise.
+pub fn draw_map(
+    base_path: &Path,
+    output_path: &Path,
+    mode: MapMode,
+    source: Option<&MapSource>,
+    decorations: &Decorations,
+) -> Result<(), String> {
     // 1. Load Definitions (ID -> Color, Color -> ID)
     let def_path = base_path.join("map/definition.csv");
     println!("Loading definitions from {:?}", def_path);
@@ -88,14 +114,20 @@ pub fn draw_map(base_path: &Path, output_path: &Path, mode: MapMode) -> Result<(
     }
 
     // 3. Load Province History (ID -> Data)
-    println!("Loading history...");
-    let (province_history, stats_history) =
-        eu4data::history::load_province_history(base_path).map_err(|e| e.to_string())?;
-
-    println!(
-        "History Stats: Success={}, Failure={}",
-        stats_history.0, stats_history.1
-    );
+    let province_history = if let Some(source) = source {
+        println!("Loading provinces from {}...", source);
+        source.load_history(base_path)?
+    } else {
+        println!("Loading history...");
+        let (province_history, stats_history) =
+            eu4data::history::load_province_history(base_path).map_err(|e| e.to_string())?;
+
+        println!(
+            "History Stats: Success={}, Failure={}",
+            stats_history.0, stats_history.1
+        );
+        province_history
+    };
 
     // 4. Render
     let map_path = base_path.join("map/provinces.bmp");
@@ -159,11 +191,54 @@ pub fn draw_map(base_path: &Path, output_path: &Path, mode: MapMode) -> Result<(
         }
     }
 
+    if !decorations.is_empty() {
+        println!("Drawing borders and labels...");
+        let province_ids: Vec<u32> = img
+            .pixels()
+            .map(|p| {
+                color_to_id
+                    .get(&(p[0], p[1], p[2]))
+                    .copied()
+                    .unwrap_or(u32::MAX)
+            })
+            .collect();
+        decorate_map(
+            &mut out_img,
+            &province_ids,
+            &water_ids,
+            &province_history,
+            decorations,
+        );
+    }
+
     out_img.save(output_path).map_err(|e| e.to_string())?;
     println!("Saved {:?}", output_path);
     Ok(())
 }
 
+/// Draw `decorations` over `map`, taking owners from `province_history`.
+///
+/// `province_ids` holds the province id of each map pixel (`u32::MAX` if unknown).
+pub fn decorate_map(
+    map: &mut RgbImage,
+    province_ids: &[u32],
+    water_ids: &HashSet<u32>,
+    province_history: &HashMap<u32, ProvinceHistory>,
+    decorations: &Decorations,
+) {
+    if decorations.borders {
+        let borders =
+            borders::extract_borders(map.width(), province_ids, water_ids, province_history);
+        borders::draw_borders(map, &borders);
+    }
+    if let Some(names) = &decorations.labels {
+        let shapes = labels::realm_shapes(map.width(), province_ids, water_ids, province_history);
+        labels::draw_labels(map, &shapes, &TextRenderer::bundled(), |tag| {
+            names.get(tag).cloned().unwrap_or_else(|| tag.to_string())
+        });
+    }
+}
+
 pub struct ScanStats {
     pub success: usize,
     pub failure: usize,
@@ -412,6 +487,53 @@ pub fn load_world_data(base_path: &Path) -> Result<window::WorldData, String> {
     })
 }
 
+/// Rasterize one map mode over the world's province map, colouring provinces
+/// from `province_history` instead of the history loaded with the world.
+pub fn render_map_mode(
+    world_data: &window::WorldData,
+    province_history: &HashMap<u32, ProvinceHistory>,
+    mode: MapMode,
+) -> RgbImage {
+    let map = &world_data.province_map;
+    let (width, height) = map.dimensions();
+    let ids = &world_data.color_to_id;
+    let water = &world_data.water_ids;
+    match mode {
+        MapMode::Political => draw_map_political(
+            width,
+            height,
+            map,
+            ids,
+            province_history,
+            &world_data.countries,
+            water,
+        ),
+        MapMode::TradeGoods => {
+            draw_map_tradegoods(width, height, map, ids, province_history, water)
+        }
+        MapMode::Religion => draw_map_religion(
+            width,
+            height,
+            map,
+            ids,
+            province_history,
+            &world_data.religions,
+            water,
+        ),
+        MapMode::Culture => draw_map_culture(
+            width,
+            height,
+            map,
+            ids,
+            province_history,
+            &world_data.cultures,
+            water,
+        ),
+        MapMode::Province => map.clone(),
+        MapMode::All => unreachable!("MapMode::All should be handled by caller"),
+    }
+}
+
 fn draw_map_political(
     width: u32,
     height: u32,
@@ -470,18 +592,7 @@ pub fn regenerate_political_map(
                     if world_data.water_ids.contains(&id) {
                         [64, 164, 223] // Water blue
                     } else {
-                        // Check timeline for owner override, fallback to province_history
-                        let owner_tag = current_owners
-                            .get(&id)
-                            .and_then(|opt| opt.as_ref())
-                            .or_else(|| {
-                                world_data
-                                    .province_history
-                                    .get(&id)
-                                    .and_then(|h| h.owner.as_ref())
-                            });
-
-                        if let Some(tag) = owner_tag {
+                        if let Some(tag) = province_owner(world_data, current_owners, id) {
                             if let Some(country) = world_data.countries.get(tag)
                                 && country.color.len() >= 3
                             {
@@ -511,6 +622,25 @@ pub fn regenerate_political_map(
         .expect("Buffer size mismatch in regenerate_political_map")
 }
 
+/// Owner of a province at the timeline's current tick.
+///
+/// Checks the timeline for an owner override, falling back to province history.
+pub fn province_owner<'a>(
+    world_data: &'a window::WorldData,
+    current_owners: &'a HashMap<ProvinceId, Option<Tag>>,
+    id: u32,
+) -> Option<&'a Tag> {
+    current_owners
+        .get(&id)
+        .and_then(|opt| opt.as_ref())
+        .or_else(|| {
+            world_data
+                .province_history
+                .get(&id)
+                .and_then(|h| h.owner.as_ref())
+        })
+}
+
 fn draw_map_tradegoods(
     width: u32,
     height: u32,
@@ -729,7 +859,13 @@ mod tests {
         create_mock_eu4(dir.path());
 
         let output = dir.path().join("out_tg.png");
-        let res = draw_map(dir.path(), &output, MapMode::TradeGoods);
+        let res = draw_map(
+            dir.path(),
+            &output,
+            MapMode::TradeGoods,
+            None,
+            &Decorations::default(),
+        );
         assert!(res.is_ok());
         assert!(output.exists());
 
@@ -752,7 +888,13 @@ mod tests {
         create_mock_eu4(dir.path());
 
         let output = dir.path().join("out_pol.png");
-        let res = draw_map(dir.path(), &output, MapMode::Political);
+        let res = draw_map(
+            dir.path(),
+            &output,
+            MapMode::Political,
+            None,
+            &Decorations::default(),
+        );
 
         assert!(res.is_ok(), "draw_map political failed: {:?}", res.err());
         assert!(output.exists());
@@ -762,12 +904,38 @@ mod tests {
         assert_eq!(img.get_pixel(0, 0), &Rgb([0, 0, 255]));
     }
 
+    #[test]
+    fn test_draw_map_political_with_borders() {
+        let dir = tempdir().unwrap();
+        create_mock_eu4(dir.path());
+
+        let output = dir.path().join("out_pol_borders.png");
+        let decorations = Decorations {
+            borders: true,
+            labels: None,
+        };
+        let res = draw_map(dir.path(), &output, MapMode::Political, None, &decorations);
+        assert!(res.is_ok(), "draw_map failed: {:?}", res.err());
+
+        let img = image::open(output).unwrap().to_rgb8();
+        // Stockholm borders the sea below it: coastline
+        assert_eq!(img.get_pixel(0, 0), &Rgb([32, 64, 96]));
+        // Uppsala only touches the unknown pixel: no border
+        assert_eq!(img.get_pixel(1, 0), &Rgb([0, 0, 255]));
+    }
+
     #[test]
     fn test_draw_map_religion() {
         let dir = tempdir().unwrap();
         create_mock_eu4(dir.path());
         let output = dir.path().join("out_rel.png");
-        let res = draw_map(dir.path(), &output, MapMode::Religion);
+        let res = draw_map(
+            dir.path(),
+            &output,
+            MapMode::Religion,
+            None,
+            &Decorations::default(),
+        );
         assert!(res.is_ok());
         let img = image::open(output).unwrap().to_rgb8();
         // Catholic = Yellow (255, 255, 0)
@@ -779,7 +947,13 @@ mod tests {
         let dir = tempdir().unwrap();
         create_mock_eu4(dir.path());
         let output = dir.path().join("out_cul.png");
-        let res = draw_map(dir.path(), &output, MapMode::Culture);
+        let res = draw_map(
+            dir.path(),
+            &output,
+            MapMode::Culture,
+            None,
+            &Decorations::default(),
+        );
         assert!(res.is_ok());
         let img = image::open(output).unwrap().to_rgb8();
         // Swedish = Hashed color
@@ -795,7 +969,13 @@ mod tests {
         let output = dir.path().join("out.png");
 
         let result = std::panic::catch_unwind(|| {
-            let _ = draw_map(dir.path(), &output, MapMode::All);
+            let _ = draw_map(
+                dir.path(),
+                &output,
+                MapMode::All,
+                None,
+                &Decorations::default(),
+            );
         });
         assert!(result.is_err());
     }
@@ -0,0 +1,223 @@
+//! Province data for the map rasterizers beyond the 1444 history.
+//!
+//! A [`MapSource`] reads an EU4 save (through `eu4sim_verify::parse`) or a
+//! JSON-serialized sim `WorldState` into the `ProvinceHistory` map that the
+//! `ops` rasterizers colour from, so a real game and a sim run are drawn with
+//! the same palettes.
+
+use eu4data::history::ProvinceHistory;
+use eu4sim_core::WorldState;
+use eu4sim_verify::ExtractedState;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Where to read province owners, religions, cultures and trade goods from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MapSource {
+    /// An EU4 save (text, compressed or ironman).
+    Save(PathBuf),
+    /// A sim `WorldState` serialized with serde_json.
+    WorldState(PathBuf),
+}
+
+impl MapSource {
+    /// `.json` files are sim snapshots; anything else is treated as a save.
+    pub fn from_path(path: &Path) -> Self {
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if is_json {
+            Self::WorldState(path.to_path_buf())
+        } else {
+            Self::Save(path.to_path_buf())
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Save(path) | Self::WorldState(path) => path,
+        }
+    }
+
+    /// Load the source's provinces in the shape the rasterizers expect.
+    ///
+    /// `base_path` is the game directory, needed to map a sim's trade good ids
+    /// back to names.
+    pub fn load_history(&self, base_path: &Path) -> Result<HashMap<u32, ProvinceHistory>, String> {
+        match self {
+            Self::Save(path) => {
+                let state = eu4sim_verify::parse::load_save(path)
+                    .map_err(|e| format!("Failed to load save {}: {:#}", path.display(), e))?;
+                log::info!(
+                    "Loaded {} provinces from save dated {}",
+                    state.provinces.len(),
+                    state.meta.date
+                );
+                Ok(history_from_save(&state))
+            }
+            Self::WorldState(path) => {
+                let file = File::open(path)
+                    .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+                let state: WorldState = serde_json::from_reader(BufReader::new(file))
+                    .map_err(|e| format!("Failed to parse WorldState {}: {}", path.display(), e))?;
+                log::info!("Loaded sim state dated {}", state.date);
+                Ok(history_from_world_state(
+                    &state,
+                    &tradegood_names(base_path),
+                ))
+            }
+        }
+    }
+}
+
+impl fmt::Display for MapSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path();
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        f.write_str(&name)
+    }
+}
+
+/// Province data from a parsed save.
+pub fn history_from_save(state: &ExtractedState) -> HashMap<u32, ProvinceHistory> {
+    state
+        .provinces
+        .iter()
+        .map(|(&id, province)| {
+            let history = ProvinceHistory {
+                owner: province.owner.clone(),
+                trade_goods: province.trade_good.clone(),
+                religion: province.religion.clone(),
+                culture: province.culture.clone(),
+                ..Default::default()
+            };
+            (id, history)
+        })
+        .collect()
+}
+
+/// Province data from a sim snapshot.
+///
+/// `tradegoods` holds trade good names indexed by `TradegoodId`.
+pub fn history_from_world_state(
+    state: &WorldState,
+    tradegoods: &[String],
+) -> HashMap<u32, ProvinceHistory> {
+    state
+        .provinces
+        .iter()
+        .map(|(&id, province)| {
+            let history = ProvinceHistory {
+                owner: province.owner.clone(),
+                trade_goods: province
+                    .trade_goods_id
+                    .and_then(|good| tradegoods.get(good.0 as usize).cloned()),
+                religion: province.religion.clone(),
+                culture: province.culture.clone(),
+                ..Default::default()
+            };
+            (id, history)
+        })
+        .collect()
+}
+
+/// Trade good names in `TradegoodId` order.
+///
+/// The sim loader assigns ids by sorting the names from `common/prices`.
+pub fn tradegood_names(base_path: &Path) -> Vec<String> {
+    let mut names: Vec<String> = eu4data::tradegoods::load_tradegoods(base_path)
+        .map(|goods| goods.into_keys().collect())
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eu4sim_core::modifiers::TradegoodId;
+    use eu4sim_core::testing::WorldStateBuilder;
+    use eu4sim_verify::{ExtractedProvince, SaveMeta};
+
+    #[test]
+    fn test_from_path_detects_world_state() {
+        assert_eq!(
+            MapSource::from_path(Path::new("runs/sim_1600.json")),
+            MapSource::WorldState(PathBuf::from("runs/sim_1600.json"))
+        );
+        assert_eq!(
+            MapSource::from_path(Path::new("saves/real_1600.eu4")),
+            MapSource::Save(PathBuf::from("saves/real_1600.eu4"))
+        );
+        assert_eq!(
+            MapSource::from_path(Path::new("saves/real_1600.eu4")).to_string(),
+            "real_1600.eu4"
+        );
+    }
+
+    #[test]
+    fn test_history_from_save() {
+        let stockholm = ExtractedProvince {
+            id: 1,
+            owner: Some("SWE".to_string()),
+            trade_good: Some("copper".to_string()),
+            religion: Some("protestant".to_string()),
+            culture: Some("swedish".to_string()),
+            ..Default::default()
+        };
+        let state = ExtractedState {
+            meta: SaveMeta {
+                date: "1600.1.1".to_string(),
+                player: None,
+                ironman: false,
+                save_version: None,
+            },
+            countries: HashMap::new(),
+            provinces: HashMap::from([(1, stockholm)]),
+            subjects: HashMap::new(),
+            celestial_empire: None,
+            trade_nodes: HashMap::new(),
+            armies: Vec::new(),
+            fleets: Vec::new(),
+            wars: Vec::new(),
+            diplomacy: Default::default(),
+            military_and_diplomacy_extracted: false,
+        };
+
+        let history = history_from_save(&state);
+        let stockholm = &history[&1];
+        assert_eq!(stockholm.owner.as_deref(), Some("SWE"));
+        assert_eq!(stockholm.trade_goods.as_deref(), Some("copper"));
+        assert_eq!(stockholm.religion.as_deref(), Some("protestant"));
+        assert_eq!(stockholm.culture.as_deref(), Some("swedish"));
+    }
+
+    #[test]
+    fn test_history_from_world_state_round_trip() {
+        let mut state = WorldStateBuilder::new()
+            .with_country("SWE")
+            .with_province(1, Some("SWE"))
+            .with_province(2, None)
+            .build();
+        let stockholm = state.provinces.get_mut(&1).unwrap();
+        stockholm.trade_goods_id = Some(TradegoodId(1));
+        stockholm.religion = Some("catholic".to_string());
+
+        // Snapshots reach eu4viz as JSON
+        let json = serde_json::to_string(&state).unwrap();
+        let state: WorldState = serde_json::from_str(&json).unwrap();
+
+        let names = vec!["cloth".to_string(), "copper".to_string()];
+        let history = history_from_world_state(&state, &names);
+        assert_eq!(history[&1].owner.as_deref(), Some("SWE"));
+        assert_eq!(history[&1].trade_goods.as_deref(), Some("copper"));
+        assert_eq!(history[&1].religion.as_deref(), Some("catholic"));
+        assert_eq!(history[&2].owner, None);
+    }
+}
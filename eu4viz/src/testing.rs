@@ -1,6 +1,59 @@
-use image::RgbaImage;
+use crate::window::WorldData;
+use eu4data::countries::Country;
+use eu4data::history::ProvinceHistory;
+use image::{Rgb, RgbImage, RgbaImage};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+/// 4x2 world: provinces 1-3 on land, 4 is sea.
+pub fn test_world() -> WorldData {
+    let ids = [1, 1, 2, 2, 3, 3, 4, 4];
+    let province_map = RgbImage::from_fn(4, 2, |x, y| {
+        let id = ids[(y * 4 + x) as usize] as u8;
+        Rgb([id, 0, 0])
+    });
+    let color_to_id = (1..=4).map(|id| ((id as u8, 0, 0), id)).collect();
+    let country = |color: [u8; 3]| Country {
+        color: color.to_vec(),
+    };
+    let countries = HashMap::from([
+        ("SWE".to_string(), country([0, 0, 255])),
+        ("DAN".to_string(), country([255, 0, 0])),
+    ]);
+    let province_history = owners(&[(1, "SWE"), (2, "SWE"), (3, "DAN")]);
+
+    WorldData {
+        political_map: province_map.clone(),
+        tradegoods_map: province_map.clone(),
+        religion_map: province_map.clone(),
+        culture_map: province_map.clone(),
+        province_id_buffer: ids.to_vec(),
+        province_map,
+        color_to_id,
+        province_history,
+        countries,
+        religions: HashMap::new(),
+        cultures: HashMap::new(),
+        tradegoods: HashMap::new(),
+        water_ids: HashSet::from([4]),
+        adjacency_graph: Default::default(),
+    }
+}
+
+/// Province history where each `(id, tag)` province is owned by `tag`.
+pub fn owners(entries: &[(u32, &str)]) -> HashMap<u32, ProvinceHistory> {
+    entries
+        .iter()
+        .map(|&(id, owner)| {
+            let history = ProvinceHistory {
+                owner: Some(owner.to_string()),
+                ..Default::default()
+            };
+            (id, history)
+        })
+        .collect()
+}
+
 pub fn assert_snapshot(actual: &RgbaImage, name: &str) {
     let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
     let golden_dir = PathBuf::from(manifest_dir).join("tests/goldens");
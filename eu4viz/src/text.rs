@@ -1,6 +1,9 @@
 use ab_glyph::{Font, FontArc, PxScale, ScaleFont, point};
 use image::{Rgba, RgbaImage};
 
+/// Roboto, bundled so headless rendering doesn't depend on the working directory.
+pub const BUNDLED_FONT: &[u8] = include_bytes!("../../assets/Roboto-Regular.ttf");
+
 pub struct TextRenderer {
     font: FontArc,
 }
@@ -12,6 +15,63 @@ impl TextRenderer {
         }
     }
 
+    /// Renderer using the bundled Roboto font.
+    pub fn bundled() -> Self {
+        Self::new(BUNDLED_FONT.to_vec())
+    }
+
+    /// Width in pixels of a single line as drawn by [`Self::render`].
+    pub fn line_width(&self, line: &str) -> f32 {
+        let scaled_font = self.font.as_scaled(PxScale { x: 24.0, y: 24.0 });
+        line.chars()
+            .filter(|c| !c.is_control())
+            .map(|c| scaled_font.h_advance(self.font.glyph_id(c)))
+            .sum()
+    }
+
+    /// Renders one line at `size` px into an image cropped to the line's extent.
+    ///
+    /// Glyphs are white; coverage is in the alpha channel.
+    pub fn render_line(&self, line: &str, size: f32) -> RgbaImage {
+        let scale = PxScale { x: size, y: size };
+        let scaled_font = self.font.as_scaled(scale);
+        let line: String = line.chars().filter(|c| !c.is_control()).collect();
+        let width: f32 = line
+            .chars()
+            .map(|c| scaled_font.h_advance(self.font.glyph_id(c)))
+            .sum();
+        let height = scaled_font.ascent() - scaled_font.descent();
+        let mut image = RgbaImage::new(width.ceil() as u32 + 2, height.ceil() as u32 + 2);
+
+        let mut x_pos = 1.0;
+        for c in line.chars() {
+            let glyph_id = self.font.glyph_id(c);
+            let glyph =
+                glyph_id.with_scale_and_position(scale, point(x_pos, 1.0 + scaled_font.ascent()));
+            if let Some(outlined) = self.font.outline_glyph(glyph) {
+                let bounds = outlined.px_bounds();
+                outlined.draw(|x, y, coverage| {
+                    let px = bounds.min.x as i64 + x as i64;
+                    let py = bounds.min.y as i64 + y as i64;
+                    if px >= 0
+                        && py >= 0
+                        && (px as u32) < image.width()
+                        && (py as u32) < image.height()
+                    {
+                        let pixel = image.get_pixel_mut(px as u32, py as u32);
+                        let alpha = (coverage * 255.0) as u8;
+                        if alpha > 0 {
+                            // Overlapping glyph edges keep the stronger coverage
+                            *pixel = Rgba([255, 255, 255, pixel[3].max(alpha)]);
+                        }
+                    }
+                });
+            }
+            x_pos += scaled_font.h_advance(glyph_id);
+        }
+        image
+    }
+
     /// Renders multiline text to an image.
     pub fn render(&self, text: &str, width: u32, height: u32) -> RgbaImage {
         let mut image = RgbaImage::new(width, height);
@@ -90,4 +150,16 @@ mod tests {
 
         crate::testing::assert_snapshot(&img, "text_render_stockholm");
     }
+
+    #[test]
+    fn test_render_line_is_cropped() {
+        let renderer = TextRenderer::bundled();
+        let small = renderer.render_line("SWEDEN", 12.0);
+        let large = renderer.render_line("SWEDEN", 48.0);
+
+        assert!(large.width() > 3 * small.width());
+        assert!(large.height() > 3 * small.height());
+        assert!(large.width() as f32 >= renderer.line_width("SWEDEN") * 2.0);
+        assert!(large.pixels().any(|p| p[3] == 255));
+    }
 }
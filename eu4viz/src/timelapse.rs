@@ -0,0 +1,396 @@
+//! Animated timelapse export from a simulation event log.
+//!
+//! Seeks a [`Timeline`] at a fixed game-time interval, rasterizes each step with
+//! [`ops::regenerate_political_map`], and writes the frames as an animated GIF
+//! or a numbered PNG sequence.
+
+use crate::ops;
+use crate::text::TextRenderer;
+use crate::timeline::Timeline;
+use crate::window::WorldData;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use image::{Delay, Frame, Rgb, RgbImage};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// Game time between timelapse frames (e.g. `1y`, `6m`, `30d`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameInterval {
+    Days(u32),
+    Months(u32),
+    Years(u32),
+}
+
+impl std::str::FromStr for FrameInterval {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s.char_indices().last().map_or(0, |(i, _)| i);
+        let (count, unit) = s.split_at(split);
+        let count: u32 = count
+            .parse()
+            .map_err(|_| format!("Invalid interval '{}': expected e.g. 1y, 6m, 30d", s))?;
+        if count == 0 {
+            return Err(format!("Invalid interval '{}': must be at least 1", s));
+        }
+        match unit {
+            "d" => Ok(Self::Days(count)),
+            "m" => Ok(Self::Months(count)),
+            "y" => Ok(Self::Years(count)),
+            _ => Err(format!(
+                "Invalid interval unit in '{}': use d (days), m (months) or y (years)",
+                s
+            )),
+        }
+    }
+}
+
+/// Output container, chosen from the output path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimelapseFormat {
+    /// Animated GIF (`.gif`).
+    Gif,
+    /// Directory of `frame_00000.png` files (path without extension).
+    PngSequence,
+}
+
+impl TimelapseFormat {
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("gif") => Ok(Self::Gif),
+            None => Ok(Self::PngSequence),
+            Some(ext) => Err(format!(
+                "Unsupported timelapse output '.{}': use a .gif file or a directory for PNG frames",
+                ext
+            )),
+        }
+    }
+}
+
+/// Settings for [`render_timelapse`].
+#[derive(Clone, Debug)]
+pub struct TimelapseOptions {
+    pub every: FrameInterval,
+    pub output: PathBuf,
+    /// Countries drawn at full color; everyone else is dimmed. Empty = no dimming.
+    pub highlight: HashSet<String>,
+    /// Output frame width in pixels (never upscaled).
+    pub width: u32,
+    /// Delay between frames.
+    pub frame_ms: u32,
+    /// Draw the date caption in the top-left corner.
+    pub caption: bool,
+}
+
+/// Ticks to render: the start date plus every interval after it, and the last tick.
+pub fn frame_ticks(bounds: (u64, u64), every: FrameInterval) -> Vec<u64> {
+    let (start, end) = bounds;
+    let mut ticks: Vec<u64> = match every {
+        FrameInterval::Days(n) => (start..=end).step_by(n as usize).collect(),
+        FrameInterval::Months(n) => month_ticks(bounds, n as u64),
+        FrameInterval::Years(n) => month_ticks(bounds, n as u64 * 12),
+    };
+    if ticks.last() != Some(&end) {
+        ticks.push(end);
+    }
+    ticks
+}
+
+/// Ticks of every `step`-th month anniversary of the start date within `bounds`.
+fn month_ticks((start, end): (u64, u64), step: u64) -> Vec<u64> {
+    // Months counted from 1444.11; the 11th exists in every month
+    (0..)
+        .map(|k| {
+            let month_index = 10 + k * step;
+            Timeline::date_to_tick(1444 + month_index / 12, month_index % 12 + 1, 11)
+                .expect("dates after the start have a tick")
+        })
+        .skip_while(|&tick| tick < start)
+        .take_while(|&tick| tick <= end)
+        .collect()
+}
+
+/// Render the timeline to `opts.output`, returning the number of frames written.
+pub fn render_timelapse(
+    world_data: &WorldData,
+    timeline: &mut Timeline,
+    opts: &TimelapseOptions,
+) -> Result<usize, String> {
+    let ticks = frame_ticks(timeline.bounds(), opts.every);
+    log::info!(
+        "Rendering {} timelapse frames to {}",
+        ticks.len(),
+        opts.output.display()
+    );
+
+    let renderer = opts.caption.then(TextRenderer::bundled);
+    let mut sink = FrameSink::create(&opts.output, opts.frame_ms)?;
+
+    for &tick in &ticks {
+        timeline.seek_to(tick);
+        let mut frame = ops::regenerate_political_map(world_data, timeline);
+        if !opts.highlight.is_empty() {
+            dim_unhighlighted(&mut frame, world_data, timeline, &opts.highlight);
+        }
+
+        let mut frame = scale_to_width(frame, opts.width);
+        if let Some(renderer) = &renderer {
+            draw_caption(&mut frame, renderer, &Timeline::tick_to_date(tick));
+        }
+        sink.push(frame)?;
+    }
+
+    // GifEncoder writes the trailer when dropped
+    drop(sink);
+    Ok(ticks.len())
+}
+
+/// Blend provinces not owned by a highlighted country toward the uncolonized grey.
+fn dim_unhighlighted(
+    frame: &mut RgbImage,
+    world_data: &WorldData,
+    timeline: &Timeline,
+    highlight: &HashSet<String>,
+) {
+    let current_owners = timeline.current_owners();
+    for (pixel, &id) in frame.pixels_mut().zip(&world_data.province_id_buffer) {
+        if id == u32::MAX || world_data.water_ids.contains(&id) {
+            continue;
+        }
+        let highlighted = ops::province_owner(world_data, current_owners, id)
+            .is_some_and(|tag| highlight.contains(tag));
+        if !highlighted {
+            dim(pixel);
+        }
+    }
+}
+
+/// Wash a colour two thirds of the way toward the unowned grey.
+pub fn dim(pixel: &mut Rgb<u8>) {
+    *pixel = Rgb(pixel.0.map(|c| ((c as u16 + 2 * 100) / 3) as u8));
+}
+
+pub fn scale_to_width(frame: RgbImage, width: u32) -> RgbImage {
+    if width == 0 || width >= frame.width() {
+        return frame;
+    }
+    let height = (frame.height() as u64 * width as u64 / frame.width() as u64).max(1) as u32;
+    image::imageops::resize(&frame, width, height, FilterType::Triangle)
+}
+
+/// Draw `text` over a darkened box in the top-left corner.
+pub fn draw_caption(frame: &mut RgbImage, renderer: &TextRenderer, text: &str) {
+    // TextRenderer draws 24px text with a 10px margin
+    let box_width = (renderer.line_width(text).ceil() as u32 + 20).min(frame.width());
+    let box_height = 40.min(frame.height());
+    let text_layer = renderer.render(text, box_width, box_height);
+
+    for (x, y, overlay) in text_layer.enumerate_pixels() {
+        let pixel = frame.get_pixel_mut(x, y);
+        let alpha = overlay[3] as u16;
+        for c in 0..3 {
+            let background = pixel[c] as u16 / 3;
+            pixel[c] = ((overlay[c] as u16 * alpha + background * (255 - alpha)) / 255) as u8;
+        }
+    }
+}
+
+/// Destination for rendered frames.
+enum FrameSink {
+    Gif(Box<GifEncoder<BufWriter<File>>>, Delay),
+    PngSequence { dir: PathBuf, next: usize },
+}
+
+impl FrameSink {
+    fn create(output: &Path, frame_ms: u32) -> Result<Self, String> {
+        match TimelapseFormat::from_path(output)? {
+            TimelapseFormat::Gif => {
+                if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+                    std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+                }
+                let file = File::create(output)
+                    .map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+                let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+                encoder
+                    .set_repeat(Repeat::Infinite)
+                    .map_err(|e| e.to_string())?;
+                Ok(Self::Gif(
+                    Box::new(encoder),
+                    Delay::from_numer_denom_ms(frame_ms, 1),
+                ))
+            }
+            TimelapseFormat::PngSequence => {
+                std::fs::create_dir_all(output)
+                    .map_err(|e| format!("Failed to create {}: {}", output.display(), e))?;
+                Ok(Self::PngSequence {
+                    dir: output.to_path_buf(),
+                    next: 0,
+                })
+            }
+        }
+    }
+
+    fn push(&mut self, frame: RgbImage) -> Result<(), String> {
+        match self {
+            Self::Gif(encoder, delay) => {
+                let rgba = image::DynamicImage::ImageRgb8(frame).into_rgba8();
+                encoder
+                    .encode_frame(Frame::from_parts(rgba, 0, 0, *delay))
+                    .map_err(|e| e.to_string())
+            }
+            Self::PngSequence { dir, next } => {
+                let path = dir.join(format!("frame_{:05}.png", next));
+                *next += 1;
+                frame.save(&path).map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::test_world;
+    use std::io::Write;
+    use tempfile::{NamedTempFile, tempdir};
+
+    /// Sweden takes Danish province 3 in 1446.
+    fn test_log() -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        let events = [
+            r#"{"type":"province_owner_changed","tick":0,"date":"1444.11.11","province_id":3,"old_owner":null,"new_owner":"DAN"}"#,
+            r#"{"type":"province_owner_changed","tick":600,"date":"1446.7.4","province_id":3,"old_owner":"DAN","new_owner":"SWE"}"#,
+            r#"{"type":"province_owner_changed","tick":1100,"date":"1447.11.16","province_id":2,"old_owner":"SWE","new_owner":"SWE"}"#,
+        ];
+        for event in &events {
+            writeln!(file, "{}", event).unwrap();
+        }
+        file.flush().unwrap();
+        file
+    }
+
+    fn options(output: PathBuf) -> TimelapseOptions {
+        TimelapseOptions {
+            every: FrameInterval::Years(1),
+            output,
+            highlight: HashSet::new(),
+            width: 1024,
+            frame_ms: 100,
+            caption: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!("1y".parse(), Ok(FrameInterval::Years(1)));
+        assert_eq!("6m".parse(), Ok(FrameInterval::Months(6)));
+        assert_eq!("30d".parse(), Ok(FrameInterval::Days(30)));
+        assert!("0y".parse::<FrameInterval>().is_err());
+        assert!("2w".parse::<FrameInterval>().is_err());
+        assert!("y".parse::<FrameInterval>().is_err());
+        assert!("".parse::<FrameInterval>().is_err());
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            TimelapseFormat::from_path(Path::new("run.gif")),
+            Ok(TimelapseFormat::Gif)
+        );
+        assert_eq!(
+            TimelapseFormat::from_path(Path::new("out/frames")),
+            Ok(TimelapseFormat::PngSequence)
+        );
+        assert!(TimelapseFormat::from_path(Path::new("run.mp4")).is_err());
+    }
+
+    #[test]
+    fn test_frame_ticks() {
+        // Yearly frames land on 11.11, with the final tick appended
+        assert_eq!(
+            frame_ticks((0, 800), FrameInterval::Years(1)),
+            vec![0, 365, 730, 800]
+        );
+        // 1445.5.11 is 181 days after 1444.11.11
+        assert_eq!(
+            frame_ticks((0, 365), FrameInterval::Months(6)),
+            vec![0, 181, 365]
+        );
+        assert_eq!(
+            frame_ticks((0, 20), FrameInterval::Days(10)),
+            vec![0, 10, 20]
+        );
+    }
+
+    #[test]
+    fn test_timelapse_png_sequence() {
+        let world = test_world();
+        let log = test_log();
+        let mut timeline = Timeline::from_file(log.path()).unwrap();
+        let dir = tempdir().unwrap();
+        let output = dir.path().join("frames");
+
+        let frames = render_timelapse(&world, &mut timeline, &options(output.clone())).unwrap();
+        // 1444.11.11, 1445.11.11, 1446.11.11, 1447.11.11 (tick 1095), then tick 1100
+        assert_eq!(frames, 5);
+
+        let first = image::open(output.join("frame_00000.png"))
+            .unwrap()
+            .to_rgb8();
+        let last = image::open(output.join("frame_00004.png"))
+            .unwrap()
+            .to_rgb8();
+        assert_eq!(first.get_pixel(0, 1), &Rgb([255, 0, 0]), "Danish at start");
+        assert_eq!(last.get_pixel(0, 1), &Rgb([0, 0, 255]), "Swedish at end");
+        assert_eq!(last.get_pixel(2, 1), &Rgb([64, 164, 223]), "Sea stays blue");
+    }
+
+    #[test]
+    fn test_timelapse_highlight_dims_others() {
+        let world = test_world();
+        let log = test_log();
+        let mut timeline = Timeline::from_file(log.path()).unwrap();
+        let dir = tempdir().unwrap();
+        let output = dir.path().join("frames");
+        let mut opts = options(output.clone());
+        opts.highlight = HashSet::from(["SWE".to_string()]);
+
+        render_timelapse(&world, &mut timeline, &opts).unwrap();
+
+        let first = image::open(output.join("frame_00000.png"))
+            .unwrap()
+            .to_rgb8();
+        assert_eq!(
+            first.get_pixel(0, 0),
+            &Rgb([0, 0, 255]),
+            "Sweden at full color"
+        );
+        assert_eq!(first.get_pixel(0, 1), &Rgb([151, 66, 66]), "Denmark dimmed");
+        assert_eq!(first.get_pixel(2, 1), &Rgb([64, 164, 223]), "Sea untouched");
+    }
+
+    #[test]
+    fn test_timelapse_gif() {
+        let world = test_world();
+        let log = test_log();
+        let mut timeline = Timeline::from_file(log.path()).unwrap();
+        let dir = tempdir().unwrap();
+        let output = dir.path().join("run.gif");
+
+        render_timelapse(&world, &mut timeline, &options(output.clone())).unwrap();
+
+        let decoder = image::codecs::gif::GifDecoder::new(std::io::BufReader::new(
+            File::open(&output).unwrap(),
+        ))
+        .unwrap();
+        let frames = image::AnimationDecoder::into_frames(decoder)
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0].buffer().dimensions(), (4, 2));
+    }
+}
@@ -145,6 +145,7 @@ impl Timeline {
             GameEvent::ProvinceOwnerChanged { tick, .. } => *tick,
             GameEvent::BattleFought { tick, .. } => *tick,
             GameEvent::SiegeCompleted { tick, .. } => *tick,
+            GameEvent::OpinionThresholdCrossed { tick, .. } => *tick,
         }
     }
 
@@ -224,7 +225,7 @@ impl Timeline {
     /// Convert a tick number to a date string.
     ///
     /// EU4 starts at 1444.11.11. Each tick is one day.
-    fn tick_to_date(tick: u64) -> String {
+    pub fn tick_to_date(tick: u64) -> String {
         // Days in each month (non-leap year)
         const DAYS_IN_MONTH: [u64; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
 
@@ -259,6 +260,26 @@ impl Timeline {
         format!("{}.{}.{}", year, month, day)
     }
 
+    /// Convert a date to a tick number (inverse of [`Self::tick_to_date`]).
+    ///
+    /// Returns `None` for dates before the 1444.11.11 start.
+    pub fn date_to_tick(year: u64, month: u64, day: u64) -> Option<u64> {
+        let days = Self::days_from_civil(year, month, day) - Self::days_from_civil(1444, 11, 11);
+        u64::try_from(days).ok()
+    }
+
+    /// Days since 0000.3.1 in the proleptic Gregorian calendar.
+    fn days_from_civil(year: u64, month: u64, day: u64) -> i64 {
+        // Count years from March so the leap day is the last day of the year
+        let year = year as i64 - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let shifted_month = (month as i64 + 9) % 12;
+        let day_of_year = (153 * shifted_month + 2) / 5 + day as i64 - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era
+    }
+
     /// Check if a year is a leap year (Gregorian calendar).
     fn is_leap_year(year: u64) -> bool {
         (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
@@ -424,4 +445,25 @@ mod tests {
         // Total: 50 + 365*3 + 60 = 50 + 1095 + 60 = 1205
         assert_eq!(Timeline::tick_to_date(1205), "1448.2.29");
     }
+
+    #[test]
+    fn test_date_to_tick() {
+        assert_eq!(Timeline::date_to_tick(1444, 11, 11), Some(0));
+        assert_eq!(Timeline::date_to_tick(1444, 12, 1), Some(20));
+        assert_eq!(Timeline::date_to_tick(1445, 1, 1), Some(51));
+        assert_eq!(Timeline::date_to_tick(1448, 2, 29), Some(1205));
+        assert_eq!(Timeline::date_to_tick(1444, 11, 10), None);
+
+        // Round trip across leap and century years
+        for tick in [0, 365, 1205, 20_000, 56_000, 137_000] {
+            let date = Timeline::tick_to_date(tick);
+            let parts: Vec<u64> = date.split('.').map(|p| p.parse().unwrap()).collect();
+            assert_eq!(
+                Timeline::date_to_tick(parts[0], parts[1], parts[2]),
+                Some(tick),
+                "round trip failed for {}",
+                date
+            );
+        }
+    }
 }
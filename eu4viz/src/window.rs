@@ -793,6 +793,7 @@ pub async fn snapshot(
     eu4_path: &std::path::Path,
     output_path: &std::path::Path,
     mode: MapMode,
+    source: Option<&crate::sources::MapSource>,
     log_level: log::LevelFilter,
 ) -> Result<(), String> {
     // We need to re-init logger if it hasn't been initialized?
@@ -857,12 +858,17 @@ pub async fn snapshot(
     } else {
         println!("Loading world data for Snapshot...");
         let world_data = crate::ops::load_world_data(eu4_path)?;
-        let img = match mode {
-            MapMode::Political => world_data.political_map.clone(),
-            MapMode::TradeGoods => world_data.tradegoods_map.clone(),
-            MapMode::Religion => world_data.religion_map.clone(),
-            MapMode::Culture => world_data.culture_map.clone(),
-            _ => world_data.province_map.clone(),
+        let img = match (mode, source) {
+            (MapMode::Province | MapMode::All, _) => world_data.province_map.clone(),
+            (_, Some(source)) => {
+                println!("Loading provinces from {}...", source);
+                let history = source.load_history(eu4_path)?;
+                crate::ops::render_map_mode(&world_data, &history, mode)
+            }
+            (MapMode::Political, None) => world_data.political_map.clone(),
+            (MapMode::TradeGoods, None) => world_data.tradegoods_map.clone(),
+            (MapMode::Religion, None) => world_data.religion_map.clone(),
+            (MapMode::Culture, None) => world_data.culture_map.clone(),
         };
         map_images.insert(mode, image::DynamicImage::ImageRgb8(img));
         // Ensure Province map exists as it is required for the default bind group in Eu4Renderer
@@ -1097,6 +1103,7 @@ mod tests {
             path,
             &output_path,
             MapMode::Province,
+            None,
             log::LevelFilter::Info,
         )) {
             Ok(_) => {
@@ -1133,6 +1140,7 @@ mod tests {
                 steam_path,
                 &output_path,
                 MapMode::Political,
+                None,
                 log::LevelFilter::Info,
             )) {
                 Ok(_) => {
@@ -1164,6 +1172,7 @@ mod tests {
                 steam_path,
                 &output_path,
                 MapMode::TradeGoods,
+                None,
                 log::LevelFilter::Info,
             )) {
                 Ok(_) => {
@@ -1197,6 +1206,7 @@ mod tests {
                 steam_path,
                 &output_path,
                 MapMode::Religion,
+                None,
                 log::LevelFilter::Info,
             )) {
                 Ok(_) => {
@@ -1230,6 +1240,7 @@ mod tests {
                 steam_path,
                 &output_path,
                 MapMode::Culture,
+                None,
                 log::LevelFilter::Info,
             )) {
                 Ok(_) => {
@@ -0,0 +1,423 @@
+//! Dijkstra flood fills, reachability sets and multi-goal searches.
+//!
+//! A* answers "how do I get from A to B". The AI mostly asks different
+//! questions: which provinces are within N days, which enemy fort is closest
+//! to any of my armies, can I reach the capital without crossing hostile
+//! zones of control. All of those are a single Dijkstra expansion from one or
+//! more sources, optionally cut off at a cost limit or at the first goal.
+
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+use crate::{Graph, State, reconstruct_path};
+
+/// Result of a Dijkstra expansion: the cheapest known cost to every settled node
+/// plus the predecessor links needed to rebuild paths.
+#[derive(Debug, Clone)]
+pub struct SearchTree<Node> {
+    costs: HashMap<Node, u32>,
+    came_from: HashMap<Node, Node>,
+}
+
+impl<Node> SearchTree<Node>
+where
+    Node: Copy + Eq + Hash,
+{
+    /// Cost of the cheapest path to `node`, if it was reached.
+    pub fn cost_to(&self, node: Node) -> Option<u32> {
+        self.costs.get(&node).copied()
+    }
+
+    /// Whether `node` was reached within the search limits.
+    pub fn contains(&self, node: Node) -> bool {
+        self.costs.contains_key(&node)
+    }
+
+    /// Cheapest path from the nearest source to `node` (inclusive of both ends).
+    pub fn path_to(&self, node: Node) -> Option<Vec<Node>> {
+        self.contains(node)
+            .then(|| reconstruct_path(&self.came_from, node))
+    }
+
+    /// Iterate over every reached node and its cost. Order is unspecified.
+    pub fn iter(&self) -> impl Iterator<Item = (Node, u32)> + '_ {
+        self.costs.iter().map(|(&node, &cost)| (node, cost))
+    }
+
+    /// Number of reached nodes (sources included).
+    pub fn len(&self) -> usize {
+        self.costs.len()
+    }
+
+    /// True if nothing was reached, i.e. the search had no sources.
+    pub fn is_empty(&self) -> bool {
+        self.costs.is_empty()
+    }
+
+    /// Consume the tree, keeping only the cost map.
+    pub fn into_costs(self) -> HashMap<Node, u32> {
+        self.costs
+    }
+}
+
+/// Uniform-cost search over a [`Graph`], ignoring its heuristic.
+pub struct Dijkstra;
+
+impl Dijkstra {
+    /// Expand from every node in `starts`, settling all nodes whose cheapest
+    /// cost is at most `max_cost` (or every reachable node if `None`).
+    ///
+    /// Costs are measured from the nearest source, so passing all of a
+    /// country's army locations gives "days until any army could be there".
+    pub fn flood_fill<Node, Ctx, G>(
+        graph: &G,
+        starts: impl IntoIterator<Item = Node>,
+        max_cost: Option<u32>,
+        context: &Ctx,
+    ) -> SearchTree<Node>
+    where
+        Node: Copy + Eq + Hash,
+        G: Graph<Node, Ctx>,
+    {
+        let (tree, _) = Self::expand(graph, starts, max_cost, context, |_| false);
+        tree
+    }
+
+    /// The set of nodes reachable from `starts` within `max_cost`.
+    pub fn reachable<Node, Ctx, G>(
+        graph: &G,
+        starts: impl IntoIterator<Item = Node>,
+        max_cost: Option<u32>,
+        context: &Ctx,
+    ) -> HashSet<Node>
+    where
+        Node: Copy + Eq + Hash,
+        G: Graph<Node, Ctx>,
+    {
+        Self::flood_fill(graph, starts, max_cost, context)
+            .costs
+            .into_keys()
+            .collect()
+    }
+
+    /// Find the cheapest path from any of `starts` to any node satisfying `is_goal`.
+    ///
+    /// The search stops at the first goal settled, so it never explores
+    /// further than the answer. Returns the path (source first, goal last) and
+    /// its cost, or `None` if no goal lies within `max_cost`.
+    pub fn find_nearest<Node, Ctx, G, P>(
+        graph: &G,
+        starts: impl IntoIterator<Item = Node>,
+        is_goal: P,
+        max_cost: Option<u32>,
+        context: &Ctx,
+    ) -> Option<(Vec<Node>, u32)>
+    where
+        Node: Copy + Eq + Hash,
+        G: Graph<Node, Ctx>,
+        P: Fn(Node) -> bool,
+    {
+        let (tree, found) = Self::expand(graph, starts, max_cost, context, is_goal);
+        let goal = found?;
+        let path = reconstruct_path(&tree.came_from, goal);
+        Some((path, tree.costs[&goal]))
+    }
+
+    /// Shared expansion loop. Stops early when `stop_at` accepts a settled node.
+    fn expand<Node, Ctx, G, P>(
+        graph: &G,
+        starts: impl IntoIterator<Item = Node>,
+        max_cost: Option<u32>,
+        context: &Ctx,
+        stop_at: P,
+    ) -> (SearchTree<Node>, Option<Node>)
+    where
+        Node: Copy + Eq + Hash,
+        G: Graph<Node, Ctx>,
+        P: Fn(Node) -> bool,
+    {
+        let limit = max_cost.unwrap_or(u32::MAX);
+        let mut open_set = BinaryHeap::new();
+        let mut best: HashMap<Node, u32> = HashMap::new();
+        let mut came_from: HashMap<Node, Node> = HashMap::new();
+        let mut settled: HashMap<Node, u32> = HashMap::new();
+
+        for start in starts {
+            if best.insert(start, 0).is_none() {
+                open_set.push(State {
+                    node: start,
+                    cost: 0,
+                    priority: 0,
+                });
+            }
+        }
+
+        while let Some(State {
+            node: current,
+            cost: current_cost,
+            ..
+        }) = open_set.pop()
+        {
+            // Stale queue entry: a cheaper path was already settled
+            if settled.contains_key(&current) {
+                continue;
+            }
+            settled.insert(current, current_cost);
+
+            if stop_at(current) {
+                let tree = SearchTree {
+                    costs: settled,
+                    came_from,
+                };
+                return (tree, Some(current));
+            }
+
+            for neighbor in graph.neighbors(current, context) {
+                if settled.contains_key(&neighbor) || !graph.is_passable(neighbor, context) {
+                    continue;
+                }
+
+                let tentative = current_cost.saturating_add(graph.cost(current, neighbor, context));
+                if tentative > limit {
+                    continue;
+                }
+
+                if tentative < *best.get(&neighbor).unwrap_or(&u32::MAX) {
+                    best.insert(neighbor, tentative);
+                    came_from.insert(neighbor, current);
+                    open_set.push(State {
+                        node: neighbor,
+                        cost: tentative,
+                        priority: tentative,
+                    });
+                }
+            }
+        }
+
+        let tree = SearchTree {
+            costs: settled,
+            came_from,
+        };
+        (tree, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AStar, FilteredGraph};
+    use proptest::prelude::*;
+
+    /// Line graph 0 - 1 - 2 - ... - (len-1) where entering node `n` costs `n + 1`.
+    struct LineGraph {
+        len: u32,
+    }
+
+    impl Graph<u32, ()> for LineGraph {
+        fn neighbors(&self, node: u32, _context: &()) -> Vec<u32> {
+            let mut n = Vec::new();
+            if node > 0 {
+                n.push(node - 1);
+            }
+            if node + 1 < self.len {
+                n.push(node + 1);
+            }
+            n
+        }
+
+        fn cost(&self, _from: u32, to: u32, _context: &()) -> u32 {
+            to + 1
+        }
+
+        fn heuristic(&self, _from: u32, _target: u32, _context: &()) -> u32 {
+            0
+        }
+    }
+
+    #[test]
+    fn test_flood_fill_costs() {
+        let graph = LineGraph { len: 5 };
+        let tree = Dijkstra::flood_fill(&graph, [0], None, &());
+        assert_eq!(tree.len(), 5);
+        assert_eq!(tree.cost_to(0), Some(0));
+        assert_eq!(tree.cost_to(1), Some(2));
+        assert_eq!(tree.cost_to(2), Some(5));
+        assert_eq!(tree.cost_to(4), Some(14));
+        assert_eq!(tree.path_to(3), Some(vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn test_flood_fill_respects_cost_limit() {
+        let graph = LineGraph { len: 10 };
+        let reachable = Dijkstra::reachable(&graph, [0], Some(5), &());
+        assert_eq!(reachable, HashSet::from([0, 1, 2]));
+    }
+
+    #[test]
+    fn test_flood_fill_multi_source() {
+        let graph = LineGraph { len: 10 };
+        let tree = Dijkstra::flood_fill(&graph, [0, 9], None, &());
+        // Node 8 is one hop from 9 (cost 9) rather than eight hops from 0
+        assert_eq!(tree.cost_to(8), Some(9));
+        assert_eq!(tree.path_to(8), Some(vec![9, 8]));
+    }
+
+    #[test]
+    fn test_find_nearest_stops_at_first_goal() {
+        let graph = LineGraph { len: 10 };
+        let (path, cost) =
+            Dijkstra::find_nearest(&graph, [5], |n| n == 2 || n == 9, None, &()).unwrap();
+        // Going down to 2 costs 5+4+3 = 12, going up to 9 costs 7+8+9+10 = 34
+        assert_eq!(path, vec![5, 4, 3, 2]);
+        assert_eq!(cost, 12);
+    }
+
+    #[test]
+    fn test_find_nearest_outside_limit() {
+        let graph = LineGraph { len: 10 };
+        assert!(Dijkstra::find_nearest(&graph, [0], |n| n == 9, Some(20), &()).is_none());
+    }
+
+    #[test]
+    fn test_flood_fill_filtered() {
+        let graph = LineGraph { len: 10 };
+        let walled = FilteredGraph::new(&graph, |n: u32, _ctx: &()| n != 4);
+        let reachable = Dijkstra::reachable(&walled, [0], None, &());
+        assert_eq!(reachable, HashSet::from([0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn test_flood_fill_no_sources() {
+        let graph = LineGraph { len: 3 };
+        assert!(Dijkstra::flood_fill(&graph, [], None, &()).is_empty());
+    }
+
+    /// Grid graph with per-node entry costs and impassable cells, used to
+    /// cross-check Dijkstra against A* on random maps.
+    #[derive(Debug)]
+    struct RandomGrid {
+        width: u32,
+        height: u32,
+        costs: Vec<u32>,
+        blocked: Vec<bool>,
+    }
+
+    impl Graph<u32, ()> for RandomGrid {
+        fn neighbors(&self, node: u32, _context: &()) -> Vec<u32> {
+            let mut n = Vec::new();
+            let x = node % self.width;
+            let y = node / self.width;
+            if x > 0 {
+                n.push(node - 1);
+            }
+            if x + 1 < self.width {
+                n.push(node + 1);
+            }
+            if y > 0 {
+                n.push(node - self.width);
+            }
+            if y + 1 < self.height {
+                n.push(node + self.width);
+            }
+            n
+        }
+
+        fn cost(&self, _from: u32, to: u32, _context: &()) -> u32 {
+            self.costs[to as usize]
+        }
+
+        fn heuristic(&self, from: u32, target: u32, _context: &()) -> u32 {
+            // Manhattan distance scaled by the cheapest entry cost (admissible)
+            let min_cost = self.costs.iter().copied().min().unwrap_or(0);
+            let dx = (from % self.width).abs_diff(target % self.width);
+            let dy = (from / self.width).abs_diff(target / self.width);
+            (dx + dy) * min_cost
+        }
+
+        fn is_passable(&self, node: u32, _context: &()) -> bool {
+            !self.blocked[node as usize]
+        }
+    }
+
+    fn random_grid() -> impl Strategy<Value = RandomGrid> {
+        (2..8u32, 2..8u32).prop_flat_map(|(width, height)| {
+            let size = (width * height) as usize;
+            (
+                proptest::collection::vec(1..20u32, size),
+                proptest::collection::vec(proptest::bool::weighted(0.2), size),
+            )
+                .prop_map(move |(costs, blocked)| RandomGrid {
+                    width,
+                    height,
+                    costs,
+                    blocked,
+                })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn prop_flood_fill_matches_astar(grid in random_grid(), start_seed in 0..64u32) {
+            let start = start_seed % (grid.width * grid.height);
+            let tree = Dijkstra::flood_fill(&grid, [start], None, &());
+
+            for goal in 0..grid.width * grid.height {
+                let astar = AStar::find_path(&grid, start, goal, &()).map(|(_, cost)| cost);
+                prop_assert_eq!(tree.cost_to(goal), astar, "goal {}", goal);
+            }
+        }
+
+        #[test]
+        fn prop_flood_fill_paths_are_consistent(grid in random_grid(), start_seed in 0..64u32) {
+            let start = start_seed % (grid.width * grid.height);
+            let tree = Dijkstra::flood_fill(&grid, [start], None, &());
+
+            for (node, cost) in tree.iter() {
+                let path = tree.path_to(node).unwrap();
+                prop_assert_eq!(path[0], start);
+                prop_assert_eq!(*path.last().unwrap(), node);
+
+                // Summed edge costs equal the reported cost, and no step enters a blocked cell
+                let summed: u32 = path.windows(2).map(|w| grid.cost(w[0], w[1], &())).sum();
+                prop_assert_eq!(summed, cost);
+                prop_assert!(path[1..].iter().all(|&n| grid.is_passable(n, &())));
+            }
+        }
+
+        #[test]
+        fn prop_cost_limit_is_a_prefix(grid in random_grid(), start_seed in 0..64u32, limit in 0..60u32) {
+            let start = start_seed % (grid.width * grid.height);
+            let full = Dijkstra::flood_fill(&grid, [start], None, &());
+            let limited = Dijkstra::reachable(&grid, [start], Some(limit), &());
+
+            let expected: HashSet<u32> = full
+                .iter()
+                .filter(|&(_, cost)| cost <= limit)
+                .map(|(node, _)| node)
+                .collect();
+            prop_assert_eq!(limited, expected);
+        }
+
+        #[test]
+        fn prop_find_nearest_matches_best_goal(
+            grid in random_grid(),
+            start_seed in 0..64u32,
+            goal_seeds in proptest::collection::vec(0..64u32, 1..4)
+        ) {
+            let size = grid.width * grid.height;
+            let start = start_seed % size;
+            let goals: Vec<u32> = goal_seeds.iter().map(|g| g % size).collect();
+
+            let nearest = Dijkstra::find_nearest(&grid, [start], |n| goals.contains(&n), None, &())
+                .map(|(_, cost)| cost);
+            let best = goals
+                .iter()
+                .filter_map(|&g| AStar::find_path(&grid, start, g, &()).map(|(_, cost)| cost))
+                .min();
+            prop_assert_eq!(nearest, best);
+
+            let astar_any = AStar::find_path_to_any(&grid, start, &goals, &()).map(|(_, cost)| cost);
+            prop_assert_eq!(astar_any, best);
+        }
+    }
+}
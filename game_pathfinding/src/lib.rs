@@ -38,11 +38,24 @@
 //! The `Ctx` generic allows passing runtime state to cost calculations.
 //! For EU4, this enables terrain-aware movement costs, diplomatic access
 //! checks, and other dynamic factors.
+//!
+//! ## Beyond Point-to-Point
+//!
+//! [`Dijkstra`] covers the queries A* can't answer directly: flood fills
+//! with a cost limit ("everything within 30 days"), reachability sets, and
+//! multi-source / multi-goal searches ("nearest enemy fort from any of my
+//! armies"). Every search honours [`Graph::is_passable`], and
+//! [`FilteredGraph`] layers an ad-hoc predicate (hostile ZoC, military
+//! access) on top of an existing graph without touching its impl.
 
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::hash::Hash;
 
+mod dijkstra;
+
+pub use dijkstra::{Dijkstra, SearchTree};
+
 /// A trait for graphs that can be searched.
 ///
 /// `Node`: The type of node identifiers (e.g., ProvinceId).
@@ -58,6 +71,76 @@ pub trait Graph<Node, Ctx> {
     /// Calculate the estimated cost (heuristic) from `from` to `target`.
     /// For A*, this must be admissible (never overestimate).
     fn heuristic(&self, from: Node, target: Node, context: &Ctx) -> u32;
+
+    /// Whether a search may enter `node`.
+    ///
+    /// Impassable nodes are never expanded or returned as part of a path.
+    /// Start nodes are exempt so a unit standing in hostile territory can
+    /// still leave it. Defaults to every node being passable.
+    fn is_passable(&self, _node: Node, _context: &Ctx) -> bool {
+        true
+    }
+}
+
+/// A graph adapter that restricts an inner graph with a node predicate.
+///
+/// The predicate is combined with the inner graph's own
+/// [`Graph::is_passable`], so filters stack.
+///
+/// ```
+/// use game_pathfinding::{AStar, FilteredGraph, Graph};
+///
+/// struct Line;
+///
+/// impl Graph<u32, ()> for Line {
+///     fn neighbors(&self, node: u32, _ctx: &()) -> Vec<u32> {
+///         vec![node.saturating_sub(1), node + 1]
+///     }
+///     fn cost(&self, _from: u32, _to: u32, _ctx: &()) -> u32 {
+///         1
+///     }
+///     fn heuristic(&self, _from: u32, _target: u32, _ctx: &()) -> u32 {
+///         0
+///     }
+/// }
+///
+/// // Province 3 is blocked, so 5 can't be reached from 0 on a line.
+/// let blocked = FilteredGraph::new(&Line, |node: u32, _ctx: &()| node != 3);
+/// assert!(AStar::find_path(&blocked, 0, 5, &()).is_none());
+/// ```
+pub struct FilteredGraph<'g, G, F> {
+    inner: &'g G,
+    filter: F,
+}
+
+impl<'g, G, F> FilteredGraph<'g, G, F> {
+    /// Wrap `inner`, allowing only nodes for which `filter` returns true.
+    pub fn new(inner: &'g G, filter: F) -> Self {
+        Self { inner, filter }
+    }
+}
+
+impl<Node, Ctx, G, F> Graph<Node, Ctx> for FilteredGraph<'_, G, F>
+where
+    Node: Copy,
+    G: Graph<Node, Ctx>,
+    F: Fn(Node, &Ctx) -> bool,
+{
+    fn neighbors(&self, node: Node, context: &Ctx) -> Vec<Node> {
+        self.inner.neighbors(node, context)
+    }
+
+    fn cost(&self, from: Node, to: Node, context: &Ctx) -> u32 {
+        self.inner.cost(from, to, context)
+    }
+
+    fn heuristic(&self, from: Node, target: Node, context: &Ctx) -> u32 {
+        self.inner.heuristic(from, target, context)
+    }
+
+    fn is_passable(&self, node: Node, context: &Ctx) -> bool {
+        (self.filter)(node, context) && self.inner.is_passable(node, context)
+    }
 }
 
 /// A generic A* pathfinder.
@@ -75,6 +158,36 @@ impl AStar {
         Node: Copy + Eq + Hash + std::fmt::Debug,
         G: Graph<Node, Ctx>,
     {
+        Self::find_path_to_any(graph, start, &[goal], context)
+    }
+
+    /// Find the shortest path from `start` to whichever of `goals` is cheapest to reach.
+    ///
+    /// The heuristic used is the minimum over all goals, which stays admissible
+    /// as long as the per-goal heuristic is. Returns `None` if `goals` is empty
+    /// or none of them are reachable.
+    pub fn find_path_to_any<Node, Ctx, G>(
+        graph: &G,
+        start: Node,
+        goals: &[Node],
+        context: &Ctx,
+    ) -> Option<(Vec<Node>, u32)>
+    where
+        Node: Copy + Eq + Hash + std::fmt::Debug,
+        G: Graph<Node, Ctx>,
+    {
+        if goals.is_empty() {
+            return None;
+        }
+
+        let heuristic = |node: Node| {
+            goals
+                .iter()
+                .map(|&goal| graph.heuristic(node, goal, context))
+                .min()
+                .unwrap_or(0)
+        };
+
         let mut open_set = BinaryHeap::new();
         let mut came_from: HashMap<Node, Node> = HashMap::new();
         let mut g_score: HashMap<Node, u32> = HashMap::new();
@@ -84,7 +197,7 @@ impl AStar {
         open_set.push(State {
             node: start,
             cost: 0,
-            priority: graph.heuristic(start, goal, context),
+            priority: heuristic(start),
         });
 
         while let Some(State { node: current, .. }) = open_set.pop() {
@@ -93,27 +206,20 @@ impl AStar {
                 continue;
             }
 
-            if current == goal {
-                // Reconstruct path
-                let mut path = vec![current];
-                let mut curr = current;
-                while let Some(&prev) = came_from.get(&curr) {
-                    path.push(prev);
-                    curr = prev;
-                }
-                path.reverse();
-                return Some((path, g_score[&goal]));
+            if goals.contains(&current) {
+                let path = reconstruct_path(&came_from, current);
+                return Some((path, g_score[&current]));
             }
 
             let current_g = g_score[&current];
 
             for neighbor in graph.neighbors(current, context) {
-                // Skip already-processed nodes
-                if closed_set.contains(&neighbor) {
+                // Skip already-processed nodes and nodes the graph forbids
+                if closed_set.contains(&neighbor) || !graph.is_passable(neighbor, context) {
                     continue;
                 }
 
-                let tentative_g = current_g + graph.cost(current, neighbor, context);
+                let tentative_g = current_g.saturating_add(graph.cost(current, neighbor, context));
 
                 if tentative_g < *g_score.get(&neighbor).unwrap_or(&u32::MAX) {
                     came_from.insert(neighbor, current);
@@ -121,7 +227,7 @@ impl AStar {
                     open_set.push(State {
                         node: neighbor,
                         cost: tentative_g,
-                        priority: tentative_g + graph.heuristic(neighbor, goal, context),
+                        priority: tentative_g.saturating_add(heuristic(neighbor)),
                     });
                 }
             }
@@ -131,6 +237,21 @@ impl AStar {
     }
 }
 
+/// Walk `came_from` back from `end` and return the path in start-to-end order.
+fn reconstruct_path<Node>(came_from: &HashMap<Node, Node>, end: Node) -> Vec<Node>
+where
+    Node: Copy + Eq + Hash,
+{
+    let mut path = vec![end];
+    let mut curr = end;
+    while let Some(&prev) = came_from.get(&curr) {
+        path.push(prev);
+        curr = prev;
+    }
+    path.reverse();
+    path
+}
+
 /// Helper struct for the priority queue.
 #[derive(Copy, Clone, Eq, PartialEq)]
 struct State<Node> {
@@ -289,4 +410,45 @@ mod tests {
         assert!(path == vec![0, 1, 3] || path == vec![0, 2, 3]);
         assert_eq!(path.len(), 3);
     }
+
+    #[test]
+    fn test_find_path_to_any_picks_cheapest_goal() {
+        let graph = GridGraph;
+        // From the top-left corner, 2 is two steps away and 8 is four
+        let (path, cost) = AStar::find_path_to_any(&graph, 0, &[8, 2], &()).unwrap();
+        assert_eq!(cost, 2);
+        assert_eq!(path, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_find_path_to_any_empty_goals() {
+        assert!(AStar::find_path_to_any(&GridGraph, 0, &[], &()).is_none());
+    }
+
+    #[test]
+    fn test_filtered_graph_routes_around_blocked_nodes() {
+        // Block the middle column except the bottom row:
+        // 0 1 2
+        // 3 X 5
+        // 6 7 8
+        let graph = FilteredGraph::new(&GridGraph, |node: u32, _ctx: &()| node != 1 && node != 4);
+        let (path, cost) = AStar::find_path(&graph, 0, 2, &()).unwrap();
+        assert_eq!(cost, 6);
+        assert!(!path.contains(&1) && !path.contains(&4));
+    }
+
+    #[test]
+    fn test_filtered_graph_blocked_goal() {
+        let graph = FilteredGraph::new(&GridGraph, |node: u32, _ctx: &()| node != 8);
+        assert!(AStar::find_path(&graph, 0, 8, &()).is_none());
+    }
+
+    #[test]
+    fn test_filtered_graph_start_is_exempt() {
+        // Starting inside a forbidden node is allowed; only entering is not
+        let graph = FilteredGraph::new(&GridGraph, |node: u32, _ctx: &()| node != 0);
+        let (path, cost) = AStar::find_path(&graph, 0, 2, &()).unwrap();
+        assert_eq!(cost, 2);
+        assert_eq!(path.first(), Some(&0));
+    }
 }